@@ -7,7 +7,7 @@
  *   Layer 3 (A, 20%): AI quality — Claude scores each opinion's text
  *
  *   Final score: S = (W × 0.5) + (C × 0.3) + (A × 0.2)
- *   Payout: proportional to S, 10% protocol fee
+ *   Payout: proportional to S, tiered protocol fee (10% to $1k, 5% beyond)
  *
  * Settlement flow:
  *   Market closes → record_sentiment (market-level) → record_ai_score (per opinion)
@@ -56,6 +56,23 @@ const provider = new anchor.AnchorProvider(connection, wallet, {
 
 logger.info({ network: config.network, programId: config.programId }, "Triple-Check Oracle initialized");
 
+// Mirrors the on-chain default ProgramConfig.fee_tiers: [threshold, bps] pairs,
+// each bps charged only on the part of the pool above its threshold
+const PROTOCOL_FEE_TIERS: Array<[number, number]> = [
+  [0, 1_000],
+  [1_000_000_000, 500],
+];
+
+function calculateProtocolFee(poolMicroUsdc: number, tiers = PROTOCOL_FEE_TIERS): number {
+  let feeBpsTotal = 0;
+  tiers.forEach(([threshold, bps], i) => {
+    if (poolMicroUsdc <= threshold) return;
+    const upper = i + 1 < tiers.length ? Math.min(tiers[i + 1][0], poolMicroUsdc) : poolMicroUsdc;
+    feeBpsTotal += (upper - threshold) * bps;
+  });
+  return Math.floor(feeBpsTotal / 10_000);
+}
+
 function loadOracleKeypair(keypairPath: string): anchor.Wallet {
   if (!fs.existsSync(keypairPath)) {
     logger.error({ path: keypairPath }, "Oracle keypair not found");
@@ -107,24 +124,40 @@ class TripleCheckScorer {
   // ── Layer 2: Crowd Score ────────────────────────────────────────────────────
 
   /**
-   * Volume-weighted mean of all opinion_score values.
-   * Users who staked MORE (original + backing) have more influence.
+   * Stake-weighted mean of all market_prediction values, rounded to an integer.
+   * Must match Market::onchain_crowd_score — the program rejects any other value.
    *
-   * crowdScore = Σ(opinion_score_i × weight_i) / Σ(weight_i)
-   *   where weight_i = amount_i + backing_total_i
+   * crowdScore = round(Σ(market_prediction_i × amount_i) / Σ(amount_i))
    */
   calculateCrowdScore(opinions: OpinionData[]): number {
     let weightedSum = 0;
     let totalWeight = 0;
 
     for (const op of opinions) {
-      const weight = op.amount + op.backing_total;
-      weightedSum += (op.opinion_score ?? 50) * weight;
-      totalWeight += weight;
+      weightedSum += (op.market_prediction ?? 50) * op.amount;
+      totalWeight += op.amount;
     }
 
     if (totalWeight === 0) return 50;
-    return Math.round((weightedSum / totalWeight) * 10) / 10;
+    return Math.round(weightedSum / totalWeight);
+  }
+
+  /**
+   * Stake-weighted median market_prediction, for markets in CrowdScoreMode::Median.
+   * Must match CrowdHistogram::weighted_median: the lowest score whose
+   * cumulative stake reaches half of the total.
+   */
+  calculateCrowdMedian(opinions: OpinionData[]): number {
+    const totalWeight = opinions.reduce((sum, op) => sum + op.amount, 0);
+    if (totalWeight === 0) return 50;
+
+    const sorted = [...opinions].sort((a, b) => (a.market_prediction ?? 50) - (b.market_prediction ?? 50));
+    let cumulative = 0;
+    for (const op of sorted) {
+      cumulative += op.amount;
+      if (cumulative * 2 >= totalWeight) return op.market_prediction ?? 50;
+    }
+    return 100;
   }
 
   // ── Layer 1: Weight Scores ──────────────────────────────────────────────────
@@ -133,7 +166,8 @@ class TripleCheckScorer {
    * Normalize net backing (backing_total - slashing_total) to 0–100 across all opinions.
    * Minimum weight is 5 so no opinion ever scores zero.
    *
-   * weight_i = max(5, ((netBacking_i - minNet) / range) × 95 + 5)
+   * weight_i = round(((netBacking_i - minNet) / range) × 95) + 5
+   * Verified on-chain against the range posted with post_weight_range.
    */
   calculateWeightScores(opinions: OpinionData[]): Map<string, number> {
     const netBacking = opinions.map((op) => ({
@@ -148,7 +182,8 @@ class TripleCheckScorer {
 
     const scores = new Map<string, number>();
     for (const { id, net } of netBacking) {
-      const score = Math.max(5, Math.round(((net - minNet) / range) * 95) + 5);
+      // Integer form of round((net - minNet) / range × 95) + 5 — must match compute_weight_score
+      const score = Math.floor(((net - minNet) * 190 + range) / (2 * range)) + 5;
       scores.set(id, score);
     }
     return scores;
@@ -308,7 +343,8 @@ Respond in JSON:
   async computeTripleCheckScores(
     statement: string,
     opinions: OpinionData[],
-    totalStakeMicroUsdc: number
+    totalStakeMicroUsdc: number,
+    crowdScoreMode: "mean" | "median" = "mean"
   ): Promise<{ crowdScore: number; scoredOpinions: ScoredOpinion[] }> {
     logger.info(
       { opinionCount: opinions.length, totalStake: totalStakeMicroUsdc },
@@ -319,7 +355,8 @@ Respond in JSON:
     const weightScores = this.calculateWeightScores(opinions);
 
     // Layer 2
-    const crowdScore = this.calculateCrowdScore(opinions);
+    const crowdScore =
+      crowdScoreMode === "median" ? this.calculateCrowdMedian(opinions) : this.calculateCrowdScore(opinions);
     const predictionScores = this.calculatePredictionScores(opinions, crowdScore);
 
     // Layer 3
@@ -327,8 +364,7 @@ Respond in JSON:
     const aiScores = await this.scoreOpinionTexts(statement, opinions);
 
     // Composite
-    const PROTOCOL_FEE_BPS = 1000; // 10%
-    const protocolFee = Math.floor((totalStakeMicroUsdc * PROTOCOL_FEE_BPS) / 10_000);
+    const protocolFee = calculateProtocolFee(totalStakeMicroUsdc);
     const distributablePool = totalStakeMicroUsdc - protocolFee;
 
     const scored: ScoredOpinion[] = opinions.map((op) => {
@@ -493,11 +529,17 @@ class SettlementCoordinator {
     // TODO: Replace with actual Anchor CPI calls:
     //
     // 1. await program.methods
-    //      .recordSentiment(sentimentScore, confidence, Array.from(summaryHash))
+    //      .recordSentiment(sentimentScore, confidence, Array.from(summaryHash),
+    //        { modelHash, promptHash, runAt: new BN(runStartedAt) })
     //      .accounts({ oracleAuthority, config, market })
     //      .signers([oracleKeypair])
     //      .rpc();
     //
+    // 1b. await program.methods
+    //      .postWeightRange(new BN(minNetBacking), new BN(maxNetBacking))
+    //      .accounts({ oracleAuthority, config, market })
+    //      .rpc();
+    //
     // 2. for (const op of scoredOpinions) {
     //      await program.methods
     //        .recordAiScore(op.ai_score)
@@ -513,13 +555,22 @@ class SettlementCoordinator {
     //    }
     //
     // 4. await program.methods
-    //      .finalizeSettlement()
-    //      .accounts({ oracleAuthority, config, market, escrowTokenAccount, treasuryUsdc, tokenProgram })
+    //      .finalizeSettlement(
+    //        new BN(totalNetBacking),
+    //        new BN(totalPredictionWeight),
+    //        0, // top_n_cutoff — only read by TopN payout-mode markets, which check it on-chain
+    //      )
+    //      .accounts({ oracleAuthority, config, market, escrowTokenAccount, treasuryUsdc, oracleUsdc, treasuryLedger, tokenProgram })
     //      .rpc();
     //
     // 5. for (const op of scoredOpinions) {
+    //      // Counts the score in the staker's profile even if it paid nothing
+    //      await program.methods
+    //        .recordOpinionScore()
+    //        .accounts({ payer, market, opinion: opinionPDA, userProfile })
+    //        .rpc();
     //      await program.methods
-    //        .claimPayout(new BN(1), new BN(totalNetBacking), new BN(totalPredictionWeight))
+    //        .claimPayout(null) // null = claim everything available
     //        .accounts({ staker, config, market, escrowTokenAccount, opinion: opinionPDA, stakerUsdc, tokenProgram })
     //        .rpc();
     //    }
@@ -1,40 +1,237 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_lang::InstructionData;
+use anchor_lang::solana_program::instruction::{
+    get_stack_height, AccountMeta, Instruction, TRANSACTION_LEVEL_STACK_HEIGHT,
+};
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use solana_bn254::prelude::{alt_bn128_addition, alt_bn128_multiplication, alt_bn128_pairing};
+use solana_curve25519::ristretto::{multiscalar_multiply_ristretto, PodRistrettoPoint};
+use solana_curve25519::scalar::PodScalar;
+use solana_address_lookup_table_interface::{
+    instruction::{create_lookup_table, extend_lookup_table},
+    state::AddressLookupTable,
+};
+use solana_sha256_hasher::hashv;
+use switchboard_on_demand::{RandomnessAccountData, SWITCHBOARD_ON_DEMAND_PROGRAM_ID};
+use anchor_lang::solana_program::sysvar::instructions::{
+    self as sysvar_instructions, load_current_index_checked, load_instruction_at_checked,
+};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::memo::{self, Memo};
+use anchor_spl::token::{
+    self, spl_token::instruction::AuthorityType, CloseAccount, FreezeAccount, Mint, MintTo,
+    SetAuthority, ThawAccount, Token, TokenAccount, Transfer,
+};
+use anchor_spl::token_2022::{
+    self,
+    spl_token_2022::{self, extension::ExtensionType},
+    Token2022,
+};
+use anchor_spl::token_interface;
+use spl_token_2022::extension::confidential_transfer::{
+    instruction as confidential_transfer, DecryptableBalance,
+};
+use spl_token_2022::solana_zk_sdk::encryption::pod::elgamal::PodElGamalCiphertext;
+use spl_token_confidential_transfer_proof_extraction::instruction::ProofLocation;
 
 declare_id!("2NaUpg4jEZVGDBmmuKYLdsAfSGKwHxjghhfgVpQvZJYu");
 
 // ── Constants ────────────────────────────────────────────────────────────────
 /// $5.00 USDC (6 decimal places)
 pub const CREATE_FEE: u64 = 5_000_000;
+/// Opinions a market needs for its escrowed creation fee to be refunded
+pub const DEFAULT_FEE_REFUND_MIN_OPINIONS: u32 = 5;
+/// Creation-fee rebate tiers: (lifetime settled volume, rebate bps), ascending
+pub const CREATOR_REBATE_TIERS: [(u64, u16); 3] = [
+    (1_000_000_000, 1_000),   // $1k volume   → 10% off
+    (10_000_000_000, 5_000),  // $10k volume  → 50% off
+    (100_000_000_000, 7_500), // $100k volume → 75% off
+];
 /// $0.50 USDC
 pub const MIN_STAKE: u64 = 500_000;
 /// $10.00 USDC
 pub const MAX_STAKE: u64 = 10_000_000;
-/// 10% protocol fee on prize pool
-pub const PROTOCOL_FEE_BPS: u64 = 1_000;
-pub const MAX_STATEMENT_LEN: usize = 280;
+/// $0.10 USDC — most a relayer may charge for submitting a stake intent
+pub const MAX_RELAY_FEE: u64 = 100_000;
+/// Default marginal protocol fee schedule: 10% on the first $1k of pool,
+/// 5% beyond (unused tiers start at u64::MAX)
+pub const DEFAULT_FEE_TIERS: [FeeTier; MAX_FEE_TIERS] = [
+    FeeTier { threshold: 0, bps: 1_000 },
+    FeeTier { threshold: 1_000_000_000, bps: 500 },
+    FeeTier { threshold: u64::MAX, bps: 0 },
+    FeeTier { threshold: u64::MAX, bps: 0 },
+];
+/// Tiers in the protocol fee schedule
+pub const MAX_FEE_TIERS: usize = 4;
+/// Longest statement in bytes; statement accounts are sized to the text they
+/// hold, and append_statement extends it past what create_market takes
+pub const MAX_STATEMENT_LEN: usize = 1000;
+/// Longest statement chunk create_market or append_statement takes. Bounded by
+/// the 1232-byte legacy transaction: create_market's 20 distinct accounts and
+/// fixed arguments take 806 bytes, a priority-fee instruction 44 more, which
+/// leaves 382 for the statement.
+pub const MAX_STATEMENT_CHUNK_LEN: usize = 380;
 pub const MAX_IPFS_CID_LEN: usize = 64;
+/// Layout version written to every account at creation; accounts from the
+/// unversioned baseline read as 0. Bump it with the next layout change and
+/// teach the migrate_* instructions the upgrade.
+pub const ACCOUNT_SCHEMA_VERSION: u8 = 1;
+/// Most confidential stakes a market's confidential escrow holds pending
+/// until release_confidential_stakes applies them
+pub const CONFIDENTIAL_PENDING_CREDIT_LIMIT: u64 = 65_536;
+/// Time after closes_at stakers have to reveal confidential stakes before
+/// release_confidential_stakes may forfeit the unrevealed ones (2 days)
+pub const CONFIDENTIAL_REVEAL_WINDOW: i64 = 172_800;
+/// Max reactions per react_batch call (2 remaining accounts each)
+pub const MAX_BATCH_REACTIONS: usize = 16;
+/// Max opinions per oracle batch instruction (1 remaining account each)
+pub const MAX_ORACLE_BATCH: usize = 32;
+/// Max claims per claim_payouts_batch call (3 remaining accounts each)
+pub const MAX_BATCH_CLAIMS: usize = 8;
+/// Max opinions or reactions closed per archive call (2–3 remaining accounts each)
+pub const MAX_ARCHIVE_BATCH: usize = 16;
+/// Max Merkle proof depth accepted by claim_payout_with_proof (~1M leaves)
+pub const MAX_MERKLE_PROOF_LEN: usize = 20;
+/// Max oracle committee members
+pub const MAX_COMMITTEE_SIZE: usize = 7;
+/// Time during which an optimistic settlement root may be disputed (24h)
+pub const CHALLENGE_WINDOW: i64 = 86_400;
+/// USDC bond posted to dispute an optimistic settlement ($10.00)
+pub const CHALLENGE_BOND: u64 = 10_000_000;
+/// Oracle silence after which closed markets may refund before score_deadline (3 days)
+pub const ORACLE_LIVENESS_TIMEOUT: i64 = 259_200;
+/// USDC bond an author posts to challenge their opinion's ai_score ($1.00)
+pub const AI_CHALLENGE_BOND: u64 = 1_000_000;
+/// Public inputs of the scoring circuit: [inputs_hash, verified_scores_root, crowd_score]
+pub const ZK_PUBLIC_INPUTS: usize = 3;
+/// BN254 base field modulus (big-endian), used to negate G1 points
+pub const BN254_FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+];
+/// Pedersen generators of the Token-2022 confidential transfer commitments
+/// (compressed Ristretto): G is the basepoint, H the hash-derived blinding base
+pub const PEDERSEN_G: [u8; 32] = [
+    226, 242, 174, 10, 106, 188, 78, 113, 168, 132, 169, 97, 197, 0, 81, 95,
+    88, 227, 11, 106, 165, 130, 221, 141, 182, 166, 89, 69, 224, 141, 45, 118,
+];
+pub const PEDERSEN_H: [u8; 32] = [
+    140, 146, 64, 180, 86, 169, 230, 220, 101, 195, 119, 161, 4, 141, 116, 95,
+    148, 160, 140, 219, 127, 68, 203, 205, 123, 70, 243, 64, 72, 135, 17, 52,
+];
+/// Bits of a confidential transfer amount carried by its low ciphertext
+pub const CONFIDENTIAL_AMOUNT_LO_BITS: u32 = 16;
+/// Native Ed25519 signature verification program
+pub const ED25519_PROGRAM_ID: Pubkey = pubkey!("Ed25519SigVerify111111111111111111111111111");
+/// Clockwork thread program (v2) that runs market lifecycle threads
+pub const CLOCKWORK_THREAD_PROGRAM_ID: Pubkey =
+    pubkey!("CLoCKyJ6DXBJqqu2VWx9RLbgnwwR6BMHHuyD5bqF7ap");
+/// 0.01 SOL moved into a lifecycle thread to pay its execution fees
+pub const LIFECYCLE_THREAD_FUNDING: u64 = 10_000_000;
+/// Metaplex Bubblegum program that mints winner badges as compressed NFTs
+pub const BUBBLEGUM_PROGRAM_ID: Pubkey = pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+/// SPL account compression program backing Bubblegum trees
+pub const ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey =
+    pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+/// SPL noop program Bubblegum logs leaves through
+pub const NOOP_PROGRAM_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+/// Winner badge metadata URI prefix; the market, rank and score follow
+pub const BADGE_URI_BASE: &str = "https://opinion.markets/badge/";
+/// Pyth Solana receiver program, owner of PriceUpdateV2 accounts
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey =
+    pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+/// A Pyth-resolved market reads the first price published within this many
+/// seconds after closes_at
+pub const PYTH_RESOLUTION_WINDOW: i64 = 300;
+/// Wormhole core bridge that settlement messages are posted to
+pub const WORMHOLE_CORE_BRIDGE_ID: Pubkey =
+    pubkey!("worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth");
+/// Core bridge post_message instruction tag
+pub const WORMHOLE_POST_MESSAGE_IX: u8 = 1;
+/// Guardians sign settlement messages once the slot is finalized
+pub const WORMHOLE_CONSISTENCY_FINALIZED: u8 = 1;
+/// Layout version of the cross-chain settlement payload
+pub const SETTLEMENT_PAYLOAD_VERSION: u8 = 1;
+/// Metaplex Core program that holds each market's asset
+pub const MPL_CORE_PROGRAM_ID: Pubkey = pubkey!("CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d");
+/// Core CreateV1 / UpdatePluginV1 instruction tags
+pub const MPL_CORE_CREATE_V1_IX: u8 = 0;
+pub const MPL_CORE_UPDATE_PLUGIN_V1_IX: u8 = 6;
+/// Core Plugin::Attributes tag
+pub const MPL_CORE_ATTRIBUTES_PLUGIN: u8 = 6;
+/// Market asset metadata URI prefix; the market key follows
+pub const MARKET_ASSET_URI_BASE: &str = "https://opinion.markets/market/";
 
 /// Triple-Check scoring formula weights (must sum to 100)
 /// S = (W × 0.5) + (C × 0.3) + (A × 0.2)
 pub const WEIGHT_MULTIPLIER: u64 = 50;     // 50% — Layer 1: peer backing
 pub const CONSENSUS_MULTIPLIER: u64 = 30;  // 30% — Layer 2: crowd alignment
 pub const AI_MULTIPLIER: u64 = 20;         // 20% — Layer 3: AI quality
+/// Default admin bounds on each per-market layer weight (percent)
+pub const DEFAULT_MIN_LAYER_WEIGHT: u8 = 10;
+pub const DEFAULT_MAX_LAYER_WEIGHT: u8 = 70;
 
 /// Duration options in seconds
 pub const DURATION_24H: u64 = 86_400;
 pub const DURATION_3D: u64 = 259_200;
 pub const DURATION_7D: u64 = 604_800;
 pub const DURATION_14D: u64 = 1_209_600;
-/// Time after market closes before stakers can recover stakes (14 days)
+/// Time after market closes before an unfinalized market may refund stakes (14 days)
 pub const RECOVERY_PERIOD: i64 = 1_209_600;
+/// Ceiling on the keeper bounty deducted by push_payout (5%)
+pub const MAX_PUSH_BOUNTY_BPS: u16 = 500;
+/// Stakers claim their own payouts for PUSH_GRACE_PERIOD (7 days) after
+/// settled_at before keepers may push them for a bounty
+pub const PUSH_GRACE_PERIOD: i64 = 604_800;
+/// Ceiling on a market's settlement bounty budget, as a share of its protocol fee (50%)
+pub const MAX_SETTLEMENT_BOUNTY_CAP_BPS: u16 = 5_000;
+/// Highest share of each protocol fee routed to the insurance vault (20%)
+pub const MAX_INSURANCE_SHARE_BPS: u16 = 2_000;
+/// Default smallest payout worth claiming: $0.01 USDC
+pub const DEFAULT_MIN_PAYOUT: u64 = 10_000;
+/// Ceiling on config.min_payout: $1 USDC
+pub const MAX_MIN_PAYOUT: u64 = 1_000_000;
+/// Payouts must be claimed within CLAIM_WINDOW (90 days) of settled_at
+pub const CLAIM_WINDOW: i64 = 7_776_000;
+/// After the claim window, opted-in claimants have 30 days to collect their
+/// share of expired payouts before the remainder can be swept
+pub const REDISTRIBUTION_WINDOW: i64 = 2_592_000;
+/// UserProfile reputation halves every REPUTATION_HALF_LIFE (30 days)
+pub const REPUTATION_HALF_LIFE: i64 = 2_592_000;
+/// Reputation points per combined_score point, for precision under decay
+pub const REPUTATION_SCALE: u64 = 1_000;
+/// Longest optional reaction window after opinion submission closes (3 days)
+pub const MAX_REACTION_WINDOW: u64 = 259_200;
+
+/// Back aligns with a combined_score at or above this; Slash aligns below it
+pub const REACTION_ALIGNMENT_THRESHOLD: u8 = 50;
+/// Number of reactors tracked on the global leaderboard
+pub const REACTOR_LEADERBOARD_SIZE: usize = 20;
+/// Number of users tracked on the global top-earners leaderboard
+pub const EARNER_LEADERBOARD_SIZE: usize = 50;
+
+/// A slashed opinion settling at or above this combined_score is "vindicated"
+pub const VINDICATION_SCORE_THRESHOLD: u8 = 70;
+/// Default share of slashers' stakes paid to a vindicated author (50%)
+pub const DEFAULT_VINDICATION_SHARE_BPS: u16 = 5_000;
+/// Highest principal refund floor a creator may guarantee (90%)
+pub const MAX_REFUND_FLOOR_BPS: u16 = 9_000;
+/// Largest slice of the distributable pool a charity market may donate (50%)
+pub const MAX_CHARITY_POOL_BPS: u16 = 5_000;
+/// Longest payout vesting period; must end well inside CLAIM_WINDOW
+pub const MAX_VESTING_DAYS: u16 = 60;
+
+/// Number of distinct emoji codes accepted by lite_react (0..LITE_REACTION_KINDS)
+pub const LITE_REACTION_KINDS: usize = 8;
+/// Minimum seconds between two lite reactions from the same wallet
+pub const LITE_REACT_COOLDOWN: i64 = 30;
 
 // ── Errors ───────────────────────────────────────────────────────────────────
 #[error_code]
 pub enum OpinionError {
     #[msg("Statement cannot be empty")]
     StatementEmpty,
-    #[msg("Statement exceeds 280 characters")]
+    #[msg("Statement exceeds 1000 bytes, or a chunk of it 380")]
     StatementTooLong,
     #[msg("Duration must be 24h, 3d, 7d, or 14d")]
     InvalidDuration,
@@ -66,6 +263,8 @@ pub enum OpinionError {
     MintMismatch,
     #[msg("Treasury pubkey mismatch")]
     TreasuryMismatch,
+    #[msg("Treasury token account is frozen or has a delegate or close authority")]
+    UnsafeTreasuryAccount,
     #[msg("Prize pool is zero — no stakes to distribute")]
     EmptyPrizePool,
     #[msg("Market is not in AwaitingRandomness state")]
@@ -86,6 +285,334 @@ pub enum OpinionError {
     InvalidOpinionScore,
     #[msg("Jackpot has already been claimed for this market")]
     JackpotAlreadyClaimed,
+    #[msg("Batch must contain at least one entry")]
+    EmptyBatch,
+    #[msg("Batch exceeds the maximum number of entries")]
+    BatchTooLarge,
+    #[msg("Remaining accounts do not match batch entries")]
+    BatchAccountsMismatch,
+    #[msg("Opinion does not belong to this market")]
+    OpinionMarketMismatch,
+    #[msg("Reaction account does not match expected PDA")]
+    InvalidReactionAccount,
+    #[msg("Reaction has already been scored")]
+    ReactionAlreadyScored,
+    #[msg("Basis points cannot exceed 10000")]
+    InvalidBps,
+    #[msg("Fee tiers must start at 0 with ascending thresholds")]
+    InvalidFeeSchedule,
+    #[msg("Unknown emoji reaction code")]
+    InvalidEmojiCode,
+    #[msg("Lite reaction cooldown has not elapsed")]
+    LiteReactCooldown,
+    #[msg("Market options can only be changed before the first stake")]
+    MarketAlreadyHasStakes,
+    #[msg("Reaction window exceeds the maximum")]
+    ReactionWindowTooLong,
+    #[msg("Reaction cap must be non-zero")]
+    InvalidReactionCap,
+    #[msg("Reaction would exceed this opinion's backing cap")]
+    BackingCapExceeded,
+    #[msg("Reaction would exceed this opinion's slashing cap")]
+    SlashingCapExceeded,
+    #[msg("Merkle proof does not match the settlement root")]
+    InvalidMerkleProof,
+    #[msg("Merkle proof exceeds the maximum depth")]
+    MerkleProofTooLong,
+    #[msg("Compressed reaction tree is full")]
+    ReactionTreeFull,
+    #[msg("Proof root is no longer in the reaction tree's changelog; refetch the proof")]
+    StaleReactionProof,
+    #[msg("Reaction proof does not match the reaction tree")]
+    InvalidReactionProof,
+    #[msg("Market was settled by Merkle root — claim with a proof")]
+    MerkleSettlementActive,
+    #[msg("Market was not settled by Merkle root")]
+    NotMerkleSettled,
+    #[msg("Payouts exceed the distributable pool")]
+    PayoutExceedsPool,
+    #[msg("Expected an Ed25519 verification instruction before this one")]
+    MissingSignatureInstruction,
+    #[msg("Ed25519 instruction does not carry the oracle's signature over this payload")]
+    InvalidOracleSignature,
+    #[msg("Oracle committee is full")]
+    CommitteeFull,
+    #[msg("Committee threshold must be between 0 and the number of members")]
+    InvalidThreshold,
+    #[msg("A committee-approved settlement proposal is required")]
+    ProposalRequired,
+    #[msg("Settlement proposal does not match this action")]
+    ProposalMismatch,
+    #[msg("Settlement proposal lacks enough committee approvals")]
+    InsufficientApprovals,
+    #[msg("Committee member has already approved this proposal")]
+    AlreadyApproved,
+    #[msg("Challenge window for this settlement has closed")]
+    ChallengeWindowClosed,
+    #[msg("Challenge window for this settlement is still open")]
+    ChallengeWindowOpen,
+    #[msg("Settlement is under dispute")]
+    SettlementDisputed,
+    #[msg("Settlement is not under dispute")]
+    SettlementNotDisputed,
+    #[msg("An optimistic settlement proposal is pending or disputed")]
+    OptimisticSettlementOpen,
+    #[msg("crowd_score does not match the on-chain stake-weighted mean or median")]
+    CrowdScoreMismatch,
+    #[msg("Market has already been settled")]
+    MarketAlreadySettled,
+    #[msg("Market is not in Refunding state")]
+    MarketNotRefunding,
+    #[msg("Oracle bond accounts are required")]
+    OracleBondRequired,
+    #[msg("Oracle bond is below the required minimum")]
+    OracleBondBelowMinimum,
+    #[msg("AI-score challenges are still awaiting an oracle response")]
+    OpenAiChallenges,
+    #[msg("AI-score challenge has already been resolved")]
+    ChallengeAlreadyResolved,
+    #[msg("Scoring weights must sum to 100 and respect the admin bounds")]
+    InvalidScoringWeights,
+    #[msg("Layer weight bounds must admit a combination summing to 100")]
+    InvalidWeightBounds,
+    #[msg("Top-N payout mode needs N of at least 1")]
+    InvalidPayoutMode,
+    #[msg("Refund floor exceeds the maximum")]
+    InvalidRefundFloor,
+    #[msg("Opinion has already been settled — use correct_settlement")]
+    OpinionAlreadySettled,
+    #[msg("Opinion has not been settled yet")]
+    OpinionNotSettled,
+    #[msg("Every opinion must be settled before finalize_settlement")]
+    UnsettledOpinions,
+    #[msg("Oracle has not posted a scores root for this market")]
+    ScoresRootNotPosted,
+    #[msg("Opinion inputs have already been snapshotted")]
+    InputsAlreadySnapshotted,
+    #[msg("Every opinion's inputs must be snapshotted before posting verified scores")]
+    InputsSnapshotIncomplete,
+    #[msg("Market volume has already been credited to its creator")]
+    VolumeAlreadyRecorded,
+    #[msg("Claim window has not ended yet")]
+    ClaimWindowOpen,
+    #[msg("Claim window has ended; this payout has expired")]
+    ClaimWindowClosed,
+    #[msg("Opinion must be paid before opting in to redistribution")]
+    NotPaid,
+    #[msg("Opinion is not opted in to redistribution, or already collected its share")]
+    NotRedistributable,
+    #[msg("Redistribution window has ended")]
+    RedistributionClosed,
+    #[msg("Scoring queue is full")]
+    ScoringQueueFull,
+    #[msg("Market is already in the scoring queue")]
+    AlreadyQueued,
+    #[msg("weight_score does not match the opinion's net backing and the posted range")]
+    WeightScoreMismatch,
+    #[msg("Oracle has not posted the net-backing range for this market")]
+    WeightRangeNotPosted,
+    #[msg("Net-backing range can only be posted before any opinion is settled")]
+    WeightRangeLocked,
+    #[msg("Opinion net backing lies outside the posted range")]
+    NetBackingOutOfRange,
+    #[msg("Posted net-backing range was not attained by any settled opinion")]
+    WeightRangeNotAttained,
+    #[msg("consensus_score does not match the opinion's prediction and crowd_score")]
+    ConsensusScoreMismatch,
+    #[msg("Settlement requires the configured reviewer's signature")]
+    ReviewerSignatureRequired,
+    #[msg("Reviewer must be independent of the oracle")]
+    ReviewerNotIndependent,
+    #[msg("Insurance vault accounts are required while the insurance share is set")]
+    InsuranceVaultRequired,
+    #[msg("Insurance vault balance is too low for this claim")]
+    InsufficientInsuranceFunds,
+    #[msg("No swap program is whitelisted for swapped claims and stakes")]
+    SwapProgramNotAllowed,
+    #[msg("Swap returned less than the minimum output amount")]
+    SwapOutputTooLow,
+    #[msg("Swapped stakes must spend a token other than USDC")]
+    SwapInputIsUsdc,
+    #[msg("No confidential mint is configured for confidential stakes")]
+    ConfidentialStakesDisabled,
+    #[msg("Market takes confidential stakes only")]
+    ConfidentialStakeRequired,
+    #[msg("Market does not take confidential stakes")]
+    NotConfidentialMarket,
+    #[msg("Stake-multiple reaction caps are unavailable on confidential markets")]
+    ConfidentialCapUnsupported,
+    #[msg("Confidential stake was already revealed")]
+    ConfidentialStakeAlreadyRevealed,
+    #[msg("Confidential stakes were already released")]
+    ConfidentialStakesAlreadyReleased,
+    #[msg("Confidential stakes must be released before the market is scored")]
+    ConfidentialStakesNotReleased,
+    #[msg("Released confidential balance does not match the revealed stakes")]
+    ConfidentialAggregateMismatch,
+    #[msg("Opinion's confidential stake was never revealed")]
+    ConfidentialStakeUnrevealed,
+    #[msg("Opening does not match the confidential stake's committed amount")]
+    InvalidConfidentialOpening,
+    #[msg("Confidential stakes are still inside their reveal window")]
+    ConfidentialRevealWindowOpen,
+    #[msg("Stake exceeds the compound vault's per-market limit or delegate allowance")]
+    CompoundAllowanceExceeded,
+    #[msg("Staker's reputation is below the fee waiver threshold")]
+    ReputationTooLow,
+    #[msg("Fee waiver has already been applied to this opinion")]
+    FeeWaiverAlreadyApplied,
+    #[msg("Payout is below the minimum payout threshold")]
+    PayoutBelowThreshold,
+    #[msg("No lending program is whitelisted for escrow yield")]
+    LendingProgramNotAllowed,
+    #[msg("Escrow is still deposited in the lending market")]
+    YieldStillDeposited,
+    #[msg("Lending withdrawal returned less than the deposited principal")]
+    PrincipalNotReturned,
+    #[msg("Escrow balance did not change by the requested amount")]
+    EscrowAmountMismatch,
+    #[msg("No escrow is deposited in the lending market")]
+    NoYieldDeposited,
+    #[msg("Receipt account does not match the market's lending position")]
+    YieldReceiptMismatch,
+    #[msg("Payout cap tally has not covered every opinion yet")]
+    PayoutCapTallyIncomplete,
+    #[msg("Opinion has already been counted in the payout cap tally")]
+    AlreadyTallied,
+    #[msg("Market has no payout cap")]
+    NoPayoutCap,
+    #[msg("Vesting period must be 1..=MAX_VESTING_DAYS days")]
+    InvalidVestingSchedule,
+    #[msg("No newly vested payout to claim yet")]
+    NothingVested,
+    #[msg("Requested amount exceeds the payout available to claim")]
+    ClaimExceedsAvailable,
+    #[msg("Payout must go to the staker's registered payout destination")]
+    PayoutDestinationMismatch,
+    #[msg("Charity pool share exceeds MAX_CHARITY_POOL_BPS or has no charity account")]
+    InvalidCharityConfig,
+    #[msg("Charity token account is not in the charity registry")]
+    CharityNotRegistered,
+    #[msg("Charity token account is required to finalize this market")]
+    CharityAccountRequired,
+    #[msg("Charity markets must settle through finalize_settlement")]
+    CharityRequiresClassicSettlement,
+    #[msg("Creation fee has already been released")]
+    CreationFeeReleased,
+    #[msg("Market is locked to the other settlement path")]
+    SettlementPathLocked,
+    #[msg("No-loss markets must settle through finalize_settlement")]
+    NoLossRequiresClassicSettlement,
+    #[msg("Sponsorship is only accepted before the market is scored")]
+    SponsorshipClosed,
+    #[msg("Results page is full; open the next page first")]
+    ResultsPageFull,
+    #[msg("Previous results page must be passed and full before opening a new one")]
+    ResultsPageNotFull,
+    #[msg("Account is already at the current schema version")]
+    AlreadyMigrated,
+    #[msg("No points season is running")]
+    PointsSeasonInactive,
+    #[msg("Fee points for this opinion were already claimed")]
+    PointsAlreadyClaimed,
+    #[msg("Reputation already minted for this opinion")]
+    ReputationAlreadyClaimed,
+    #[msg("Stake intent has expired")]
+    IntentExpired,
+    #[msg("Stake intent is for a different market")]
+    IntentMarketMismatch,
+    #[msg("Relay fee exceeds MAX_RELAY_FEE")]
+    RelayFeeTooHigh,
+    #[msg("Payment reference requires both the reference and its record account")]
+    PaymentReferenceRequired,
+    #[msg("Payment reference does not match the expected market or amount")]
+    PaymentReferenceMismatch,
+    #[msg("Partner revenue share exceeds MAX_PARTNER_SHARE_BPS")]
+    PartnerShareTooHigh,
+    #[msg("Releasing a partner share requires the partner entry and its payout_usdc")]
+    PartnerAccountsRequired,
+    #[msg("Market was not created via CPI from the partner program")]
+    PartnerCallerMismatch,
+    #[msg("Switchboard randomness account is invalid or was not committed this slot")]
+    InvalidRandomnessAccount,
+    #[msg("Switchboard randomness has not been revealed for the committed slot")]
+    RandomnessNotRevealed,
+    #[msg("Lottery randomness has already been consumed")]
+    RandomnessAlreadyConsumed,
+    #[msg("Lottery randomness has not been consumed yet")]
+    RandomnessNotConsumed,
+    #[msg("Pass every opinion of the market once, sorted by address")]
+    LotteryOpinionsMismatch,
+    #[msg("Pass the market's current audit page (open page 0 first on older markets)")]
+    AuditLogRequired,
+    #[msg("Audit page has no room for this batch; open the next page first")]
+    AuditPageFull,
+    #[msg("Previous audit page must be passed and lack room for a full oracle batch")]
+    AuditPageNotFull,
+    #[msg("Median crowd score markets require the crowd histogram account")]
+    CrowdHistogramRequired,
+    #[msg("Scoring proof failed verification")]
+    InvalidScoringProof,
+    #[msg("No ZK-verified scores root has been posted")]
+    VerifiedScoresNotPosted,
+    #[msg("Registry page does not list this market")]
+    RegistryEntryMismatch,
+    #[msg("Statement does not match the market's statement")]
+    StatementMismatch,
+    #[msg("The market's creation fee escrow must be passed while it holds the fee")]
+    CreationFeeEscrowRequired,
+    #[msg("Last checkpoint is less than CHECKPOINT_INTERVAL old")]
+    CheckpointTooSoon,
+    #[msg("Lookup table is not the market's")]
+    LookupTableMismatch,
+    #[msg("Market already has a lifecycle thread")]
+    LifecycleThreadExists,
+    #[msg("Thread is not the market's lifecycle thread")]
+    LifecycleThreadMismatch,
+    #[msg("Market is resolved by its Pyth feed, not the oracle")]
+    ObjectiveMarket,
+    #[msg("Market has no Pyth resolution")]
+    NotObjectiveMarket,
+    #[msg("Account is not a fully verified Pyth price update for the market's feed")]
+    InvalidPriceUpdate,
+    #[msg("Price was not published within PYTH_RESOLUTION_WINDOW after close")]
+    PriceOutsideResolutionWindow,
+    #[msg("Winner badge already minted for this opinion")]
+    BadgeAlreadyMinted,
+    #[msg("Program is not whitelisted as a settle hook")]
+    HookNotWhitelisted,
+    #[msg("Program is already subscribed to this market's settlement")]
+    SettleHookExists,
+    #[msg("Program is not subscribed to this market's settlement")]
+    SettleHookNotSubscribed,
+    #[msg("Market already has MAX_SETTLE_HOOKS settle hooks")]
+    SettleHooksFull,
+    #[msg("Market has settle hooks; pass settle_hooks, hook_authority and the hook programs")]
+    SettleHooksRequired,
+    #[msg("Remaining accounts do not match the market's settle hook programs")]
+    SettleHookAccountsMismatch,
+    #[msg("Publishing to Wormhole requires every wormhole account")]
+    WormholeAccountsRequired,
+    #[msg("Market already has a Metaplex Core asset")]
+    MarketAssetExists,
+    #[msg("Minimum payout exceeds MAX_MIN_PAYOUT")]
+    MinPayoutTooHigh,
+    #[msg("Payouts can only be pushed PUSH_GRACE_PERIOD after settlement")]
+    PushGracePeriodActive,
+    #[msg("TopN cutoff or weight total does not match the settled combined scores")]
+    TopNMismatch,
+    #[msg("Signed message nonce is below the market's signed_nonce")]
+    StaleSignedNonce,
+    #[msg("This opinion's score is already recorded in the staker's profile")]
+    ScoreAlreadyRecorded,
+    #[msg("A disputed proposal's bond must be returned: pass the optimistic settlement, challenger USDC and escrow authority")]
+    ChallengeBondAccountsRequired,
+    #[msg("Payout divisors do not match the totals tallied as opinions settled")]
+    SettlementTotalsMismatch,
+    #[msg("A hashed market's statement is kept off-chain")]
+    StatementHashed,
+    #[msg("Refunded principal must be non-zero and within the principal still outstanding")]
+    RefundExceedsOutstanding,
 }
 
 // ── State Enums ──────────────────────────────────────────────────────────────
@@ -96,6 +623,30 @@ pub enum MarketState {
     Scored,             // Awaiting Triple-Check settlement
     AwaitingRandomness, // Legacy: kept for backward compatibility
     Settled,
+    Refunding,          // Oracle missed score_deadline — stakes returned at par
+}
+
+impl MarketState {
+    /// Display name, as shown in the market's asset attributes
+    pub fn label(&self) -> &'static str {
+        match self {
+            MarketState::Active => "Active",
+            MarketState::Closed => "Closed",
+            MarketState::Scored => "Scored",
+            MarketState::AwaitingRandomness => "AwaitingRandomness",
+            MarketState::Settled => "Settled",
+            MarketState::Refunding => "Refunding",
+        }
+    }
+}
+
+/// Settlement path a Scored market is locked to by its first settlement action
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SettlementMode {
+    Unset,
+    TripleCheck, // settle_opinion / finalize_settlement or a Merkle root
+    Lottery,     // run_lottery_random
+    Objective,   // resolve_pyth_market
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
@@ -104,15 +655,465 @@ pub enum ReactionType {
     Slash,  // Disagree — adds to slashing_total
 }
 
+/// Action recorded in an ActivityLog entry (stored as its u8 value)
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ActivityKind {
+    Stake,
+    Back,
+    Slash,
+    Close,
+}
+
+impl From<&ReactionType> for ActivityKind {
+    fn from(reaction_type: &ReactionType) -> Self {
+        match reaction_type {
+            ReactionType::Back => ActivityKind::Back,
+            ReactionType::Slash => ActivityKind::Slash,
+        }
+    }
+}
+
+/// Per-opinion ceiling on backing_total or slashing_total
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ReactionCap {
+    None,
+    /// Absolute ceiling in micro-USDC
+    Absolute(u64),
+    /// Ceiling as a multiple of the author's own stake
+    StakeMultiple(u16),
+}
+
+impl ReactionCap {
+    pub const SPACE: usize = 1 + 8;
+
+    /// Resolved ceiling for an opinion with the given author stake, if any
+    pub fn limit(&self, stake_amount: u64) -> Option<u64> {
+        match *self {
+            ReactionCap::None => None,
+            ReactionCap::Absolute(amount) => Some(amount),
+            ReactionCap::StakeMultiple(multiple) => Some(stake_amount.saturating_mul(multiple as u64)),
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        !matches!(self, ReactionCap::Absolute(0) | ReactionCap::StakeMultiple(0))
+    }
+
+    /// Whether the ceiling scales with the author's stake
+    pub fn is_stake_multiple(&self) -> bool {
+        matches!(self, ReactionCap::StakeMultiple(_))
+    }
+}
+
+/// How the opinion pool (70%) is divided at claim_payout
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PayoutMode {
+    /// Pro-rata to net backing (backing − slashing)
+    Proportional,
+    /// The N highest combined scores split the pool equally; opinions tied
+    /// at the cutoff split the slots left below it
+    TopN(u8),
+    /// Pro-rata to 2^(combined_score / 10), favouring the best opinions
+    Exponential,
+}
+
+impl PayoutMode {
+    pub const SPACE: usize = 1 + 1;
+}
+
+/// Markets the global ScoringQueue can hold at once
+pub const MAX_SCORING_QUEUE: usize = 128;
+
+/// Entries per AuditLog page
+pub const AUDIT_PAGE_SIZE: usize = 32;
+
+// A freshly opened audit page takes any oracle batch
+const _: () = assert!(AUDIT_PAGE_SIZE >= MAX_ORACLE_BATCH);
+
+/// Opinions per ResultsArchive page
+pub const RESULTS_PAGE_SIZE: usize = 32;
+
+/// Depth of a market's compressed reaction tree (~1M reactions)
+pub const REACTION_TREE_DEPTH: usize = 20;
+/// Recent tree updates kept so proofs against a slightly stale root still apply
+pub const REACTION_TREE_BUFFER: usize = 8;
+
+/// Number of leaderboard slots kept on each Market
+pub const PODIUM_SIZE: usize = 3;
+
+/// Buckets in a market's combined_score histogram, one per score 0–100
+pub const COMBINED_SCORE_BUCKETS: usize = 101;
+
+/// A TopN market's boundary: `above` opinions score over `cutoff` and take
+/// a whole slot each, and the `tied` opinions at the cutoff share the
+/// `slots - above` left, so the pool divides into exactly `slots` shares
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct TopNSplit {
+    pub cutoff: u8,
+    pub above: u32,
+    pub tied: u32,
+    pub slots: u32,
+}
+
+impl TopNSplit {
+    /// Pool weight of an opinion with `combined_score`; one slot weighs `tied`
+    pub fn weight(&self, combined_score: u8) -> u64 {
+        match combined_score.cmp(&self.cutoff) {
+            std::cmp::Ordering::Greater => self.tied as u64,
+            std::cmp::Ordering::Equal => self.slots.saturating_sub(self.above) as u64,
+            std::cmp::Ordering::Less => 0,
+        }
+    }
+
+    /// Σ weight over every settled opinion
+    pub fn total_weight(&self) -> u64 {
+        self.slots as u64 * self.tied as u64
+    }
+}
+
+/// Markets listed per MarketRegistryPage
+pub const REGISTRY_PAGE_SIZE: usize = 64;
+
+/// Minimum spacing between a market's checkpoints (1 hour)
+pub const CHECKPOINT_INTERVAL: i64 = 3_600;
+
+/// Recent actions kept in each market's ActivityLog
+pub const ACTIVITY_LOG_SIZE: usize = 32;
+
+/// Opinion keys per OpinionIndexPage
+pub const OPINION_INDEX_PAGE_SIZE: usize = 64;
+
+/// External programs that may subscribe to one market's settlement
+pub const MAX_SETTLE_HOOKS: usize = 4;
+
+/// Most of a partner-created market's creation fee shared with the partner
+pub const MAX_PARTNER_SHARE_BPS: u16 = 5_000;
+
+/// One leaderboard slot; an empty slot has a default staker
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PodiumEntry {
+    pub staker: Pubkey,
+    pub combined_score: u8,
+    pub payout: u64,
+}
+
+impl PodiumEntry {
+    pub const SPACE: usize = 32 + 1 + 8;
+}
+
+/// Marginal protocol fee rate charged on the part of a pool above `threshold`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct FeeTier {
+    pub threshold: u64,
+    pub bps: u16,
+}
+
+impl FeeTier {
+    pub const SPACE: usize = 8 + 2;
+}
+
+/// Oracle's Merkle settlement as posted, proposed, or resolved
+#[derive(Clone, Copy)]
+pub struct RootCommitment {
+    pub settlement_root: [u8; 32],
+    pub crowd_score: u8,
+    pub total_payout: u64,
+}
+
+/// One market awaiting scoring/settlement in the ScoringQueue. Progress is
+/// copied from the Market by close_market / refresh_scoring_queue.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct QueueEntry {
+    pub market: Pubkey,
+    pub sentiment_done: bool,
+    pub staker_count: u32,
+    /// Opinions with an AI score recorded
+    pub ai_scored: u32,
+    /// Opinions settled (Layer 1/2 applied)
+    pub settled: u32,
+    pub finalized: bool,
+}
+
+impl QueueEntry {
+    pub const SPACE: usize = 32 + 1 + 4 + 4 + 4 + 1;
+
+    fn from_market(market_key: Pubkey, market: &Market) -> Self {
+        Self {
+            market: market_key,
+            sentiment_done: market.state != MarketState::Closed,
+            staker_count: market.staker_count,
+            ai_scored: market.ai_scored_count,
+            settled: market.settled_count,
+            finalized: matches!(market.state, MarketState::Settled | MarketState::Refunding),
+        }
+    }
+}
+
+/// Oracle scoring action recorded in a market's audit trail
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    RecordSentiment,
+    UpdateSentiment,
+    RecordAiScore,
+    RespondAiChallenge,
+    SettleOpinion,
+    CorrectSettlement,
+    PostScoresRoot,
+    PostWeightRange,
+    FinalizeSettlement,
+    PostSettlementRoot,
+    ProposeSettlementRoot,
+}
+
+/// One audit-trail record. `scores` and `digest` hold the action's values:
+///   sentiment:   [score, confidence], summary_hash
+///   ai score:    [ai_score] / [original, final] for challenge responses
+///   settlement:  [crowd, weight, consensus, combined]
+///   roots:       [crowd_score], root
+///   weight range: -, min_net (i64 LE) || max_net (i64 LE)
+///   finalize:    [crowd_score, top_n_cutoff], sha256(finalize message)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct AuditEntry {
+    pub action: AuditAction,
+    /// Opinion the action applies to (default for market-level actions)
+    pub opinion: Pubkey,
+    pub scores: [u8; 4],
+    pub digest: [u8; 32],
+    pub slot: u64,
+}
+
+impl AuditEntry {
+    pub const SPACE: usize = 1 + 32 + 4 + 32 + 8;
+}
+
+/// An opinion's final outcome, kept after its account is closed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct ArchivedResult {
+    pub staker: Pubkey,
+    pub stake_amount: u64,
+    /// [weight, consensus, ai, combined]
+    pub scores: [u8; 4],
+    /// Entitlement at settlement (0 if skipped as dust)
+    pub payout_amount: u64,
+    /// Actually released to the staker before the claim window ended
+    pub claimed_so_far: u64,
+}
+
+impl ArchivedResult {
+    pub const SPACE: usize = 32 + 8 + 4 + 8 + 8;
+}
+
+/// A market's listing in the paginated market registry
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RegistryEntry {
+    pub market: Pubkey,
+    pub category: u8,
+    pub state: MarketState,
+    pub closes_at: i64,
+}
+
+impl RegistryEntry {
+    pub const SPACE: usize = 32 + 1 + 1 + 8;
+}
+
+/// A market's pool as returned by get_pool
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PoolSnapshot {
+    pub state: MarketState,
+    pub closes_at: i64,
+    pub total_stake: u64,
+    pub staker_count: u32,
+    pub backing_total: u64,
+    pub slashing_total: u64,
+}
+
+/// Final results passed to each settle hook's `on_market_settled`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SettlementResult {
+    pub market: Pubkey,
+    pub crowd_score: u8,
+    pub total_stake: u64,
+    pub distributable_pool: u64,
+    /// Zero unless the market settled through a Merkle root
+    pub settlement_root: [u8; 32],
+    pub settled_at: i64,
+}
+
+/// How crowd_score is derived from stakers' market_predictions
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum CrowdScoreMode {
+    /// Stake-weighted mean (default)
+    Mean,
+    /// Stake-weighted median — robust to a few max-stake extremes
+    Median,
+}
+
+/// Triple-Check layer weights in percent (W/C/A), summing to 100
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ScoringWeights {
+    pub weight: u8,
+    pub consensus: u8,
+    pub ai: u8,
+}
+
+impl ScoringWeights {
+    pub const SPACE: usize = 3;
+    pub const DEFAULT: ScoringWeights = ScoringWeights {
+        weight: WEIGHT_MULTIPLIER as u8,
+        consensus: CONSENSUS_MULTIPLIER as u8,
+        ai: AI_MULTIPLIER as u8,
+    };
+
+    fn is_valid(&self, min: u8, max: u8) -> bool {
+        let layers = [self.weight, self.consensus, self.ai];
+        layers.iter().map(|&w| w as u16).sum::<u16>() == 100
+            && layers.iter().all(|&w| w >= min && w <= max)
+    }
+}
+
+// ── Instruction Arguments ────────────────────────────────────────────────────
+
+/// Provenance of an oracle scoring run, so disputed AI scores can be traced
+/// to the exact model configuration that produced them
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScoringRun {
+    /// SHA-256 of the model identifier/version string
+    pub model_hash: [u8; 32],
+    /// SHA-256 of the scoring prompt template
+    pub prompt_hash: [u8; 32],
+    /// Unix timestamp the oracle ran the scoring job
+    pub run_at: i64,
+}
+
+impl ScoringRun {
+    pub const SPACE: usize = 32 + 32 + 8;
+}
+
+/// Stake a user signs off-chain for a relayer to submit with stake_with_intent
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct StakeIntent {
+    pub market: Pubkey,
+    pub staker: Pubkey,
+    pub stake_amount: u64,
+    pub text_hash: [u8; 32],
+    pub ipfs_cid: String,
+    pub opinion_score: u8,
+    pub market_prediction: u8,
+    /// Paid to the relayer on top of the stake; at most MAX_RELAY_FEE
+    pub relay_fee: u64,
+    /// Unix timestamp after which the intent cannot be submitted
+    pub expiry: i64,
+    /// Distinguishes otherwise identical intents
+    pub nonce: u64,
+}
+
+/// Client-computed parts of a Token-2022 confidential transfer into a
+/// market's confidential escrow; the proofs are passed as context state
+/// accounts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ConfidentialStakeTransfer {
+    /// Staker's available balance after the transfer, under their AE key
+    pub new_source_decryptable_balance: [u8; 36],
+    /// Stake amount (low/high bits) encrypted under the mint's auditor key
+    pub auditor_ciphertext_lo: [u8; 64],
+    pub auditor_ciphertext_hi: [u8; 64],
+}
+
+/// Groth16 verifying key for the Layer 1/2 scoring circuit. Points use the
+/// EIP-197 big-endian encoding expected by the alt_bn128 syscalls.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ScoringVerifyingKey {
+    pub alpha_g1: [u8; 64],
+    pub beta_g2: [u8; 128],
+    pub gamma_g2: [u8; 128],
+    pub delta_g2: [u8; 128],
+    /// One G1 point per public input, plus the constant term first
+    pub ic: [[u8; 64]; ZK_PUBLIC_INPUTS + 1],
+}
+
+impl ScoringVerifyingKey {
+    pub const SPACE: usize = 64 + 128 * 3 + 64 * (ZK_PUBLIC_INPUTS + 1);
+}
+
+/// Groth16 proof (A ∈ G1, B ∈ G2, C ∈ G1)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Groth16Proof {
+    pub a: [u8; 64],
+    pub b: [u8; 128],
+    pub c: [u8; 64],
+}
+
+/// Oracle-computed Layer 1/2 scores for one opinion in settle_opinions_batch
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct OpinionSettlement {
+    pub weight_score: u8,
+    pub consensus_score: u8,
+}
+
+/// One entry of a react_batch call, matched positionally to remaining accounts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BatchReaction {
+    pub reaction_type: ReactionType,
+    pub stake_amount: u64,
+}
+
+/// preview_payout result, returned as instruction return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct PayoutPreview {
+    /// Full payout after payout_cap
+    pub entitlement: u64,
+    /// Already released to the staker
+    pub claimed_so_far: u64,
+    /// What a claim would transfer right now (0 if not claimable)
+    pub claimable_now: u64,
+    pub claim_deadline: i64,
+}
+
+/// A compressed reaction as committed in the market's ReactionTree, plus
+/// its proof against a recent root
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CompressedReactionProof {
+    pub reaction_type: ReactionType,
+    pub stake_amount: u64,
+    pub scored: bool,
+    pub leaf_index: u32,
+    /// Root the proof was built against (current or still in the changelog)
+    pub root: [u8; 32],
+    /// Sibling hashes from the leaf level up; REACTION_TREE_DEPTH entries
+    pub proof: Vec<[u8; 32]>,
+}
+
 // ── Events ────────────────────────────────────────────────────────────────────
+// Every event carries `seq`: the market's event_seq for events with a
+// `market`, else GlobalStats.event_seq. A gap means a missed event.
 
 #[event]
 pub struct MarketCreatedEvent {
     pub market: Pubkey,
     pub creator: Pubkey,
+    /// Full text, or its IPFS CID when statement_hash is set
     pub statement: String,
+    pub statement_hash: [u8; 32],
     pub closes_at: i64,
     pub duration_secs: u64,
+    /// Creation fee charged after the creator's volume rebate
+    pub create_fee: u64,
+    /// Program that created the market via CPI, if any
+    pub created_via: Option<Pubkey>,
+    /// Part of create_fee owed to the registered partner if the fee goes to
+    /// the treasury
+    pub partner_fee: u64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct StatementAppendedEvent {
+    pub market: Pubkey,
+    pub chunk: String,
+    /// Statement length in bytes with the chunk appended
+    pub statement_len: u32,
+    pub seq: u64,
 }
 
 #[event]
@@ -124,6 +1125,61 @@ pub struct OpinionStakedEvent {
     pub market_prediction: u8,
     pub ipfs_cid: String,
     pub total_stake_after: u64,
+    pub source_program: Option<Pubkey>,
+    pub seq: u64,
+}
+
+#[event]
+pub struct PaymentReferenceRecordedEvent {
+    pub reference: Pubkey,
+    pub payer: Pubkey,
+    pub market: Pubkey,
+    /// Default for a market creation
+    pub opinion: Pubkey,
+    pub amount: u64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct ConfidentialStakeCommittedEvent {
+    pub market: Pubkey,
+    pub opinion: Pubkey,
+    pub staker: Pubkey,
+    pub opinion_score: u8,
+    pub market_prediction: u8,
+    pub ipfs_cid: String,
+    pub seq: u64,
+}
+
+#[event]
+pub struct ConfidentialStakeRevealedEvent {
+    pub market: Pubkey,
+    pub opinion: Pubkey,
+    pub staker: Pubkey,
+    pub stake_amount: u64,
+    pub total_stake_after: u64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct ConfidentialStakesReleasedEvent {
+    pub market: Pubkey,
+    /// Whole confidential escrow balance, proven and unwrapped into escrow
+    pub released_amount: u64,
+    pub revealed_total: u64,
+    /// Unrevealed remainder added to the market's bonus pool
+    pub forfeited: u64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct StakeIntentRelayedEvent {
+    pub market: Pubkey,
+    pub staker: Pubkey,
+    pub relayer: Pubkey,
+    pub relay_fee: u64,
+    pub nonce: u64,
+    pub seq: u64,
 }
 
 #[event]
@@ -131,6 +1187,7 @@ pub struct JackpotClaimedEvent {
     pub market: Pubkey,
     pub winner: Pubkey,
     pub amount: u64,
+    pub seq: u64,
 }
 
 #[event]
@@ -140,1009 +1197,16656 @@ pub struct ReactionSubmittedEvent {
     pub reactor: Pubkey,
     pub reaction_type: ReactionType,
     pub stake_amount: u64,
+    pub source_program: Option<Pubkey>,
+    pub seq: u64,
 }
 
+/// Notification for an opinion's author: someone backed it
 #[event]
-pub struct MarketClosedEvent {
+pub struct YourOpinionBackedEvent {
+    /// The opinion's author, who should be notified
+    pub staker: Pubkey,
     pub market: Pubkey,
-    pub closed_at: i64,
-    pub total_stakers: u32,
-    pub total_stake: u64,
+    pub opinion: Pubkey,
+    pub reactor: Pubkey,
+    pub stake_amount: u64,
+    pub backing_total: u64,
+    pub seq: u64,
 }
 
+/// Notification for an opinion's author: someone slashed it
 #[event]
-pub struct SentimentRecordedEvent {
+pub struct YourOpinionSlashedEvent {
+    /// The opinion's author, who should be notified
+    pub staker: Pubkey,
     pub market: Pubkey,
-    pub sentiment_score: u8,
-    pub confidence: u8,
-    pub summary_hash: [u8; 32],
+    pub opinion: Pubkey,
+    pub reactor: Pubkey,
+    pub stake_amount: u64,
+    pub slashing_total: u64,
+    pub seq: u64,
 }
 
 #[event]
-pub struct AiScoreRecordedEvent {
+pub struct LiteReactionEvent {
     pub market: Pubkey,
     pub opinion: Pubkey,
-    pub staker: Pubkey,
-    pub ai_score: u8,
+    pub reactor: Pubkey,
+    pub emoji_code: u8,
+    pub count_after: u32,
+    pub seq: u64,
 }
 
+/// Logged for every compressed reaction so indexers can rebuild the tree
 #[event]
-pub struct OpinionSettledEvent {
+pub struct CompressedReactionEvent {
     pub market: Pubkey,
     pub opinion: Pubkey,
-    pub staker: Pubkey,
-    pub weight_score: u8,
-    pub consensus_score: u8,
-    pub ai_score: u8,
-    pub combined_score: u8,
+    pub reactor: Pubkey,
+    pub reaction_type: ReactionType,
+    pub stake_amount: u64,
+    pub leaf_index: u32,
+    pub leaf: [u8; 32],
+    pub root: [u8; 32],
+    pub seq: u64,
 }
 
+/// A compressed reaction leaf was rewritten (scored, or zeroed on recovery)
 #[event]
-pub struct MarketFinalizedEvent {
+pub struct ReactionLeafReplacedEvent {
     pub market: Pubkey,
-    pub total_pool: u64,
-    pub distributable_pool: u64,
-    pub protocol_fee: u64,
-    pub crowd_score: u8,
+    pub leaf_index: u32,
+    pub leaf: [u8; 32],
+    pub root: [u8; 32],
+    pub seq: u64,
 }
 
 #[event]
-pub struct PayoutClaimedEvent {
+pub struct ReactionScoredEvent {
     pub market: Pubkey,
     pub opinion: Pubkey,
-    pub staker: Pubkey,
-    pub payout_amount: u64,
+    pub reactor: Pubkey,
+    pub reaction_type: ReactionType,
     pub combined_score: u8,
+    pub aligned: bool,
+    pub seq: u64,
 }
 
 #[event]
-pub struct LotterySettledEvent {
+pub struct MarketClosedEvent {
     pub market: Pubkey,
-    pub winner: Pubkey,
-    pub prize_amount: u64,
-    pub protocol_fee: u64,
+    pub closed_at: i64,
+    pub total_stakers: u32,
+    pub total_stake: u64,
+    pub seq: u64,
 }
 
 #[event]
-pub struct VrfRandomnessRequestedEvent {
+pub struct MarketRefundingEvent {
     pub market: Pubkey,
-    pub vrf_request_id: u64,
-    pub request_timestamp: i64,
+    pub score_deadline: i64,
+    pub total_stake: u64,
+    pub seq: u64,
 }
 
 #[event]
-pub struct VrfRandomnessFulfilledEvent {
+pub struct OracleBondSlashedEvent {
     pub market: Pubkey,
-    pub vrf_request_id: u64,
-    pub randomness: [u8; 32],
+    pub amount: u64,
+    pub remaining_bond: u64,
+    pub seq: u64,
 }
 
-// ── Account Structs ──────────────────────────────────────────────────────────
+#[event]
+pub struct OracleHeartbeatEvent {
+    pub oracle: Pubkey,
+    pub slot: u64,
+    pub timestamp: i64,
+    pub pending_markets: u32,
+    pub seq: u64,
+}
 
-/// Global program configuration — initialized once by deployer
-#[account]
-pub struct ProgramConfig {
-    pub oracle_authority: Pubkey,
-    pub treasury: Pubkey,
-    pub usdc_mint: Pubkey,
-    pub bump: u8,
-}
-
-impl ProgramConfig {
-    pub const SPACE: usize = 8 + 32 + 32 + 32 + 1;
+#[event]
+pub struct SentimentRecordedEvent {
+    pub market: Pubkey,
+    pub sentiment_score: u8,
+    pub confidence: u8,
+    pub summary_hash: [u8; 32],
+    pub scoring_run: ScoringRun,
+    pub seq: u64,
 }
 
-/// A single opinion market
-#[account]
-pub struct Market {
-    pub creator: Pubkey,
-    pub uuid: [u8; 16],
-    pub statement: String,
-    pub created_at: i64,
-    pub closes_at: i64,
-    pub state: MarketState,
-    pub staker_count: u32,
-    /// Total USDC staked in micro-USDC (6 decimals) — includes reactions
-    pub total_stake: u64,
-    /// Portion available after protocol fee (set at finalize_settlement)
-    pub distributable_pool: u64,
-    /// Volume-weighted mean of all agreement predictions (set at settlement)
-    pub crowd_score: u8,
-    /// Market-level AI sentiment score 0–100 (set by record_sentiment)
+#[event]
+pub struct SentimentCorrectedEvent {
+    pub market: Pubkey,
+    pub previous_sentiment_score: u8,
+    pub previous_confidence: u8,
+    pub previous_summary_hash: [u8; 32],
     pub sentiment_score: u8,
-    /// 0 = low, 1 = medium, 2 = high
     pub confidence: u8,
-    /// SHA-256 of the LLM summary string
     pub summary_hash: [u8; 32],
-    /// Highest-earning staker (set after settlement for display)
-    pub winner: Option<Pubkey>,
-
-    // ── Dual Pool Fields (set at finalize_settlement) ─────────────────────
-    /// 70% of distributable_pool — paid proportionally to net backing
-    pub opinion_pool: u64,
-    /// 24% of distributable_pool — paid by inverse distance to crowd_score
-    pub prediction_pool: u64,
-    /// 6% of distributable_pool — lottery for top 20% predictors
-    pub jackpot_amount: u64,
-    /// Guard: jackpot can only be claimed once
-    pub jackpot_claimed: bool,
-
-    pub bump: u8,
+    pub seq: u64,
 }
 
-impl Market {
-    pub const SPACE: usize =
-        8   // discriminator
-        + 32  // creator
-        + 16  // uuid
-        + 4 + MAX_STATEMENT_LEN // statement String
-        + 8   // created_at
-        + 8   // closes_at
-        + 1   // state enum tag
-        + 4   // staker_count
-        + 8   // total_stake
-        + 8   // distributable_pool
-        + 1   // crowd_score
-        + 1   // sentiment_score
-        + 1   // confidence
-        + 32  // summary_hash
-        + 1 + 32 // winner: Option<Pubkey>
-        + 8   // opinion_pool
-        + 8   // prediction_pool
-        + 8   // jackpot_amount
-        + 1   // jackpot_claimed
-        + 1;  // bump
+#[event]
+pub struct AiScoreRecordedEvent {
+    pub market: Pubkey,
+    pub opinion: Pubkey,
+    pub staker: Pubkey,
+    pub ai_score: u8,
+    pub scoring_run: ScoringRun,
+    pub seq: u64,
 }
 
-/// A single staked opinion — extended with Triple-Check scoring fields
-#[account]
-pub struct Opinion {
+#[event]
+pub struct AiScoreChallengedEvent {
     pub market: Pubkey,
+    pub opinion: Pubkey,
     pub staker: Pubkey,
-    /// Amount staked in micro-USDC
-    pub stake_amount: u64,
-    /// SHA-256 of opinion text (integrity proof)
-    pub text_hash: [u8; 32],
-    /// IPFS CID pointing to full opinion text
-    pub ipfs_cid: String,
-    pub created_at: i64,
-
-    // ── User's Agreement Score ─────────────────────────────────────────────
-    /// 0–100: how much user agrees with the market statement (shapes truth score)
-    pub opinion_score: u8,
-
-    // ── Market Prediction ─────────────────────────────────────────────────
-    /// 0–100: user's bet on where the crowd will settle (shapes payout)
-    pub market_prediction: u8,
+    pub ai_score: u8,
+    pub bond: u64,
+    pub seq: u64,
+}
 
-    // ── Layer 1: Peer Backing ────────────────────────────────────────────────
-    /// Total USDC staked to Back (agree with) this opinion
-    pub backing_total: u64,
-    /// Total USDC staked to Slash (disagree with) this opinion
-    pub slashing_total: u64,
+#[event]
+pub struct AiChallengeResolvedEvent {
+    pub market: Pubkey,
+    pub opinion: Pubkey,
+    pub original_score: u8,
+    pub final_score: u8,
+    /// True when the oracle corrected the score (bond refunded)
+    pub corrected: bool,
+    pub seq: u64,
+}
 
-    // ── Triple-Check Scores (set by oracle at settlement) ────────────────────
-    /// Layer 1 score: normalized net backing (0–100)
+#[event]
+pub struct OpinionSettledEvent {
+    pub market: Pubkey,
+    pub opinion: Pubkey,
+    pub staker: Pubkey,
     pub weight_score: u8,
-    /// Layer 2 score: closeness to crowd_score (0–100)
     pub consensus_score: u8,
-    /// Layer 3 score: AI text quality rating (0–100)
     pub ai_score: u8,
-    /// Final composite: W*50 + C*30 + A*20 stored as 0–100 (divide by 100 from 0–10000)
     pub combined_score: u8,
-
-    // ── Payout ───────────────────────────────────────────────────────────────
-    pub payout_amount: u64,
-    pub paid: bool,
-
-    pub bump: u8,
+    pub seq: u64,
 }
 
-impl Opinion {
-    pub const SPACE: usize =
-        8   // discriminator
-        + 32  // market
-        + 32  // staker
-        + 8   // stake_amount
-        + 32  // text_hash
-        + 4 + MAX_IPFS_CID_LEN // ipfs_cid
-        + 8   // created_at
-        + 1   // opinion_score
-        + 1   // market_prediction
-        + 8   // backing_total
-        + 8   // slashing_total
-        + 1   // weight_score
-        + 1   // consensus_score
-        + 1   // ai_score
-        + 1   // combined_score
-        + 8   // payout_amount
-        + 1   // paid
-        + 1;  // bump
+#[event]
+pub struct SettlementCorrectedEvent {
+    pub market: Pubkey,
+    pub opinion: Pubkey,
+    pub previous_weight_score: u8,
+    pub previous_consensus_score: u8,
+    pub previous_combined_score: u8,
+    pub weight_score: u8,
+    pub consensus_score: u8,
+    pub combined_score: u8,
+    pub seq: u64,
 }
 
-/// Tracks a Back or Slash reaction from one user to another's opinion
-#[account]
-pub struct Reaction {
+#[event]
+pub struct VindicationAwardedEvent {
+    pub market: Pubkey,
     pub opinion: Pubkey,
-    pub reactor: Pubkey,
-    pub reaction_type: ReactionType,
-    pub stake_amount: u64,
-    pub bump: u8,
+    pub staker: Pubkey,
+    pub slashing_total: u64,
+    pub bonus: u64,
+    pub seq: u64,
 }
 
-impl Reaction {
-    pub const SPACE: usize = 8 + 32 + 32 + 1 + 8 + 1;
+#[event]
+pub struct MarketFinalizedEvent {
+    pub market: Pubkey,
+    pub total_pool: u64,
+    pub distributable_pool: u64,
+    pub protocol_fee: u64,
+    pub oracle_fee: u64,
+    pub crowd_score: u8,
+    pub seq: u64,
 }
 
-/// Tracks a pending Chainlink VRF randomness request (legacy)
-#[account]
-pub struct VrfRequest {
+#[event]
+pub struct SettlementMirroredEvent {
     pub market: Pubkey,
-    pub request_id: u64,
-    pub randomness: Option<[u8; 32]>,
-    pub requested_at: i64,
-    pub fulfilled_at: Option<i64>,
-    pub bump: u8,
+    /// Core bridge message account; the VAA is fetched by (emitter, sequence)
+    pub message: Pubkey,
+    pub emitter: Pubkey,
+    pub sequence: u64,
+    pub seq: u64,
 }
 
-impl VrfRequest {
-    pub const SPACE: usize =
-        8   // discriminator
-        + 32  // market
-        + 8   // request_id
-        + 1 + 32 // randomness: Option<[u8; 32]>
-        + 8   // requested_at
-        + 1 + 8 // fulfilled_at: Option<i64>
-        + 1;  // bump
+#[event]
+pub struct SettlementRootPostedEvent {
+    pub market: Pubkey,
+    pub settlement_root: [u8; 32],
+    pub crowd_score: u8,
+    pub total_payout: u64,
+    pub protocol_fee: u64,
+    pub seq: u64,
 }
 
-// ── Program ──────────────────────────────────────────────────────────────────
-#[program]
-pub mod opinion_market {
-    use super::*;
+#[event]
+pub struct SettlementBountyAccruedEvent {
+    pub market: Pubkey,
+    pub keeper: Pubkey,
+    pub amount: u64,
+    /// Bounties accrued on the market so far, including this one
+    pub total_accrued: u64,
+    pub seq: u64,
+}
 
-    /// Initialize global config — called once by deployer
-    pub fn initialize(
-        ctx: Context<InitializeConfig>,
-        oracle_authority: Pubkey,
-        treasury: Pubkey,
-    ) -> Result<()> {
-        let config = &mut ctx.accounts.config;
-        config.oracle_authority = oracle_authority;
-        config.treasury = treasury;
-        config.usdc_mint = ctx.accounts.usdc_mint.key();
-        config.bump = ctx.bumps.config;
-        msg!("ProgramConfig initialized: oracle_authority={} treasury={}", oracle_authority, treasury);
-        Ok(())
-    }
+#[event]
+pub struct SettlementBountyPaidEvent {
+    pub market: Pubkey,
+    pub keeper: Pubkey,
+    pub amount: u64,
+    /// Bounties paid on the market so far, including this one
+    pub total_paid: u64,
+    pub seq: u64,
+}
 
-    /// Create a new opinion market. Costs $5 USDC paid to treasury.
-    pub fn create_market(
-        ctx: Context<CreateMarket>,
-        statement: String,
-        duration_secs: u64,
-        uuid: [u8; 16],
-    ) -> Result<()> {
-        require!(!statement.is_empty(), OpinionError::StatementEmpty);
-        require!(statement.len() <= MAX_STATEMENT_LEN, OpinionError::StatementTooLong);
-        require!(
-            matches!(duration_secs, DURATION_24H | DURATION_3D | DURATION_7D | DURATION_14D),
-            OpinionError::InvalidDuration
-        );
+#[event]
+pub struct SettlementProposedEvent {
+    pub market: Pubkey,
+    pub settlement_root: [u8; 32],
+    pub crowd_score: u8,
+    pub total_payout: u64,
+    pub challenge_ends_at: i64,
+    pub seq: u64,
+}
 
-        let cpi_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.creator_usdc.to_account_info(),
-                to: ctx.accounts.treasury_usdc.to_account_info(),
-                authority: ctx.accounts.creator.to_account_info(),
-            },
-        );
-        token::transfer(cpi_ctx, CREATE_FEE)?;
+#[event]
+pub struct SettlementDisputedEvent {
+    pub market: Pubkey,
+    pub challenger: Pubkey,
+    pub bond: u64,
+    pub seq: u64,
+}
 
-        let clock = Clock::get()?;
-        let market_key = ctx.accounts.market.key();
-        let statement_for_event = statement.clone();
-        let market = &mut ctx.accounts.market;
-        market.creator = ctx.accounts.creator.key();
-        market.uuid = uuid;
-        market.statement = statement;
-        market.created_at = clock.unix_timestamp;
-        market.closes_at = clock.unix_timestamp + duration_secs as i64;
-        market.state = MarketState::Active;
-        market.staker_count = 0;
-        market.total_stake = 0;
-        market.distributable_pool = 0;
-        market.crowd_score = 0;
-        market.sentiment_score = 0;
-        market.confidence = 0;
-        market.summary_hash = [0u8; 32];
-        market.winner = None;
-        market.opinion_pool = 0;
-        market.prediction_pool = 0;
-        market.jackpot_amount = 0;
-        market.jackpot_claimed = false;
-        market.bump = ctx.bumps.market;
+#[event]
+pub struct DisputeResolvedEvent {
+    pub market: Pubkey,
+    pub challenger: Pubkey,
+    /// True when the bond was returned: the proposal was rejected, the
+    /// fallback root differs from it, or the market left Scored first
+    pub challenger_won: bool,
+    pub bond: u64,
+    pub seq: u64,
+}
 
-        emit!(MarketCreatedEvent {
-            market: market_key,
-            creator: ctx.accounts.creator.key(),
-            statement: statement_for_event,
-            closes_at: market.closes_at,
-            duration_secs,
-        });
+#[event]
+pub struct ScoresRootPostedEvent {
+    pub market: Pubkey,
+    pub scores_root: [u8; 32],
+    pub crowd_score: u8,
+    pub seq: u64,
+}
 
-        Ok(())
-    }
+#[event]
+pub struct OpinionInputsSnapshotEvent {
+    pub market: Pubkey,
+    pub opinion: Pubkey,
+    /// Position in the hash chain (0-based)
+    pub index: u32,
+    pub inputs_hash: [u8; 32],
+    pub seq: u64,
+}
 
-    /// Stake a USDC-backed opinion on a market ($0.50–$10).
-    /// Accepts two scores:
-    ///   - opinion_score (0–100): how much user agrees with the statement (shapes truth)
-    ///   - market_prediction (0–100): bet on where the crowd will settle (shapes payout)
-    pub fn stake_opinion(
-        ctx: Context<StakeOpinion>,
-        stake_amount: u64,
-        text_hash: [u8; 32],
-        ipfs_cid: String,
-        opinion_score: u8,
-        market_prediction: u8,
-    ) -> Result<()> {
-        require!(stake_amount >= MIN_STAKE, OpinionError::StakeTooSmall);
-        require!(stake_amount <= MAX_STAKE, OpinionError::StakeTooLarge);
-        require!(ipfs_cid.len() <= MAX_IPFS_CID_LEN, OpinionError::CidTooLong);
-        require!(opinion_score <= 100, OpinionError::InvalidOpinionScore);
-        require!(market_prediction <= 100, OpinionError::InvalidPrediction);
+#[event]
+pub struct VerifiedScoresPostedEvent {
+    pub market: Pubkey,
+    pub verified_scores_root: [u8; 32],
+    pub inputs_hash: [u8; 32],
+    pub crowd_score: u8,
+    pub seq: u64,
+}
 
-        let clock = Clock::get()?;
-        {
-            let market = &ctx.accounts.market;
-            require!(market.state == MarketState::Active, OpinionError::MarketNotActive);
-            require!(clock.unix_timestamp < market.closes_at, OpinionError::MarketExpired);
-        }
+#[event]
+pub struct SettlementApprovedEvent {
+    pub market: Pubkey,
+    pub member: Pubkey,
+    pub payload_hash: [u8; 32],
+    pub approvals: u8,
+    pub threshold: u8,
+    pub seq: u64,
+}
 
-        let cpi_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.staker_usdc.to_account_info(),
-                to: ctx.accounts.escrow_token_account.to_account_info(),
-                authority: ctx.accounts.staker.to_account_info(),
-            },
-        );
-        token::transfer(cpi_ctx, stake_amount)?;
+#[event]
+pub struct PayoutClaimedEvent {
+    pub market: Pubkey,
+    pub opinion: Pubkey,
+    pub staker: Pubkey,
+    pub payout_amount: u64,
+    pub combined_score: u8,
+    pub seq: u64,
+}
 
-        let market_key = ctx.accounts.market.key();
-        let staker_key = ctx.accounts.staker.key();
-        let ipfs_cid_for_event = ipfs_cid.clone();
+#[event]
+pub struct LotterySettledEvent {
+    pub market: Pubkey,
+    pub winner: Pubkey,
+    pub prize_amount: u64,
+    pub protocol_fee: u64,
+    pub seq: u64,
+}
 
-        let opinion = &mut ctx.accounts.opinion;
-        opinion.market = market_key;
-        opinion.staker = staker_key;
-        opinion.stake_amount = stake_amount;
-        opinion.text_hash = text_hash;
-        opinion.ipfs_cid = ipfs_cid.clone();
-        opinion.created_at = clock.unix_timestamp;
-        opinion.opinion_score = opinion_score;
-        opinion.market_prediction = market_prediction;
-        // Author's own stake counts as initial backing for Layer 1
-        opinion.backing_total = stake_amount;
-        opinion.slashing_total = 0;
-        opinion.weight_score = 0;
-        opinion.consensus_score = 0;
-        opinion.ai_score = 0;
-        opinion.combined_score = 0;
-        opinion.payout_amount = 0;
-        opinion.paid = false;
-        opinion.bump = ctx.bumps.opinion;
+#[event]
+pub struct PayoutPushedEvent {
+    pub market: Pubkey,
+    pub opinion: Pubkey,
+    pub staker: Pubkey,
+    pub keeper: Pubkey,
+    /// Amount that reached the staker (payout − bounty)
+    pub delivered: u64,
+    pub bounty: u64,
+    pub seq: u64,
+}
 
-        let market = &mut ctx.accounts.market;
-        market.total_stake = market.total_stake.saturating_add(stake_amount);
-        market.staker_count = market.staker_count.saturating_add(1);
-        let total_stake_after = market.total_stake;
+#[event]
+pub struct PayoutExpiredEvent {
+    pub market: Pubkey,
+    pub opinion: Pubkey,
+    pub staker: Pubkey,
+    pub seq: u64,
+}
 
-        emit!(OpinionStakedEvent {
-            market: market_key,
-            staker: staker_key,
-            stake_amount,
-            opinion_score,
-            market_prediction,
-            ipfs_cid: ipfs_cid_for_event,
-            total_stake_after,
-        });
+/// Notification for a staker: their payout on a settled market can be claimed
+#[event]
+pub struct PayoutReadyEvent {
+    pub staker: Pubkey,
+    pub market: Pubkey,
+    pub opinion: Pubkey,
+    pub amount: u64,
+    pub claim_deadline: i64,
+    pub seq: u64,
+}
 
-        Ok(())
+#[event]
+pub struct ReputationMintedEvent {
+    pub market: Pubkey,
+    pub opinion: Pubkey,
+    pub staker: Pubkey,
+    pub payout: u64,
+    pub combined_score: u8,
+    /// payout × combined_score / 100
+    pub amount: u64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct FeePointsClaimedEvent {
+    pub market: Pubkey,
+    pub opinion: Pubkey,
+    pub staker: Pubkey,
+    /// Opinion's stake-weighted share of the market's protocol fee
+    pub fee_share: u64,
+    pub points: u64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct PayoutSkippedEvent {
+    pub market: Pubkey,
+    pub opinion: Pubkey,
+    pub staker: Pubkey,
+    /// Dust left in escrow for sweep_dust
+    pub amount: u64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct RedistributionClaimedEvent {
+    pub market: Pubkey,
+    pub opinion: Pubkey,
+    pub staker: Pubkey,
+    pub amount: u64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct DustSweptEvent {
+    pub market: Pubkey,
+    pub amount: u64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct PayoutCompoundedEvent {
+    pub owner: Pubkey,
+    pub market: Pubkey,
+    pub amount: u64,
+    pub vault_balance: u64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct StakeSwappedEvent {
+    pub market: Pubkey,
+    pub opinion: Pubkey,
+    pub staker: Pubkey,
+    pub input_mint: Pubkey,
+    /// Input tokens spent by the route
+    pub amount_in: u64,
+    /// USDC received from the swap and staked
+    pub stake_amount: u64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct PayoutSwappedEvent {
+    pub market: Pubkey,
+    pub opinion: Pubkey,
+    pub staker: Pubkey,
+    /// USDC payout routed into the swap
+    pub payout_amount: u64,
+    pub output_mint: Pubkey,
+    /// Output tokens received by the staker
+    pub amount_out: u64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct EscrowYieldEvent {
+    pub market: Pubkey,
+    /// Principal deposited (deposit) or withdrawn (withdraw)
+    pub principal: u64,
+    /// Interest returned on withdrawal (0 on deposit)
+    pub yield_amount: u64,
+    pub deposited: bool,
+    pub seq: u64,
+}
+
+#[event]
+pub struct CreationFeeReleasedEvent {
+    pub market: Pubkey,
+    pub creator: Pubkey,
+    pub amount: u64,
+    /// true = refunded to the creator, false = paid to the treasury
+    pub refunded: bool,
+    /// Paid to the partner on top of amount (which went to the treasury)
+    pub partner_fee: u64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct MarketSponsoredEvent {
+    pub market: Pubkey,
+    pub sponsor: Pubkey,
+    pub amount: u64,
+    pub bonus_pool: u64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct AccountMigratedEvent {
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub seq: u64,
+}
+
+#[event]
+pub struct CharityDonationEvent {
+    pub market: Pubkey,
+    pub charity_usdc: Pubkey,
+    /// Protocol fee routed to the charity instead of the treasury
+    pub protocol_fee: u64,
+    /// Slice of the distributable pool donated (charity_pool_bps)
+    pub pool_share: u64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct InsuranceFundedEvent {
+    pub market: Pubkey,
+    pub amount: u64,
+    pub vault_balance: u64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct InsuranceClaimPaidEvent {
+    pub market: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    /// sha256 of the off-chain incident report
+    pub reason_hash: [u8; 32],
+    pub seq: u64,
+}
+
+#[event]
+pub struct VrfRandomnessRequestedEvent {
+    pub market: Pubkey,
+    pub vrf_request_id: u64,
+    pub request_timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct VrfRandomnessFulfilledEvent {
+    pub market: Pubkey,
+    pub vrf_request_id: u64,
+    pub randomness: [u8; 32],
+    pub seq: u64,
+}
+
+#[event]
+pub struct MarketCheckpointEvent {
+    pub market: Pubkey,
+    pub index: u32,
+    pub timestamp: i64,
+    pub total_stake: u64,
+    pub staker_count: u32,
+    pub backing_total: u64,
+    pub slashing_total: u64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct MarketAssetMintedEvent {
+    pub market: Pubkey,
+    pub asset: Pubkey,
+    pub owner: Pubkey,
+    pub seq: u64,
+}
+
+#[event]
+pub struct WinnerBadgeMintedEvent {
+    pub market: Pubkey,
+    pub opinion: Pubkey,
+    pub staker: Pubkey,
+    pub merkle_tree: Pubkey,
+    /// Podium position (1-based); 0 when off the podium
+    pub rank: u8,
+    pub combined_score: u8,
+    pub seq: u64,
+}
+
+#[event]
+pub struct PythMarketResolvedEvent {
+    pub market: Pubkey,
+    pub price: i64,
+    pub publish_time: i64,
+    pub outcome: bool,
+    /// Stake on the side that matched the outcome
+    pub winning_stake: u64,
+    pub distributable_pool: u64,
+    pub protocol_fee: u64,
+    pub seq: u64,
+}
+
+// ── Account Structs ──────────────────────────────────────────────────────────
+
+/// Global program configuration — initialized once by deployer
+#[account]
+pub struct ProgramConfig {
+    pub oracle_authority: Pubkey,
+    /// Owner of every treasury_usdc account: a wallet or a PDA such as a
+    /// Squads vault
+    pub treasury: Pubkey,
+    pub usdc_mint: Pubkey,
+    /// May update tunable protocol parameters (deployer at initialize)
+    pub admin: Pubkey,
+    /// Share of slashers' stakes routed to a vindicated opinion's author
+    pub vindication_share_bps: u16,
+    /// Number of active OracleMember accounts
+    pub committee_size: u8,
+    /// Distinct member approvals required before settlement moves funds (0 = off)
+    pub committee_threshold: u8,
+    /// Bond the oracle must hold before scoring a market (0 = not required)
+    pub oracle_bond_minimum: u64,
+    /// Bond slashed per lost dispute or missed score_deadline (0 = off)
+    pub oracle_slash_amount: u64,
+    /// Bounds on each per-market Triple-Check layer weight (percent)
+    pub min_layer_weight: u8,
+    pub max_layer_weight: u8,
+    /// Oracle compensation per finalized market: flat amount plus bps of the
+    /// post-protocol-fee pool
+    pub oracle_fee_flat: u64,
+    pub oracle_fee_bps: u16,
+    /// Human reviewer whose co-signature settlement may require
+    pub reviewer_authority: Pubkey,
+    /// Every instruction that settles a market or an opinion against oracle
+    /// scores needs the reviewer's signature
+    pub reviewer_required: bool,
+    /// Batch score submissions need the reviewer's signature too
+    pub reviewer_on_batches: bool,
+    /// Share of a pushed payout paid to the keeper that delivered it
+    pub push_bounty_bps: u16,
+    /// Marginal protocol fee schedule, ascending by threshold
+    pub fee_tiers: [FeeTier; MAX_FEE_TIERS],
+    /// Share of each finalize_settlement protocol fee paid into the InsuranceVault
+    pub insurance_share_bps: u16,
+    /// Swap aggregator claim_payout_swapped and stake_opinion_with_swap may
+    /// CPI into (default = disabled)
+    pub swap_program: Pubkey,
+    /// Share of a qualifying staker's protocol fee that is waived (0 = off)
+    pub fee_waiver_bps: u16,
+    /// Reputation threshold: scored reactions and accuracy in ReactorStats
+    pub fee_waiver_min_scored: u32,
+    pub fee_waiver_min_accuracy_bps: u16,
+    /// Payouts below this are skipped and left in escrow for sweep_dust
+    pub min_payout: u64,
+    /// Lending market escrow may be deposited into between close and
+    /// settlement (default = disabled)
+    pub lending_program: Pubkey,
+    /// Points minted per unit of protocol fee generated, in bps (0 = no season)
+    pub points_rate_bps: u32,
+    /// Opinions a new market needs for its creation fee to be refunded
+    pub fee_refund_min_opinions: u32,
+    /// Treasury proposed by propose_treasury; takes over once it signs
+    /// accept_treasury (default = none pending)
+    pub pending_treasury: Pubkey,
+    /// Paid to the keeper landing each relayed settlement transaction (0 = off)
+    pub settlement_bounty: u64,
+    /// Per-market cap on settlement bounties, in bps of the market's protocol fee
+    pub settlement_bounty_cap_bps: u16,
+    /// Token-2022 wrapped USDC with the confidential transfer extension that
+    /// confidential markets stake in (default = disabled)
+    pub confidential_mint: Pubkey,
+    /// Wrapper program that unwraps confidential_mint into USDC
+    pub confidential_wrap_program: Pubkey,
+    /// Bumped by remove_oracle_member; approvals from an earlier epoch no
+    /// longer count
+    pub committee_epoch: u32,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl ProgramConfig {
+    pub const SPACE: usize =
+        8 + 32 + 32 + 32 + 32 + 2 + 1 + 1 + 8 + 8 + 1 + 1 + 8 + 2 + 32 + 1 + 1 + 2
+        + FeeTier::SPACE * MAX_FEE_TIERS + 2 + 32 + 2 + 4 + 2 + 8 + 32 + 4 + 4 + 32 + 8 + 2 + 32 + 32
+        + 4 + 1 + 1;
+
+    /// A fresh config for the given keys, every tunable at its default
+    pub fn with_defaults(
+        oracle_authority: Pubkey,
+        treasury: Pubkey,
+        usdc_mint: Pubkey,
+        admin: Pubkey,
+        bump: u8,
+    ) -> Self {
+        Self {
+            oracle_authority,
+            treasury,
+            usdc_mint,
+            admin,
+            vindication_share_bps: DEFAULT_VINDICATION_SHARE_BPS,
+            committee_size: 0,
+            committee_threshold: 0,
+            oracle_bond_minimum: 0,
+            oracle_slash_amount: 0,
+            min_layer_weight: DEFAULT_MIN_LAYER_WEIGHT,
+            max_layer_weight: DEFAULT_MAX_LAYER_WEIGHT,
+            oracle_fee_flat: 0,
+            oracle_fee_bps: 0,
+            reviewer_authority: Pubkey::default(),
+            reviewer_required: false,
+            reviewer_on_batches: false,
+            push_bounty_bps: 0,
+            fee_tiers: DEFAULT_FEE_TIERS,
+            insurance_share_bps: 0,
+            swap_program: Pubkey::default(),
+            fee_waiver_bps: 0,
+            fee_waiver_min_scored: 0,
+            fee_waiver_min_accuracy_bps: 0,
+            min_payout: DEFAULT_MIN_PAYOUT,
+            lending_program: Pubkey::default(),
+            points_rate_bps: 0,
+            fee_refund_min_opinions: DEFAULT_FEE_REFUND_MIN_OPINIONS,
+            pending_treasury: Pubkey::default(),
+            settlement_bounty: 0,
+            settlement_bounty_cap_bps: 0,
+            confidential_mint: Pubkey::default(),
+            confidential_wrap_program: Pubkey::default(),
+            committee_epoch: 0,
+            bump,
+            schema_version: ACCOUNT_SCHEMA_VERSION,
+        }
+    }
+
+    /// Oracle compensation owed out of `pool`, never more than the pool itself
+    pub fn oracle_fee(&self, pool: u64) -> u64 {
+        let bps_fee = (pool as u128 * self.oracle_fee_bps as u128 / 10_000) as u64;
+        self.oracle_fee_flat.saturating_add(bps_fee).min(pool)
+    }
+
+    /// Protocol fee owed on `pool`: each tier's bps applies only to the slice
+    /// of the pool between its threshold and the next tier's
+    pub fn protocol_fee(&self, pool: u64) -> u64 {
+        let mut fee_bps_total: u128 = 0;
+        for (i, tier) in self.fee_tiers.iter().enumerate() {
+            if pool <= tier.threshold {
+                break;
+            }
+            let upper = self.fee_tiers.get(i + 1).map_or(pool, |next| next.threshold.min(pool));
+            fee_bps_total += (upper - tier.threshold) as u128 * tier.bps as u128;
+        }
+        (fee_bps_total / 10_000) as u64
+    }
+
+    /// Most a market staking `pool` may pay in settlement bounties
+    pub fn settlement_bounty_budget(&self, pool: u64) -> u64 {
+        (self.protocol_fee(pool) as u128 * self.settlement_bounty_cap_bps as u128 / 10_000) as u64
+    }
+}
+
+/// ProgramConfig layout deployed before schema versioning, read only by
+/// migrate_config
+#[derive(AnchorDeserialize)]
+pub struct BaselineConfig {
+    pub oracle_authority: Pubkey,
+    pub treasury: Pubkey,
+    pub usdc_mint: Pubkey,
+    pub bump: u8,
+}
+
+/// A fee schedule must start at 0, keep thresholds ascending, and stay ≤ 100%
+fn is_valid_fee_schedule(tiers: &[FeeTier; MAX_FEE_TIERS]) -> bool {
+    tiers[0].threshold == 0
+        && tiers.windows(2).all(|w| w[0].threshold < w[1].threshold || w[1].threshold == u64::MAX)
+        && tiers.iter().all(|tier| tier.bps <= 10_000)
+}
+
+/// Running totals of fees paid out by settlement
+#[account]
+pub struct TreasuryLedger {
+    /// Σ protocol fees sent to the treasury
+    pub protocol_fees: u64,
+    /// Σ compensation paid to oracle operators
+    pub oracle_fees: u64,
+    /// Markets finalized through finalize_settlement
+    pub markets_finalized: u64,
+    /// Σ escrow residue swept by sweep_dust
+    pub dust_swept: u64,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+/// Protocol-wide totals for dashboards, kept current by the instructions
+/// that create, fund, settle and refund markets
+#[account]
+pub struct GlobalStats {
+    pub markets_created: u64,
+    pub markets_settled: u64,
+    /// Σ opinion and reaction stakes ever placed
+    pub cumulative_volume: u64,
+    /// Σ protocol fees charged at settlement
+    pub cumulative_fees: u64,
+    /// Stake held by markets that have not yet settled or entered Refunding
+    pub active_tvl: u64,
+    pub bump: u8,
+    pub schema_version: u8,
+
+    // ── Event sequencing ─────────────────────────────────────────────────────
+    /// Events emitted that are not about a single market
+    pub event_seq: u64,
+}
+
+impl GlobalStats {
+    pub const SPACE: usize = 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1
+        + 8;  // event_seq
+
+    /// Claim the seq for the next protocol-level event
+    pub fn next_event_seq(&mut self) -> u64 {
+        let seq = self.event_seq;
+        self.event_seq = seq.saturating_add(1);
+        seq
     }
 
-    /// Back or Slash another user's opinion — Layer 1 of the Triple-Check.
-    /// Reactor's stake goes into the escrow and affects the opinion's weight score.
-    pub fn react_to_opinion(
-        ctx: Context<ReactToOpinion>,
-        reaction_type: ReactionType,
-        stake_amount: u64,
-    ) -> Result<()> {
-        require!(stake_amount >= MIN_STAKE, OpinionError::StakeTooSmall);
-        require!(stake_amount <= MAX_STAKE, OpinionError::StakeTooLarge);
+    /// Registry page the next created market is listed on
+    pub fn next_registry_page(&self) -> u32 {
+        (self.markets_created / REGISTRY_PAGE_SIZE as u64) as u32
+    }
+
+    /// Stake entering a live market
+    pub fn record_stake(&mut self, amount: u64) {
+        self.cumulative_volume = self.cumulative_volume.saturating_add(amount);
+        self.active_tvl = self.active_tvl.saturating_add(amount);
+    }
+
+    /// `market` just settled; its stake leaves the active set
+    pub fn record_settlement(&mut self, market: &Market) {
+        self.markets_settled = self.markets_settled.saturating_add(1);
+        self.cumulative_fees = self.cumulative_fees.saturating_add(market.protocol_fee_generated);
+        self.record_market_exit(market);
+    }
+
+    /// `market` stopped being active (settled or refunding). A slashed oracle
+    /// bond folded into total_stake was never counted as stake.
+    pub fn record_market_exit(&mut self, market: &Market) {
+        let staked = market.total_stake.saturating_sub(market.oracle_slashed);
+        self.active_tvl = self.active_tvl.saturating_sub(staked);
+    }
+}
+
+/// Per-user activity summary at ["user_profile", user], created at the
+/// user's first stake and updated at every stake and claim
+#[account]
+pub struct UserProfile {
+    pub user: Pubkey,
+    /// Opinions staked (one per market)
+    pub markets_entered: u32,
+    pub total_staked: u64,
+    /// Σ payouts claimed, including redistribution shares
+    pub total_won: u64,
+    /// Σ combined_score over scored_opinions, for the average
+    pub combined_score_sum: u64,
+    pub scored_opinions: u32,
+    pub last_active: i64,
+    pub bump: u8,
+    pub schema_version: u8,
+
+    // ── Reputation ───────────────────────────────────────────────────────────
+    /// Decaying Σ combined_score × REPUTATION_SCALE as of reputation_updated_at;
+    /// read it through reputation_at
+    pub reputation: u64,
+    pub reputation_updated_at: i64,
+}
+
+impl UserProfile {
+    pub const SPACE: usize = 8 + 32 + 4 + 8 + 8 + 8 + 4 + 8 + 1 + 1
+        + 8   // reputation
+        + 8;  // reputation_updated_at
+
+    /// Stamps identity and activity; init_if_needed leaves a fresh profile zeroed
+    pub fn touch(&mut self, user: Pubkey, bump: u8, now: i64) {
+        self.user = user;
+        self.bump = bump;
+        self.schema_version = ACCOUNT_SCHEMA_VERSION;
+        self.last_active = now;
+    }
+
+    pub fn record_stake(&mut self, amount: u64) {
+        self.markets_entered = self.markets_entered.saturating_add(1);
+        self.total_staked = self.total_staked.saturating_add(amount);
+    }
+
+    pub fn record_claim(&mut self, amount: u64) {
+        self.total_won = self.total_won.saturating_add(amount);
+    }
+
+    /// Count a settled opinion's combined_score (and reputation) once,
+    /// whatever it paid; later calls for the same opinion are no-ops. Claims
+    /// call it before releasing anything.
+    pub fn record_score(&mut self, opinion: &mut Opinion, now: i64) {
+        if opinion.score_recorded() {
+            return;
+        }
+        let score = opinion.combined_score() as u64;
+        self.combined_score_sum = self.combined_score_sum.saturating_add(score);
+        self.scored_opinions = self.scored_opinions.saturating_add(1);
+        self.reputation = self.reputation_at(now).saturating_add(score * REPUTATION_SCALE);
+        self.reputation_updated_at = now;
+        opinion.score_recorded = 1;
+    }
+
+    /// Reputation decayed to `now`: halved once per whole REPUTATION_HALF_LIFE,
+    /// then interpolated linearly toward the next halving. This is the value
+    /// other instructions should gate on.
+    pub fn reputation_at(&self, now: i64) -> u64 {
+        let elapsed = now.saturating_sub(self.reputation_updated_at).max(0);
+        let halvings = elapsed / REPUTATION_HALF_LIFE;
+        if halvings >= 64 {
+            return 0;
+        }
+        let halved = self.reputation >> halvings;
+        let rem = (elapsed % REPUTATION_HALF_LIFE) as u128;
+        let span = 2 * REPUTATION_HALF_LIFE as u128;
+        (halved as u128 * (span - rem) / span) as u64
+    }
+
+    pub fn average_combined_score(&self) -> u8 {
+        if self.scored_opinions == 0 {
+            return 0;
+        }
+        (self.combined_score_sum / self.scored_opinions as u64) as u8
+    }
+}
+
+impl TreasuryLedger {
+    pub const SPACE: usize = 8 + 8 + 8 + 8 + 8 + 1 + 1;
+}
+
+/// Protocol backstop funded by a slice of protocol fees; tokens sit in the
+/// ["insurance_vault_usdc"] account
+#[account]
+pub struct InsuranceVault {
+    /// Σ protocol fee slices paid in
+    pub total_deposited: u64,
+    /// Σ compensation paid out
+    pub total_paid: u64,
+    /// Claims paid out
+    pub claims_paid: u32,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl InsuranceVault {
+    pub const SPACE: usize = 8 + 8 + 8 + 4 + 1 + 1;
+}
+
+/// Oracle liveness beacon at ["oracle_status", oracle], pinged by
+/// oracle_heartbeat
+#[account]
+pub struct OracleStatus {
+    pub oracle: Pubkey,
+    pub last_seen_slot: u64,
+    pub last_seen_at: i64,
+    /// Markets the oracle reports as awaiting scoring or settlement
+    pub pending_markets: u32,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl OracleStatus {
+    pub const SPACE: usize = 8 + 32 + 8 + 8 + 4 + 1 + 1;
+
+    /// False once the oracle has been silent for ORACLE_LIVENESS_TIMEOUT
+    pub fn is_alive(&self, now: i64) -> bool {
+        now < self.last_seen_at + ORACLE_LIVENESS_TIMEOUT
+    }
+}
+
+/// Oracle's locked USDC bond at ["oracle_bond", oracle]; tokens sit in the
+/// ["oracle_bond_vault", oracle] account. A market's bond and status are its
+/// resolved oracle's (see Market::oracle).
+#[account]
+pub struct OracleBond {
+    pub oracle: Pubkey,
+    /// Bonded amount in micro-USDC
+    pub amount: u64,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl OracleBond {
+    pub const SPACE: usize = 8 + 32 + 8 + 1 + 1;
+}
+
+/// Marks a wallet as an oracle committee member
+#[account]
+pub struct OracleMember {
+    pub member: Pubkey,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl OracleMember {
+    pub const SPACE: usize = 8 + 32 + 1 + 1;
+}
+
+/// Registers a partner program that creates markets via CPI, and the share
+/// of those markets' creation fees it receives
+#[account]
+pub struct Partner {
+    pub program: Pubkey,
+    /// USDC token account receiving the partner's share
+    pub payout_usdc: Pubkey,
+    pub revenue_share_bps: u16,
+    pub markets_created: u64,
+    pub fees_earned: u64,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl Partner {
+    pub const SPACE: usize = 8 + 32 + 32 + 2 + 8 + 8 + 1 + 1;
+
+    /// Partner's share of a creation fee
+    pub fn share_of(&self, create_fee: u64) -> u64 {
+        (create_fee as u128 * self.revenue_share_bps as u128 / 10_000) as u64
+    }
+}
+
+/// Whitelists an external program as a settle hook subscriber
+#[account]
+pub struct HookProgram {
+    pub program: Pubkey,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl HookProgram {
+    pub const SPACE: usize = 8 + 32 + 1 + 1;
+}
+
+/// Whitelists a charity's USDC token account for charity markets, at
+/// ["charity", usdc_account]
+#[account]
+pub struct Charity {
+    pub usdc_account: Pubkey,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl Charity {
+    pub const SPACE: usize = 8 + 32 + 1 + 1;
+}
+
+/// Settlement bounties one keeper accrued on one market, at
+/// ["settlement_bounty", market, keeper]; claim_settlement_bounty pays and
+/// closes it
+#[account]
+pub struct SettlementBounty {
+    pub market: Pubkey,
+    pub keeper: Pubkey,
+    pub accrued: u64,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl SettlementBounty {
+    pub const SPACE: usize = 8 + 32 + 32 + 8 + 1 + 1;
+}
+
+/// Programs notified (in order) when a market settles
+#[account]
+pub struct SettleHooks {
+    pub market: Pubkey,
+    pub programs: Vec<Pubkey>,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl SettleHooks {
+    pub const SPACE: usize = 8 + 32 + 4 + 32 * MAX_SETTLE_HOOKS + 1 + 1;
+}
+
+/// Committee approvals for one money-moving settlement action on a market.
+/// Approvals are bound to `payload_hash` (sha256 of the action's signed
+/// message); a new hash resets the approval set.
+#[account]
+pub struct SettlementProposal {
+    pub market: Pubkey,
+    pub payload_hash: [u8; 32],
+    pub approvals: Vec<Pubkey>,
+    /// config.committee_epoch the approvals were given in
+    pub committee_epoch: u32,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl SettlementProposal {
+    pub const SPACE: usize = 8 + 32 + 32 + 4 + 32 * MAX_COMMITTEE_SIZE + 4 + 1 + 1;
+}
+
+/// Discriminator of the fixed-size Market layout, whose statement lives in
+/// MarketStatement. Changed from the default so baseline markets, still
+/// carrying their statement inline, are rejected until migrate_market has
+/// rewritten them.
+pub const MARKET_DISCRIMINATOR: &[u8] = b"market01";
+
+/// Discriminator of baseline markets (Anchor's default for `account:Market`)
+pub const BASELINE_MARKET_DISCRIMINATOR: [u8; 8] = [219, 190, 213, 55, 0, 227, 198, 154];
+
+/// A single opinion market. Fixed-size: the statement is kept apart in
+/// MarketStatement so the hot path never decodes it.
+#[account(discriminator = MARKET_DISCRIMINATOR)]
+pub struct Market {
+    pub creator: Pubkey,
+    pub uuid: [u8; 16],
+    pub created_at: i64,
+    /// Opinion submission closes here
+    pub closes_at: i64,
+    /// Reactions remain open until here (== closes_at unless a window is set)
+    pub reactions_close_at: i64,
+    /// Set at close_market; unfinalized after this, the market may enter Refunding
+    pub score_deadline: i64,
+    /// When the market became Settled; claims run until settled_at + CLAIM_WINDOW
+    pub settled_at: i64,
+    pub state: MarketState,
+    /// Set by the first settlement action; the other path is then rejected
+    pub settlement_mode: SettlementMode,
+    pub staker_count: u32,
+    /// Opinions settled so far; must reach staker_count before finalize_settlement
+    pub settled_count: u32,
+    /// Total USDC staked in micro-USDC (6 decimals) — includes reactions
+    pub total_stake: u64,
+    /// Portion available after protocol fee (set at finalize_settlement)
+    pub distributable_pool: u64,
+    /// Oracle operator assigned to this market (None = config.oracle_authority)
+    pub oracle_override: Option<Pubkey>,
+    /// Volume-weighted mean of all agreement predictions (set at settlement)
+    pub crowd_score: u8,
+    /// Market-level AI sentiment score 0–100 (set by record_sentiment)
+    pub sentiment_score: u8,
+    /// 0 = low, 1 = medium, 2 = high
+    pub confidence: u8,
+    /// SHA-256 of the LLM summary string
+    pub summary_hash: [u8; 32],
+    /// Model/prompt provenance of the scoring run (set by record_sentiment)
+    pub scoring_run: ScoringRun,
+    /// Top earners by payout, highest first (filled as payouts are claimed)
+    pub podium: [PodiumEntry; PODIUM_SIZE],
+    /// AuditLog pages opened; 0 only for markets created before create_market
+    /// opened page 0
+    pub audit_pages: u32,
+
+    // ── Dual Pool Fields (set at finalize_settlement) ─────────────────────
+    /// 70% of distributable_pool — paid proportionally to net backing
+    pub opinion_pool: u64,
+    /// 24% of distributable_pool — paid by inverse distance to crowd_score
+    pub prediction_pool: u64,
+    /// 6% of distributable_pool — lottery for top 20% predictors
+    pub jackpot_amount: u64,
+    /// Guard: jackpot can only be claimed once
+    pub jackpot_claimed: bool,
+    /// Σ opinion_pool_weight across all opinions — opinion pool divisor
+    /// (Σ max(0, backing − slashing) in Proportional mode)
+    pub total_net_backing: u64,
+    /// Σ 1_000_000 / (|prediction − crowd_score| + 1) — prediction pool divisor
+    pub sum_prediction_weights: u64,
+    /// Σ opinion_pool_weight of settled opinions (TopN excluded), tallied
+    /// as they settle; total_net_backing must match it
+    pub settled_pool_weight: u64,
+    /// Σ prediction_weight of settled opinions, tallied as they settle;
+    /// sum_prediction_weights must match it
+    pub settled_prediction_weight: u64,
+    /// Slash stakes reserved for vindicated authors (excluded from the pools)
+    pub vindication_total: u64,
+    /// Ceiling on each opinion's backing_total (author stake included)
+    pub backing_cap: ReactionCap,
+    /// Ceiling on each opinion's slashing_total
+    pub slashing_cap: ReactionCap,
+    /// Triple-Check W/C/A weights used by settle_opinion
+    pub scoring_weights: ScoringWeights,
+    /// Opinion pool distribution curve
+    pub payout_mode: PayoutMode,
+    /// TopN only: lowest combined_score inside the top N (posted at
+    /// finalize, checked against combined_score_counts)
+    pub top_n_cutoff: u8,
+    /// Mean or median crowd_score
+    pub crowd_score_mode: CrowdScoreMode,
+    /// Share of principal refunded to every opinion reaching
+    /// refund_floor_min_score before the pools are split; 0 = off
+    pub refund_floor_bps: u16,
+    /// Minimum combined_score an opinion needs to earn the refund floor
+    pub refund_floor_min_score: u8,
+    /// Σ refund floors owed to settled opinions (excluded from the pools)
+    pub refund_floor_total: u64,
+    /// Σ stake_amount of opinions whose staker qualified for a fee waiver
+    pub fee_waived_stake: u64,
+    /// Protocol fee waived at finalize, shared pro-rata by waived opinions
+    pub fee_waiver_pool: u64,
+    /// Σ payouts skipped as below config.min_payout (left for sweep_dust)
+    pub below_threshold_total: u64,
+    /// Ceiling on any one opinion's payout; the excess goes to uncapped claimants
+    pub payout_cap: ReactionCap,
+    /// Opinions counted by tally_payout_cap; claims wait for all of them
+    pub cap_tally_count: u32,
+    /// Σ uncapped payout above the cap across capped opinions
+    pub cap_excess_total: u64,
+    /// Σ uncapped payouts of opinions under the cap (excess divisor)
+    pub uncapped_payout_total: u64,
+    /// Payouts at or above this vest linearly from settled_at (0 = no vesting)
+    pub vesting_threshold: u64,
+    pub vesting_days: u16,
+    /// Gross protocol fee charged at settlement (basis for fee points)
+    pub protocol_fee_generated: u64,
+    /// Charity token account that receives the protocol fee (default = none)
+    pub charity_usdc: Pubkey,
+    /// Extra slice of the distributable pool donated to the charity
+    pub charity_pool_bps: u16,
+    /// Creation fee held in ["creation_fee", market] until close (0 once released)
+    pub creation_fee_escrowed: u64,
+    /// Opinions needed for the creation fee to go back to the creator
+    pub fee_refund_min_opinions: u32,
+    /// Sponsor deposits (sponsor_market); added to the settled pool
+    pub bonus_pool: u64,
+    /// Principal is always returned; only bonus_pool (and yield) is at risk
+    pub no_loss: bool,
+
+    // ── Merkle Settlement (set by post_settlement_root) ──────────────────
+    /// Root of (opinion, weight, consensus, ai, payout) leaves; zero if unused
+    pub settlement_root: [u8; 32],
+    /// Sum of all leaf payouts committed by the oracle
+    pub merkle_payout_total: u64,
+    /// Sum of leaf payouts claimed so far
+    pub merkle_claimed_total: u64,
+    /// Root of (opinion, weight, consensus, ai) score leaves for keeper-run
+    /// execute_settlement; zero if unused
+    pub scores_root: [u8; 32],
+
+    // ── ZK-Verified Scoring ───────────────────────────────────────────────
+    /// Hash chain over (opinion, backing, slashing, prediction) snapshots
+    pub inputs_hash: [u8; 32],
+    /// Opinions folded into inputs_hash so far
+    pub inputs_snapshot_count: u32,
+    /// Proof-verified root of (opinion, weight, consensus) leaves; zero if unused
+    pub verified_scores_root: [u8; 32],
+
+    // ── Crowd Score Accumulators (updated in stake_opinion) ──────────────
+    /// Σ market_prediction × stake_amount across all opinions
+    pub crowd_weighted_sum: u64,
+    /// Σ stake_amount across all opinions (reactions excluded)
+    pub crowd_weight_total: u64,
+    /// Median mode only: stake-weighted median market_prediction, recomputed from
+    /// the CrowdHistogram on every stake
+    pub crowd_median: u8,
+
+    // ── Layer 1 Weight Range (set by post_weight_range) ───────────────────
+    /// Lowest / highest net backing (backing − slashing) across all opinions
+    pub weight_min_net: i64,
+    pub weight_max_net: i64,
+    pub weight_range_posted: bool,
+    /// Some settled opinion sits exactly at the posted min / max; both must
+    /// hold before finalization so the oracle cannot widen the range
+    pub weight_min_attained: bool,
+    pub weight_max_attained: bool,
+
+    // ── Oracle Accountability ─────────────────────────────────────────────
+    /// Oracle bond slashed into this market (pool top-up or refund bonus)
+    pub oracle_slashed: u64,
+    /// total_stake when Refunding began
+    pub refund_base: u64,
+    /// Principal (opinion and reaction stakes) not yet refunded; each refund
+    /// takes principal / refund_outstanding of the current escrow balance
+    pub refund_outstanding: u64,
+    /// AI-score challenges awaiting respond_ai_challenge; blocks finalization
+    pub open_ai_challenges: u32,
+    /// Opinions with an AI score recorded (progress for the ScoringQueue)
+    pub ai_scored_count: u32,
+    /// Σ payout_amount of paid opinions opted in to redistribution
+    pub opt_in_payout_total: u64,
+    /// Escrow balance snapshotted at the first claim_redistribution
+    pub redistribution_pool: u64,
+    pub redistribution_started: bool,
+    /// Market has been added to the ScoringQueue
+    pub queued: bool,
+    /// total_stake has been credited to the creator's CreatorProfile
+    pub volume_recorded: bool,
+
+    // ── Escrow Yield ──────────────────────────────────────────────────────
+    /// Escrow principal currently lent out; must be 0 before settlement
+    pub yield_deposited: u64,
+    /// Interest returned by the lending market, added to the prize pool
+    pub yield_earned: u64,
+    /// Market-owned account holding the lending receipt while deposited
+    pub yield_receipt: Pubkey,
+
+    pub bump: u8,
+    pub schema_version: u8,
+
+    // ── Results archive ────────────────────────────────────────────────────
+    /// ResultsArchive pages opened for this market
+    pub results_pages: u32,
+    /// Opinions copied into the archive and closed
+    pub archived_opinions: u32,
+
+    // ── Market registry ──────────────────────────────────────────────────────
+    /// Creator-chosen listing category (0 = uncategorized)
+    pub category: u8,
+    /// Position in the market registry; None for markets created before it
+    pub registry_index: Option<u64>,
+
+    // ── Event sequencing ─────────────────────────────────────────────────────
+    /// Events emitted for this market so far; the next event's seq
+    pub event_seq: u64,
+
+    // ── Hash-only statements ─────────────────────────────────────────────────
+    /// SHA-256 of the statement text for markets created by
+    /// create_market_hashed; zero when `statement` holds the text itself
+    pub statement_hash: [u8; 32],
+
+    // ── Escrow authority ─────────────────────────────────────────────────────
+    /// Bump of the ["escrow_authority", market] PDA that owns the market's
+    /// escrow token accounts; set at creation or by migrate_escrow_authority
+    pub escrow_authority_bump: u8,
+
+    // ── Checkpoints ──────────────────────────────────────────────────────────
+    /// Σ stake of live Back reactions across the market's opinions
+    pub backing_total: u64,
+    /// Σ stake of live Slash reactions across the market's opinions
+    pub slashing_total: u64,
+    /// MarketCheckpoint PDAs written so far; the next index
+    pub checkpoint_count: u32,
+    pub last_checkpoint_at: i64,
+
+    // ── Lookup table ─────────────────────────────────────────────────────────
+    /// Address lookup table of the market's hot accounts, owned by the
+    /// market PDA (default until bootstrap_lookup_table)
+    pub lookup_table: Pubkey,
+
+    // ── Opinion index ────────────────────────────────────────────────────────
+    /// Opinions listed in the market's OpinionIndexPages; the next position
+    pub indexed_opinions: u32,
+
+    // ── Automation ───────────────────────────────────────────────────────────
+    /// Clockwork thread that closes the market at reactions_close_at; default
+    /// when none is registered
+    pub lifecycle_thread: Pubkey,
+
+    // ── Pyth resolution ──────────────────────────────────────────────────────
+    /// Pyth feed that resolves the market; zero for oracle-scored markets
+    pub pyth_feed_id: [u8; 32],
+    /// The statement is true iff the feed's price at close is at least this,
+    /// in units of 10^pyth_exponent
+    pub pyth_threshold: i64,
+    pub pyth_exponent: i32,
+    /// Stake on opinions agreeing (opinion_score above 50) and disagreeing
+    /// (below 50) with the statement
+    pub yes_stake: u64,
+    pub no_stake: u64,
+    /// Set by resolve_pyth_market
+    pub pyth_outcome: bool,
+
+    // ── Settle hooks ─────────────────────────────────────────────────────────
+    /// Programs in the market's SettleHooks
+    pub settle_hook_count: u8,
+
+    // ── Cross-chain mirroring ────────────────────────────────────────────────
+    /// Highest combined scores from Triple-Check settlement (payout unused);
+    /// the winners in the Wormhole settlement payload
+    pub top_scorers: [PodiumEntry; PODIUM_SIZE],
+
+    // ── Market asset ─────────────────────────────────────────────────────────
+    /// Metaplex Core asset minted by mint_market_asset; default if none
+    pub asset: Pubkey,
+
+    // ── Partner attribution ──────────────────────────────────────────────────
+    /// Program that created the market via CPI; default if created directly
+    pub created_via: Pubkey,
+
+    // ── Settlement bounty ────────────────────────────────────────────────────
+    /// Settlement bounties accrued to keepers, owed out of the protocol fee
+    /// once the market finalizes
+    pub settlement_bounty_accrued: u64,
+
+    // ── Confidential stakes ──────────────────────────────────────────────────
+    /// Token-2022 account holding confidential stakes; default if the market
+    /// takes public stakes
+    pub confidential_escrow: Pubkey,
+    /// Confidential stakes committed, revealed or not
+    pub confidential_stakes: u32,
+    /// Confidential stakes revealed by their stakers
+    pub confidential_revealed: u32,
+    /// Σ revealed confidential stake amounts
+    pub confidential_revealed_total: u64,
+    /// The confidential escrow was emptied into escrow_token_account
+    pub confidential_released: bool,
+
+    // ── Optimistic settlement ────────────────────────────────────────────────
+    /// A propose_settlement_root proposal is pending or disputed; direct
+    /// settlement waits until it is finalized, resolved or rejected
+    pub optimistic_open: bool,
+    /// Protocol fee held back in escrow at finalization for keepers'
+    /// settlement bounties (never more than the net fee)
+    pub settlement_bounty_reserved: u64,
+    /// Σ settlement bounties claimed out of settlement_bounty_reserved
+    pub settlement_bounty_paid: u64,
+    /// Partner's share of the escrowed creation fee, paid to it by
+    /// release_creation_fee when the fee goes to the treasury
+    pub partner_fee: u64,
+    /// Triple-Check settled opinions per combined_score, from which TopN
+    /// derives its cutoff
+    pub combined_score_counts: [u32; COMBINED_SCORE_BUCKETS],
+    /// Lowest nonce an oracle-signed message may still carry; relaying one
+    /// moves it past that message's nonce, so no signature is replayed
+    pub signed_nonce: u64,
+}
+
+impl Market {
+    /// Settlement bounties reserved at finalization and not yet claimed
+    pub fn settlement_bounty_outstanding(&self) -> u64 {
+        self.settlement_bounty_reserved.saturating_sub(self.settlement_bounty_paid)
+    }
+
+    /// Partner program recorded at creation, if any
+    pub fn created_via(&self) -> Option<Pubkey> {
+        (self.created_via != Pubkey::default()).then_some(self.created_via)
+    }
+
+    /// Whether the market takes confidential stakes
+    pub fn is_confidential(&self) -> bool {
+        self.confidential_escrow != Pubkey::default()
+    }
+
+    /// Revealed confidential stake counted in total_stake whose USDC is still
+    /// in the confidential escrow
+    pub fn unreleased_confidential_stake(&self) -> u64 {
+        if self.is_confidential() && !self.confidential_released {
+            self.confidential_revealed_total
+        } else {
+            0
+        }
+    }
+
+    /// Whether `statement` is this market's statement text; `stored` is its
+    /// MarketStatement
+    pub fn statement_matches(&self, stored: &MarketStatement, statement: &str) -> bool {
+        if self.statement_hash == [0u8; 32] {
+            stored.statement == statement
+        } else {
+            hashv(&[statement.as_bytes()]).to_bytes() == self.statement_hash
+        }
+    }
+
+    /// Claim the seq for the next event about this market
+    pub fn next_event_seq(&mut self) -> u64 {
+        let seq = self.event_seq;
+        self.event_seq = seq.saturating_add(1);
+        seq
+    }
+
+    /// Whether the market is resolved from a Pyth feed
+    pub fn is_objective(&self) -> bool {
+        self.pyth_feed_id != [0u8; 32]
+    }
+
+    /// Stake on the side matching the resolved outcome
+    pub fn winning_stake(&self) -> u64 {
+        if self.pyth_outcome { self.yes_stake } else { self.no_stake }
+    }
+
+    /// A resolved Pyth market's payout: the distributable pool split pro rata
+    /// by stake among opinions on the winning side. Neutral opinions (score
+    /// 50) and the losing side get nothing.
+    pub fn objective_payout(&self, opinion: &Opinion) -> u64 {
+        let on_winning_side = if self.pyth_outcome {
+            opinion.opinion_score > 50
+        } else {
+            opinion.opinion_score < 50
+        };
+        let winning_stake = self.winning_stake();
+        if !on_winning_side || winning_stake == 0 {
+            return 0;
+        }
+        (self.distributable_pool as u128 * opinion.stake_amount() as u128
+            / winning_stake as u128) as u64
+    }
+
+    /// Pool totals returned to CPI callers by get_pool
+    pub fn pool_snapshot(&self) -> PoolSnapshot {
+        PoolSnapshot {
+            state: self.state.clone(),
+            closes_at: self.closes_at,
+            total_stake: self.total_stake,
+            staker_count: self.staker_count,
+            backing_total: self.backing_total,
+            slashing_total: self.slashing_total,
+        }
+    }
+
+    /// Index page the next staked opinion is listed on
+    pub fn opinion_index_page(&self) -> u32 {
+        self.indexed_opinions / OPINION_INDEX_PAGE_SIZE as u32
+    }
+
+    /// Registry page holding this market's entry (0 when unregistered)
+    pub fn registry_page(&self) -> u32 {
+        self.registry_index.map_or(0, |index| (index / REGISTRY_PAGE_SIZE as u64) as u32)
+    }
+
+    /// Count a reaction's stake in the market's aggregate backing or slashing
+    pub fn add_reaction_stake(&mut self, reaction_type: &ReactionType, amount: u64) -> Result<()> {
+        let total = match reaction_type {
+            ReactionType::Back => &mut self.backing_total,
+            ReactionType::Slash => &mut self.slashing_total,
+        };
+        *total = total.checked_add(amount).ok_or(OpinionError::Overflow)?;
+        Ok(())
+    }
+
+    /// Drop a withdrawn reaction's stake from the aggregate totals
+    pub fn remove_reaction_stake(&mut self, reaction_type: &ReactionType, amount: u64) {
+        let total = match reaction_type {
+            ReactionType::Back => &mut self.backing_total,
+            ReactionType::Slash => &mut self.slashing_total,
+        };
+        *total = total.saturating_sub(amount);
+    }
+
+    /// Reject a reaction that pushed an opinion past the market's caps
+    pub fn check_reaction_caps(&self, opinion: &Opinion) -> Result<()> {
+        if let Some(limit) = self.backing_cap.limit(opinion.stake_amount()) {
+            require!(opinion.backing_total <= limit, OpinionError::BackingCapExceeded);
+        }
+        if let Some(limit) = self.slashing_cap.limit(opinion.stake_amount()) {
+            require!(opinion.slashing_total <= limit, OpinionError::SlashingCapExceeded);
+        }
+        Ok(())
+    }
+
+    /// Oracle authorized to score and settle this market
+    pub fn oracle(&self, config: &ProgramConfig) -> Pubkey {
+        self.oracle_override.unwrap_or(config.oracle_authority)
+    }
+
+    /// When an unfinalized market may start refunding. Markets never closed
+    /// fall back to RECOVERY_PERIOD after reactions close.
+    pub fn refund_deadline(&self) -> i64 {
+        if self.score_deadline > 0 {
+            self.score_deadline
+        } else {
+            self.reactions_close_at + RECOVERY_PERIOD
+        }
+    }
+
+    /// Move an unfinalized market past its deadline into Refunding and
+    /// snapshot the refund base. A closed market may refund early once the
+    /// oracle is known dead. Returns true if this call performed the transition.
+    pub fn begin_refunding(&mut self, now: i64, oracle_dead: bool) -> Result<bool> {
+        let awaiting_oracle = matches!(self.state, MarketState::Closed | MarketState::Scored);
+        require!(
+            now >= self.refund_deadline() || (oracle_dead && awaiting_oracle),
+            OpinionError::MarketNotExpired
+        );
+        match self.state {
+            MarketState::Refunding => Ok(false),
+            MarketState::Settled => err!(OpinionError::MarketAlreadySettled),
+            _ => {
+                self.start_refunding();
+                Ok(true)
+            }
+        }
+    }
+
+    /// Close an Active market whose reactions window has passed and start
+    /// the oracle's scoring deadline. A lone opinion has nothing to be
+    /// scored against: it skips the Triple-Check and the market refunds the
+    /// stake and any reactions at par. Returns true in that case.
+    pub fn close_at(&mut self, now: i64) -> Result<bool> {
+        require!(self.state == MarketState::Active, OpinionError::MarketNotActive);
+        require!(now >= self.reactions_close_at, OpinionError::MarketNotExpired);
+        self.state = MarketState::Closed;
+        self.score_deadline = now + RECOVERY_PERIOD;
+        let single_staker = self.staker_count == 1;
+        if single_staker {
+            self.start_refunding();
+        }
+        Ok(single_staker)
+    }
+
+    /// Enter Refunding with every staked dollar in escrow still owed;
+    /// revealed confidential stakes join once released
+    pub fn start_refunding(&mut self) {
+        let owed = self.total_stake.saturating_sub(self.unreleased_confidential_stake());
+        self.state = MarketState::Refunding;
+        self.refund_base = owed;
+        self.refund_outstanding = owed;
+    }
+
+    /// Gate for stake recovery: enter_refunding must have been called
+    pub fn require_refunding(&self, now: i64) -> Result<()> {
+        if self.state == MarketState::Refunding {
+            require!(self.yield_deposited == 0, OpinionError::YieldStillDeposited);
+            return Ok(());
+        }
+        require!(now >= self.refund_deadline(), OpinionError::MarketNotExpired);
+        err!(OpinionError::MarketNotRefunding)
+    }
+
+    /// Fails if the market is already locked to a different settlement path
+    pub fn require_settlement_mode(&self, mode: SettlementMode) -> Result<()> {
+        require!(
+            self.settlement_mode == SettlementMode::Unset || self.settlement_mode == mode,
+            OpinionError::SettlementPathLocked
+        );
+        Ok(())
+    }
+
+    /// Lock the market to `mode` (no-op if already locked to it)
+    pub fn lock_settlement_mode(&mut self, mode: SettlementMode) -> Result<()> {
+        self.require_settlement_mode(mode)?;
+        self.settlement_mode = mode;
+        Ok(())
+    }
+
+    /// Stake plus lending yield and sponsorship available to settle; fails while escrow is
+    /// still lent out
+    pub fn settlement_stake(&self) -> Result<u64> {
+        require!(self.yield_deposited == 0, OpinionError::YieldStillDeposited);
+        self.total_stake
+            .checked_add(self.yield_earned)
+            .and_then(|stake| stake.checked_add(self.bonus_pool))
+            .ok_or(error!(OpinionError::Overflow))
+    }
+
+    /// Refund owed on `principal`: its pro-rata share of what the escrow
+    /// actually holds (stakes plus any slashed oracle bond). Retires the
+    /// principal, so the last refund drains the escrow exactly.
+    pub fn take_refund(&mut self, principal: u64, escrow_balance: u64) -> Result<u64> {
+        require!(
+            principal > 0 && principal <= self.refund_outstanding,
+            OpinionError::RefundExceedsOutstanding
+        );
+        let refund = (escrow_balance as u128)
+            .checked_mul(principal as u128)
+            .ok_or(OpinionError::Overflow)?
+            / self.refund_outstanding as u128;
+        self.refund_outstanding -= principal;
+        Ok(refund as u64)
+    }
+
+    /// An opinion's share weight of the opinion pool under this market's payout_mode
+    pub fn opinion_pool_weight(&self, opinion: &Opinion) -> u64 {
+        match self.payout_mode {
+            PayoutMode::Proportional => opinion.backing_total.saturating_sub(opinion.slashing_total),
+            PayoutMode::TopN(n) => match self.top_n_split(n) {
+                Some(split) => split.weight(opinion.combined_score()),
+                None => (opinion.combined_score() >= self.top_n_cutoff) as u64,
+            },
+            PayoutMode::Exponential => 1u64 << (opinion.combined_score() / 10),
+        }
+    }
+
+    /// An opinion's share in settled_pool_weight: its opinion pool weight,
+    /// except in TopN mode, where weights depend on every score and
+    /// top_n_split checks the total instead
+    pub fn tallied_pool_weight(&self, opinion: &Opinion) -> u64 {
+        match self.payout_mode {
+            PayoutMode::TopN(_) => 0,
+            _ => self.opinion_pool_weight(opinion),
+        }
+    }
+
+    /// An opinion's share weight of the prediction pool: inverse distance
+    /// from crowd_score
+    pub fn prediction_weight(&self, opinion: &Opinion) -> u64 {
+        let diff = (opinion.market_prediction as i64 - self.crowd_score as i64).unsigned_abs();
+        1_000_000u64 / (diff + 1)
+    }
+
+    /// Whether every settled opinion is in combined_score_counts and the
+    /// settled_* tallies; opinions settled under the baseline layout are not
+    pub fn settlement_tallied(&self) -> bool {
+        self.combined_score_counts.iter().sum::<u32>() == self.settled_count
+    }
+
+    /// Accept an oracle-signed message carrying `nonce`: it must not be below
+    /// signed_nonce, which then moves past it. Messages may skip nonces but
+    /// must land in nonce order.
+    pub fn consume_signed_nonce(&mut self, nonce: u64) -> Result<()> {
+        require!(nonce >= self.signed_nonce, OpinionError::StaleSignedNonce);
+        self.signed_nonce = nonce.checked_add(1).ok_or(OpinionError::Overflow)?;
+        Ok(())
+    }
+
+    /// Move a settled opinion's combined_score between histogram buckets
+    pub fn tally_combined_score(&mut self, previous: Option<u8>, score: u8) {
+        if let Some(count) = previous.and_then(|p| self.combined_score_counts.get_mut(p as usize)) {
+            *count = count.saturating_sub(1);
+        }
+        if let Some(count) = self.combined_score_counts.get_mut(score as usize) {
+            *count = count.saturating_add(1);
+        }
+    }
+
+    /// Where the top `n` combined scores end, from combined_score_counts.
+    /// None unless settlement_tallied, leaving the posted top_n_cutoff in
+    /// force.
+    pub fn top_n_split(&self, n: u8) -> Option<TopNSplit> {
+        if !self.settlement_tallied() {
+            return None;
+        }
+        let mut above = 0u32;
+        let mut lowest = None;
+        for (score, &count) in self.combined_score_counts.iter().enumerate().rev() {
+            if count == 0 {
+                continue;
+            }
+            let split = TopNSplit { cutoff: score as u8, above, tied: count, slots: n as u32 };
+            if above + count >= n as u32 {
+                return Some(split);
+            }
+            above += count;
+            lowest = Some(split);
+        }
+        // Fewer than n opinions: every one of them is inside
+        Some(lowest.map_or(TopNSplit::default(), |split| TopNSplit {
+            slots: split.above + split.tied,
+            ..split
+        }))
+    }
+
+    /// Stake-weighted mean (rounded half up) or median market_prediction, per
+    /// crowd_score_mode; None before any stake
+    pub fn onchain_crowd_score(&self) -> Option<u8> {
+        if self.crowd_weight_total == 0 {
+            return None;
+        }
+        if self.crowd_score_mode == CrowdScoreMode::Median {
+            return Some(self.crowd_median);
+        }
+        let score = (self.crowd_weighted_sum as u128 + self.crowd_weight_total as u128 / 2)
+            / self.crowd_weight_total as u128;
+        Some(score as u8)
+    }
+
+    /// Insert a paid staker into the podium if their payout ranks in the top
+    /// PODIUM_SIZE; ties keep the earlier claimant ahead
+    pub fn record_podium(&mut self, staker: Pubkey, combined_score: u8, payout: u64) {
+        let entry = PodiumEntry { staker, combined_score, payout };
+        let Some(rank) = self
+            .podium
+            .iter()
+            .position(|slot| slot.staker == Pubkey::default() || payout > slot.payout)
+        else {
+            return;
+        };
+        self.podium[rank..].rotate_right(1);
+        self.podium[rank] = entry;
+    }
+
+    /// Keep the PODIUM_SIZE highest combined scores seen by Triple-Check
+    /// settlement; a re-settled staker is re-ranked and ties keep the earlier
+    /// settlement ahead. A correction that lowers a listed score does not
+    /// promote opinions settled below the list.
+    pub fn record_top_scorer(&mut self, staker: Pubkey, combined_score: u8) {
+        if let Some(previous) = self.top_scorers.iter().position(|slot| slot.staker == staker) {
+            self.top_scorers[previous..].rotate_left(1);
+            self.top_scorers[PODIUM_SIZE - 1] = PodiumEntry::default();
+        }
+        let Some(rank) = self.top_scorers.iter().position(|slot| {
+            slot.staker == Pubkey::default() || combined_score > slot.combined_score
+        }) else {
+            return;
+        };
+        self.top_scorers[rank..].rotate_right(1);
+        self.top_scorers[rank] = PodiumEntry { staker, combined_score, payout: 0 };
+    }
+
+    /// Cross-chain settlement payload, big-endian for EVM decoding:
+    /// version (1) | market (32) | uuid (16) | crowd_score (1) |
+    /// distributable_pool (8) | settled_at (8) | winner count (1) |
+    /// per winner: staker (32) | combined_score (1)
+    pub fn settlement_payload(&self, market_key: &Pubkey) -> Vec<u8> {
+        let winners: Vec<&PodiumEntry> = self
+            .top_scorers
+            .iter()
+            .filter(|slot| slot.staker != Pubkey::default())
+            .collect();
+        let mut payload = Vec::with_capacity(67 + 33 * winners.len());
+        payload.push(SETTLEMENT_PAYLOAD_VERSION);
+        payload.extend_from_slice(market_key.as_ref());
+        payload.extend_from_slice(&self.uuid);
+        payload.push(self.crowd_score);
+        payload.extend_from_slice(&self.distributable_pool.to_be_bytes());
+        payload.extend_from_slice(&self.settled_at.to_be_bytes());
+        payload.push(winners.len() as u8);
+        for winner in winners {
+            payload.extend_from_slice(winner.staker.as_ref());
+            payload.push(winner.combined_score);
+        }
+        payload
+    }
+
+    /// Attributes plugin of the market's Core asset: statement (or CID),
+    /// state and pool size
+    pub fn asset_attributes(&self, statement: &MarketStatement) -> CoreAttributesPlugin {
+        let attribute = |key: &str, value: String| (key.to_string(), value);
+        CoreAttributesPlugin {
+            plugin: MPL_CORE_ATTRIBUTES_PLUGIN,
+            attribute_list: vec![
+                attribute("statement", statement.statement.clone()),
+                attribute("state", self.state.label().to_string()),
+                attribute("total_stake", self.total_stake.to_string()),
+                attribute("staker_count", self.staker_count.to_string()),
+                attribute("closes_at", self.closes_at.to_string()),
+            ],
+        }
+    }
+
+    /// Classic (non-Merkle) payout owed to an unpaid opinion:
+    ///   opinion pool share (by payout_mode) + prediction pool share
+    ///   (inverse distance from crowd_score) + vindication bonus + refund floor
+    ///   + share of any waived protocol fee
+    ///
+    /// Limited by payout_cap (see capped_payout). Fails if the market is not
+    /// claimable or the opinion was paid/expired.
+    pub fn claimable_payout(&self, opinion: &Opinion) -> Result<u64> {
+        require!(self.state == MarketState::Settled, OpinionError::MarketNotAwaitingSettlement);
+        require!(self.settlement_root == [0u8; 32], OpinionError::MerkleSettlementActive);
+        require!(
+            Clock::get()?.unix_timestamp < self.claim_deadline(),
+            OpinionError::ClaimWindowClosed
+        );
+        require!(!opinion.paid(), OpinionError::AlreadyPaid);
+        require!(!opinion.expired(), OpinionError::ClaimWindowClosed);
+        require!(!opinion.below_threshold(), OpinionError::PayoutBelowThreshold);
+        require!(!opinion.confidential_pending(), OpinionError::ConfidentialStakeUnrevealed);
+
+        let raw = self.raw_payout(opinion)?;
+        self.capped_payout(opinion, raw)
+    }
+
+    /// Payout an unpaid opinion may take now, as (entitlement, release).
+    /// Entitlements at or above vesting_threshold unlock linearly over
+    /// vesting_days from settled_at; release is what has vested beyond
+    /// claimed_so_far.
+    pub fn payout_release(&self, opinion: &Opinion, now: i64) -> Result<(u64, u64)> {
+        let entitlement = self.claimable_payout(opinion)?;
+        let vested = if self.vesting_threshold == 0 || entitlement < self.vesting_threshold {
+            entitlement
+        } else {
+            let duration = self.vesting_days as i64 * 86_400;
+            let elapsed = now.saturating_sub(self.settled_at).clamp(0, duration);
+            (entitlement as u128 * elapsed as u128 / duration as u128) as u64
+        };
+        let release = vested.saturating_sub(opinion.claimed_so_far);
+        require!(release > 0, OpinionError::NothingVested);
+        Ok((entitlement, release))
+    }
+
+    /// Apply payout_cap to a raw payout. Capped opinions get the ceiling;
+    /// uncapped ones add a pro-rata share of the tallied excess, still bounded
+    /// by the ceiling (anything left over stays for sweep_dust).
+    pub fn capped_payout(&self, opinion: &Opinion, raw: u64) -> Result<u64> {
+        let Some(limit) = self.payout_cap.limit(opinion.stake_amount()) else {
+            return Ok(raw);
+        };
+        require!(self.cap_tally_count == self.staker_count, OpinionError::PayoutCapTallyIncomplete);
+        if raw >= limit {
+            return Ok(limit);
+        }
+        let excess_share = if self.uncapped_payout_total > 0 {
+            (self.cap_excess_total as u128 * raw as u128 / self.uncapped_payout_total as u128) as u64
+        } else {
+            0
+        };
+        Ok(raw.saturating_add(excess_share).min(limit))
+    }
+
+    /// Payout before payout_cap, with no claimability checks
+    pub fn raw_payout(&self, opinion: &Opinion) -> Result<u64> {
+        if self.settlement_mode == SettlementMode::Objective {
+            return Ok(self.objective_payout(opinion));
+        }
+
+        // Opinion pool payout — share weight depends on the market's payout_mode
+        let pool_weight = self.opinion_pool_weight(opinion);
+        let opinion_payout = if self.total_net_backing > 0 {
+            pool_weight
+                .checked_mul(self.opinion_pool).ok_or(OpinionError::Overflow)?
+                .checked_div(self.total_net_backing).ok_or(OpinionError::Overflow)?
+        } else {
+            self.opinion_pool / self.staker_count as u64 // equal split fallback
+        };
+
+        // Prediction pool payout — inverse distance from crowd score
+        let prediction_payout = if self.sum_prediction_weights > 0 {
+            self.prediction_weight(opinion)
+                .checked_mul(self.prediction_pool).ok_or(OpinionError::Overflow)?
+                .checked_div(self.sum_prediction_weights).ok_or(OpinionError::Overflow)?
+        } else {
+            0
+        };
+
+        Ok(opinion_payout
+            .checked_add(prediction_payout).ok_or(OpinionError::Overflow)?
+            .checked_add(opinion.vindication_bonus).ok_or(OpinionError::Overflow)?
+            .checked_add(opinion.refund_floor()).ok_or(OpinionError::Overflow)?
+            .checked_add(self.fee_waiver_share(opinion)).ok_or(OpinionError::Overflow)?)
+    }
+
+    /// An opinion's pro-rata share of the waived protocol fee
+    pub fn fee_waiver_share(&self, opinion: &Opinion) -> u64 {
+        if !opinion.fee_waived() || self.fee_waived_stake == 0 {
+            return 0;
+        }
+        (self.fee_waiver_pool as u128 * opinion.stake_amount() as u128 / self.fee_waived_stake as u128) as u64
+    }
+
+    /// Principal refund floor an opinion earns at this combined_score
+    /// (the whole stake in a no-loss market)
+    pub fn refund_floor_for(&self, opinion: &Opinion, combined_score: u8) -> u64 {
+        if self.no_loss {
+            return opinion.stake_amount();
+        }
+        if self.refund_floor_bps == 0 || combined_score < self.refund_floor_min_score {
+            return 0;
+        }
+        (opinion.stake_amount() as u128 * self.refund_floor_bps as u128 / 10_000) as u64
+    }
+
+    /// End of the payout claim window
+    pub fn claim_deadline(&self) -> i64 {
+        self.settled_at.saturating_add(CLAIM_WINDOW)
+    }
+
+    /// Leftover escrow may be swept from here on; opted-in claimants get an
+    /// extra REDISTRIBUTION_WINDOW to collect expired payouts first
+    pub fn sweep_after(&self) -> i64 {
+        if self.opt_in_payout_total > 0 {
+            self.claim_deadline().saturating_add(REDISTRIBUTION_WINDOW)
+        } else {
+            self.claim_deadline()
+        }
+    }
+
+    /// Reject an oracle-supplied weight_score that disagrees with the opinion's
+    /// net backing and the posted range; records whether the range endpoints
+    /// are attained
+    pub fn verify_weight_score(&mut self, opinion: &Opinion, weight_score: u8) -> Result<()> {
+        require!(self.weight_range_posted, OpinionError::WeightRangeNotPosted);
+        let net = opinion.net_backing();
+        require!(
+            net >= self.weight_min_net && net <= self.weight_max_net,
+            OpinionError::NetBackingOutOfRange
+        );
+        require!(
+            weight_score == compute_weight_score(net, self.weight_min_net, self.weight_max_net),
+            OpinionError::WeightScoreMismatch
+        );
+        self.weight_min_attained |= net == self.weight_min_net;
+        self.weight_max_attained |= net == self.weight_max_net;
+        Ok(())
+    }
+
+    /// Count a new opinion's stake in total_stake, staker_count and the
+    /// crowd-score accumulators (and the histogram in Median mode)
+    pub fn record_opinion_stake(
+        &mut self,
+        histogram: Option<&mut CrowdHistogram>,
+        opinion_score: u8,
+        market_prediction: u8,
+        stake_amount: u64,
+    ) -> Result<()> {
+        self.staker_count = self.staker_count.saturating_add(1);
+        self.record_stake_weight(histogram, opinion_score, market_prediction, stake_amount)
+    }
+
+    /// Add a stake's amount to the pool, the yes/no split (by opinion_score)
+    /// and the crowd-score accumulators (by market_prediction); the opinion
+    /// itself is counted separately (a confidential stake is counted when
+    /// committed and weighed when revealed)
+    pub fn record_stake_weight(
+        &mut self,
+        histogram: Option<&mut CrowdHistogram>,
+        opinion_score: u8,
+        market_prediction: u8,
+        stake_amount: u64,
+    ) -> Result<()> {
+        self.total_stake = self.total_stake.saturating_add(stake_amount);
+        if opinion_score > 50 {
+            self.yes_stake = self.yes_stake.saturating_add(stake_amount);
+        } else if opinion_score < 50 {
+            self.no_stake = self.no_stake.saturating_add(stake_amount);
+        }
+        self.crowd_weighted_sum = self
+            .crowd_weighted_sum
+            .checked_add(market_prediction as u64 * stake_amount)
+            .ok_or(OpinionError::Overflow)?;
+        self.crowd_weight_total = self
+            .crowd_weight_total
+            .checked_add(stake_amount)
+            .ok_or(OpinionError::Overflow)?;
+        if self.crowd_score_mode == CrowdScoreMode::Median {
+            let histogram = histogram.ok_or(OpinionError::CrowdHistogramRequired)?;
+            let bucket = &mut histogram.stake_by_score[market_prediction as usize];
+            *bucket = bucket.saturating_add(stake_amount);
+            self.crowd_median = histogram.weighted_median(self.crowd_weight_total);
+        }
+        Ok(())
+    }
+
+    /// Reject an oracle-supplied crowd_score that disagrees with the accumulators
+    pub fn verify_crowd_score(&self, crowd_score: u8) -> Result<()> {
+        if let Some(expected) = self.onchain_crowd_score() {
+            require!(crowd_score == expected, OpinionError::CrowdScoreMismatch);
+        }
+        Ok(())
+    }
+
+    pub const SPACE: usize =
+        8   // discriminator
+        + 32  // creator
+        + 16  // uuid
+        + 8   // created_at
+        + 8   // closes_at
+        + 8   // reactions_close_at
+        + 8   // score_deadline
+        + 8   // settled_at
+        + 1   // state enum tag
+        + 1   // settlement_mode
+        + 4   // staker_count
+        + 4   // settled_count
+        + 8   // total_stake
+        + 8   // distributable_pool
+        + 1 + 32 // oracle_override: Option<Pubkey>
+        + 1   // crowd_score
+        + 1   // sentiment_score
+        + 1   // confidence
+        + 32  // summary_hash
+        + ScoringRun::SPACE // scoring_run
+        + PodiumEntry::SPACE * PODIUM_SIZE // podium
+        + 4   // audit_pages
+        + 8   // opinion_pool
+        + 8   // prediction_pool
+        + 8   // jackpot_amount
+        + 1   // jackpot_claimed
+        + 8   // total_net_backing
+        + 8   // sum_prediction_weights
+        + 8   // settled_pool_weight
+        + 8   // settled_prediction_weight
+        + 8   // vindication_total
+        + ReactionCap::SPACE // backing_cap
+        + ReactionCap::SPACE // slashing_cap
+        + ScoringWeights::SPACE // scoring_weights
+        + PayoutMode::SPACE // payout_mode
+        + 1   // top_n_cutoff
+        + 1   // crowd_score_mode
+        + 2   // refund_floor_bps
+        + 1   // refund_floor_min_score
+        + 8   // refund_floor_total
+        + 8   // fee_waived_stake
+        + 8   // fee_waiver_pool
+        + 8   // below_threshold_total
+        + ReactionCap::SPACE // payout_cap
+        + 4   // cap_tally_count
+        + 8   // cap_excess_total
+        + 8   // uncapped_payout_total
+        + 8   // vesting_threshold
+        + 2   // vesting_days
+        + 8   // protocol_fee_generated
+        + 32  // charity_usdc
+        + 2   // charity_pool_bps
+        + 8   // creation_fee_escrowed
+        + 4   // fee_refund_min_opinions
+        + 8   // bonus_pool
+        + 1   // no_loss
+        + 32  // settlement_root
+        + 8   // merkle_payout_total
+        + 8   // merkle_claimed_total
+        + 32  // scores_root
+        + 32  // inputs_hash
+        + 4   // inputs_snapshot_count
+        + 32  // verified_scores_root
+        + 8   // crowd_weighted_sum
+        + 8   // crowd_weight_total
+        + 1   // crowd_median
+        + 8   // weight_min_net
+        + 8   // weight_max_net
+        + 1   // weight_range_posted
+        + 1   // weight_min_attained
+        + 1   // weight_max_attained
+        + 8   // oracle_slashed
+        + 8   // refund_base
+        + 8   // refund_outstanding
+        + 4   // open_ai_challenges
+        + 4   // ai_scored_count
+        + 8   // opt_in_payout_total
+        + 8   // redistribution_pool
+        + 1   // redistribution_started
+        + 1   // queued
+        + 1   // volume_recorded
+        + 8   // yield_deposited
+        + 8   // yield_earned
+        + 32  // yield_receipt
+        + 1   // bump
+        + 1   // schema_version
+        + 4   // results_pages
+        + 4   // archived_opinions
+        + 1   // category
+        + 9   // registry_index
+        + 8   // event_seq
+        + 32  // statement_hash
+        + 1   // escrow_authority_bump
+        + 8   // backing_total
+        + 8   // slashing_total
+        + 4   // checkpoint_count
+        + 8   // last_checkpoint_at
+        + 32  // lookup_table
+        + 4   // indexed_opinions
+        + 32  // lifecycle_thread
+        + 32  // pyth_feed_id
+        + 8   // pyth_threshold
+        + 4   // pyth_exponent
+        + 8   // yes_stake
+        + 8   // no_stake
+        + 1   // pyth_outcome
+        + 1   // settle_hook_count
+        + PodiumEntry::SPACE * PODIUM_SIZE // top_scorers
+        + 32  // asset
+        + 32  // created_via
+        + 8   // settlement_bounty_accrued
+        + 32  // confidential_escrow
+        + 4   // confidential_stakes
+        + 4   // confidential_revealed
+        + 8   // confidential_revealed_total
+        + 1   // confidential_released
+        + 1   // optimistic_open
+        + 8   // settlement_bounty_reserved
+        + 8   // settlement_bounty_paid
+        + 8   // partner_fee
+        + 4 * COMBINED_SCORE_BUCKETS // combined_score_counts
+        + 8;  // signed_nonce
+}
+
+/// A market's statement: the full text, or for a hashed market the IPFS
+/// CID of the text. Split from Market so only instructions that read the
+/// statement decode it.
+#[account]
+pub struct MarketStatement {
+    pub market: Pubkey,
+    pub statement: String,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl MarketStatement {
+    /// Size of a statement account holding `statement_len` bytes
+    pub const fn space_for(statement_len: usize) -> usize {
+        8 + 32 + 4 + statement_len + 1 + 1
+    }
+}
+
+/// Borsh Market layout deployed before schema versioning, read only by
+/// migrate_market
+#[derive(AnchorDeserialize)]
+pub struct BaselineMarket {
+    pub creator: Pubkey,
+    pub uuid: [u8; 16],
+    pub statement: String,
+    pub created_at: i64,
+    pub closes_at: i64,
+    pub state: MarketState,
+    pub staker_count: u32,
+    pub total_stake: u64,
+    pub distributable_pool: u64,
+    pub crowd_score: u8,
+    pub sentiment_score: u8,
+    pub confidence: u8,
+    pub summary_hash: [u8; 32],
+    pub winner: Option<Pubkey>,
+    pub opinion_pool: u64,
+    pub prediction_pool: u64,
+    pub jackpot_amount: u64,
+    pub jackpot_claimed: bool,
+    pub bump: u8,
+}
+
+impl BaselineMarket {
+    /// The same market in the fixed-size layout, and its statement. Fields
+    /// the baseline lacked take create_market's defaults; the escrow stays
+    /// with the market until migrate_escrow_authority hands it over. The
+    /// baseline kept no pool divisors, so a market it settled pays out of
+    /// the equal-split fallbacks.
+    pub fn split(self) -> Result<(Market, String)> {
+        let mut market = Market::try_deserialize_unchecked(&mut &vec![0u8; Market::SPACE][..])?;
+        market.creator = self.creator;
+        market.uuid = self.uuid;
+        market.created_at = self.created_at;
+        market.closes_at = self.closes_at;
+        market.reactions_close_at = self.closes_at;
+        market.state = self.state;
+        market.staker_count = self.staker_count;
+        market.total_stake = self.total_stake;
+        market.distributable_pool = self.distributable_pool;
+        market.crowd_score = self.crowd_score;
+        market.sentiment_score = self.sentiment_score;
+        market.confidence = self.confidence;
+        market.summary_hash = self.summary_hash;
+        market.opinion_pool = self.opinion_pool;
+        market.prediction_pool = self.prediction_pool;
+        market.jackpot_amount = self.jackpot_amount;
+        market.jackpot_claimed = self.jackpot_claimed;
+        market.scoring_weights = ScoringWeights::DEFAULT;
+        market.bump = self.bump;
+        market.schema_version = ACCOUNT_SCHEMA_VERSION;
+        Ok((market, self.statement))
+    }
+}
+
+/// Bits of Opinion::flags
+pub mod opinion_flags {
+    pub const SETTLED: u16 = 1 << 0;
+    pub const REFUNDED: u16 = 1 << 1;
+    pub const FEE_WAIVED: u16 = 1 << 2;
+    pub const BELOW_THRESHOLD: u16 = 1 << 3;
+    pub const CAP_TALLIED: u16 = 1 << 4;
+    pub const POINTS_CLAIMED: u16 = 1 << 5;
+    pub const PAID: u16 = 1 << 6;
+    pub const INPUTS_SNAPSHOTTED: u16 = 1 << 7;
+    pub const AI_SCORED: u16 = 1 << 8;
+    pub const EXPIRED: u16 = 1 << 9;
+    pub const REDISTRIBUTION_OPT_IN: u16 = 1 << 10;
+    pub const REDISTRIBUTION_CLAIMED: u16 = 1 << 11;
+    pub const BADGE_MINTED: u16 = 1 << 12;
+    pub const REPUTATION_CLAIMED: u16 = 1 << 13;
+    pub const PAYOUT_ANNOUNCED: u16 = 1 << 14;
+    pub const CONFIDENTIAL_PENDING: u16 = 1 << 15;
+}
+
+// Opinion::stake holds any valid stake
+const _: () = assert!(MAX_STAKE <= u32::MAX as u64);
+
+/// Discriminator of the packed Opinion layout. Changed from the default so
+/// opinions still in the baseline layout are rejected until migrate_opinion
+/// has rewritten them.
+pub const OPINION_DISCRIMINATOR: &[u8] = b"opinion1";
+
+/// Generates a getter/setter pair per Opinion::flags bit
+macro_rules! opinion_flag_accessors {
+    ($($(#[$doc:meta])* $get:ident, $set:ident => $bit:path;)*) => {$(
+        $(#[$doc])*
+        pub fn $get(&self) -> bool {
+            self.flags & $bit != 0
+        }
+
+        pub fn $set(&mut self, on: bool) {
+            if on {
+                self.flags |= $bit;
+            } else {
+                self.flags &= !$bit;
+            }
+        }
+    )*};
+}
+
+/// A single staked opinion — extended with Triple-Check scoring fields.
+/// Zero-copy: fields are ordered widest-first so the `repr(C)` layout has
+/// no padding, and batch instructions can read opinions without a Borsh pass.
+/// Scores, status flags and the stake-bounded amounts are packed to keep
+/// rent down; instruction code goes through the accessors below.
+#[account(zero_copy, discriminator = OPINION_DISCRIMINATOR)]
+#[repr(C)]
+pub struct Opinion {
+    pub created_at: i64,
+
+    // ── Layer 1: Peer Backing ────────────────────────────────────────────────
+    /// Total USDC staked to Back (agree with) this opinion
+    pub backing_total: u64,
+    /// Total USDC staked to Slash (disagree with) this opinion
+    pub slashing_total: u64,
+
+    // ── Payout ───────────────────────────────────────────────────────────────
+    /// Share of slashers' stakes awarded at settlement if the opinion was vindicated
+    pub vindication_bonus: u64,
+    pub payout_amount: u64,
+    /// Released so far by vesting or partial claims; paid once it reaches
+    /// payout_amount
+    pub claimed_so_far: u64,
+
+    pub market: Pubkey,
+    pub staker: Pubkey,
+    /// SHA-256 of opinion text (integrity proof)
+    pub text_hash: [u8; 32],
+    /// Partner program that staked this opinion via CPI (default if direct)
+    pub source_program: Pubkey,
+    /// IPFS CID pointing to full opinion text; first `ipfs_cid_len` bytes
+    pub ipfs_cid: [u8; MAX_IPFS_CID_LEN],
+
+    /// Amount staked in micro-USDC; bounded by MAX_STAKE (see stake_amount)
+    pub stake: u32,
+    /// Principal guaranteed back under the market's refund floor; never
+    /// above the stake (see refund_floor)
+    pub floor: u32,
+
+    // ── Triple-Check Scores (set by oracle at settlement) ────────────────────
+    /// One byte each, low to high: weight (Layer 1: normalized net backing),
+    /// consensus (Layer 2: closeness to crowd_score), ai (Layer 3: text
+    /// quality) and combined (W*50 + C*30 + A*20 stored as 0–100)
+    pub scores: u32,
+    /// opinion_flags bits
+    pub flags: u16,
+
+    pub ipfs_cid_len: u8,
+
+    // ── User's Agreement Score ─────────────────────────────────────────────
+    /// 0–100: how much user agrees with the market statement (shapes truth score)
+    pub opinion_score: u8,
+
+    // ── Market Prediction ─────────────────────────────────────────────────
+    /// 0–100: user's bet on where the crowd will settle (shapes payout)
+    pub market_prediction: u8,
+
+    pub bump: u8,
+    /// Layout version (0 = written before versioning)
+    pub schema_version: u8,
+    /// Non-zero once combined_score has been counted in the staker's
+    /// UserProfile (opinion_flags is full)
+    pub score_recorded: u8,
+    /// Keeps the struct a multiple of 8 bytes
+    pub _reserved: [u8; 4],
+}
+
+impl Opinion {
+    pub const SPACE: usize = 8 + std::mem::size_of::<Opinion>();
+
+    /// Store `cid` in the fixed-size buffer; length was validated by the caller
+    pub fn set_ipfs_cid(&mut self, cid: &str) {
+        let bytes = cid.as_bytes();
+        self.ipfs_cid = [0u8; MAX_IPFS_CID_LEN];
+        self.ipfs_cid[..bytes.len()].copy_from_slice(bytes);
+        self.ipfs_cid_len = bytes.len() as u8;
+    }
+
+    pub fn ipfs_cid(&self) -> String {
+        String::from_utf8_lossy(&self.ipfs_cid[..self.ipfs_cid_len as usize]).into_owned()
+    }
+
+    /// Partner program recorded at stake time, if any
+    pub fn source_program(&self) -> Option<Pubkey> {
+        (self.source_program != Pubkey::default()).then_some(self.source_program)
+    }
+
+    pub fn stake_amount(&self) -> u64 {
+        self.stake as u64
+    }
+
+    /// `amount` was checked against MAX_STAKE by the caller
+    pub fn set_stake_amount(&mut self, amount: u64) {
+        self.stake = amount as u32;
+    }
+
+    pub fn refund_floor(&self) -> u64 {
+        self.floor as u64
+    }
+
+    /// `amount` never exceeds the stake
+    pub fn set_refund_floor(&mut self, amount: u64) {
+        self.floor = amount.min(self.stake_amount()) as u32;
+    }
+
+    fn score(&self, lane: u32) -> u8 {
+        (self.scores >> (lane * 8)) as u8
+    }
+
+    fn set_score(&mut self, lane: u32, score: u8) {
+        self.scores = (self.scores & !(0xff << (lane * 8))) | ((score as u32) << (lane * 8));
+    }
+
+    pub fn weight_score(&self) -> u8 {
+        self.score(0)
+    }
+
+    pub fn set_weight_score(&mut self, score: u8) {
+        self.set_score(0, score)
+    }
+
+    pub fn consensus_score(&self) -> u8 {
+        self.score(1)
+    }
+
+    pub fn set_consensus_score(&mut self, score: u8) {
+        self.set_score(1, score)
+    }
+
+    pub fn ai_score(&self) -> u8 {
+        self.score(2)
+    }
+
+    pub fn set_ai_score(&mut self, score: u8) {
+        self.set_score(2, score)
+    }
+
+    pub fn combined_score(&self) -> u8 {
+        self.score(3)
+    }
+
+    pub fn set_combined_score(&mut self, score: u8) {
+        self.set_score(3, score)
+    }
+
+    opinion_flag_accessors! {
+        /// Set by the first settlement; later changes go through correct_settlement
+        settled, set_settled => opinion_flags::SETTLED;
+        /// Stake returned by recover_stake; cannot be recovered again
+        refunded, set_refunded => opinion_flags::REFUNDED;
+        /// Staker's reputation qualified this opinion for a protocol fee waiver
+        fee_waived, set_fee_waived => opinion_flags::FEE_WAIVED;
+        /// Payout fell below config.min_payout and was skipped; nothing to claim
+        below_threshold, set_below_threshold => opinion_flags::BELOW_THRESHOLD;
+        /// Counted once in the market's payout cap tally
+        cap_tallied, set_cap_tallied => opinion_flags::CAP_TALLIED;
+        /// Fee points for this opinion have been minted
+        points_claimed, set_points_claimed => opinion_flags::POINTS_CLAIMED;
+        paid, set_paid => opinion_flags::PAID;
+        /// Folded into the market's inputs_hash for ZK-verified scoring
+        inputs_snapshotted, set_inputs_snapshotted => opinion_flags::INPUTS_SNAPSHOTTED;
+        /// An AI score has been recorded (counted once in market.ai_scored_count)
+        ai_scored, set_ai_scored => opinion_flags::AI_SCORED;
+        /// Unclaimed when the claim window ended; can no longer be claimed
+        expired, set_expired => opinion_flags::EXPIRED;
+        /// Staker wants a pro-rata share of expired payouts
+        redistribution_opt_in, set_redistribution_opt_in => opinion_flags::REDISTRIBUTION_OPT_IN;
+        redistribution_claimed, set_redistribution_claimed => opinion_flags::REDISTRIBUTION_CLAIMED;
+        /// A winner badge cNFT has been minted to the staker
+        badge_minted, set_badge_minted => opinion_flags::BADGE_MINTED;
+        /// Soulbound reputation has been minted for the payout
+        reputation_claimed, set_reputation_claimed => opinion_flags::REPUTATION_CLAIMED;
+        /// PayoutReadyEvent has been emitted for the opinion
+        payout_announced, set_payout_announced => opinion_flags::PAYOUT_ANNOUNCED;
+        /// Confidential stake not yet revealed; the stake reads as zero
+        confidential_pending, set_confidential_pending => opinion_flags::CONFIDENTIAL_PENDING;
+    }
+
+    /// Scoring and payout state for a freshly staked opinion; the author's own
+    /// stake counts as initial backing for Layer 1
+    pub fn reset_for_stake(&mut self, stake_amount: u64) {
+        self.backing_total = stake_amount;
+        self.slashing_total = 0;
+        self.scores = 0;
+        self.flags = 0;
+        self.vindication_bonus = 0;
+        self.floor = 0;
+        self.payout_amount = 0;
+        self.claimed_so_far = 0;
+    }
+
+    /// Whether combined_score is already counted in the staker's profile
+    pub fn score_recorded(&self) -> bool {
+        self.score_recorded != 0
+    }
+
+    /// Record a (possibly partial) release of `entitlement`; the opinion is
+    /// paid once everything has been released
+    pub fn record_claim(&mut self, entitlement: u64, release: u64) {
+        self.payout_amount = entitlement;
+        self.claimed_so_far = self.claimed_so_far.saturating_add(release);
+        self.set_paid(self.claimed_so_far >= entitlement);
+    }
+
+    /// backing_total − slashing_total (Layer 1 input; may be negative)
+    pub fn net_backing(&self) -> i64 {
+        self.backing_total as i64 - self.slashing_total as i64
+    }
+}
+
+/// Discriminator of baseline opinions (Anchor's default for `account:Opinion`)
+pub const BASELINE_OPINION_DISCRIMINATOR: [u8; 8] = [4, 133, 78, 114, 10, 11, 56, 220];
+
+/// Borsh Opinion layout deployed before schema versioning, read only by
+/// migrate_opinion
+#[derive(AnchorDeserialize)]
+pub struct BaselineOpinion {
+    pub market: Pubkey,
+    pub staker: Pubkey,
+    pub stake_amount: u64,
+    pub text_hash: [u8; 32],
+    pub ipfs_cid: String,
+    pub created_at: i64,
+    pub opinion_score: u8,
+    pub market_prediction: u8,
+    pub backing_total: u64,
+    pub slashing_total: u64,
+    pub weight_score: u8,
+    pub consensus_score: u8,
+    pub ai_score: u8,
+    pub combined_score: u8,
+    pub payout_amount: u64,
+    pub paid: bool,
+    pub bump: u8,
+}
+
+impl BaselineOpinion {
+    /// The same opinion in the packed layout. A paid opinion was settled and
+    /// fully claimed, with no profile to count its score in; the baseline
+    /// tracked nothing else.
+    pub fn repack(&self) -> Result<Opinion> {
+        require!(self.ipfs_cid.len() <= MAX_IPFS_CID_LEN, ErrorCode::AccountDidNotDeserialize);
+        let mut opinion: Opinion = bytemuck::Zeroable::zeroed();
+        opinion.created_at = self.created_at;
+        opinion.backing_total = self.backing_total;
+        opinion.slashing_total = self.slashing_total;
+        opinion.payout_amount = self.payout_amount;
+        opinion.market = self.market;
+        opinion.staker = self.staker;
+        opinion.text_hash = self.text_hash;
+        opinion.set_ipfs_cid(&self.ipfs_cid);
+        opinion.opinion_score = self.opinion_score;
+        opinion.market_prediction = self.market_prediction;
+        opinion.set_stake_amount(self.stake_amount);
+        opinion.set_weight_score(self.weight_score);
+        opinion.set_consensus_score(self.consensus_score);
+        opinion.set_ai_score(self.ai_score);
+        opinion.set_combined_score(self.combined_score);
+        if self.paid {
+            opinion.claimed_so_far = self.payout_amount;
+            opinion.set_settled(true);
+            opinion.set_paid(true);
+            opinion.score_recorded = 1;
+        }
+        opinion.bump = self.bump;
+        opinion.schema_version = ACCOUNT_SCHEMA_VERSION;
+        Ok(opinion)
+    }
+}
+
+/// Tracks a Back or Slash reaction from one user to another's opinion
+#[account]
+pub struct Reaction {
+    pub opinion: Pubkey,
+    pub reactor: Pubkey,
+    pub reaction_type: ReactionType,
+    pub stake_amount: u64,
+    /// Set once the reaction has been counted in the reactor's accuracy stats
+    pub scored: bool,
+    /// Partner program that submitted this reaction via CPI (None if direct)
+    pub source_program: Option<Pubkey>,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl Reaction {
+    pub const SPACE: usize = 8 + 32 + 32 + 1 + 8 + 1 + (1 + 32) + 1 + 1;
+}
+
+/// One update of a ReactionTree: the root it produced and the new nodes on
+/// the updated leaf's path (leaf level first)
+#[zero_copy(unsafe)]
+#[repr(C)]
+pub struct TreeChangeLog {
+    pub root: [u8; 32],
+    pub path: [[u8; 32]; REACTION_TREE_DEPTH],
+    pub index: u32,
+}
+
+/// Per-market concurrent Merkle tree of compressed reactions. Leaves are
+/// appended while the market takes reactions and rewritten (scored, or
+/// zeroed on recovery) only afterwards, so the append frontier in
+/// `filled_subtrees` never goes stale. Recent updates are kept in
+/// `changelog` so a proof built against any root still in the buffer can be
+/// fast-forwarded instead of failing.
+#[account(zero_copy(unsafe))]
+#[repr(C)]
+pub struct ReactionTree {
+    pub market: Pubkey,
+    /// Left node at each level of the rightmost (append) path
+    pub filled_subtrees: [[u8; 32]; REACTION_TREE_DEPTH],
+    /// Ring buffer; changelog[active_index] holds the current root
+    pub changelog: [TreeChangeLog; REACTION_TREE_BUFFER],
+    pub next_index: u32,
+    pub active_index: u32,
+    pub buffer_size: u32,
+    pub bump: u8,
+    pub schema_version: u8,
+    pub _reserved: [u8; 2],
+}
+
+impl ReactionTree {
+    pub const SPACE: usize = 8 + std::mem::size_of::<ReactionTree>();
+
+    pub fn root(&self) -> [u8; 32] {
+        self.changelog[self.active_index as usize].root
+    }
+
+    /// Start as an empty tree whose root is the all-empty subtree hash
+    pub fn initialize(&mut self) {
+        let mut empty = [0u8; 32];
+        for _ in 0..REACTION_TREE_DEPTH {
+            empty = merkle_node(&empty, &empty);
+        }
+        self.changelog[0].root = empty;
+        self.active_index = 0;
+        self.buffer_size = 1;
+        self.next_index = 0;
+    }
+
+    /// Append `leaf` at the next index and return that index
+    pub fn append(&mut self, leaf: [u8; 32]) -> Result<u32> {
+        let index = self.next_index;
+        require!((index as u64) < 1u64 << REACTION_TREE_DEPTH, OpinionError::ReactionTreeFull);
+
+        let mut path = [[0u8; 32]; REACTION_TREE_DEPTH];
+        let mut node = leaf;
+        let mut empty = [0u8; 32];
+        for (level, slot) in path.iter_mut().enumerate() {
+            *slot = node;
+            node = if (index >> level) & 1 == 0 {
+                self.filled_subtrees[level] = node;
+                merkle_node(&node, &empty)
+            } else {
+                merkle_node(&self.filled_subtrees[level], &node)
+            };
+            empty = merkle_node(&empty, &empty);
+        }
+
+        self.push_changelog(node, path, index);
+        self.next_index = index + 1;
+        Ok(index)
+    }
+
+    /// Rewrite the leaf at `index` from `old_leaf` to `new_leaf`. The proof
+    /// may be against any root still in the changelog; updates made since
+    /// are applied to it first.
+    pub fn replace(
+        &mut self,
+        root: [u8; 32],
+        index: u32,
+        proof: &[[u8; 32]],
+        old_leaf: [u8; 32],
+        new_leaf: [u8; 32],
+    ) -> Result<()> {
+        require!(index < self.next_index, OpinionError::InvalidReactionProof);
+        let mut proof: [[u8; 32]; REACTION_TREE_DEPTH] =
+            proof.try_into().map_err(|_| OpinionError::InvalidReactionProof)?;
+
+        // Find the proof's root, newest first, then replay every later update
+        let size = REACTION_TREE_BUFFER as u32;
+        let steps_back = (0..self.buffer_size)
+            .find(|back| self.changelog[((self.active_index + size - back) % size) as usize].root == root)
+            .ok_or(OpinionError::StaleReactionProof)?;
+        for forward in (0..steps_back).rev() {
+            let entry = &self.changelog[((self.active_index + size - forward) % size) as usize];
+            require!(entry.index != index, OpinionError::StaleReactionProof);
+            let critbit = (31 - (entry.index ^ index).leading_zeros()) as usize;
+            proof[critbit] = entry.path[critbit];
+        }
+
+        let (current, _) = climb(old_leaf, index, &proof);
+        require!(current == self.root(), OpinionError::InvalidReactionProof);
+        let (new_root, path) = climb(new_leaf, index, &proof);
+        self.push_changelog(new_root, path, index);
+        Ok(())
+    }
+
+    fn push_changelog(&mut self, root: [u8; 32], path: [[u8; 32]; REACTION_TREE_DEPTH], index: u32) {
+        self.active_index = (self.active_index + 1) % REACTION_TREE_BUFFER as u32;
+        let entry = &mut self.changelog[self.active_index as usize];
+        entry.root = root;
+        entry.path = path;
+        entry.index = index;
+        self.buffer_size = (self.buffer_size + 1).min(REACTION_TREE_BUFFER as u32);
+    }
+}
+
+/// One ActivityLog entry
+#[zero_copy(unsafe)]
+#[repr(C)]
+pub struct ActivityEntry {
+    pub actor: Pubkey,
+    /// Stake moved, or total_stake for Close
+    pub amount: u64,
+    pub slot: u64,
+    /// ActivityKind: 0 Stake, 1 Back, 2 Slash, 3 Close
+    pub kind: u8,
+    pub _reserved: [u8; 7],
+}
+
+/// Ring buffer of a market's last ACTIVITY_LOG_SIZE stakes, reactions and
+/// its close at ["activity_log", market], so market pages can show recent
+/// activity without an indexer
+#[account(zero_copy(unsafe))]
+#[repr(C)]
+pub struct ActivityLog {
+    pub market: Pubkey,
+    pub entries: [ActivityEntry; ACTIVITY_LOG_SIZE],
+    /// Actions recorded so far; the newest is at (count - 1) % ACTIVITY_LOG_SIZE
+    pub count: u64,
+    pub bump: u8,
+    pub schema_version: u8,
+    pub _reserved: [u8; 6],
+}
+
+impl ActivityLog {
+    pub const SPACE: usize = 8 + std::mem::size_of::<ActivityLog>();
+
+    pub fn record(&mut self, actor: Pubkey, kind: ActivityKind, amount: u64, slot: u64) {
+        let entry = &mut self.entries[(self.count % ACTIVITY_LOG_SIZE as u64) as usize];
+        entry.actor = actor;
+        entry.amount = amount;
+        entry.slot = slot;
+        entry.kind = kind as u8;
+        self.count += 1;
+    }
+}
+
+/// Hash `leaf` up to the root along `proof`, returning the root and the
+/// nodes on the way (leaf level first)
+fn climb(
+    leaf: [u8; 32],
+    index: u32,
+    proof: &[[u8; 32]; REACTION_TREE_DEPTH],
+) -> ([u8; 32], [[u8; 32]; REACTION_TREE_DEPTH]) {
+    let mut path = [[0u8; 32]; REACTION_TREE_DEPTH];
+    let mut node = leaf;
+    for (level, sibling) in proof.iter().enumerate() {
+        path[level] = node;
+        node = if (index >> level) & 1 == 0 {
+            merkle_node(&node, sibling)
+        } else {
+            merkle_node(sibling, &node)
+        };
+    }
+    (node, path)
+}
+
+/// Free emoji reaction tallies for one opinion (indexed by emoji code)
+#[account]
+pub struct LiteReactionCounts {
+    pub opinion: Pubkey,
+    pub counts: [u32; LITE_REACTION_KINDS],
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl LiteReactionCounts {
+    pub const SPACE: usize = 8 + 32 + 4 * LITE_REACTION_KINDS + 1 + 1;
+}
+
+/// Per-wallet rate limit state for lite_react
+#[account]
+pub struct LiteReactor {
+    pub reactor: Pubkey,
+    pub last_reacted_at: i64,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl LiteReactor {
+    pub const SPACE: usize = 8 + 32 + 8 + 1 + 1;
+}
+
+/// Lifetime curation accuracy for one reactor
+#[account]
+pub struct ReactorStats {
+    pub reactor: Pubkey,
+    /// Reactions counted after their market settled
+    pub reactions_scored: u32,
+    /// Of those, Backs on opinions that scored well and Slashes on ones that didn't
+    pub reactions_aligned: u32,
+    /// Total USDC staked across scored reactions
+    pub total_staked: u64,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl ReactorStats {
+    pub const SPACE: usize = 8 + 32 + 4 + 4 + 8 + 1 + 1;
+
+    /// reactions_aligned / reactions_scored in bps (0 before any scored reaction)
+    pub fn accuracy_bps(&self) -> u16 {
+        if self.reactions_scored == 0 {
+            return 0;
+        }
+        (self.reactions_aligned as u64 * 10_000 / self.reactions_scored as u64) as u16
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct EarnerRank {
+    pub user: Pubkey,
+    pub total_won: u64,
+}
+
+impl EarnerRank {
+    pub const SPACE: usize = 32 + 8;
+}
+
+/// Global top users by cumulative winnings (UserProfile.total_won), refreshed
+/// by claims that pass it in
+#[account]
+pub struct EarnerLeaderboard {
+    pub entries: Vec<EarnerRank>,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl EarnerLeaderboard {
+    pub const SPACE: usize = 8 + 4 + EARNER_LEADERBOARD_SIZE * EarnerRank::SPACE + 1 + 1;
+
+    /// Insert or refresh a user's entry, evicting the lowest earner when full
+    pub fn record(&mut self, profile: &UserProfile) {
+        let rank = EarnerRank {
+            user: profile.user,
+            total_won: profile.total_won,
+        };
+
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.user == rank.user) {
+            *entry = rank;
+        } else if self.entries.len() < EARNER_LEADERBOARD_SIZE {
+            self.entries.push(rank);
+        } else if let Some(last) = self.entries.last_mut() {
+            if rank.total_won > last.total_won {
+                *last = rank;
+            }
+        }
+
+        self.entries.sort_by_key(|e| std::cmp::Reverse(e.total_won));
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ReactorRank {
+    pub reactor: Pubkey,
+    pub reactions_aligned: u32,
+    pub reactions_scored: u32,
+}
+
+impl ReactorRank {
+    pub const SPACE: usize = 32 + 4 + 4;
+}
+
+/// Global top reactors by aligned reactions (ties broken by accuracy)
+#[account]
+pub struct ReactorLeaderboard {
+    pub entries: Vec<ReactorRank>,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl ReactorLeaderboard {
+    pub const SPACE: usize = 8 + 4 + REACTOR_LEADERBOARD_SIZE * ReactorRank::SPACE + 1 + 1;
+
+    /// Insert or refresh a reactor's entry, evicting the lowest rank when full
+    pub fn record(&mut self, stats: &ReactorStats) {
+        let rank = ReactorRank {
+            reactor: stats.reactor,
+            reactions_aligned: stats.reactions_aligned,
+            reactions_scored: stats.reactions_scored,
+        };
+
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.reactor == rank.reactor) {
+            *entry = rank;
+        } else if self.entries.len() < REACTOR_LEADERBOARD_SIZE {
+            self.entries.push(rank);
+        } else if let Some(last) = self.entries.last_mut() {
+            if rank.reactions_aligned > last.reactions_aligned {
+                *last = rank;
+            }
+        }
+
+        self.entries.sort_by(|a, b| {
+            b.reactions_aligned
+                .cmp(&a.reactions_aligned)
+                .then(a.reactions_scored.cmp(&b.reactions_scored))
+        });
+    }
+}
+
+/// Oracle's proposed Merkle settlement, finalized by anyone after
+/// CHALLENGE_WINDOW unless disputed with a CHALLENGE_BOND
+#[account]
+pub struct OptimisticSettlement {
+    pub market: Pubkey,
+    pub settlement_root: [u8; 32],
+    pub crowd_score: u8,
+    pub total_payout: u64,
+    pub proposed_at: i64,
+    /// Set by dispute_settlement; the bond sits in the market escrow
+    pub challenger: Option<Pubkey>,
+    pub bond_amount: u64,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl OptimisticSettlement {
+    pub const SPACE: usize =
+        8   // discriminator
+        + 32  // market
+        + 32  // settlement_root
+        + 1   // crowd_score
+        + 8   // total_payout
+        + 8   // proposed_at
+        + 1 + 32 // challenger: Option<Pubkey>
+        + 8   // bond_amount
+        + 1   // bump
+        + 1;  // schema_version
+}
+
+/// An author's bonded challenge against their opinion's ai_score
+#[account]
+pub struct AiScoreChallenge {
+    pub market: Pubkey,
+    pub opinion: Pubkey,
+    pub staker: Pubkey,
+    /// ai_score at the time of the challenge
+    pub original_score: u8,
+    /// Bond held in the market escrow until the oracle responds
+    pub bond: u64,
+    pub resolved: bool,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl AiScoreChallenge {
+    pub const SPACE: usize = 8 + 32 + 32 + 32 + 1 + 8 + 1 + 1 + 1;
+}
+
+/// Append-only page of a market's oracle scoring audit trail
+#[account]
+pub struct AuditLog {
+    pub market: Pubkey,
+    pub page: u32,
+    pub entries: Vec<AuditEntry>,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl AuditLog {
+    pub const SPACE: usize = 8 + 32 + 4 + 4 + AuditEntry::SPACE * AUDIT_PAGE_SIZE + 1 + 1;
+}
+
+/// Page of a settled market's final per-opinion results; written as the
+/// Opinion accounts are closed so history stays queryable
+#[account]
+pub struct ResultsArchive {
+    pub market: Pubkey,
+    pub page: u32,
+    pub entries: Vec<ArchivedResult>,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl ResultsArchive {
+    pub const SPACE: usize = 8 + 32 + 4 + 4 + ArchivedResult::SPACE * RESULTS_PAGE_SIZE + 1 + 1;
+}
+
+/// Page of the global market registry at ["market_registry", page_le]; lets
+/// clients list markets without a getProgramAccounts scan. create_market
+/// appends, state transitions refresh the entry when the page is passed.
+#[account]
+pub struct MarketRegistryPage {
+    pub page: u32,
+    pub entries: Vec<RegistryEntry>,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl MarketRegistryPage {
+    pub const SPACE: usize = 8 + 4 + 4 + RegistryEntry::SPACE * REGISTRY_PAGE_SIZE + 1 + 1;
+}
+
+/// Snapshot of an Active market's totals at ["checkpoint", market, index_le],
+/// written by record_checkpoint at most once per CHECKPOINT_INTERVAL
+#[account]
+pub struct MarketCheckpoint {
+    pub market: Pubkey,
+    pub index: u32,
+    pub timestamp: i64,
+    pub slot: u64,
+    pub total_stake: u64,
+    pub staker_count: u32,
+    pub backing_total: u64,
+    pub slashing_total: u64,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl MarketCheckpoint {
+    pub const SPACE: usize = 8 + 32 + 4 + 8 + 8 + 8 + 4 + 8 + 8 + 1 + 1;
+}
+
+/// Page of a market's opinions in submission order at
+/// ["opinion_index", market, page_le]; position p is entry p % page size of
+/// page p / page size. Written by stake_opinion, stake_from_vault and
+/// stake_with_intent.
+#[account]
+pub struct OpinionIndexPage {
+    pub market: Pubkey,
+    pub page: u32,
+    pub opinions: Vec<Pubkey>,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl OpinionIndexPage {
+    pub const SPACE: usize = 8 + 32 + 4 + 4 + 32 * OPINION_INDEX_PAGE_SIZE + 1 + 1;
+
+    /// Append `opinion` as the market's next position, initializing the page
+    /// if it was just created
+    pub fn push(&mut self, market: &mut Market, market_key: Pubkey, opinion: Pubkey, bump: u8) {
+        self.market = market_key;
+        self.page = market.opinion_index_page();
+        self.bump = bump;
+        self.schema_version = ACCOUNT_SCHEMA_VERSION;
+        self.opinions.push(opinion);
+        market.indexed_opinions += 1;
+    }
+}
+
+/// Solana Pay reference recorded at ["payment_reference", reference] by
+/// create_market or stake_opinion, so a point-of-sale flow can match the
+/// payment it requested to the market or opinion it funded
+#[account]
+pub struct PaymentReference {
+    pub reference: Pubkey,
+    pub payer: Pubkey,
+    pub market: Pubkey,
+    /// Staked opinion; default for a market creation
+    pub opinion: Pubkey,
+    /// Creation fee or stake paid
+    pub amount: u64,
+    pub recorded_at: i64,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl PaymentReference {
+    pub const SPACE: usize = 8 + 32 + 32 + 32 + 32 + 8 + 8 + 1 + 1;
+}
+
+/// A confidential stake's transfer amount as encrypted to the confidential
+/// mint's auditor, at ["confidential_stake", opinion]. Token-2022 binds the
+/// ciphertexts' commitments to the amount transferred; `amount` is zero until
+/// reveal_confidential_stake opens them.
+#[account]
+pub struct ConfidentialStake {
+    pub opinion: Pubkey,
+    /// Auditor ElGamal ciphertexts of the amount's low 16 and high 32 bits
+    pub auditor_ciphertext_lo: [u8; 64],
+    pub auditor_ciphertext_hi: [u8; 64],
+    pub amount: u64,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl ConfidentialStake {
+    pub const SPACE: usize = 8 + 32 + 64 + 64 + 8 + 1 + 1;
+}
+
+/// Per-creator lifetime stats; settled volume unlocks creation-fee rebates
+#[account]
+pub struct CreatorProfile {
+    pub creator: Pubkey,
+    /// Σ total_stake of this creator's settled markets
+    pub settled_volume: u64,
+    pub markets_created: u32,
+    pub markets_settled: u32,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl CreatorProfile {
+    pub const SPACE: usize = 8 + 32 + 8 + 4 + 4 + 1 + 1;
+
+    /// Highest rebate tier reached by settled_volume (bps of CREATE_FEE)
+    pub fn rebate_bps(&self) -> u16 {
+        CREATOR_REBATE_TIERS
+            .iter()
+            .rev()
+            .find(|(threshold, _)| self.settled_volume >= *threshold)
+            .map_or(0, |(_, bps)| *bps)
+    }
+
+    /// CREATE_FEE less this creator's rebate
+    pub fn create_fee(&self) -> u64 {
+        CREATE_FEE - CREATE_FEE * self.rebate_bps() as u64 / 10_000
+    }
+}
+
+/// Per-user balance of claimed payouts that can be restaked into new markets
+/// without a wallet round-trip; tokens sit in ["compound_vault_usdc", owner]
+#[account]
+pub struct CompoundVault {
+    pub owner: Pubkey,
+    /// May claim into and stake from the vault for the owner (default = none)
+    pub delegate: Pubkey,
+    /// Remaining total the delegate may stake
+    pub delegate_allowance: u64,
+    /// Ceiling on a single stake from the vault (0 = MAX_STAKE)
+    pub max_stake_per_market: u64,
+    /// Σ payouts claimed into the vault
+    pub total_compounded: u64,
+    /// Σ stakes paid from the vault
+    pub total_restaked: u64,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl CompoundVault {
+    pub const SPACE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 1 + 1;
+
+    /// Owner, or the delegate if one is set
+    pub fn is_authorized(&self, signer: &Pubkey) -> bool {
+        *signer == self.owner || (self.delegate != Pubkey::default() && *signer == self.delegate)
+    }
+}
+
+/// Staker's registered payout destination (e.g. a cold wallet's USDC account);
+/// claim_payout, claim_payouts_batch and push_payout pay there instead of a
+/// token account owned by the staking key
+#[account]
+pub struct PayoutPreference {
+    pub staker: Pubkey,
+    pub destination: Pubkey,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl PayoutPreference {
+    pub const SPACE: usize = 8 + 32 + 32 + 1 + 1;
+}
+
+/// Global work queue of closed markets still to be scored, settled or
+/// finalized. Crankers and the oracle read it instead of scraping events.
+#[account]
+pub struct ScoringQueue {
+    pub entries: Vec<QueueEntry>,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl ScoringQueue {
+    pub const SPACE: usize = 8 + 4 + QueueEntry::SPACE * MAX_SCORING_QUEUE + 1 + 1;
+
+    pub fn enqueue(&mut self, market_key: Pubkey, market: &mut Market) -> Result<()> {
+        require!(!market.queued, OpinionError::AlreadyQueued);
+        require!(self.entries.len() < MAX_SCORING_QUEUE, OpinionError::ScoringQueueFull);
+        self.entries.push(QueueEntry::from_market(market_key, market));
+        market.queued = true;
+        Ok(())
+    }
+}
+
+/// Stake per market_prediction bucket for median crowd_score markets
+#[account]
+pub struct CrowdHistogram {
+    pub market: Pubkey,
+    /// stake_by_score[s] = Σ stake_amount of opinions with market_prediction == s
+    pub stake_by_score: [u64; 101],
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl CrowdHistogram {
+    pub const SPACE: usize = 8 + 32 + 8 * 101 + 1 + 1;
+
+    /// Lowest score whose cumulative stake reaches half of `total`
+    pub fn weighted_median(&self, total: u64) -> u8 {
+        let mut cumulative = 0u128;
+        for (score, stake) in self.stake_by_score.iter().enumerate() {
+            cumulative += *stake as u128;
+            if cumulative * 2 >= total as u128 {
+                return score as u8;
+            }
+        }
+        100
+    }
+}
+
+/// Admin-registered verifying key for ZK-verified Layer 1/2 scoring
+#[account]
+pub struct ScoringVerifier {
+    pub vk: ScoringVerifyingKey,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl ScoringVerifier {
+    pub const SPACE: usize = 8 + ScoringVerifyingKey::SPACE + 1 + 1;
+}
+
+/// Tracks a pending Chainlink VRF randomness request (legacy)
+#[account]
+pub struct VrfRequest {
+    pub market: Pubkey,
+    pub request_id: u64,
+    pub randomness: Option<[u8; 32]>,
+    pub requested_at: i64,
+    pub fulfilled_at: Option<i64>,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+/// Switchboard on-demand randomness committed for a market's lottery
+#[account]
+pub struct RandomnessRequest {
+    pub market: Pubkey,
+    /// Switchboard RandomnessAccountData committed at request time
+    pub randomness_account: Pubkey,
+    /// Slot the randomness was committed to; the reveal must match it
+    pub seed_slot: u64,
+    /// Revealed value (set by consume_randomness)
+    pub randomness: Option<[u8; 32]>,
+    pub requested_at: i64,
+    pub fulfilled_at: Option<i64>,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl RandomnessRequest {
+    pub const SPACE: usize =
+        8   // discriminator
+        + 32  // market
+        + 32  // randomness_account
+        + 8   // seed_slot
+        + 1 + 32 // randomness: Option<[u8; 32]>
+        + 8   // requested_at
+        + 1 + 8 // fulfilled_at: Option<i64>
+        + 1   // bump
+        + 1;  // schema_version
+}
+
+impl VrfRequest {
+    pub const SPACE: usize =
+        8   // discriminator
+        + 32  // market
+        + 8   // request_id
+        + 1 + 32 // randomness: Option<[u8; 32]>
+        + 8   // requested_at
+        + 1 + 8 // fulfilled_at: Option<i64>
+        + 1   // bump
+        + 1;  // schema_version
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────────────
+
+/// Create a program-owned PDA from inside an instruction (used for accounts
+/// passed via remaining_accounts, where `init` constraints are unavailable).
+/// Mirrors Anchor's `init`: tolerates a pre-funded address by topping up rent.
+pub fn create_pda_account<'info>(
+    payer: &AccountInfo<'info>,
+    new_account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    let current_lamports = new_account.lamports();
+
+    if current_lamports == 0 {
+        anchor_lang::system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                anchor_lang::system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: new_account.clone(),
+                },
+                &[signer_seeds],
+            ),
+            rent,
+            space as u64,
+            &crate::ID,
+        )?;
+    } else {
+        let top_up = rent.saturating_sub(current_lamports);
+        if top_up > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    anchor_lang::system_program::Transfer {
+                        from: payer.clone(),
+                        to: new_account.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        anchor_lang::system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                anchor_lang::system_program::Allocate {
+                    account_to_allocate: new_account.clone(),
+                },
+                &[signer_seeds],
+            ),
+            space as u64,
+        )?;
+        anchor_lang::system_program::assign(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                anchor_lang::system_program::Assign {
+                    account_to_assign: new_account.clone(),
+                },
+                &[signer_seeds],
+            ),
+            &crate::ID,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Grow a program-owned account holding `discriminator` to `space` bytes,
+/// topping up rent from `payer`. The new tail is zeroed, so fields appended
+/// to a layout read as zero/false/None until the migration fills them in.
+pub fn grow_program_account<'info>(
+    account: &AccountInfo<'info>,
+    discriminator: &[u8],
+    space: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    require_keys_eq!(*account.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+    require!(
+        account.try_borrow_data()?.starts_with(discriminator),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    if account.data_len() >= space {
+        return Ok(());
+    }
+
+    let top_up = Rent::get()?.minimum_balance(space).saturating_sub(account.lamports());
+    if top_up > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    account.resize(space)?;
+    Ok(())
+}
+
+/// Layer 1: weight = round((net − min) / range × 95) + 5, range = max − min (or 1)
+pub fn compute_weight_score(net: i64, min_net: i64, max_net: i64) -> u8 {
+    let range = (max_net as i128 - min_net as i128).max(1);
+    let offset = net as i128 - min_net as i128;
+    ((offset * 190 + range) / (2 * range) + 5) as u8
+}
+
+/// Layer 2: consensus = max(0, 100 − |prediction − crowd_score|)
+pub fn compute_consensus_score(market_prediction: u8, crowd_score: u8) -> u8 {
+    100u8.saturating_sub(market_prediction.abs_diff(crowd_score))
+}
+
+/// S = (W × w%) + (C × c%) + (A × a%) — 50/30/20 unless the market overrides
+/// Computed as integer basis points (0–10000), then divided by 100
+pub fn compute_combined_score(
+    weights: &ScoringWeights,
+    weight_score: u8,
+    consensus_score: u8,
+    ai_score: u8,
+) -> Result<u8> {
+    let combined_bps: u64 =
+        (weight_score as u64)
+            .checked_mul(weights.weight as u64)
+            .ok_or(OpinionError::Overflow)?
+        .checked_add(
+            (consensus_score as u64)
+                .checked_mul(weights.consensus as u64)
+                .ok_or(OpinionError::Overflow)?
+        )
+        .ok_or(OpinionError::Overflow)?
+        .checked_add(
+            (ai_score as u64)
+                .checked_mul(weights.ai as u64)
+                .ok_or(OpinionError::Overflow)?
+        )
+        .ok_or(OpinionError::Overflow)?;
+
+    Ok((combined_bps / 100) as u8)
+}
+
+/// Merkle leaf for post_settlement_root / claim_payout_with_proof.
+/// leaf = sha256(0x00 || opinion || weight || consensus || ai || payout_le)
+pub fn settlement_leaf(
+    opinion: &Pubkey,
+    weight_score: u8,
+    consensus_score: u8,
+    ai_score: u8,
+    payout: u64,
+) -> [u8; 32] {
+    hashv(&[
+        &[0u8],
+        opinion.as_ref(),
+        &[weight_score, consensus_score, ai_score],
+        &payout.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Merkle leaf for post_scores_root / execute_settlement.
+/// leaf = sha256(0x02 || opinion || weight || consensus || ai)
+pub fn score_leaf(opinion: &Pubkey, weight_score: u8, consensus_score: u8, ai_score: u8) -> [u8; 32] {
+    hashv(&[
+        &[2u8],
+        opinion.as_ref(),
+        &[weight_score, consensus_score, ai_score],
+    ])
+    .to_bytes()
+}
+
+/// Merkle leaf for post_verified_scores / execute_verified_settlement.
+/// leaf = sha256(0x03 || opinion || weight || consensus)
+pub fn verified_score_leaf(opinion: &Pubkey, weight_score: u8, consensus_score: u8) -> [u8; 32] {
+    hashv(&[&[3u8], opinion.as_ref(), &[weight_score, consensus_score]]).to_bytes()
+}
+
+/// Leaf of a market's ReactionTree.
+/// leaf = sha256(0x04 || opinion || reactor || type || stake_le || scored)
+pub fn reaction_leaf(
+    opinion: &Pubkey,
+    reactor: &Pubkey,
+    reaction_type: &ReactionType,
+    stake_amount: u64,
+    scored: bool,
+) -> [u8; 32] {
+    let kind = match reaction_type {
+        ReactionType::Back => 0u8,
+        ReactionType::Slash => 1u8,
+    };
+    hashv(&[
+        &[4u8],
+        opinion.as_ref(),
+        reactor.as_ref(),
+        &[kind],
+        &stake_amount.to_le_bytes(),
+        &[scored as u8],
+    ])
+    .to_bytes()
+}
+
+/// Positional interior node of a ReactionTree (0x01 node prefix)
+pub fn merkle_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    hashv(&[&[1u8][..], left, right]).to_bytes()
+}
+
+/// Map a 32-byte hash into the BN254 scalar field by clearing its top byte
+pub fn hash_to_field(hash: &[u8; 32]) -> [u8; 32] {
+    let mut element = *hash;
+    element[0] = 0;
+    element
+}
+
+/// Negate a G1 point (x, y) → (x, p − y), big-endian
+fn negate_g1(point: &[u8; 64]) -> [u8; 64] {
+    let mut negated = *point;
+    let y = &point[32..];
+    if y.iter().all(|&b| b == 0) {
+        return negated;
+    }
+    let mut borrow = 0i16;
+    for i in (0..32).rev() {
+        let mut diff = BN254_FIELD_MODULUS[i] as i16 - y[i] as i16 - borrow;
+        borrow = (diff < 0) as i16;
+        if diff < 0 {
+            diff += 256;
+        }
+        negated[32 + i] = diff as u8;
+    }
+    negated
+}
+
+/// Groth16 verification over BN254 with the alt_bn128 syscalls:
+///   e(−A, B) · e(α, β) · e(vk_x, γ) · e(C, δ) == 1
+pub fn verify_groth16(
+    vk: &ScoringVerifyingKey,
+    proof: &Groth16Proof,
+    public_inputs: &[[u8; 32]; ZK_PUBLIC_INPUTS],
+) -> Result<()> {
+    let invalid = |_| error!(OpinionError::InvalidScoringProof);
+
+    let mut vk_x = vk.ic[0].to_vec();
+    for (input, ic) in public_inputs.iter().zip(vk.ic[1..].iter()) {
+        let term = alt_bn128_multiplication(&[ic.as_ref(), input.as_ref()].concat()).map_err(invalid)?;
+        vk_x = alt_bn128_addition(&[vk_x.as_slice(), term.as_slice()].concat()).map_err(invalid)?;
+    }
+
+    let pairing_input = [
+        negate_g1(&proof.a).as_ref(),
+        proof.b.as_ref(),
+        vk.alpha_g1.as_ref(),
+        vk.beta_g2.as_ref(),
+        vk_x.as_slice(),
+        vk.gamma_g2.as_ref(),
+        proof.c.as_ref(),
+        vk.delta_g2.as_ref(),
+    ]
+    .concat();
+    let result = alt_bn128_pairing(&pairing_input).map_err(invalid)?;
+
+    let mut one = [0u8; 32];
+    one[31] = 1;
+    require!(result.as_slice() == one, OpinionError::InvalidScoringProof);
+    Ok(())
+}
+
+/// Whether `opening` opens the Pedersen commitment of an ElGamal
+/// `ciphertext` (commitment ‖ handle) to `amount`: C == amount·G + opening·H
+pub fn pedersen_opens(ciphertext: &[u8; 64], amount: u64, opening: &[u8; 32]) -> bool {
+    let mut amount_scalar = [0u8; 32];
+    amount_scalar[..8].copy_from_slice(&amount.to_le_bytes());
+    let commitment = multiscalar_multiply_ristretto(
+        &[PodScalar(amount_scalar), PodScalar(*opening)],
+        &[PodRistrettoPoint(PEDERSEN_G), PodRistrettoPoint(PEDERSEN_H)],
+    );
+    commitment.is_some_and(|point| point.0[..] == ciphertext[..32])
+}
+
+/// A market's escrow token account and the PDA that signs for it
+pub struct EscrowAccounts<'info> {
+    pub token_account: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub memo_program: AccountInfo<'info>,
+}
+
+/// Token transfer followed by a memo that lets indexers and accounting tools
+/// categorize it: `opinion-market:<action>:<market uuid hex, or ->:<counterparty>`.
+/// The counterparty is the signing wallet for deposits and the destination
+/// token account for transfers a program PDA signs.
+pub fn transfer_with_memo<'info>(
+    cpi: CpiContext<'_, '_, '_, 'info, Transfer<'info>>,
+    amount: u64,
+    memo_program: &impl ToAccountInfo<'info>,
+    action: &str,
+    market_uuid: Option<&[u8; 16]>,
+) -> Result<()> {
+    let counterparty = if cpi.signer_seeds.is_empty() {
+        cpi.accounts.authority.key()
+    } else {
+        cpi.accounts.to.key()
+    };
+    let uuid = market_uuid.map_or_else(
+        || "-".to_string(),
+        |uuid| uuid.iter().map(|byte| format!("{:02x}", byte)).collect(),
+    );
+    let memo = format!("opinion-market:{}:{}:{}", action, uuid, counterparty);
+    token::transfer(cpi, amount)?;
+    memo::build_memo(
+        CpiContext::new(memo_program.to_account_info(), memo::BuildMemo {}),
+        memo.as_bytes(),
+    )
+}
+
+/// Pay the protocol fee to the treasury and the rest of the escrowed stake to
+/// the lottery winner, then mark the market Settled. Needs the reviewer's
+/// co-signature when the config requires it. Returns the event for the
+/// caller to emit_cpi!.
+#[allow(clippy::too_many_arguments)]
+pub fn distribute_lottery_prize<'info>(
+    market: &mut Account<'info, Market>,
+    escrow: EscrowAccounts<'info>,
+    winner_token_account: AccountInfo<'info>,
+    treasury_usdc: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    config: &ProgramConfig,
+    winner: Pubkey,
+    reviewer: Option<Pubkey>,
+) -> Result<LotterySettledEvent> {
+    require_reviewer(config, market, reviewer, false)?;
+    require!(market.charity_usdc == Pubkey::default(), OpinionError::CharityRequiresClassicSettlement);
+    market.lock_settlement_mode(SettlementMode::Lottery)?;
+    require!(!market.no_loss, OpinionError::NoLossRequiresClassicSettlement);
+    let total_stake = market.settlement_stake()?;
+    let protocol_fee = config.protocol_fee(total_stake);
+    let prize_pool = total_stake.checked_sub(protocol_fee).ok_or(OpinionError::Overflow)?;
+    market.protocol_fee_generated = protocol_fee;
+
+    let market_key = market.key();
+    let escrow_bump = market.escrow_authority_bump;
+    let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+    let signer_seeds = &[seeds];
+
+    let fee_cpi = CpiContext::new_with_signer(
+        token_program.clone(),
+        Transfer {
+            from: escrow.token_account.clone(),
+            to: treasury_usdc,
+            authority: escrow.authority.clone(),
+        },
+        signer_seeds,
+    );
+    transfer_with_memo(
+        fee_cpi,
+        protocol_fee,
+        &escrow.memo_program,
+        "protocol_fee",
+        Some(&market.uuid),
+    )?;
+
+    let prize_cpi = CpiContext::new_with_signer(
+        token_program,
+        Transfer {
+            from: escrow.token_account,
+            to: winner_token_account,
+            authority: escrow.authority,
+        },
+        signer_seeds,
+    );
+    transfer_with_memo(
+        prize_cpi,
+        prize_pool,
+        &escrow.memo_program,
+        "lottery_prize",
+        Some(&market.uuid),
+    )?;
+
+    market.record_podium(winner, 0, prize_pool);
+    market.state = MarketState::Settled;
+    market.settled_at = Clock::get()?.unix_timestamp;
+
+    Ok(LotterySettledEvent {
+        market: market.key(),
+        winner,
+        prize_amount: prize_pool,
+        protocol_fee,
+        seq: market.next_event_seq(),
+    })
+}
+
+/// Append an oracle scoring action to the market's current audit page.
+/// Markets created before create_market opened page 0 must have their audit
+/// trail opened before the oracle can act on them.
+pub fn record_audit(
+    market: &Market,
+    audit_log: Option<&mut Account<'_, AuditLog>>,
+    action: AuditAction,
+    opinion: Pubkey,
+    scores: [u8; 4],
+    digest: [u8; 32],
+) -> Result<()> {
+    require!(market.audit_pages > 0, OpinionError::AuditLogRequired);
+    let log = audit_log.ok_or(OpinionError::AuditLogRequired)?;
+    require!(log.entries.len() < AUDIT_PAGE_SIZE, OpinionError::AuditPageFull);
+    log.entries.push(AuditEntry {
+        action,
+        opinion,
+        scores,
+        digest,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+/// Copy a market's category, state and closes_at into its registry entry.
+/// A no-op unless the registry page is passed.
+pub fn sync_registry(
+    market_key: Pubkey,
+    market: &Market,
+    registry_page: Option<&mut Account<'_, MarketRegistryPage>>,
+) -> Result<()> {
+    let Some(page) = registry_page else {
+        return Ok(());
+    };
+    let index = market.registry_index.ok_or(OpinionError::RegistryEntryMismatch)?;
+    let entry = page
+        .entries
+        .get_mut((index % REGISTRY_PAGE_SIZE as u64) as usize)
+        .filter(|entry| entry.market == market_key)
+        .ok_or(OpinionError::RegistryEntryMismatch)?;
+    entry.category = market.category;
+    entry.state = market.state.clone();
+    entry.closes_at = market.closes_at;
+    Ok(())
+}
+
+/// Verify a Merkle proof using sorted-pair hashing with a 0x01 node prefix,
+/// so proofs carry no left/right flags and leaves can't pose as nodes.
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        let (first, second) = if node <= *sibling { (node, *sibling) } else { (*sibling, node) };
+        hashv(&[&[1u8][..], &first, &second]).to_bytes()
+    });
+    computed == root
+}
+
+/// Charge the protocol fee (less the settlement bounties it reserves for
+/// keepers) and commit a Merkle settlement root, with the reviewer's
+/// co-signature when the config requires it.
+/// Shared by post_settlement_root, post_settlement_root_signed and the
+/// optimistic settlement paths; returns the event for the caller to emit_cpi!.
+pub fn commit_settlement_root<'info>(
+    market: &mut Account<'info, Market>,
+    escrow: EscrowAccounts<'info>,
+    treasury_usdc: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    config: &ProgramConfig,
+    commitment: RootCommitment,
+    reviewer: Option<Pubkey>,
+) -> Result<SettlementRootPostedEvent> {
+    let RootCommitment { settlement_root, crowd_score, total_payout } = commitment;
+    require_reviewer(config, market, reviewer, false)?;
+    require!(crowd_score <= 100, OpinionError::InvalidScore);
+    require!(market.state == MarketState::Scored, OpinionError::MarketNotScored);
+    require!(market.total_stake > 0, OpinionError::EmptyPrizePool);
+    require!(market.open_ai_challenges == 0, OpinionError::OpenAiChallenges);
+    require!(!market.optimistic_open, OpinionError::OptimisticSettlementOpen);
+    require!(market.weight_range_posted, OpinionError::WeightRangeNotPosted);
+    market.verify_crowd_score(crowd_score)?;
+    require!(market.charity_usdc == Pubkey::default(), OpinionError::CharityRequiresClassicSettlement);
+    market.lock_settlement_mode(SettlementMode::TripleCheck)?;
+    require!(!market.no_loss, OpinionError::NoLossRequiresClassicSettlement);
+
+    let total_stake = market.settlement_stake()?;
+    let protocol_fee = config.protocol_fee(total_stake);
+    // Accrued settlement bounties stay in escrow for their keepers, up to the fee
+    let bounty_reserved = market.settlement_bounty_accrued.min(protocol_fee);
+    let distributable_pool = total_stake
+        .checked_sub(protocol_fee)
+        .ok_or(OpinionError::Overflow)?;
+    require!(total_payout <= distributable_pool, OpinionError::PayoutExceedsPool);
+    market.protocol_fee_generated = protocol_fee;
+
+    let market_key = market.key();
+    let escrow_bump = market.escrow_authority_bump;
+    let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+    let signer_seeds = &[seeds];
+
+    let fee_cpi = CpiContext::new_with_signer(
+        token_program,
+        Transfer {
+            from: escrow.token_account,
+            to: treasury_usdc,
+            authority: escrow.authority,
+        },
+        signer_seeds,
+    );
+    transfer_with_memo(
+        fee_cpi,
+        protocol_fee - bounty_reserved,
+        &escrow.memo_program,
+        "protocol_fee",
+        Some(&market.uuid),
+    )?;
+
+    market.crowd_score = crowd_score;
+    market.distributable_pool = distributable_pool;
+    market.settlement_root = settlement_root;
+    market.merkle_payout_total = total_payout;
+    market.merkle_claimed_total = 0;
+    market.settlement_bounty_reserved = bounty_reserved;
+    // Jackpot and vindication are folded into leaf payouts
+    market.opinion_pool = 0;
+    market.prediction_pool = 0;
+    market.jackpot_amount = 0;
+    market.jackpot_claimed = true;
+    market.state = MarketState::Settled;
+    market.settled_at = Clock::get()?.unix_timestamp;
+
+    Ok(SettlementRootPostedEvent {
+        market: market_key,
+        settlement_root,
+        crowd_score,
+        total_payout,
+        protocol_fee,
+        seq: market.next_event_seq(),
+    })
+}
+
+/// Accrue the config's settlement_bounty to the keeper that landed a relayed
+/// settlement transaction, within the market's settlement bounty budget.
+/// Nothing accrues when no settlement_bounty account is passed. Nothing
+/// leaves escrow here: finalization holds the accrued total back out of the
+/// protocol fee it actually charges, and claim_settlement_bounty pays it.
+/// Returns the event for the caller to emit_cpi!, if a bounty accrued.
+pub fn accrue_settlement_bounty(
+    market: &mut Account<Market>,
+    config: &ProgramConfig,
+    keeper: Pubkey,
+    record: Option<&mut Account<SettlementBounty>>,
+    bump: Option<u8>,
+) -> Result<Option<SettlementBountyAccruedEvent>> {
+    let (Some(record), Some(bump)) = (record, bump) else {
+        return Ok(None);
+    };
+    let budget = config.settlement_bounty_budget(market.total_stake);
+    let amount = config
+        .settlement_bounty
+        .min(budget.saturating_sub(market.settlement_bounty_accrued));
+    if amount == 0 {
+        return Ok(None);
+    }
+
+    record.market = market.key();
+    record.keeper = keeper;
+    record.accrued = record.accrued.saturating_add(amount);
+    record.bump = bump;
+    record.schema_version = ACCOUNT_SCHEMA_VERSION;
+    market.settlement_bounty_accrued = market.settlement_bounty_accrued.saturating_add(amount);
+
+    Ok(Some(SettlementBountyAccruedEvent {
+        market: market.key(),
+        keeper,
+        amount,
+        total_accrued: market.settlement_bounty_accrued,
+        seq: market.next_event_seq(),
+    }))
+}
+
+/// Events from one apply_opinion_settlement, for the caller to emit_cpi!
+pub struct SettlementEvents {
+    pub vindication: Option<VindicationAwardedEvent>,
+    pub settled: OpinionSettledEvent,
+}
+
+/// Apply the Triple-Check formula to one opinion and reserve any vindication
+/// bonus on the market. Shared by the settle_opinion variants.
+///
+///   combined_bps = weight*w + consensus*c + ai*a  (market scoring_weights, range 0–10000)
+///   combined_score = combined_bps / 100              (stored 0–100)
+pub fn apply_opinion_settlement(
+    market: &mut Market,
+    market_key: Pubkey,
+    opinion: &mut Opinion,
+    opinion_key: Pubkey,
+    weight_score: u8,
+    consensus_score: u8,
+    vindication_share_bps: u16,
+) -> Result<SettlementEvents> {
+    market.lock_settlement_mode(SettlementMode::TripleCheck)?;
+    require!(
+        consensus_score == compute_consensus_score(opinion.market_prediction, market.crowd_score),
+        OpinionError::ConsensusScoreMismatch
+    );
+    market.verify_weight_score(opinion, weight_score)?;
+
+    let ai_score_val = opinion.ai_score();
+    let staker_key = opinion.staker;
+
+    opinion.set_weight_score(weight_score);
+    opinion.set_consensus_score(consensus_score);
+    let previous_combined = opinion.settled().then(|| opinion.combined_score());
+    // Re-settlement replaces this opinion's previous pool weight; its
+    // prediction weight only depends on crowd_score, fixed once Scored
+    let previous_pool_weight = if opinion.settled() {
+        market.tallied_pool_weight(opinion)
+    } else {
+        market.settled_count = market.settled_count.saturating_add(1);
+        market.settled_prediction_weight = market.settled_prediction_weight
+            .checked_add(market.prediction_weight(opinion))
+            .ok_or(OpinionError::Overflow)?;
+        0
+    };
+    opinion.set_settled(true);
+
+    opinion.set_combined_score(compute_combined_score(
+        &market.scoring_weights,
+        weight_score,
+        consensus_score,
+        ai_score_val,
+    )?);
+    let combined_score_val = opinion.combined_score();
+    market.tally_combined_score(previous_combined, combined_score_val);
+    market.settled_pool_weight = market.settled_pool_weight
+        .checked_sub(previous_pool_weight)
+        .ok_or(OpinionError::Overflow)?
+        .checked_add(market.tallied_pool_weight(opinion))
+        .ok_or(OpinionError::Overflow)?;
+    market.record_top_scorer(staker_key, combined_score_val);
+
+    let previous_bonus = opinion.vindication_bonus;
+    let slashing_total = opinion.slashing_total;
+    // Slash stakes are returned in no-loss markets, so there is nothing to award
+    let vindication_bonus = if !market.no_loss
+        && combined_score_val >= VINDICATION_SCORE_THRESHOLD
+        && slashing_total > 0
+    {
+        slashing_total
+            .checked_mul(vindication_share_bps as u64)
+            .ok_or(OpinionError::Overflow)?
+            / 10_000
+    } else {
+        0
+    };
+    opinion.vindication_bonus = vindication_bonus;
+
+    // Re-settlement replaces this opinion's previous reservation
+    market.vindication_total = market.vindication_total
+        .checked_sub(previous_bonus)
+        .ok_or(OpinionError::Overflow)?
+        .checked_add(vindication_bonus)
+        .ok_or(OpinionError::Overflow)?;
+
+    let previous_floor = opinion.refund_floor();
+    let refund_floor = market.refund_floor_for(opinion, combined_score_val);
+    opinion.set_refund_floor(refund_floor);
+    market.refund_floor_total = market.refund_floor_total
+        .checked_sub(previous_floor)
+        .ok_or(OpinionError::Overflow)?
+        .checked_add(refund_floor)
+        .ok_or(OpinionError::Overflow)?;
+
+    let vindication = (vindication_bonus > 0).then(|| VindicationAwardedEvent {
+        market: market_key,
+        opinion: opinion_key,
+        staker: staker_key,
+        slashing_total,
+        bonus: vindication_bonus,
+        seq: market.next_event_seq(),
+    });
+
+    Ok(SettlementEvents {
+        vindication,
+        settled: OpinionSettledEvent {
+            market: market_key,
+            opinion: opinion_key,
+            staker: staker_key,
+            weight_score,
+            consensus_score,
+            ai_score: ai_score_val,
+            combined_score: combined_score_val,
+            seq: market.next_event_seq(),
+        },
+    })
+}
+
+/// Require that the instruction immediately before this one is an Ed25519
+/// program verification of `expected_message` signed by `signer`. The
+/// Ed25519 program has already checked the signature itself; we only confirm
+/// it covered the right key and payload.
+pub fn verify_ed25519_signature(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    expected_message: &[u8],
+) -> Result<()> {
+    let current_index = load_current_index_checked(instructions)? as usize;
+    require!(current_index > 0, OpinionError::MissingSignatureInstruction);
+    let ix = load_instruction_at_checked(current_index - 1, instructions)?;
+    require_keys_eq!(ix.program_id, ED25519_PROGRAM_ID, OpinionError::MissingSignatureInstruction);
+
+    // [num_signatures: u8, padding: u8, Ed25519SignatureOffsets (7 × u16)]
+    let data = &ix.data;
+    require!(data.len() >= 16 && data[0] == 1, OpinionError::InvalidOracleSignature);
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let signature_ix = read_u16(4);
+    let public_key_offset = read_u16(6) as usize;
+    let public_key_ix = read_u16(8);
+    let message_offset = read_u16(10) as usize;
+    let message_size = read_u16(12) as usize;
+    let message_ix = read_u16(14);
+
+    // Key, signature and message must all live in the Ed25519 instruction itself
+    require!(
+        signature_ix == u16::MAX && public_key_ix == u16::MAX && message_ix == u16::MAX,
+        OpinionError::InvalidOracleSignature
+    );
+    let public_key = data
+        .get(public_key_offset..public_key_offset + 32)
+        .ok_or(OpinionError::InvalidOracleSignature)?;
+    require!(public_key == signer.as_ref(), OpinionError::InvalidOracleSignature);
+    let message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(OpinionError::InvalidOracleSignature)?;
+    require!(message == expected_message, OpinionError::InvalidOracleSignature);
+
+    Ok(())
+}
+
+/// Move up to config.oracle_slash_amount from the oracle bond vault into a
+/// market escrow. The bond accounts are mandatory once slashing is enabled.
+/// Returns the amount slashed.
+pub fn slash_oracle_bond<'info>(
+    config: &ProgramConfig,
+    oracle_bond: Option<&mut Account<'info, OracleBond>>,
+    oracle_bond_vault: Option<AccountInfo<'info>>,
+    escrow_token_account: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    memo_program: AccountInfo<'info>,
+    market_uuid: &[u8; 16],
+) -> Result<u64> {
+    if config.oracle_slash_amount == 0 {
+        return Ok(0);
+    }
+    let (oracle_bond, oracle_bond_vault) = match (oracle_bond, oracle_bond_vault) {
+        (Some(bond), Some(vault)) => (bond, vault),
+        _ => return err!(OpinionError::OracleBondRequired),
+    };
+    let amount = config.oracle_slash_amount.min(oracle_bond.amount);
+    if amount == 0 {
+        return Ok(0);
+    }
+
+    let bond_oracle = oracle_bond.oracle;
+    let bond_bump = oracle_bond.bump;
+    let seeds: &[&[u8]] = &[b"oracle_bond", bond_oracle.as_ref(), &[bond_bump]];
+    let signer_seeds = &[seeds];
+    let slash_cpi = CpiContext::new_with_signer(
+        token_program,
+        Transfer {
+            from: oracle_bond_vault,
+            to: escrow_token_account,
+            authority: oracle_bond.to_account_info(),
+        },
+        signer_seeds,
+    );
+    transfer_with_memo(slash_cpi, amount, &memo_program, "oracle_bond_slash", Some(market_uuid))?;
+
+    oracle_bond.amount -= amount;
+    Ok(amount)
+}
+
+/// Pay a dispute's CHALLENGE_BOND out of the market escrow to `to` (the
+/// challenger, or the treasury when the oracle's proposal stood)
+pub fn release_dispute_bond<'info>(
+    market: &Account<'info, Market>,
+    escrow: EscrowAccounts<'info>,
+    to: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    bond: u64,
+) -> Result<()> {
+    if bond == 0 {
+        return Ok(());
+    }
+    let market_key = market.key();
+    let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[market.escrow_authority_bump]];
+    let signer_seeds = &[seeds];
+    let bond_cpi = CpiContext::new_with_signer(
+        token_program,
+        Transfer {
+            from: escrow.token_account,
+            to,
+            authority: escrow.authority,
+        },
+        signer_seeds,
+    );
+    transfer_with_memo(bond_cpi, bond, &escrow.memo_program, "dispute_bond_release", Some(&market.uuid))
+}
+
+/// Slash the oracle bond for an upheld dispute into the market's pool.
+/// Returns the event for the caller to emit_cpi!, if anything was slashed.
+pub fn slash_oracle_bond_into_pool<'info>(
+    market: &mut Account<'info, Market>,
+    config: &ProgramConfig,
+    oracle_bond: Option<&mut Account<'info, OracleBond>>,
+    oracle_bond_vault: Option<AccountInfo<'info>>,
+    escrow_token_account: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    memo_program: AccountInfo<'info>,
+) -> Result<Option<OracleBondSlashedEvent>> {
+    let mut oracle_bond = oracle_bond;
+    let slashed = slash_oracle_bond(
+        config,
+        oracle_bond.as_deref_mut(),
+        oracle_bond_vault,
+        escrow_token_account,
+        token_program,
+        memo_program,
+        &market.uuid,
+    )?;
+    if slashed == 0 {
+        return Ok(None);
+    }
+    market.oracle_slashed = slashed;
+    market.total_stake = market.total_stake.checked_add(slashed).ok_or(OpinionError::Overflow)?;
+    Ok(Some(OracleBondSlashedEvent {
+        market: market.key(),
+        amount: slashed,
+        remaining_bond: oracle_bond.map_or(0, |bond| bond.amount),
+        seq: market.next_event_seq(),
+    }))
+}
+
+/// Emitted whenever a market transitions into Refunding
+pub fn refunding_event(market: &mut Market, market_key: Pubkey) -> MarketRefundingEvent {
+    MarketRefundingEvent {
+        market: market_key,
+        score_deadline: market.refund_deadline(),
+        total_stake: market.total_stake,
+        seq: market.next_event_seq(),
+    }
+}
+
+/// Oracle-signed payload for record_ai_score_signed; `nonce` is checked
+/// against Market::signed_nonce
+pub fn ai_score_message(market: &Pubkey, opinion: &Pubkey, ai_score: u8, nonce: u64) -> Vec<u8> {
+    [
+        b"opinion-market:ai_score".as_ref(),
+        market.as_ref(),
+        opinion.as_ref(),
+        &[ai_score],
+        &nonce.to_le_bytes(),
+    ]
+    .concat()
+}
+
+/// Oracle-signed payload for settle_opinion_signed; `nonce` is checked
+/// against Market::signed_nonce
+pub fn settle_opinion_message(
+    market: &Pubkey,
+    opinion: &Pubkey,
+    crowd_score: u8,
+    weight_score: u8,
+    consensus_score: u8,
+    nonce: u64,
+) -> Vec<u8> {
+    [
+        b"opinion-market:settle_opinion".as_ref(),
+        market.as_ref(),
+        opinion.as_ref(),
+        &[crowd_score, weight_score, consensus_score],
+        &nonce.to_le_bytes(),
+    ]
+    .concat()
+}
+
+/// Payload a staker signs to authorize stake_with_intent
+pub fn stake_intent_message(intent: &StakeIntent) -> Vec<u8> {
+    [
+        b"opinion-market:stake_intent".as_ref(),
+        intent.market.as_ref(),
+        intent.staker.as_ref(),
+        &intent.stake_amount.to_le_bytes(),
+        &intent.text_hash,
+        &(intent.ipfs_cid.len() as u32).to_le_bytes(),
+        intent.ipfs_cid.as_bytes(),
+        &[intent.opinion_score, intent.market_prediction],
+        &intent.relay_fee.to_le_bytes(),
+        &intent.expiry.to_le_bytes(),
+        &intent.nonce.to_le_bytes(),
+    ]
+    .concat()
+}
+
+/// Payload approved by the committee for finalize_settlement
+pub fn finalize_settlement_message(
+    market: &Pubkey,
+    total_net_backing: u64,
+    sum_prediction_weights: u64,
+    top_n_cutoff: u8,
+) -> Vec<u8> {
+    [
+        b"opinion-market:finalize_settlement".as_ref(),
+        market.as_ref(),
+        &total_net_backing.to_le_bytes(),
+        &sum_prediction_weights.to_le_bytes(),
+        &[top_n_cutoff],
+    ]
+    .concat()
+}
+
+/// Payload approved by the committee for compensate_from_insurance
+pub fn insurance_claim_message(
+    market: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+    reason_hash: &[u8; 32],
+) -> Vec<u8> {
+    [
+        b"opinion-market:insurance_claim".as_ref(),
+        market.as_ref(),
+        recipient.as_ref(),
+        &amount.to_le_bytes(),
+        reason_hash.as_ref(),
+    ]
+    .concat()
+}
+
+/// When the committee is enabled, require a proposal for this market whose
+/// payload matches `message` and that carries at least `threshold` approvals.
+pub fn require_committee_approval(
+    config: &ProgramConfig,
+    proposal: Option<&SettlementProposal>,
+    market: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    if config.committee_threshold == 0 {
+        return Ok(());
+    }
+    let proposal = proposal.ok_or(OpinionError::ProposalRequired)?;
+    require_keys_eq!(proposal.market, *market, OpinionError::ProposalMismatch);
+    require!(proposal.payload_hash == hashv(&[message]).to_bytes(), OpinionError::ProposalMismatch);
+    // A member removed since approving may be among the approvals
+    require!(
+        proposal.committee_epoch == config.committee_epoch
+            && proposal.approvals.len() >= config.committee_threshold as usize,
+        OpinionError::InsufficientApprovals
+    );
+    Ok(())
+}
+
+/// When the config requires human review for this kind of submission, the
+/// configured reviewer must have co-signed and must not be the market's oracle.
+pub fn require_reviewer(
+    config: &ProgramConfig,
+    market: &Market,
+    reviewer: Option<Pubkey>,
+    batch: bool,
+) -> Result<()> {
+    let required = if batch { config.reviewer_on_batches } else { config.reviewer_required };
+    if !required {
+        return Ok(());
+    }
+    let reviewer = reviewer.ok_or(OpinionError::ReviewerSignatureRequired)?;
+    require_keys_eq!(reviewer, config.reviewer_authority, OpinionError::ReviewerSignatureRequired);
+    require_keys_neq!(reviewer, market.oracle(config), OpinionError::ReviewerNotIndependent);
+    Ok(())
+}
+
+/// Oracle-signed payload for post_settlement_root_signed, and the committee
+/// payload for every root commitment. `nonce` is checked against
+/// Market::signed_nonce; unsigned paths pass the market's current value.
+pub fn settlement_root_message(
+    market: &Pubkey,
+    settlement_root: &[u8; 32],
+    crowd_score: u8,
+    total_payout: u64,
+    nonce: u64,
+) -> Vec<u8> {
+    [
+        b"opinion-market:settlement_root".as_ref(),
+        market.as_ref(),
+        settlement_root.as_ref(),
+        &[crowd_score],
+        &total_payout.to_le_bytes(),
+        &nonce.to_le_bytes(),
+    ]
+    .concat()
+}
+
+/// Whether `destination` may receive `staker`'s payouts: the registered
+/// PayoutPreference destination if one exists, else any account the staker owns
+pub fn is_payout_destination(
+    preference: &AccountInfo,
+    staker: &Pubkey,
+    destination: &Account<TokenAccount>,
+) -> bool {
+    if preference.data_is_empty() {
+        return destination.owner == *staker;
+    }
+    let data = preference.try_borrow_data();
+    match data.map(|d| PayoutPreference::try_deserialize(&mut &d[..])) {
+        Ok(Ok(pref)) => destination.key() == pref.destination,
+        _ => false,
+    }
+}
+
+/// Return a reaction's principal at par from a settled no-loss market. The
+/// opinion's backing and the market's totals are left as settled.
+pub fn return_no_loss_reaction(ctx: &Context<RecoverReactionStake>, stake_amount: u64) -> Result<()> {
+    let market_key = ctx.accounts.market.key();
+    let escrow_bump = ctx.accounts.market.escrow_authority_bump;
+    let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+    let signer_seeds = &[seeds];
+
+    let return_cpi = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.escrow_token_account.to_account_info(),
+            to: ctx.accounts.reactor_usdc.to_account_info(),
+            authority: ctx.accounts.escrow_authority.to_account_info(),
+        },
+        signer_seeds,
+    );
+    transfer_with_memo(
+        return_cpi,
+        stake_amount,
+        &ctx.accounts.memo_program,
+        "reaction_refund",
+        Some(&ctx.accounts.market.uuid),
+    )?;
+
+    msg!("Reaction stake returned: reactor={} amount={}", ctx.accounts.reactor.key(), stake_amount);
+    Ok(())
+}
+
+/// Invoke the whitelisted lending program with the remaining accounts, signed
+/// by the escrow authority PDA
+pub fn invoke_lending<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, EscrowYield<'info>>,
+    lending_data: Vec<u8>,
+) -> Result<()> {
+    let escrow_authority = ctx.accounts.escrow_authority.key();
+    let lending_ix = Instruction {
+        program_id: ctx.accounts.lending_program.key(),
+        accounts: ctx
+            .remaining_accounts
+            .iter()
+            .map(|acc| AccountMeta {
+                pubkey: acc.key(),
+                is_signer: acc.is_signer || acc.key() == escrow_authority,
+                is_writable: acc.is_writable,
+            })
+            .collect(),
+        data: lending_data,
+    };
+    let market_key = ctx.accounts.market.key();
+    let escrow_bump = ctx.accounts.market.escrow_authority_bump;
+    let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+    invoke_signed(&lending_ix, ctx.remaining_accounts, &[seeds])?;
+    Ok(())
+}
+
+/// Verification level of a Pyth PriceUpdateV2
+#[derive(AnchorDeserialize)]
+pub enum PythVerificationLevel {
+    Partial { num_signatures: u8 },
+    Full,
+}
+
+/// Pyth receiver PriceUpdateV2 account, after its discriminator
+#[derive(AnchorDeserialize)]
+pub struct PythPriceUpdate {
+    pub write_authority: Pubkey,
+    pub verification_level: PythVerificationLevel,
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+    pub posted_slot: u64,
+}
+
+impl PythPriceUpdate {
+    /// Read a fully verified price update owned by the Pyth receiver
+    pub fn load(info: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(*info.owner, PYTH_RECEIVER_PROGRAM_ID, OpinionError::InvalidPriceUpdate);
+        let data = info.try_borrow_data()?;
+        let discriminator = &hashv(&[b"account:PriceUpdateV2"]).to_bytes()[..8];
+        require!(data.starts_with(discriminator), OpinionError::InvalidPriceUpdate);
+        let update = Self::deserialize(&mut &data[8..])
+            .map_err(|_| error!(OpinionError::InvalidPriceUpdate))?;
+        require!(
+            matches!(update.verification_level, PythVerificationLevel::Full),
+            OpinionError::InvalidPriceUpdate
+        );
+        Ok(update)
+    }
+}
+
+/// Bubblegum MetadataArgs for a winner badge. Enum fields are stored as
+/// their Borsh tags and unused optional structs as tuples of their fields.
+#[derive(AnchorSerialize)]
+pub struct BadgeMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub edition_nonce: Option<u8>,
+    /// TokenStandard; 0 = NonFungible
+    pub token_standard: Option<u8>,
+    /// Collection (verified, key)
+    pub collection: Option<(bool, Pubkey)>,
+    /// Uses (use_method, remaining, total)
+    pub uses: Option<(u8, u64, u64)>,
+    /// TokenProgramVersion; 0 = Original
+    pub token_program_version: u8,
+    /// Creators (address, verified, share)
+    pub creators: Vec<(Pubkey, bool, u8)>,
+}
+
+impl BadgeMetadata {
+    /// Badge for a paid opinion: `rank` is its podium position (0 if off the
+    /// podium). Name, symbol and URI stay within Bubblegum's 32/10/200 limits.
+    pub fn winner(market_key: &Pubkey, rank: u8, combined_score: u8) -> Self {
+        let name = if rank > 0 {
+            format!("Opinion Winner #{} ({})", rank, combined_score)
+        } else {
+            format!("Opinion Winner ({})", combined_score)
+        };
+        BadgeMetadata {
+            name,
+            symbol: "OPWIN".to_string(),
+            uri: format!("{}{}/{}/{}", BADGE_URI_BASE, market_key, rank, combined_score),
+            seller_fee_basis_points: 0,
+            primary_sale_happened: true,
+            is_mutable: false,
+            edition_nonce: None,
+            token_standard: Some(0),
+            collection: None,
+            uses: None,
+            token_program_version: 0,
+            creators: Vec::new(),
+        }
+    }
+}
+
+/// Core Plugin::Attributes; `attribute_list` holds (key, value) pairs
+#[derive(AnchorSerialize)]
+pub struct CoreAttributesPlugin {
+    pub plugin: u8,
+    pub attribute_list: Vec<(String, String)>,
+}
+
+/// Core CreateV1Args with the market's Attributes plugin. Enum fields are
+/// stored as their Borsh tags, as in BadgeMetadata.
+#[derive(AnchorSerialize)]
+pub struct CoreCreateArgs {
+    /// DataState; 0 = AccountState
+    pub data_state: u8,
+    pub name: String,
+    pub uri: String,
+    /// PluginAuthorityPair list: (plugin, PluginAuthority tag; 2 = UpdateAuthority)
+    pub plugins: Option<Vec<(CoreAttributesPlugin, Option<u8>)>>,
+}
+
+/// Instruction in a Clockwork thread, as the thread program serializes it
+#[derive(AnchorSerialize)]
+pub struct ThreadInstruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<ThreadAccountMeta>,
+    pub data: Vec<u8>,
+}
+
+#[derive(AnchorSerialize)]
+pub struct ThreadAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl From<Instruction> for ThreadInstruction {
+    fn from(ix: Instruction) -> Self {
+        ThreadInstruction {
+            program_id: ix.program_id,
+            accounts: ix
+                .accounts
+                .into_iter()
+                .map(|meta| ThreadAccountMeta {
+                    pubkey: meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: ix.data,
+        }
+    }
+}
+
+/// Clockwork thread trigger; variants up to Timestamp, in the thread
+/// program's order so the Borsh tags match
+#[derive(AnchorSerialize)]
+pub enum ThreadTrigger {
+    Account { address: Pubkey, offset: u64, size: u64 },
+    Cron { schedule: String, skippable: bool },
+    Now,
+    Slot { slot: u64 },
+    Epoch { epoch: u64 },
+    Timestamp { unix_ts: i64 },
+}
+
+/// Data for an Anchor program's `name` instruction (Clockwork, Bubblegum)
+pub fn anchor_ix_data(name: &str, args: impl AnchorSerialize) -> Result<Vec<u8>> {
+    let mut data = hashv(&[format!("global:{}", name).as_bytes()]).to_bytes()[..8].to_vec();
+    args.serialize(&mut data)?;
+    Ok(data)
+}
+
+/// close_market for `market`, run by its lifecycle thread. The registry page
+/// and scoring queue are passed when present so the close enqueues the market
+/// for scoring.
+pub fn lifecycle_close_ix(
+    market_key: Pubkey,
+    market: &Market,
+    thread: Pubkey,
+    scoring_queue: Option<Pubkey>,
+) -> Instruction {
+    let find = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &crate::ID).0;
+    let registry_page = market.registry_index.map(|_| {
+        find(&[b"market_registry".as_ref(), &market.registry_page().to_le_bytes()])
+    });
+    let accounts = crate::accounts::CloseMarket {
+        caller: thread,
+        market: market_key,
+        global_stats: find(&[b"global_stats"]),
+        activity_log: find(&[b"activity_log", market_key.as_ref()]),
+        registry_page,
+        scoring_queue,
+        event_authority: find(&[b"__event_authority"]),
+        program: crate::ID,
+    };
+    Instruction {
+        program_id: crate::ID,
+        accounts: accounts.to_account_metas(None),
+        data: crate::instruction::CloseMarket {}.data(),
+    }
+}
+
+/// CPI `on_market_settled(SettlementResult)` into each of the market's settle
+/// hooks. `hook_programs` are the subscribed programs in SettleHooks order;
+/// each hook gets [market, hook_authority (signer)] so it can tell the call
+/// came from this program. The market is written back first so hooks read
+/// its settled state.
+pub fn notify_settle_hooks<'info>(
+    market: &Account<'info, Market>,
+    settle_hooks: Option<&Account<'info, SettleHooks>>,
+    hook_authority: Option<&UncheckedAccount<'info>>,
+    hook_authority_bump: Option<u8>,
+    hook_programs: &[AccountInfo<'info>],
+) -> Result<()> {
+    if market.settle_hook_count == 0 {
+        return Ok(());
+    }
+    let (Some(hooks), Some(authority), Some(bump)) =
+        (settle_hooks, hook_authority, hook_authority_bump)
+    else {
+        return err!(OpinionError::SettleHooksRequired);
+    };
+    require!(hook_programs.len() == hooks.programs.len(), OpinionError::SettleHookAccountsMismatch);
+    market.exit(&crate::ID)?;
+
+    let result = SettlementResult {
+        market: market.key(),
+        crowd_score: market.crowd_score,
+        total_stake: market.total_stake,
+        distributable_pool: market.distributable_pool,
+        settlement_root: market.settlement_root,
+        settled_at: market.settled_at,
+    };
+    let data = anchor_ix_data("on_market_settled", result)?;
+    let seeds: &[&[u8]] = &[b"settle_hook_authority", &[bump]];
+    for (program_id, program) in hooks.programs.iter().zip(hook_programs) {
+        require_keys_eq!(program.key(), *program_id, OpinionError::SettleHookAccountsMismatch);
+        let hook_ix = Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(market.key(), false),
+                AccountMeta::new_readonly(authority.key(), true),
+            ],
+            data: data.clone(),
+        };
+        invoke_signed(
+            &hook_ix,
+            &[market.to_account_info(), authority.to_account_info(), program.clone()],
+            &[seeds],
+        )?;
+    }
+    Ok(())
+}
+
+/// Post the market's settlement payload to the Wormhole core bridge, paying
+/// the bridge fee from the oracle. Returns the message account, the emitter
+/// and the message's sequence number under it.
+pub fn post_settlement_message<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, FinalizeSettlement<'info>>,
+) -> Result<(Pubkey, Pubkey, u64)> {
+    let accounts = &ctx.accounts;
+    let (
+        Some(wormhole_program),
+        Some(bridge),
+        Some(message),
+        Some(emitter),
+        Some(sequence),
+        Some(fee_collector),
+        Some(clock),
+        Some(rent),
+        Some(system_program),
+        Some(message_bump),
+        Some(emitter_bump),
+    ) = (
+        accounts.wormhole_program.as_ref(),
+        accounts.wormhole_bridge.as_ref(),
+        accounts.wormhole_message.as_ref(),
+        accounts.wormhole_emitter.as_ref(),
+        accounts.wormhole_sequence.as_ref(),
+        accounts.wormhole_fee_collector.as_ref(),
+        accounts.clock.as_ref(),
+        accounts.rent.as_ref(),
+        accounts.system_program.as_ref(),
+        ctx.bumps.wormhole_message,
+        ctx.bumps.wormhole_emitter,
+    )
+    else {
+        return err!(OpinionError::WormholeAccountsRequired);
+    };
+    let read_u64 = |info: &AccountInfo, offset: usize| -> Result<u64> {
+        Ok(info
+            .try_borrow_data()?
+            .get(offset..offset + 8)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map_or(0, u64::from_le_bytes))
+    };
+    // BridgeData: guardian_set_index u32, last_lamports u64, expiration u32, fee u64
+    let fee = read_u64(bridge, 16)?;
+    // The next message takes the emitter's current sequence (0 before the first)
+    let next_sequence = read_u64(sequence, 0)?;
+
+    if fee > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: accounts.oracle_authority.to_account_info(),
+                    to: fee_collector.to_account_info(),
+                },
+            ),
+            fee,
+        )?;
+    }
+
+    let market_key = accounts.market.key();
+    let payload = accounts.market.settlement_payload(&market_key);
+    let mut data = vec![WORMHOLE_POST_MESSAGE_IX];
+    (0u32, payload, WORMHOLE_CONSISTENCY_FINALIZED).serialize(&mut data)?;
+    let post_ix = Instruction {
+        program_id: wormhole_program.key(),
+        accounts: vec![
+            AccountMeta::new(bridge.key(), false),
+            AccountMeta::new(message.key(), true),
+            AccountMeta::new_readonly(emitter.key(), true),
+            AccountMeta::new(sequence.key(), false),
+            AccountMeta::new(accounts.oracle_authority.key(), true),
+            AccountMeta::new(fee_collector.key(), false),
+            AccountMeta::new_readonly(clock.key(), false),
+            AccountMeta::new_readonly(rent.key(), false),
+            AccountMeta::new_readonly(system_program.key(), false),
+        ],
+        data,
+    };
+    invoke_signed(
+        &post_ix,
+        &[
+            bridge.to_account_info(),
+            message.to_account_info(),
+            emitter.to_account_info(),
+            sequence.to_account_info(),
+            accounts.oracle_authority.to_account_info(),
+            fee_collector.to_account_info(),
+            clock.to_account_info(),
+            rent.to_account_info(),
+            system_program.to_account_info(),
+            wormhole_program.to_account_info(),
+        ],
+        &[
+            &[b"wormhole_message", market_key.as_ref(), &[message_bump]],
+            &[b"emitter", &[emitter_bump]],
+        ],
+    )?;
+    Ok((message.key(), emitter.key(), next_sequence))
+}
+
+/// Shared body of create_market and create_market_hashed. `statement` is the
+/// full text, or the text's CID when `statement_hash` is set.
+pub fn open_market(
+    ctx: Context<CreateMarket>,
+    statement: String,
+    duration_secs: u64,
+    uuid: [u8; 16],
+    statement_hash: [u8; 32],
+) -> Result<Pubkey> {
+    require!(
+        matches!(duration_secs, DURATION_24H | DURATION_3D | DURATION_7D | DURATION_14D),
+        OpinionError::InvalidDuration
+    );
+
+    let creator_key = ctx.accounts.creator.key();
+    let profile = &mut ctx.accounts.creator_profile;
+    profile.creator = creator_key;
+    profile.markets_created = profile.markets_created.saturating_add(1);
+    profile.bump = ctx.bumps.creator_profile;
+    profile.schema_version = ACCOUNT_SCHEMA_VERSION;
+    let create_fee = profile.create_fee();
+
+    let created_via = match ctx.accounts.instructions.as_ref() {
+        Some(instructions) => cpi_source_program(instructions)?,
+        None => None,
+    };
+    let mut partner_fee = 0;
+    if let Some(partner) = ctx.accounts.partner.as_mut() {
+        require!(created_via == Some(partner.program), OpinionError::PartnerCallerMismatch);
+        partner_fee = partner.share_of(create_fee);
+        partner.markets_created = partner.markets_created.saturating_add(1);
+    }
+
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.creator_usdc.to_account_info(),
+            to: ctx.accounts.creation_fee_escrow.to_account_info(),
+            authority: ctx.accounts.creator.to_account_info(),
+        },
+    );
+    transfer_with_memo(
+        cpi_ctx,
+        create_fee,
+        &ctx.accounts.memo_program,
+        "creation_fee",
+        Some(&uuid),
+    )?;
+
+    let clock = Clock::get()?;
+    let market_key = ctx.accounts.market.key();
+    let statement_for_event = statement.clone();
+    let market_statement = &mut ctx.accounts.market_statement;
+    market_statement.market = market_key;
+    market_statement.statement = statement;
+    market_statement.bump = ctx.bumps.market_statement;
+    market_statement.schema_version = ACCOUNT_SCHEMA_VERSION;
+    let market = &mut ctx.accounts.market;
+    market.creator = ctx.accounts.creator.key();
+    market.uuid = uuid;
+    market.statement_hash = statement_hash;
+    market.created_at = clock.unix_timestamp;
+    market.closes_at = clock.unix_timestamp + duration_secs as i64;
+    market.reactions_close_at = market.closes_at;
+    market.score_deadline = 0;
+    market.settled_at = 0;
+    market.state = MarketState::Active;
+    market.settlement_mode = SettlementMode::Unset;
+    market.staker_count = 0;
+    market.settled_count = 0;
+    market.total_stake = 0;
+    market.distributable_pool = 0;
+    market.oracle_override = None;
+    market.crowd_score = 0;
+    market.sentiment_score = 0;
+    market.confidence = 0;
+    market.summary_hash = [0u8; 32];
+    market.scoring_run = ScoringRun::default();
+    market.podium = [PodiumEntry::default(); PODIUM_SIZE];
+    let audit_log = &mut ctx.accounts.audit_log;
+    audit_log.market = market_key;
+    audit_log.page = 0;
+    audit_log.entries = Vec::new();
+    audit_log.bump = ctx.bumps.audit_log;
+    audit_log.schema_version = ACCOUNT_SCHEMA_VERSION;
+    let market = &mut ctx.accounts.market;
+    market.audit_pages = 1;
+    market.opinion_pool = 0;
+    market.prediction_pool = 0;
+    market.jackpot_amount = 0;
+    market.jackpot_claimed = false;
+    market.total_net_backing = 0;
+    market.sum_prediction_weights = 0;
+    market.settled_pool_weight = 0;
+    market.settled_prediction_weight = 0;
+    market.vindication_total = 0;
+    market.backing_cap = ReactionCap::None;
+    market.slashing_cap = ReactionCap::None;
+    market.scoring_weights = ScoringWeights::DEFAULT;
+    market.payout_mode = PayoutMode::Proportional;
+    market.top_n_cutoff = 0;
+    market.crowd_score_mode = CrowdScoreMode::Mean;
+    market.refund_floor_bps = 0;
+    market.refund_floor_min_score = 0;
+    market.refund_floor_total = 0;
+    market.fee_waived_stake = 0;
+    market.fee_waiver_pool = 0;
+    market.below_threshold_total = 0;
+    market.payout_cap = ReactionCap::None;
+    market.vesting_threshold = 0;
+    market.vesting_days = 0;
+    market.protocol_fee_generated = 0;
+    market.charity_usdc = Pubkey::default();
+    market.charity_pool_bps = 0;
+    market.creation_fee_escrowed = create_fee;
+    market.fee_refund_min_opinions = ctx.accounts.config.fee_refund_min_opinions;
+    market.bonus_pool = 0;
+    market.no_loss = false;
+    market.results_pages = 0;
+    market.archived_opinions = 0;
+    market.category = 0;
+    market.event_seq = 0;
+    market.cap_tally_count = 0;
+    market.cap_excess_total = 0;
+    market.uncapped_payout_total = 0;
+    market.settlement_root = [0u8; 32];
+    market.merkle_payout_total = 0;
+    market.merkle_claimed_total = 0;
+    market.scores_root = [0u8; 32];
+    market.inputs_hash = [0u8; 32];
+    market.inputs_snapshot_count = 0;
+    market.verified_scores_root = [0u8; 32];
+    market.crowd_weighted_sum = 0;
+    market.crowd_weight_total = 0;
+    market.crowd_median = 0;
+    market.weight_min_net = 0;
+    market.weight_max_net = 0;
+    market.weight_range_posted = false;
+    market.weight_min_attained = false;
+    market.weight_max_attained = false;
+    market.oracle_slashed = 0;
+    market.refund_base = 0;
+    market.refund_outstanding = 0;
+    market.open_ai_challenges = 0;
+    market.ai_scored_count = 0;
+    market.opt_in_payout_total = 0;
+    market.redistribution_pool = 0;
+    market.redistribution_started = false;
+    market.queued = false;
+    market.volume_recorded = false;
+    market.yield_deposited = 0;
+    market.yield_earned = 0;
+    market.yield_receipt = Pubkey::default();
+    market.created_via = created_via.unwrap_or_default();
+    market.settlement_bounty_accrued = 0;
+    market.confidential_escrow = Pubkey::default();
+    market.confidential_stakes = 0;
+    market.confidential_revealed = 0;
+    market.confidential_revealed_total = 0;
+    market.confidential_released = false;
+    market.optimistic_open = false;
+    market.settlement_bounty_reserved = 0;
+    market.settlement_bounty_paid = 0;
+    market.partner_fee = partner_fee;
+    market.combined_score_counts = [0; COMBINED_SCORE_BUCKETS];
+    market.signed_nonce = 0;
+    market.bump = ctx.bumps.market;
+    market.escrow_authority_bump = ctx.bumps.escrow_authority;
+    market.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+    let stats = &mut ctx.accounts.global_stats;
+    let registry_index = stats.markets_created;
+    market.registry_index = Some(registry_index);
+    stats.markets_created = stats.markets_created.saturating_add(1);
+
+    let registry = &mut ctx.accounts.market_registry;
+    registry.page = (registry_index / REGISTRY_PAGE_SIZE as u64) as u32;
+    registry.bump = ctx.bumps.market_registry;
+    registry.schema_version = ACCOUNT_SCHEMA_VERSION;
+    registry.entries.push(RegistryEntry {
+        market: market_key,
+        category: market.category,
+        state: market.state.clone(),
+        closes_at: market.closes_at,
+    });
+
+    let mut log = ctx.accounts.activity_log.load_init()?;
+    log.market = market_key;
+    log.bump = ctx.bumps.activity_log;
+    log.schema_version = ACCOUNT_SCHEMA_VERSION;
+    drop(log);
+
+    emit_cpi!(MarketCreatedEvent {
+        market: market_key,
+        creator: ctx.accounts.creator.key(),
+        statement: statement_for_event,
+        statement_hash,
+        closes_at: market.closes_at,
+        duration_secs,
+        create_fee,
+        created_via,
+        partner_fee,
+        seq: ctx.accounts.market.next_event_seq(),
+    });
+    if let Some(mut event) = record_payment_reference(
+        ctx.accounts.reference.as_ref(),
+        ctx.accounts.payment_reference.as_mut(),
+        ctx.bumps.payment_reference,
+        creator_key,
+        market_key,
+        Pubkey::default(),
+        create_fee,
+    )? {
+        event.seq = ctx.accounts.market.next_event_seq();
+        emit_cpi!(event);
+    }
+
+    Ok(market_key)
+}
+
+/// Fill a freshly created PaymentReference for a Solana Pay reference passed
+/// with a create or stake; both accounts must be passed or neither. Returns
+/// the event for the caller to emit_cpi! once it has the market's next seq.
+pub fn record_payment_reference(
+    reference: Option<&UncheckedAccount>,
+    record: Option<&mut Box<Account<PaymentReference>>>,
+    bump: Option<u8>,
+    payer: Pubkey,
+    market: Pubkey,
+    opinion: Pubkey,
+    amount: u64,
+) -> Result<Option<PaymentReferenceRecordedEvent>> {
+    let (reference, record, bump) = match (reference, record, bump) {
+        (None, None, _) => return Ok(None),
+        (Some(reference), Some(record), Some(bump)) => (reference.key(), record, bump),
+        _ => return err!(OpinionError::PaymentReferenceRequired),
+    };
+    record.reference = reference;
+    record.payer = payer;
+    record.market = market;
+    record.opinion = opinion;
+    record.amount = amount;
+    record.recorded_at = Clock::get()?.unix_timestamp;
+    record.bump = bump;
+    record.schema_version = ACCOUNT_SCHEMA_VERSION;
+    Ok(Some(PaymentReferenceRecordedEvent {
+        reference,
+        payer,
+        market,
+        opinion,
+        amount,
+        seq: 0,
+    }))
+}
+
+/// Validate a new opinion stake's amount, inputs, and the market's open window
+pub fn check_opinion_stake(
+    market: &Market,
+    stake_amount: u64,
+    ipfs_cid: &str,
+    opinion_score: u8,
+    market_prediction: u8,
+    now: i64,
+) -> Result<()> {
+    require!(stake_amount >= MIN_STAKE, OpinionError::StakeTooSmall);
+    require!(stake_amount <= MAX_STAKE, OpinionError::StakeTooLarge);
+    require!(!market.is_confidential(), OpinionError::ConfidentialStakeRequired);
+    check_opinion_inputs(market, ipfs_cid, opinion_score, market_prediction, now)
+}
+
+/// Validate an opinion's inputs and the market's open window; the stake
+/// amount is checked separately (a confidential stake's only once revealed)
+pub fn check_opinion_inputs(
+    market: &Market,
+    ipfs_cid: &str,
+    opinion_score: u8,
+    market_prediction: u8,
+    now: i64,
+) -> Result<()> {
+    require!(ipfs_cid.len() <= MAX_IPFS_CID_LEN, OpinionError::CidTooLong);
+    require!(opinion_score <= 100, OpinionError::InvalidOpinionScore);
+    require!(market_prediction <= 100, OpinionError::InvalidPrediction);
+    require!(market.state == MarketState::Active, OpinionError::MarketNotActive);
+    require!(now < market.closes_at, OpinionError::MarketExpired);
+    Ok(())
+}
+
+/// A new opinion as submitted through any stake entry point
+pub struct NewOpinion {
+    pub staker: Pubkey,
+    /// 0 for a confidential stake until it is revealed
+    pub stake_amount: u64,
+    pub text_hash: [u8; 32],
+    pub ipfs_cid: String,
+    pub opinion_score: u8,
+    pub market_prediction: u8,
+    pub source_program: Option<Pubkey>,
+    pub confidential: bool,
+}
+
+/// Accounts every stake entry point writes when it creates an opinion
+pub struct OpinionStakeAccounts<'a, 'info> {
+    pub market: &'a mut Account<'info, Market>,
+    pub opinion: &'a AccountLoader<'info, Opinion>,
+    pub opinion_bump: u8,
+    pub activity_log: &'a AccountLoader<'info, ActivityLog>,
+    pub opinion_index: &'a mut OpinionIndexPage,
+    pub opinion_index_bump: u8,
+    pub user_profile: &'a mut UserProfile,
+    pub user_profile_bump: u8,
+}
+
+/// Initialize a new Opinion and record it in the market's activity log,
+/// opinion index and the staker's profile. Pool and crowd weights are left
+/// to the caller.
+pub fn init_opinion(accounts: &mut OpinionStakeAccounts, new: &NewOpinion, clock: &Clock) -> Result<()> {
+    let market_key = accounts.market.key();
+    let opinion_key = accounts.opinion.key();
+    {
+        let mut opinion = accounts.opinion.load_init()?;
+        opinion.market = market_key;
+        opinion.staker = new.staker;
+        opinion.set_stake_amount(new.stake_amount);
+        opinion.text_hash = new.text_hash;
+        opinion.set_ipfs_cid(&new.ipfs_cid);
+        opinion.created_at = clock.unix_timestamp;
+        opinion.opinion_score = new.opinion_score;
+        opinion.market_prediction = new.market_prediction;
+        opinion.source_program = new.source_program.unwrap_or_default();
+        opinion.bump = accounts.opinion_bump;
+        opinion.schema_version = ACCOUNT_SCHEMA_VERSION;
+        opinion.reset_for_stake(new.stake_amount);
+        opinion.set_confidential_pending(new.confidential);
+    }
+
+    accounts
+        .activity_log
+        .load_mut()?
+        .record(new.staker, ActivityKind::Stake, new.stake_amount, clock.slot);
+    accounts
+        .opinion_index
+        .push(accounts.market, market_key, opinion_key, accounts.opinion_index_bump);
+    accounts
+        .user_profile
+        .touch(new.staker, accounts.user_profile_bump, clock.unix_timestamp);
+    Ok(())
+}
+
+/// Create a public opinion once its stake is in escrow: init_opinion, then
+/// weigh the stake into the market's pool and crowd score, global stats and
+/// the staker's profile. Returns the event for the caller to emit_cpi!.
+pub fn init_opinion_stake(
+    mut accounts: OpinionStakeAccounts,
+    crowd_histogram: Option<&mut CrowdHistogram>,
+    global_stats: &mut GlobalStats,
+    new: NewOpinion,
+    clock: &Clock,
+) -> Result<OpinionStakedEvent> {
+    accounts.market.record_opinion_stake(
+        crowd_histogram,
+        new.opinion_score,
+        new.market_prediction,
+        new.stake_amount,
+    )?;
+    init_opinion(&mut accounts, &new, clock)?;
+    global_stats.record_stake(new.stake_amount);
+    accounts.user_profile.record_stake(new.stake_amount);
+
+    Ok(OpinionStakedEvent {
+        market: accounts.market.key(),
+        staker: new.staker,
+        stake_amount: new.stake_amount,
+        opinion_score: new.opinion_score,
+        market_prediction: new.market_prediction,
+        ipfs_cid: new.ipfs_cid,
+        total_stake_after: accounts.market.total_stake,
+        source_program: new.source_program,
+        seq: accounts.market.next_event_seq(),
+    })
+}
+
+/// Program that reached this instruction via CPI, if any.
+/// Reads the outermost (top-level) instruction from the instructions sysvar,
+/// so a partner app that wraps our instruction is attributed even through
+/// intermediate programs.
+pub fn cpi_source_program(instructions: &AccountInfo) -> Result<Option<Pubkey>> {
+    if get_stack_height() <= TRANSACTION_LEVEL_STACK_HEIGHT {
+        return Ok(None);
+    }
+    let current_index = load_current_index_checked(instructions)?;
+    let top_level = load_instruction_at_checked(current_index as usize, instructions)?;
+    Ok(if top_level.program_id == crate::ID { None } else { Some(top_level.program_id) })
+}
+
+/// Invoke the whitelisted swap program with an aggregator route: `swap_data`
+/// is the route instruction data and `route_accounts` its accounts, passed
+/// through with the signers of the enclosing instruction.
+pub fn invoke_swap<'info>(
+    swap_program: &UncheckedAccount<'info>,
+    route_accounts: &[AccountInfo<'info>],
+    swap_data: Vec<u8>,
+) -> Result<()> {
+    let swap_ix = Instruction {
+        program_id: swap_program.key(),
+        accounts: route_accounts
+            .iter()
+            .map(|acc| AccountMeta {
+                pubkey: acc.key(),
+                is_signer: acc.is_signer,
+                is_writable: acc.is_writable,
+            })
+            .collect(),
+        data: swap_data,
+    };
+    invoke(&swap_ix, route_accounts)?;
+    Ok(())
+}
+
+/// PDA derivations for integrators building with the `cpi` feature, so
+/// callers can assemble opinion_market instructions without copying seeds
+#[cfg(feature = "cpi")]
+pub mod pda {
+    use anchor_lang::prelude::Pubkey;
+
+    fn find(seeds: &[&[u8]]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(seeds, &crate::ID)
+    }
+
+    pub fn config() -> (Pubkey, u8) {
+        find(&[b"config"])
+    }
+
+    pub fn global_stats() -> (Pubkey, u8) {
+        find(&[b"global_stats"])
+    }
+
+    pub fn market_registry(page: u32) -> (Pubkey, u8) {
+        find(&[b"market_registry", &page.to_le_bytes()])
+    }
+
+    pub fn market(uuid: &[u8; 16]) -> (Pubkey, u8) {
+        find(&[b"market", uuid])
+    }
+
+    pub fn market_statement(market: &Pubkey) -> (Pubkey, u8) {
+        find(&[b"market_statement", market.as_ref()])
+    }
+
+    pub fn escrow(market: &Pubkey) -> (Pubkey, u8) {
+        find(&[b"escrow", market.as_ref()])
+    }
+
+    pub fn escrow_authority(market: &Pubkey) -> (Pubkey, u8) {
+        find(&[b"escrow_authority", market.as_ref()])
+    }
+
+    pub fn creation_fee(market: &Pubkey) -> (Pubkey, u8) {
+        find(&[b"creation_fee", market.as_ref()])
+    }
+
+    pub fn activity_log(market: &Pubkey) -> (Pubkey, u8) {
+        find(&[b"activity_log", market.as_ref()])
+    }
+
+    /// `page` is Market::opinion_index_page() at the time of the stake
+    pub fn opinion_index(market: &Pubkey, page: u32) -> (Pubkey, u8) {
+        find(&[b"opinion_index", market.as_ref(), &page.to_le_bytes()])
+    }
+
+    pub fn opinion(market: &Pubkey, staker: &Pubkey) -> (Pubkey, u8) {
+        find(&[b"opinion", market.as_ref(), staker.as_ref()])
+    }
+
+    pub fn reaction(opinion: &Pubkey, reactor: &Pubkey) -> (Pubkey, u8) {
+        find(&[b"reaction", opinion.as_ref(), reactor.as_ref()])
+    }
+
+    pub fn oracle_bond(oracle: &Pubkey) -> (Pubkey, u8) {
+        find(&[b"oracle_bond", oracle.as_ref()])
+    }
+
+    pub fn oracle_bond_vault(oracle: &Pubkey) -> (Pubkey, u8) {
+        find(&[b"oracle_bond_vault", oracle.as_ref()])
+    }
+
+    pub fn oracle_status(oracle: &Pubkey) -> (Pubkey, u8) {
+        find(&[b"oracle_status", oracle.as_ref()])
+    }
+
+    pub fn user_profile(user: &Pubkey) -> (Pubkey, u8) {
+        find(&[b"user_profile", user.as_ref()])
+    }
+
+    pub fn creator_profile(creator: &Pubkey) -> (Pubkey, u8) {
+        find(&[b"creator_profile", creator.as_ref()])
+    }
+
+    pub fn partner(program: &Pubkey) -> (Pubkey, u8) {
+        find(&[b"partner", program.as_ref()])
+    }
+}
+
+// ── Program ──────────────────────────────────────────────────────────────────
+#[program]
+pub mod opinion_market {
+    use super::*;
+
+    /// Initialize global config — called once by deployer
+    pub fn initialize(
+        ctx: Context<InitializeConfig>,
+        oracle_authority: Pubkey,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.config.set_inner(ProgramConfig::with_defaults(
+            oracle_authority,
+            treasury,
+            ctx.accounts.usdc_mint.key(),
+            ctx.accounts.deployer.key(),
+            ctx.bumps.config,
+        ));
+        msg!("ProgramConfig initialized: oracle_authority={} treasury={}", oracle_authority, treasury);
+        Ok(())
+    }
+
+    /// Admin sets the share of slashers' stakes paid to vindicated authors.
+    pub fn set_vindication_share(ctx: Context<UpdateConfig>, share_bps: u16) -> Result<()> {
+        require!(share_bps <= 10_000, OpinionError::InvalidBps);
+        ctx.accounts.config.vindication_share_bps = share_bps;
+        msg!("Vindication share set: {} bps", share_bps);
+        Ok(())
+    }
+
+    /// Admin bounds the per-market Triple-Check layer weights creators may choose.
+    pub fn set_layer_weight_bounds(ctx: Context<UpdateConfig>, min: u8, max: u8) -> Result<()> {
+        require!(
+            min <= max && max <= 100 && (min as u16) * 3 <= 100 && (max as u16) * 3 >= 100,
+            OpinionError::InvalidWeightBounds
+        );
+        let config = &mut ctx.accounts.config;
+        config.min_layer_weight = min;
+        config.max_layer_weight = max;
+        msg!("Layer weight bounds set: {}–{}%", min, max);
+        Ok(())
+    }
+
+    /// Admin assigns a dedicated oracle operator to a market (None restores the
+    /// global oracle), e.g. for markets in another language or topic domain.
+    /// Only allowed before the market is scored.
+    pub fn set_market_oracle(ctx: Context<SetMarketOracle>, oracle: Option<Pubkey>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(
+            matches!(market.state, MarketState::Active | MarketState::Closed),
+            OpinionError::MarketNotActive
+        );
+        market.oracle_override = oracle;
+        msg!("Market oracle set: market={} oracle={:?}", market.key(), oracle);
+        Ok(())
+    }
+
+    /// Admin sets oracle compensation per finalized market (flat + bps of pool).
+    pub fn set_oracle_fee(ctx: Context<UpdateConfig>, flat: u64, bps: u16) -> Result<()> {
+        require!(bps <= 10_000, OpinionError::InvalidBps);
+        let config = &mut ctx.accounts.config;
+        config.oracle_fee_flat = flat;
+        config.oracle_fee_bps = bps;
+        msg!("Oracle fee set: flat={} bps={}", flat, bps);
+        Ok(())
+    }
+
+    /// Admin sets the human reviewer and where their co-signature is required:
+    /// on settlement (finalize_settlement, post_settlement_root) and/or on
+    /// batch score submissions.
+    pub fn set_reviewer(
+        ctx: Context<UpdateConfig>,
+        reviewer_authority: Pubkey,
+        required: bool,
+        on_batches: bool,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            !(required || on_batches) || reviewer_authority != Pubkey::default(),
+            OpinionError::ReviewerSignatureRequired
+        );
+        require_keys_neq!(reviewer_authority, config.oracle_authority, OpinionError::ReviewerNotIndependent);
+        config.reviewer_authority = reviewer_authority;
+        config.reviewer_required = required;
+        config.reviewer_on_batches = on_batches;
+        msg!(
+            "Reviewer set: {} (settlement={} batches={})",
+            reviewer_authority,
+            required,
+            on_batches
+        );
+        Ok(())
+    }
+
+    /// Admin sets how many points (in bps per unit of protocol fee) stakers
+    /// earn for the fees their stakes generated; 0 ends the season.
+    pub fn set_points_rate(ctx: Context<UpdateConfig>, points_rate_bps: u32) -> Result<()> {
+        ctx.accounts.config.points_rate_bps = points_rate_bps;
+        msg!("Points rate set: {} bps", points_rate_bps);
+        Ok(())
+    }
+
+    /// Admin creates the points mint. The mint PDA is its own mint and freeze
+    /// authority, so points can only be issued by claim_fee_points and every
+    /// holder account stays frozen (non-transferable).
+    pub fn initialize_points_mint(ctx: Context<InitializePointsMint>) -> Result<()> {
+        msg!("Points mint initialized: {}", ctx.accounts.points_mint.key());
+        Ok(())
+    }
+
+    /// Admin creates the reputation mint: a Token-2022 mint with the
+    /// NonTransferable extension whose PDA is its own mint authority, so
+    /// reputation can only be issued by claim_reputation and never moves.
+    pub fn initialize_reputation_mint(ctx: Context<InitializeReputationMint>) -> Result<()> {
+        let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+            ExtensionType::NonTransferable,
+        ])?;
+        let token_program = ctx.accounts.token_2022_program.to_account_info();
+        let mint = ctx.accounts.reputation_mint.to_account_info();
+        let seeds: &[&[u8]] = &[b"reputation_mint", &[ctx.bumps.reputation_mint]];
+        let signer_seeds = &[seeds];
+
+        anchor_lang::system_program::create_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::CreateAccount {
+                    from: ctx.accounts.admin.to_account_info(),
+                    to: mint.clone(),
+                },
+                signer_seeds,
+            ),
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            &token_2022::ID,
+        )?;
+        token_interface::non_transferable_mint_initialize(CpiContext::new(
+            token_program.clone(),
+            token_interface::NonTransferableMintInitialize {
+                token_program_id: token_program.clone(),
+                mint: mint.clone(),
+            },
+        ))?;
+        token_2022::initialize_mint2(
+            CpiContext::new(token_program, token_2022::InitializeMint2 { mint: mint.clone() }),
+            6,
+            &mint.key(),
+            None,
+        )?;
+
+        msg!("Reputation mint initialized: {}", mint.key());
+        Ok(())
+    }
+
+    /// Admin creates a Bubblegum tree for winner badges with the badge
+    /// authority PDA as tree creator, so only mint_winner_badge can mint into
+    /// it. `merkle_tree` must already be allocated for the account
+    /// compression program at the size the depth and buffer require.
+    pub fn initialize_badge_tree(
+        ctx: Context<InitializeBadgeTree>,
+        max_depth: u32,
+        max_buffer_size: u32,
+    ) -> Result<()> {
+        let create_ix = Instruction {
+            program_id: BUBBLEGUM_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(ctx.accounts.tree_config.key(), false),
+                AccountMeta::new(ctx.accounts.merkle_tree.key(), false),
+                AccountMeta::new(ctx.accounts.admin.key(), true),
+                AccountMeta::new_readonly(ctx.accounts.badge_authority.key(), true),
+                AccountMeta::new_readonly(NOOP_PROGRAM_ID, false),
+                AccountMeta::new_readonly(ACCOUNT_COMPRESSION_PROGRAM_ID, false),
+                AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+            ],
+            data: anchor_ix_data("create_tree", (max_depth, max_buffer_size, Some(false)))?,
+        };
+        let seeds: &[&[u8]] = &[b"badge_authority", &[ctx.bumps.badge_authority]];
+        invoke_signed(
+            &create_ix,
+            &[
+                ctx.accounts.tree_config.to_account_info(),
+                ctx.accounts.merkle_tree.to_account_info(),
+                ctx.accounts.admin.to_account_info(),
+                ctx.accounts.badge_authority.to_account_info(),
+                ctx.accounts.log_wrapper.to_account_info(),
+                ctx.accounts.compression_program.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.bubblegum_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+        msg!("Badge tree initialized: {}", ctx.accounts.merkle_tree.key());
+        Ok(())
+    }
+
+    /// Admin sets how many opinions new markets need for their creation fee to
+    /// be refunded at close. Existing markets keep the threshold they were
+    /// created with.
+    pub fn set_fee_refund_threshold(ctx: Context<UpdateConfig>, min_opinions: u32) -> Result<()> {
+        ctx.accounts.config.fee_refund_min_opinions = min_opinions;
+        msg!("Creation fee refund threshold set: {} opinions", min_opinions);
+        Ok(())
+    }
+
+    /// Admin proposes a new treasury owner, e.g. a Squads vault PDA. Nothing
+    /// changes until the new owner signs accept_treasury; proposing again
+    /// replaces the pending treasury.
+    pub fn propose_treasury(ctx: Context<UpdateConfig>, new_treasury: Pubkey) -> Result<()> {
+        require!(new_treasury != Pubkey::default(), OpinionError::TreasuryMismatch);
+        ctx.accounts.config.pending_treasury = new_treasury;
+        msg!("Treasury proposed: {}", new_treasury);
+        Ok(())
+    }
+
+    /// The pending treasury takes over. It must sign (a multisig vault signs
+    /// by executing this through its own program), proving the fees will not
+    /// land somewhere nobody controls, and present a USDC account it owns
+    /// that nobody else can move funds out of.
+    pub fn accept_treasury(ctx: Context<AcceptTreasury>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let previous = config.treasury;
+        config.treasury = ctx.accounts.new_treasury.key();
+        config.pending_treasury = Pubkey::default();
+        msg!("Treasury rotated: {} -> {}", previous, config.treasury);
+        Ok(())
+    }
+
+    /// Admin sets the keeper bounty deducted from payouts delivered by push_payout.
+    pub fn set_push_bounty(ctx: Context<UpdateConfig>, bounty_bps: u16) -> Result<()> {
+        require!(bounty_bps <= MAX_PUSH_BOUNTY_BPS, OpinionError::InvalidBps);
+        ctx.accounts.config.push_bounty_bps = bounty_bps;
+        msg!("Push bounty set: {} bps", bounty_bps);
+        Ok(())
+    }
+
+    /// Admin sets the bounty paid to whoever lands each relayed settlement
+    /// transaction, and the per-market cap on those bounties as a share of
+    /// the market's protocol fee. A zero bounty turns them off.
+    pub fn set_settlement_bounty(
+        ctx: Context<UpdateConfig>,
+        bounty: u64,
+        cap_bps: u16,
+    ) -> Result<()> {
+        require!(cap_bps <= MAX_SETTLEMENT_BOUNTY_CAP_BPS, OpinionError::InvalidBps);
+        let config = &mut ctx.accounts.config;
+        config.settlement_bounty = bounty;
+        config.settlement_bounty_cap_bps = cap_bps;
+        msg!("Settlement bounty set: {} (cap {} bps)", bounty, cap_bps);
+        Ok(())
+    }
+
+    /// Admin replaces the marginal protocol fee schedule. Each tier's bps is
+    /// charged only on the part of a pool above its threshold; pad unused
+    /// tiers with threshold u64::MAX.
+    pub fn set_fee_tiers(ctx: Context<UpdateConfig>, tiers: [FeeTier; MAX_FEE_TIERS]) -> Result<()> {
+        require!(is_valid_fee_schedule(&tiers), OpinionError::InvalidFeeSchedule);
+        ctx.accounts.config.fee_tiers = tiers;
+        msg!("Protocol fee tiers set");
+        Ok(())
+    }
+
+    /// Admin sets the share of each protocol fee paid into the insurance vault.
+    pub fn set_insurance_share(ctx: Context<UpdateConfig>, share_bps: u16) -> Result<()> {
+        require!(share_bps <= MAX_INSURANCE_SHARE_BPS, OpinionError::InvalidBps);
+        ctx.accounts.config.insurance_share_bps = share_bps;
+        msg!("Insurance share set: {} bps", share_bps);
+        Ok(())
+    }
+
+    /// Admin whitelists the swap aggregator (e.g. Jupiter) used by
+    /// claim_payout_swapped and stake_opinion_with_swap; Pubkey::default()
+    /// disables swapped claims and stakes.
+    pub fn set_swap_program(ctx: Context<UpdateConfig>, swap_program: Pubkey) -> Result<()> {
+        ctx.accounts.config.swap_program = swap_program;
+        msg!("Swap program set: {}", swap_program);
+        Ok(())
+    }
+
+    /// Admin sets the Token-2022 mint with confidential transfers that
+    /// confidential-stake markets escrow (a wrapped USDC) and the wrapper
+    /// program release_confidential_stakes unwraps it through;
+    /// Pubkey::default() disables new confidential markets.
+    pub fn set_confidential_mint(
+        ctx: Context<UpdateConfig>,
+        mint: Pubkey,
+        wrap_program: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.confidential_mint = mint;
+        config.confidential_wrap_program = wrap_program;
+        msg!("Confidential mint set: {} (wrapper {})", mint, wrap_program);
+        Ok(())
+    }
+
+    /// Admin sets the reputation-based protocol fee waiver: stakers whose
+    /// ReactorStats show at least `min_scored` scored reactions at
+    /// `min_accuracy_bps` accuracy get `waiver_bps` of their share of the fee
+    /// back at claim. waiver_bps = 0 turns waivers off.
+    pub fn set_fee_waiver(
+        ctx: Context<UpdateConfig>,
+        waiver_bps: u16,
+        min_scored: u32,
+        min_accuracy_bps: u16,
+    ) -> Result<()> {
+        require!(waiver_bps <= 10_000 && min_accuracy_bps <= 10_000, OpinionError::InvalidBps);
+        let config = &mut ctx.accounts.config;
+        config.fee_waiver_bps = waiver_bps;
+        config.fee_waiver_min_scored = min_scored;
+        config.fee_waiver_min_accuracy_bps = min_accuracy_bps;
+        msg!(
+            "Fee waiver set: {} bps for ≥{} scored reactions at ≥{} bps accuracy",
+            waiver_bps,
+            min_scored,
+            min_accuracy_bps
+        );
+        Ok(())
+    }
+
+    /// Admin sets the smallest payout worth claiming; smaller ones can be
+    /// skipped by skip_dust_payouts. At most MAX_MIN_PAYOUT.
+    pub fn set_min_payout(ctx: Context<UpdateConfig>, min_payout: u64) -> Result<()> {
+        require!(min_payout <= MAX_MIN_PAYOUT, OpinionError::MinPayoutTooHigh);
+        ctx.accounts.config.min_payout = min_payout;
+        msg!("Minimum payout set: {}", min_payout);
+        Ok(())
+    }
+
+    /// Admin whitelists the lending program escrow may be deposited into
+    /// between close and settlement; Pubkey::default() disables escrow yield.
+    pub fn set_lending_program(ctx: Context<UpdateConfig>, lending_program: Pubkey) -> Result<()> {
+        ctx.accounts.config.lending_program = lending_program;
+        msg!("Lending program set: {}", lending_program);
+        Ok(())
+    }
+
+    /// Admin lends `amount` of a closed market's idle escrow through the
+    /// whitelisted lending program. `lending_data` and the remaining accounts
+    /// are the lending deposit instruction, invoked with the escrow authority
+    /// as signer; the receipt must land in a token account it owns. The
+    /// escrow must drop by exactly `amount`.
+    pub fn deposit_escrow_yield<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EscrowYield<'info>>,
+        amount: u64,
+        lending_data: Vec<u8>,
+    ) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(
+            matches!(market.state, MarketState::Closed | MarketState::Scored),
+            OpinionError::MarketNotClosed
+        );
+        require!(market.yield_deposited == 0, OpinionError::YieldStillDeposited);
+
+        let escrow_before = ctx.accounts.escrow_token_account.amount;
+        invoke_lending(&ctx, lending_data)?;
+        ctx.accounts.escrow_token_account.reload()?;
+        require!(
+            escrow_before.checked_sub(ctx.accounts.escrow_token_account.amount) == Some(amount),
+            OpinionError::EscrowAmountMismatch
+        );
+
+        let market_key = ctx.accounts.market.key();
+        let receipt_key = ctx.accounts.receipt_token_account.key();
+        let market = &mut ctx.accounts.market;
+        market.yield_deposited = amount;
+        market.yield_receipt = receipt_key;
+
+        emit_cpi!(EscrowYieldEvent {
+            market: market_key,
+            principal: amount,
+            yield_amount: 0,
+            deposited: true,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+        Ok(())
+    }
+
+    /// Admin redeems a market's lending position back into escrow. The
+    /// escrow must regain at least the deposited principal and the receipt
+    /// account must be emptied; anything above principal is yield for the
+    /// prize pool. Allowed in Refunding too, so refunds can proceed.
+    pub fn withdraw_escrow_yield<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EscrowYield<'info>>,
+        lending_data: Vec<u8>,
+    ) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.yield_deposited > 0, OpinionError::NoYieldDeposited);
+        require_keys_eq!(
+            ctx.accounts.receipt_token_account.key(),
+            market.yield_receipt,
+            OpinionError::YieldReceiptMismatch
+        );
+        let principal = market.yield_deposited;
+
+        let escrow_before = ctx.accounts.escrow_token_account.amount;
+        invoke_lending(&ctx, lending_data)?;
+        ctx.accounts.escrow_token_account.reload()?;
+        ctx.accounts.receipt_token_account.reload()?;
+        let returned = ctx.accounts.escrow_token_account.amount.saturating_sub(escrow_before);
+        require!(returned >= principal, OpinionError::PrincipalNotReturned);
+        require!(ctx.accounts.receipt_token_account.amount == 0, OpinionError::PrincipalNotReturned);
+
+        let market_key = ctx.accounts.market.key();
+        let yield_amount = returned - principal;
+        let market = &mut ctx.accounts.market;
+        market.yield_deposited = 0;
+        market.yield_earned = market.yield_earned.saturating_add(yield_amount);
+        market.yield_receipt = Pubkey::default();
+
+        emit_cpi!(EscrowYieldEvent {
+            market: market_key,
+            principal,
+            yield_amount,
+            deposited: false,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+        Ok(())
+    }
+
+    /// Admin creates the insurance vault and its USDC account.
+    pub fn initialize_insurance_vault(ctx: Context<InitializeInsuranceVault>) -> Result<()> {
+        let vault = &mut ctx.accounts.insurance_vault;
+        vault.total_deposited = 0;
+        vault.total_paid = 0;
+        vault.claims_paid = 0;
+        vault.bump = ctx.bumps.insurance_vault;
+        vault.schema_version = ACCOUNT_SCHEMA_VERSION;
+        Ok(())
+    }
+
+    /// Admin compensates a wallet harmed on `market` by oracle error or a
+    /// program bug, paying from the insurance vault. When the oracle committee
+    /// is enabled the payout also needs a committee-approved proposal over
+    /// insurance_claim_message.
+    pub fn compensate_from_insurance(
+        ctx: Context<CompensateFromInsurance>,
+        amount: u64,
+        reason_hash: [u8; 32],
+    ) -> Result<()> {
+        let market_key = ctx.accounts.market.key();
+        let recipient = ctx.accounts.recipient_usdc.owner;
+        let message = insurance_claim_message(&market_key, &recipient, amount, &reason_hash);
+        require_committee_approval(
+            &ctx.accounts.config,
+            ctx.accounts.settlement_proposal.as_deref(),
+            &market_key,
+            &message,
+        )?;
+        require!(
+            ctx.accounts.insurance_vault_usdc.amount >= amount,
+            OpinionError::InsufficientInsuranceFunds
+        );
+
+        let vault_bump = ctx.accounts.insurance_vault.bump;
+        let seeds: &[&[u8]] = &[b"insurance_vault", &[vault_bump]];
+        let signer_seeds = &[seeds];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.insurance_vault_usdc.to_account_info(),
+                to: ctx.accounts.recipient_usdc.to_account_info(),
+                authority: ctx.accounts.insurance_vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_with_memo(
+            cpi_ctx,
+            amount,
+            &ctx.accounts.memo_program,
+            "insurance_compensation",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        let vault = &mut ctx.accounts.insurance_vault;
+        vault.total_paid = vault.total_paid.saturating_add(amount);
+        vault.claims_paid = vault.claims_paid.saturating_add(1);
+
+        emit_cpi!(InsuranceClaimPaidEvent {
+            market: market_key,
+            recipient,
+            amount,
+            reason_hash,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Admin creates the treasury ledger that finalize_settlement updates.
+    pub fn initialize_treasury_ledger(ctx: Context<InitializeTreasuryLedger>) -> Result<()> {
+        let ledger = &mut ctx.accounts.treasury_ledger;
+        ledger.protocol_fees = 0;
+        ledger.oracle_fees = 0;
+        ledger.markets_finalized = 0;
+        ledger.dust_swept = 0;
+        ledger.bump = ctx.bumps.treasury_ledger;
+        ledger.schema_version = ACCOUNT_SCHEMA_VERSION;
+        Ok(())
+    }
+
+    /// Admin creates the GlobalStats account that market, stake and
+    /// settlement instructions update.
+    pub fn initialize_global_stats(ctx: Context<InitializeGlobalStats>) -> Result<()> {
+        let stats = &mut ctx.accounts.global_stats;
+        stats.markets_created = 0;
+        stats.markets_settled = 0;
+        stats.cumulative_volume = 0;
+        stats.cumulative_fees = 0;
+        stats.active_tvl = 0;
+        stats.event_seq = 0;
+        stats.bump = ctx.bumps.global_stats;
+        stats.schema_version = ACCOUNT_SCHEMA_VERSION;
+        Ok(())
+    }
+
+    /// Admin creates the global scoring work queue.
+    pub fn initialize_scoring_queue(ctx: Context<InitializeScoringQueue>) -> Result<()> {
+        let queue = &mut ctx.accounts.scoring_queue;
+        queue.entries = Vec::new();
+        queue.bump = ctx.bumps.scoring_queue;
+        queue.schema_version = ACCOUNT_SCHEMA_VERSION;
+        Ok(())
+    }
+
+    /// Admin registers (or rotates) the Groth16 verifying key for ZK-verified scoring.
+    pub fn set_scoring_verifier(ctx: Context<SetScoringVerifier>, vk: ScoringVerifyingKey) -> Result<()> {
+        let verifier = &mut ctx.accounts.scoring_verifier;
+        verifier.vk = vk;
+        verifier.bump = ctx.bumps.scoring_verifier;
+        verifier.schema_version = ACCOUNT_SCHEMA_VERSION;
+        msg!("Scoring verifier key updated");
+        Ok(())
+    }
+
+    /// Admin adds an oracle committee member.
+    pub fn add_oracle_member(ctx: Context<AddOracleMember>, member: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!((config.committee_size as usize) < MAX_COMMITTEE_SIZE, OpinionError::CommitteeFull);
+        config.committee_size += 1;
+
+        let oracle_member = &mut ctx.accounts.oracle_member;
+        oracle_member.member = member;
+        oracle_member.bump = ctx.bumps.oracle_member;
+        oracle_member.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        msg!("Oracle member added: {} (committee size {})", member, config.committee_size);
+        Ok(())
+    }
+
+    /// Admin removes an oracle committee member. The threshold must still be
+    /// reachable by the remaining members. Starts a new committee epoch, so
+    /// approvals already given (possibly the removed member's) stop counting
+    /// and open proposals must be approved again.
+    pub fn remove_oracle_member(ctx: Context<RemoveOracleMember>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.committee_epoch = config.committee_epoch.wrapping_add(1);
+        config.committee_size = config.committee_size.saturating_sub(1);
+        require!(config.committee_threshold <= config.committee_size, OpinionError::InvalidThreshold);
+
+        msg!("Oracle member removed: {}", ctx.accounts.oracle_member.member);
+        Ok(())
+    }
+
+    /// Admin adds a charity's USDC token account to the registry charity
+    /// markets donate to.
+    pub fn add_charity(ctx: Context<AddCharity>) -> Result<()> {
+        let charity = &mut ctx.accounts.charity;
+        charity.usdc_account = ctx.accounts.charity_usdc.key();
+        charity.bump = ctx.bumps.charity;
+        charity.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        msg!("Charity registered: {}", charity.usdc_account);
+        Ok(())
+    }
+
+    /// Admin removes a charity from the registry. Markets already pointed at
+    /// it still donate to it at finalization.
+    pub fn remove_charity(ctx: Context<RemoveCharity>) -> Result<()> {
+        msg!("Charity removed: {}", ctx.accounts.charity.usdc_account);
+        Ok(())
+    }
+
+    /// Admin whitelists a program that may subscribe to market settlement.
+    pub fn add_hook_program(ctx: Context<AddHookProgram>, program: Pubkey) -> Result<()> {
+        let hook_program = &mut ctx.accounts.hook_program;
+        hook_program.program = program;
+        hook_program.bump = ctx.bumps.hook_program;
+        hook_program.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        msg!("Settle hook program whitelisted: {}", program);
+        Ok(())
+    }
+
+    /// Admin registers a partner program whose CPI-created markets share
+    /// `revenue_share_bps` of the creation fee with `payout_usdc`.
+    pub fn register_partner(
+        ctx: Context<RegisterPartner>,
+        program: Pubkey,
+        revenue_share_bps: u16,
+    ) -> Result<()> {
+        require!(revenue_share_bps <= MAX_PARTNER_SHARE_BPS, OpinionError::PartnerShareTooHigh);
+        let partner = &mut ctx.accounts.partner;
+        partner.program = program;
+        partner.payout_usdc = ctx.accounts.payout_usdc.key();
+        partner.revenue_share_bps = revenue_share_bps;
+        partner.markets_created = 0;
+        partner.fees_earned = 0;
+        partner.bump = ctx.bumps.partner;
+        partner.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        msg!("Partner registered: {} share_bps={}", program, revenue_share_bps);
+        Ok(())
+    }
+
+    /// Admin changes a partner's revenue share and payout account; markets
+    /// already created keep what they paid.
+    pub fn update_partner(ctx: Context<UpdatePartner>, revenue_share_bps: u16) -> Result<()> {
+        require!(revenue_share_bps <= MAX_PARTNER_SHARE_BPS, OpinionError::PartnerShareTooHigh);
+        let partner = &mut ctx.accounts.partner;
+        partner.payout_usdc = ctx.accounts.payout_usdc.key();
+        partner.revenue_share_bps = revenue_share_bps;
+
+        msg!("Partner updated: {} share_bps={}", partner.program, revenue_share_bps);
+        Ok(())
+    }
+
+    /// Admin removes a partner. Its markets keep their created_via
+    /// attribution; new ones are still attributed but share no fee.
+    pub fn remove_partner(ctx: Context<RemovePartner>) -> Result<()> {
+        msg!("Partner removed: {}", ctx.accounts.partner.program);
+        Ok(())
+    }
+
+    /// Admin removes a program from the settle hook whitelist. Existing
+    /// subscriptions stay until unsubscribe_settle_hook.
+    pub fn remove_hook_program(ctx: Context<RemoveHookProgram>) -> Result<()> {
+        msg!("Settle hook program removed: {}", ctx.accounts.hook_program.program);
+        Ok(())
+    }
+
+    /// A whitelisted program subscribes to a market's settlement: finalize_settlement
+    /// and the settlement root instructions then CPI its `on_market_settled`.
+    /// The program proves itself by signing with its ["settle_hook"] PDA.
+    pub fn subscribe_settle_hook(ctx: Context<SubscribeSettleHook>) -> Result<()> {
+        let program = ctx.accounts.hook_program.program;
+        let (hook_signer, _) = Pubkey::find_program_address(&[b"settle_hook"], &program);
+        require_keys_eq!(ctx.accounts.hook_signer.key(), hook_signer, OpinionError::Unauthorized);
+
+        let hooks = &mut ctx.accounts.settle_hooks;
+        require!(!hooks.programs.contains(&program), OpinionError::SettleHookExists);
+        require!(hooks.programs.len() < MAX_SETTLE_HOOKS, OpinionError::SettleHooksFull);
+        hooks.market = ctx.accounts.market.key();
+        hooks.programs.push(program);
+        hooks.bump = ctx.bumps.settle_hooks;
+        hooks.schema_version = ACCOUNT_SCHEMA_VERSION;
+        ctx.accounts.market.settle_hook_count = hooks.programs.len() as u8;
+
+        msg!("Settle hook subscribed: market={} program={}", hooks.market, program);
+        Ok(())
+    }
+
+    /// Drop `program` from a market's settle hooks. Signed by the program's
+    /// ["settle_hook"] PDA, or by the admin so a failing hook cannot block
+    /// settlement.
+    pub fn unsubscribe_settle_hook(
+        ctx: Context<UnsubscribeSettleHook>,
+        program: Pubkey,
+    ) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        let (hook_signer, _) = Pubkey::find_program_address(&[b"settle_hook"], &program);
+        require!(
+            authority == hook_signer || authority == ctx.accounts.config.admin,
+            OpinionError::Unauthorized
+        );
+
+        let hooks = &mut ctx.accounts.settle_hooks;
+        let position = hooks
+            .programs
+            .iter()
+            .position(|p| *p == program)
+            .ok_or(OpinionError::SettleHookNotSubscribed)?;
+        hooks.programs.remove(position);
+        ctx.accounts.market.settle_hook_count = hooks.programs.len() as u8;
+
+        msg!("Settle hook unsubscribed: market={} program={}", hooks.market, program);
+        Ok(())
+    }
+
+    /// Admin sets how many distinct committee approvals settlement requires (0 disables).
+    pub fn set_committee_threshold(ctx: Context<UpdateConfig>, threshold: u8) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(threshold <= config.committee_size, OpinionError::InvalidThreshold);
+        config.committee_threshold = threshold;
+        msg!("Committee threshold set: {} of {}", threshold, config.committee_size);
+        Ok(())
+    }
+
+    /// Committee member approves a settlement action for a market, identified by
+    /// the sha256 of its payload (e.g. `finalize_settlement_message(..)`).
+    /// Approving a different payload, or approving after a member was
+    /// removed, resets the proposal's approvals.
+    pub fn approve_settlement(ctx: Context<ApproveSettlement>, payload_hash: [u8; 32]) -> Result<()> {
+        let market_key = ctx.accounts.market.key();
+        let member_key = ctx.accounts.member.key();
+        let committee_epoch = ctx.accounts.config.committee_epoch;
+
+        let proposal = &mut ctx.accounts.settlement_proposal;
+        if proposal.payload_hash != payload_hash || proposal.committee_epoch != committee_epoch {
+            proposal.approvals.clear();
+        }
+        require!(!proposal.approvals.contains(&member_key), OpinionError::AlreadyApproved);
+        require!(proposal.approvals.len() < MAX_COMMITTEE_SIZE, OpinionError::CommitteeFull);
+
+        proposal.market = market_key;
+        proposal.payload_hash = payload_hash;
+        proposal.approvals.push(member_key);
+        proposal.committee_epoch = committee_epoch;
+        proposal.bump = ctx.bumps.settlement_proposal;
+        proposal.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        emit_cpi!(SettlementApprovedEvent {
+            market: market_key,
+            member: member_key,
+            payload_hash,
+            approvals: proposal.approvals.len() as u8,
+            threshold: ctx.accounts.config.committee_threshold,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Oracle liveness ping. Frontends read OracleStatus to tell "scoring in
+    /// progress" from "oracle is dead"; enter_refunding uses it too.
+    pub fn oracle_heartbeat(ctx: Context<OracleHeartbeat>, pending_markets: u32) -> Result<()> {
+        let clock = Clock::get()?;
+        let oracle_key = ctx.accounts.oracle_authority.key();
+
+        let status = &mut ctx.accounts.oracle_status;
+        status.oracle = oracle_key;
+        status.last_seen_slot = clock.slot;
+        status.last_seen_at = clock.unix_timestamp;
+        status.pending_markets = pending_markets;
+        status.bump = ctx.bumps.oracle_status;
+        status.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        emit_cpi!(OracleHeartbeatEvent {
+            oracle: oracle_key,
+            slot: clock.slot,
+            timestamp: clock.unix_timestamp,
+            pending_markets,
+            seq: ctx.accounts.global_stats.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Oracle locks USDC into its bond vault (created on first deposit).
+    pub fn deposit_oracle_bond(ctx: Context<DepositOracleBond>, amount: u64) -> Result<()> {
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.oracle_usdc.to_account_info(),
+                to: ctx.accounts.oracle_bond_vault.to_account_info(),
+                authority: ctx.accounts.oracle_authority.to_account_info(),
+            },
+        );
+        transfer_with_memo(
+            cpi_ctx,
+            amount,
+            &ctx.accounts.memo_program,
+            "oracle_bond_deposit",
+            None,
+        )?;
+
+        let oracle_bond = &mut ctx.accounts.oracle_bond;
+        oracle_bond.oracle = ctx.accounts.oracle_authority.key();
+        oracle_bond.amount = oracle_bond.amount.checked_add(amount).ok_or(OpinionError::Overflow)?;
+        oracle_bond.bump = ctx.bumps.oracle_bond;
+        oracle_bond.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        msg!("Oracle bond deposited: {} (total {})", amount, oracle_bond.amount);
+        Ok(())
+    }
+
+    /// Oracle withdraws bond in excess of config.oracle_bond_minimum.
+    pub fn withdraw_oracle_bond(ctx: Context<WithdrawOracleBond>, amount: u64) -> Result<()> {
+        let remaining = ctx
+            .accounts
+            .oracle_bond
+            .amount
+            .checked_sub(amount)
+            .ok_or(OpinionError::OracleBondBelowMinimum)?;
+        require!(
+            remaining >= ctx.accounts.config.oracle_bond_minimum,
+            OpinionError::OracleBondBelowMinimum
+        );
+
+        let bond_oracle = ctx.accounts.oracle_bond.oracle;
+        let bond_bump = ctx.accounts.oracle_bond.bump;
+        let seeds: &[&[u8]] = &[b"oracle_bond", bond_oracle.as_ref(), &[bond_bump]];
+        let signer_seeds = &[seeds];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.oracle_bond_vault.to_account_info(),
+                to: ctx.accounts.oracle_usdc.to_account_info(),
+                authority: ctx.accounts.oracle_bond.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_with_memo(
+            cpi_ctx,
+            amount,
+            &ctx.accounts.memo_program,
+            "oracle_bond_withdrawal",
+            None,
+        )?;
+
+        ctx.accounts.oracle_bond.amount = remaining;
+        msg!("Oracle bond withdrawn: {} (remaining {})", amount, remaining);
+        Ok(())
+    }
+
+    /// The bonded oracle moves a bond held under the legacy global
+    /// ["oracle_bond"] PDA to its own ["oracle_bond", oracle] PDA, then closes
+    /// the legacy bond and vault.
+    pub fn migrate_oracle_bond(ctx: Context<MigrateOracleBond>) -> Result<()> {
+        let amount = ctx.accounts.legacy_bond_vault.amount;
+        let legacy_bump = ctx.accounts.legacy_bond.bump;
+        let seeds: &[&[u8]] = &[b"oracle_bond", &[legacy_bump]];
+        let signer_seeds = &[seeds];
+        let move_cpi = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.legacy_bond_vault.to_account_info(),
+                to: ctx.accounts.oracle_bond_vault.to_account_info(),
+                authority: ctx.accounts.legacy_bond.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_with_memo(
+            move_cpi,
+            amount,
+            &ctx.accounts.memo_program,
+            "oracle_bond_migration",
+            None,
+        )?;
+        let close_cpi = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.legacy_bond_vault.to_account_info(),
+                destination: ctx.accounts.oracle_authority.to_account_info(),
+                authority: ctx.accounts.legacy_bond.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::close_account(close_cpi)?;
+
+        let oracle_bond = &mut ctx.accounts.oracle_bond;
+        oracle_bond.oracle = ctx.accounts.oracle_authority.key();
+        oracle_bond.amount = oracle_bond
+            .amount
+            .checked_add(ctx.accounts.legacy_bond.amount)
+            .ok_or(OpinionError::Overflow)?;
+        oracle_bond.bump = ctx.bumps.oracle_bond;
+        oracle_bond.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        msg!("Oracle bond migrated: {} (total {})", amount, oracle_bond.amount);
+        Ok(())
+    }
+
+    /// Admin sets the required oracle bond and the amount slashed per fault.
+    /// Requires an existing bond so slashing paths always have a vault to draw on.
+    pub fn set_oracle_bond_params(
+        ctx: Context<SetOracleBondParams>,
+        minimum: u64,
+        slash_amount: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.oracle_bond_minimum = minimum;
+        config.oracle_slash_amount = slash_amount;
+        msg!("Oracle bond params set: minimum={} slash={}", minimum, slash_amount);
+        Ok(())
+    }
+
+    /// Create a new opinion market. Costs $5 USDC paid to treasury.
+    ///
+    /// CPI: partner programs call `opinion_market::cpi::create_market` (build
+    /// with the `cpi` feature); `pda::market` derives the accounts from `uuid`.
+    /// Passing the instructions sysvar records the calling program as
+    /// `created_via`; a registered partner (`pda::partner`) is owed its share
+    /// of the creation fee, paid by release_creation_fee if the fee is not
+    /// refunded.
+    /// Returns the created Market PDA.
+    pub fn create_market(
+        ctx: Context<CreateMarket>,
+        statement: String,
+        duration_secs: u64,
+        uuid: [u8; 16],
+    ) -> Result<Pubkey> {
+        require!(!statement.is_empty(), OpinionError::StatementEmpty);
+        require!(statement.len() <= MAX_STATEMENT_CHUNK_LEN, OpinionError::StatementTooLong);
+        open_market(ctx, statement, duration_secs, uuid, [0u8; 32])
+    }
+
+    /// Creator extends a full-text statement by `chunk`, up to
+    /// MAX_STATEMENT_LEN, for statements longer than create_market's
+    /// transaction fits. Only allowed before the first stake; the creator
+    /// pays for the statement account's growth.
+    pub fn append_statement(ctx: Context<AppendStatement>, chunk: String) -> Result<()> {
+        require!(!chunk.is_empty(), OpinionError::StatementEmpty);
+        require!(chunk.len() <= MAX_STATEMENT_CHUNK_LEN, OpinionError::StatementTooLong);
+        let statement = &mut ctx.accounts.market_statement.statement;
+        require!(
+            statement.len() + chunk.len() <= MAX_STATEMENT_LEN,
+            OpinionError::StatementTooLong
+        );
+        statement.push_str(&chunk);
+        let statement_len = statement.len() as u32;
+
+        let market_key = ctx.accounts.market.key();
+        emit_cpi!(StatementAppendedEvent {
+            market: market_key,
+            chunk,
+            statement_len,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+        Ok(())
+    }
+
+    /// Create a market that keeps only `statement_hash` (SHA-256 of the full
+    /// text) and the IPFS CID of the text on-chain, for statements longer than
+    /// MAX_STATEMENT_LEN or ones the creator prefers to keep off-chain.
+    /// Returns the created Market PDA.
+    pub fn create_market_hashed(
+        ctx: Context<CreateMarket>,
+        statement_cid: String,
+        duration_secs: u64,
+        uuid: [u8; 16],
+        statement_hash: [u8; 32],
+    ) -> Result<Pubkey> {
+        require!(!statement_cid.is_empty(), OpinionError::StatementEmpty);
+        require!(statement_cid.len() <= MAX_IPFS_CID_LEN, OpinionError::CidTooLong);
+        require!(statement_hash != [0u8; 32], OpinionError::StatementEmpty);
+        open_market(ctx, statement_cid, duration_secs, uuid, statement_hash)
+    }
+
+    /// Anyone checks `statement` against a market's statement: the stored
+    /// text, or for a hashed market its SHA-256. Fails with StatementMismatch
+    /// otherwise, so other programs can CPI it as an assertion.
+    pub fn verify_statement(ctx: Context<VerifyStatement>, statement: String) -> Result<()> {
+        require!(
+            ctx.accounts.market.statement_matches(&ctx.accounts.market_statement, &statement),
+            OpinionError::StatementMismatch
+        );
+        Ok(())
+    }
+
+    /// Read-only: returns the market's state and pool totals as return data,
+    /// so a program composing on a market can read them without deserializing
+    /// the Market account.
+    pub fn get_pool(ctx: Context<GetPool>) -> Result<PoolSnapshot> {
+        Ok(ctx.accounts.market.pool_snapshot())
+    }
+
+    /// Creator files the market under a listing category (app-defined; 0 =
+    /// uncategorized). Only allowed before the first stake; sync_registry_entry
+    /// carries it into the registry.
+    pub fn set_market_category(ctx: Context<ConfigureMarket>, category: u8) -> Result<()> {
+        ctx.accounts.market.category = category;
+        msg!("Market category set: {}", category);
+        Ok(())
+    }
+
+    /// Anyone refreshes a market's registry entry from the market, e.g. after
+    /// a category change or a transition whose caller skipped the page.
+    pub fn sync_registry_entry(ctx: Context<SyncRegistryEntry>) -> Result<()> {
+        sync_registry(
+            ctx.accounts.market.key(),
+            &ctx.accounts.market,
+            Some(&mut ctx.accounts.registry_page),
+        )
+    }
+
+    /// Keeper snapshots an Active market's stake, staker count and aggregate
+    /// backing/slashing into the next MarketCheckpoint, paying its rent.
+    /// Checkpoints are at least CHECKPOINT_INTERVAL apart.
+    pub fn record_checkpoint(ctx: Context<RecordCheckpoint>) -> Result<()> {
+        let clock = Clock::get()?;
+        let market = &mut ctx.accounts.market;
+        require!(market.state == MarketState::Active, OpinionError::MarketNotActive);
+        require!(
+            market.checkpoint_count == 0
+                || clock.unix_timestamp >= market.last_checkpoint_at + CHECKPOINT_INTERVAL,
+            OpinionError::CheckpointTooSoon
+        );
+
+        let checkpoint = &mut ctx.accounts.checkpoint;
+        checkpoint.market = market.key();
+        checkpoint.index = market.checkpoint_count;
+        checkpoint.timestamp = clock.unix_timestamp;
+        checkpoint.slot = clock.slot;
+        checkpoint.total_stake = market.total_stake;
+        checkpoint.staker_count = market.staker_count;
+        checkpoint.backing_total = market.backing_total;
+        checkpoint.slashing_total = market.slashing_total;
+        checkpoint.bump = ctx.bumps.checkpoint;
+        checkpoint.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        market.checkpoint_count += 1;
+        market.last_checkpoint_at = clock.unix_timestamp;
+
+        emit_cpi!(MarketCheckpointEvent {
+            market: checkpoint.market,
+            index: checkpoint.index,
+            timestamp: checkpoint.timestamp,
+            total_stake: checkpoint.total_stake,
+            staker_count: checkpoint.staker_count,
+            backing_total: checkpoint.backing_total,
+            slashing_total: checkpoint.slashing_total,
+            seq: market.next_event_seq(),
+        });
+        Ok(())
+    }
+
+    /// Creator opens a reaction-only phase of `window_secs` after opinion
+    /// submission closes, so late opinions get the same backing time.
+    /// Only allowed before the first stake.
+    pub fn set_reaction_window(ctx: Context<ConfigureMarket>, window_secs: u64) -> Result<()> {
+        require!(window_secs <= MAX_REACTION_WINDOW, OpinionError::ReactionWindowTooLong);
+
+        let market = &mut ctx.accounts.market;
+        market.reactions_close_at = market.closes_at + window_secs as i64;
+
+        msg!("Reaction window set: market={} reactions_close_at={}", market.key(), market.reactions_close_at);
+        Ok(())
+    }
+
+    /// Creator caps how much backing/slashing any one opinion can attract,
+    /// bounding how far a single viral opinion can dominate Layer 1.
+    /// Only allowed before the first stake.
+    pub fn set_reaction_caps(
+        ctx: Context<ConfigureMarket>,
+        backing_cap: ReactionCap,
+        slashing_cap: ReactionCap,
+    ) -> Result<()> {
+        require!(backing_cap.is_valid(), OpinionError::InvalidReactionCap);
+        require!(slashing_cap.is_valid(), OpinionError::InvalidReactionCap);
+
+        let market = &mut ctx.accounts.market;
+        require!(
+            !market.is_confidential()
+                || !(backing_cap.is_stake_multiple() || slashing_cap.is_stake_multiple()),
+            OpinionError::ConfidentialCapUnsupported
+        );
+        market.backing_cap = backing_cap;
+        market.slashing_cap = slashing_cap;
+
+        msg!("Reaction caps set: market={}", market.key());
+        Ok(())
+    }
+
+    /// Creator picks the market's Triple-Check W/C/A weights (e.g. 20/20/60 for
+    /// an expert-analysis market) within the admin bounds. Only allowed before
+    /// the first stake.
+    pub fn set_scoring_weights(ctx: Context<ConfigureMarket>, weights: ScoringWeights) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(
+            weights.is_valid(config.min_layer_weight, config.max_layer_weight),
+            OpinionError::InvalidScoringWeights
+        );
+
+        let market = &mut ctx.accounts.market;
+        market.scoring_weights = weights;
+
+        msg!(
+            "Scoring weights set: market={} W/C/A={}/{}/{}",
+            market.key(),
+            weights.weight,
+            weights.consensus,
+            weights.ai
+        );
+        Ok(())
+    }
+
+    /// Creator picks how the opinion pool is divided: proportional (default),
+    /// top-N equal split, or exponential in combined_score. Only allowed before
+    /// the first stake.
+    pub fn set_payout_mode(ctx: Context<ConfigureMarket>, payout_mode: PayoutMode) -> Result<()> {
+        require!(payout_mode != PayoutMode::TopN(0), OpinionError::InvalidPayoutMode);
+
+        let market = &mut ctx.accounts.market;
+        market.payout_mode = payout_mode;
+
+        msg!("Payout mode set: market={}", market.key());
+        Ok(())
+    }
+
+    /// Creator caps any one opinion's payout (absolute, or a multiple of its
+    /// stake). After settlement, tally_payout_cap must count every opinion
+    /// before claims open; the excess is shared by uncapped opinions. Only
+    /// allowed before the first stake.
+    pub fn set_payout_cap(ctx: Context<ConfigureMarket>, payout_cap: ReactionCap) -> Result<()> {
+        require!(payout_cap.is_valid(), OpinionError::InvalidReactionCap);
+
+        let market = &mut ctx.accounts.market;
+        market.payout_cap = payout_cap;
+
+        msg!("Payout cap set: market={}", market.key());
+        Ok(())
+    }
+
+    /// Anyone counts settled opinions into a capped market's excess pool:
+    /// payout above the cap adds to cap_excess_total, payouts under it to
+    /// uncapped_payout_total. Claims open once every opinion is counted.
+    /// remaining_accounts: writable opinions of this market.
+    pub fn tally_payout_cap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TallyPayoutCap<'info>>,
+    ) -> Result<()> {
+        require!(!ctx.remaining_accounts.is_empty(), OpinionError::EmptyBatch);
+        require!(ctx.remaining_accounts.len() <= MAX_ORACLE_BATCH, OpinionError::BatchTooLarge);
+
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Settled, OpinionError::MarketNotAwaitingSettlement);
+        require!(market.settlement_root == [0u8; 32], OpinionError::MerkleSettlementActive);
+        require!(market.payout_cap != ReactionCap::None, OpinionError::NoPayoutCap);
+        let market_key = market.key();
+
+        for opinion_info in ctx.remaining_accounts.iter() {
+            let opinion_loader = AccountLoader::<Opinion>::try_from(opinion_info)?;
+            let mut opinion = opinion_loader.load_mut()?;
+            require!(opinion.market == market_key, OpinionError::OpinionMarketMismatch);
+            require!(!opinion.cap_tallied(), OpinionError::AlreadyTallied);
+
+            let market = &mut ctx.accounts.market;
+            let raw = market.raw_payout(&opinion)?;
+            let limit = market.payout_cap.limit(opinion.stake_amount()).unwrap_or(u64::MAX);
+            if raw > limit {
+                market.cap_excess_total = market.cap_excess_total.saturating_add(raw - limit);
+            } else {
+                market.uncapped_payout_total = market.uncapped_payout_total.saturating_add(raw);
+            }
+            market.cap_tally_count = market.cap_tally_count.saturating_add(1);
+
+            opinion.set_cap_tallied(true);
+        }
+
+        msg!(
+            "Payout cap tallied: market={} {}/{}",
+            market_key,
+            ctx.accounts.market.cap_tally_count,
+            ctx.accounts.market.staker_count
+        );
+        Ok(())
+    }
+
+    /// Creator guarantees every opinion settling at or above min_score gets
+    /// floor_bps of its principal back before the remainder is split by
+    /// score. Set floor_bps to 0 to turn it off. Only allowed before the
+    /// first stake.
+    pub fn set_refund_floor(ctx: Context<ConfigureMarket>, floor_bps: u16, min_score: u8) -> Result<()> {
+        require!(floor_bps <= MAX_REFUND_FLOOR_BPS, OpinionError::InvalidRefundFloor);
+        require!(min_score <= 100, OpinionError::InvalidRefundFloor);
+
+        let market = &mut ctx.accounts.market;
+        market.refund_floor_bps = floor_bps;
+        market.refund_floor_min_score = min_score;
+
+        msg!(
+            "Refund floor set: market={} floor_bps={} min_score={}",
+            market.key(),
+            floor_bps,
+            min_score
+        );
+        Ok(())
+    }
+
+    /// Creator makes the market no-loss: every opinion gets its full stake
+    /// back and reactors reclaim theirs at par after settlement, so only
+    /// the sponsored bonus pool is distributed by score. Only allowed before
+    /// the first stake.
+    pub fn set_no_loss(ctx: Context<ConfigureMarket>, enabled: bool) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        market.no_loss = enabled;
+        msg!("No-loss mode set: market={} enabled={}", market.key(), enabled);
+        Ok(())
+    }
+
+    /// Creator makes the market objective: it resolves from Pyth feed
+    /// `feed_id` instead of the oracle. The statement is true iff the price
+    /// first published after close is at least `threshold` × 10^`exponent`.
+    /// Opinions above 50 bet on true, below 50 on false. Only allowed before
+    /// the first stake.
+    pub fn set_pyth_resolution(
+        ctx: Context<ConfigureMarket>,
+        feed_id: [u8; 32],
+        threshold: i64,
+        exponent: i32,
+    ) -> Result<()> {
+        require!(feed_id != [0u8; 32], OpinionError::InvalidPriceUpdate);
+        let market = &mut ctx.accounts.market;
+        market.lock_settlement_mode(SettlementMode::Objective)?;
+        market.pyth_feed_id = feed_id;
+        market.pyth_threshold = threshold;
+        market.pyth_exponent = exponent;
+        msg!(
+            "Pyth resolution set: market={} threshold={}e{}",
+            market.key(),
+            threshold,
+            exponent
+        );
+        Ok(())
+    }
+
+    /// Anyone adds USDC to a market's bonus pool before it is scored. The
+    /// bonus is distributed with the stakes at settlement; it is the whole
+    /// prize of a no-loss market.
+    pub fn sponsor_market(ctx: Context<SponsorMarket>, amount: u64) -> Result<()> {
+        require!(amount > 0, OpinionError::StakeTooSmall);
+        require!(
+            matches!(ctx.accounts.market.state, MarketState::Active | MarketState::Closed),
+            OpinionError::SponsorshipClosed
+        );
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.sponsor_usdc.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.sponsor.to_account_info(),
+            },
+        );
+        transfer_with_memo(
+            cpi_ctx,
+            amount,
+            &ctx.accounts.memo_program,
+            "sponsorship",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        let market_key = ctx.accounts.market.key();
+        let market = &mut ctx.accounts.market;
+        market.bonus_pool = market.bonus_pool.checked_add(amount).ok_or(OpinionError::Overflow)?;
+
+        emit_cpi!(MarketSponsoredEvent {
+            market: market_key,
+            sponsor: ctx.accounts.sponsor.key(),
+            amount,
+            bonus_pool: market.bonus_pool,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+        Ok(())
+    }
+
+    /// Creator turns the market into a charity market: at finalization the
+    /// protocol fee, plus `pool_bps` of the distributable pool, goes to the
+    /// registered charity token account passed as charity_usdc instead of the
+    /// treasury. Omit charity_usdc (with pool_bps = 0) to turn it off.
+    /// Charity markets settle through finalize_settlement only. Only allowed
+    /// before the first stake.
+    pub fn set_charity(ctx: Context<SetCharity>, pool_bps: u16) -> Result<()> {
+        let charity_usdc = ctx.accounts.charity_usdc.as_ref().map_or(Pubkey::default(), |a| a.key());
+        require!(
+            pool_bps <= MAX_CHARITY_POOL_BPS && (charity_usdc != Pubkey::default() || pool_bps == 0),
+            OpinionError::InvalidCharityConfig
+        );
+        require!(
+            ctx.accounts.charity_usdc.is_some() == ctx.accounts.charity.is_some(),
+            OpinionError::CharityNotRegistered
+        );
+
+        let market = &mut ctx.accounts.market;
+        market.charity_usdc = charity_usdc;
+        market.charity_pool_bps = pool_bps;
+
+        msg!(
+            "Charity set: market={} charity={} pool_bps={}",
+            market.key(),
+            charity_usdc,
+            pool_bps
+        );
+        Ok(())
+    }
+
+    /// Creator makes payouts of at least `threshold` vest linearly over
+    /// `days` from settlement; claims release the vested portion
+    /// incrementally. Set threshold to 0 to turn it off. Only allowed before
+    /// the first stake.
+    pub fn set_payout_vesting(ctx: Context<ConfigureMarket>, threshold: u64, days: u16) -> Result<()> {
+        require!(
+            threshold == 0 || (1..=MAX_VESTING_DAYS).contains(&days),
+            OpinionError::InvalidVestingSchedule
+        );
+
+        let market = &mut ctx.accounts.market;
+        market.vesting_threshold = threshold;
+        market.vesting_days = if threshold == 0 { 0 } else { days };
+
+        msg!(
+            "Payout vesting set: market={} threshold={} days={}",
+            market.key(),
+            threshold,
+            days
+        );
+        Ok(())
+    }
+
+    /// Anyone opens a market's next audit page; create_market opens page 0,
+    /// and for older markets the first call opens the audit trail. Every
+    /// oracle scoring action appends to the current page, so a new page may
+    /// follow once the current one has no room for a full oracle batch.
+    pub fn open_audit_page(ctx: Context<OpenAuditPage>) -> Result<()> {
+        let market_key = ctx.accounts.market.key();
+        let page = ctx.accounts.market.audit_pages;
+        if page > 0 {
+            let previous = ctx.accounts.previous_log.as_ref().ok_or(OpinionError::AuditPageNotFull)?;
+            require!(
+                previous.entries.len() + MAX_ORACLE_BATCH > AUDIT_PAGE_SIZE,
+                OpinionError::AuditPageNotFull
+            );
+        }
+
+        let log = &mut ctx.accounts.audit_log;
+        log.market = market_key;
+        log.page = page;
+        log.entries = Vec::new();
+        log.bump = ctx.bumps.audit_log;
+        log.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        let market = &mut ctx.accounts.market;
+        market.audit_pages = page.saturating_add(1);
+
+        msg!("Audit page opened: market={} page={}", market_key, page);
+        Ok(())
+    }
+
+    /// Anyone opens a settled market's next results page once the sweep
+    /// window has passed; a new page may only follow a full one.
+    pub fn open_results_page(ctx: Context<OpenResultsPage>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Settled, OpinionError::MarketNotAwaitingSettlement);
+        require!(Clock::get()?.unix_timestamp >= market.sweep_after(), OpinionError::ClaimWindowOpen);
+
+        let market_key = market.key();
+        let page = market.results_pages;
+        if page > 0 {
+            let previous = ctx.accounts.previous_archive.as_ref().ok_or(OpinionError::ResultsPageNotFull)?;
+            require!(previous.entries.len() == RESULTS_PAGE_SIZE, OpinionError::ResultsPageNotFull);
+        }
+
+        let archive = &mut ctx.accounts.results_archive;
+        archive.market = market_key;
+        archive.page = page;
+        archive.entries = Vec::new();
+        archive.bump = ctx.bumps.results_archive;
+        archive.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        let market = &mut ctx.accounts.market;
+        market.results_pages = page.saturating_add(1);
+
+        msg!("Results page opened: market={} page={}", market_key, page);
+        Ok(())
+    }
+
+    /// Crank copies each opinion's final scores and payout into the current
+    /// results page and closes the Opinion, returning its rent to the
+    /// staker. Remaining accounts are (opinion, staker) pairs. Only after
+    /// the sweep window, when nothing can touch the opinion again.
+    pub fn archive_opinions<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ArchiveOpinions<'info>>,
+    ) -> Result<()> {
+        require!(!ctx.remaining_accounts.is_empty(), OpinionError::EmptyBatch);
+        require!(ctx.remaining_accounts.len().is_multiple_of(2), OpinionError::BatchAccountsMismatch);
+        require!(
+            ctx.remaining_accounts.len() / 2 <= MAX_ARCHIVE_BATCH,
+            OpinionError::BatchTooLarge
+        );
+
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Settled, OpinionError::MarketNotAwaitingSettlement);
+        require!(Clock::get()?.unix_timestamp >= market.sweep_after(), OpinionError::ClaimWindowOpen);
+        let market_key = market.key();
+
+        let archive = &mut ctx.accounts.results_archive;
+        for pair in ctx.remaining_accounts.chunks(2) {
+            let opinion_loader = AccountLoader::<Opinion>::try_from(&pair[0])?;
+            {
+                let opinion = opinion_loader.load()?;
+                require!(opinion.market == market_key, OpinionError::OpinionMarketMismatch);
+                require_keys_eq!(opinion.staker, pair[1].key(), OpinionError::Unauthorized);
+                require!(archive.entries.len() < RESULTS_PAGE_SIZE, OpinionError::ResultsPageFull);
+                archive.entries.push(ArchivedResult {
+                    staker: opinion.staker,
+                    stake_amount: opinion.stake_amount(),
+                    scores: [
+                        opinion.weight_score(),
+                        opinion.consensus_score(),
+                        opinion.ai_score(),
+                        opinion.combined_score(),
+                    ],
+                    payout_amount: opinion.payout_amount,
+                    claimed_so_far: opinion.claimed_so_far,
+                });
+            }
+            opinion_loader.close(pair[1].clone())?;
+        }
+
+        let archived = (ctx.remaining_accounts.len() / 2) as u32;
+        let market = &mut ctx.accounts.market;
+        market.archived_opinions = market.archived_opinions.saturating_add(archived);
+
+        msg!(
+            "Opinions archived: market={} page={} count={} total={}",
+            market_key,
+            ctx.accounts.results_archive.page,
+            archived,
+            ctx.accounts.market.archived_opinions
+        );
+        Ok(())
+    }
+
+    /// Crank closes a settled market's Reaction accounts after the sweep
+    /// window, returning rent to each reactor. Remaining accounts are
+    /// (reaction, reactor, opinion staker) triples; the staker ties the
+    /// reaction's opinion to this market even once the opinion is archived.
+    pub fn close_reactions<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseReactions<'info>>,
+    ) -> Result<()> {
+        require!(!ctx.remaining_accounts.is_empty(), OpinionError::EmptyBatch);
+        require!(ctx.remaining_accounts.len().is_multiple_of(3), OpinionError::BatchAccountsMismatch);
+        require!(
+            ctx.remaining_accounts.len() / 3 <= MAX_ARCHIVE_BATCH,
+            OpinionError::BatchTooLarge
+        );
+
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Settled, OpinionError::MarketNotAwaitingSettlement);
+        require!(Clock::get()?.unix_timestamp >= market.sweep_after(), OpinionError::ClaimWindowOpen);
+        let market_key = market.key();
+
+        for triple in ctx.remaining_accounts.chunks(3) {
+            let reaction: Account<'info, Reaction> = Account::try_from(&triple[0])?;
+            let (expected_opinion, _) = Pubkey::find_program_address(
+                &[b"opinion", market_key.as_ref(), triple[2].key().as_ref()],
+                &crate::ID,
+            );
+            require_keys_eq!(reaction.opinion, expected_opinion, OpinionError::OpinionMarketMismatch);
+            require_keys_eq!(reaction.reactor, triple[1].key(), OpinionError::Unauthorized);
+            reaction.close(triple[1].clone())?;
+        }
+
+        msg!(
+            "Reactions closed: market={} count={}",
+            market_key,
+            ctx.remaining_accounts.len() / 3
+        );
+        Ok(())
+    }
+
+    /// Creator switches crowd_score between the stake-weighted mean (default)
+    /// and median. Median creates the CrowdHistogram that stake_opinion must
+    /// then pass. Only allowed before the first stake.
+    pub fn set_crowd_score_mode(ctx: Context<SetCrowdScoreMode>, mode: CrowdScoreMode) -> Result<()> {
+        let market_key = ctx.accounts.market.key();
+        let histogram = &mut ctx.accounts.crowd_histogram;
+        histogram.market = market_key;
+        histogram.stake_by_score = [0u64; 101];
+        histogram.bump = ctx.bumps.crowd_histogram;
+        histogram.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        let market = &mut ctx.accounts.market;
+        market.crowd_score_mode = mode;
+        market.crowd_median = 0;
+
+        msg!("Crowd score mode set: market={}", market_key);
+        Ok(())
+    }
+
+    /// Creator opts the market into confidential stakes before the first
+    /// stake. Creates the market's confidential escrow: a Token-2022 account
+    /// of config.confidential_mint owned by the escrow authority and
+    /// configured for confidential transfers under the ElGamal key whose
+    /// validity proof is passed as a context state account. The oracle
+    /// co-signs to vouch that the key is its own, since it must later prove
+    /// the escrow's balance in release_confidential_stakes.
+    pub fn enable_confidential_stakes(
+        ctx: Context<EnableConfidentialStakes>,
+        decryptable_zero_balance: [u8; 36],
+    ) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(
+            !market.backing_cap.is_stake_multiple() && !market.slashing_cap.is_stake_multiple(),
+            OpinionError::ConfidentialCapUnsupported
+        );
+
+        let mint = ctx.accounts.confidential_mint.to_account_info();
+        let mut extensions = {
+            let data = mint.try_borrow_data()?;
+            let mint_state = spl_token_2022::extension::StateWithExtensions::<
+                spl_token_2022::state::Mint,
+            >::unpack(&data)?;
+            let mint_extensions =
+                spl_token_2022::extension::BaseStateWithExtensions::get_extension_types(&mint_state)?;
+            require!(
+                mint_extensions.contains(&ExtensionType::ConfidentialTransferMint),
+                OpinionError::ConfidentialStakesDisabled
+            );
+            ExtensionType::get_required_init_account_extensions(&mint_extensions)
+        };
+        extensions.push(ExtensionType::ConfidentialTransferAccount);
+        let space =
+            ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(&extensions)?;
+
+        let market_key = market.key();
+        let escrow = ctx.accounts.confidential_escrow.to_account_info();
+        let authority = ctx.accounts.escrow_authority.to_account_info();
+        let token_program = ctx.accounts.token_2022_program.to_account_info();
+        let escrow_seeds: &[&[u8]] = &[
+            b"confidential_escrow",
+            market_key.as_ref(),
+            &[ctx.bumps.confidential_escrow],
+        ];
+        let authority_seeds: &[&[u8]] = &[
+            b"escrow_authority",
+            market_key.as_ref(),
+            &[market.escrow_authority_bump],
+        ];
+
+        anchor_lang::system_program::create_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::CreateAccount {
+                    from: ctx.accounts.creator.to_account_info(),
+                    to: escrow.clone(),
+                },
+                &[escrow_seeds],
+            ),
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            &token_2022::ID,
+        )?;
+        token_2022::initialize_account3(CpiContext::new(
+            token_program,
+            token_2022::InitializeAccount3 {
+                account: escrow.clone(),
+                mint: mint.clone(),
+                authority: authority.clone(),
+            },
+        ))?;
+
+        let configure_ix = confidential_transfer::inner_configure_account(
+            &token_2022::ID,
+            &escrow.key(),
+            &mint.key(),
+            &bytemuck::pod_read_unaligned::<DecryptableBalance>(&decryptable_zero_balance),
+            CONFIDENTIAL_PENDING_CREDIT_LIMIT,
+            &authority.key(),
+            &[],
+            ProofLocation::ContextStateAccount(&ctx.accounts.pubkey_validity_proof.key()),
+        )?;
+        invoke_signed(
+            &configure_ix,
+            &[
+                escrow.clone(),
+                mint,
+                ctx.accounts.pubkey_validity_proof.to_account_info(),
+                authority,
+            ],
+            &[authority_seeds],
+        )?;
+
+        ctx.accounts.market.confidential_escrow = escrow.key();
+        msg!("Confidential stakes enabled: market={} escrow={}", market_key, escrow.key());
+        Ok(())
+    }
+
+    /// Stake a USDC-backed opinion on a market ($0.50–$10).
+    /// Accepts two scores:
+    ///   - opinion_score (0–100): how much user agrees with the statement (shapes truth)
+    ///   - market_prediction (0–100): bet on where the crowd will settle (shapes payout)
+    ///
+    /// CPI: partner programs call `opinion_market::cpi::stake_opinion` (build with
+    /// the `cpi` feature). The staker must still sign; the calling program is
+    /// recorded as `source_program`. Returns the created Opinion PDA.
+    pub fn stake_opinion(
+        ctx: Context<StakeOpinion>,
+        stake_amount: u64,
+        text_hash: [u8; 32],
+        ipfs_cid: String,
+        opinion_score: u8,
+        market_prediction: u8,
+    ) -> Result<Pubkey> {
+        let clock = Clock::get()?;
+        check_opinion_stake(
+            &ctx.accounts.market,
+            stake_amount,
+            &ipfs_cid,
+            opinion_score,
+            market_prediction,
+            clock.unix_timestamp,
+        )?;
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.staker_usdc.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.staker.to_account_info(),
+            },
+        );
+        transfer_with_memo(
+            cpi_ctx,
+            stake_amount,
+            &ctx.accounts.memo_program,
+            "stake",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        let market_key = ctx.accounts.market.key();
+        let staker_key = ctx.accounts.staker.key();
+        let opinion_key = ctx.accounts.opinion.key();
+        let source_program = cpi_source_program(&ctx.accounts.instructions)?;
+
+        let event = init_opinion_stake(
+            OpinionStakeAccounts {
+                market: &mut ctx.accounts.market,
+                opinion: &ctx.accounts.opinion,
+                opinion_bump: ctx.bumps.opinion,
+                activity_log: &ctx.accounts.activity_log,
+                opinion_index: &mut ctx.accounts.opinion_index,
+                opinion_index_bump: ctx.bumps.opinion_index,
+                user_profile: &mut ctx.accounts.user_profile,
+                user_profile_bump: ctx.bumps.user_profile,
+            },
+            ctx.accounts.crowd_histogram.as_deref_mut().map(|h| &mut **h),
+            &mut ctx.accounts.global_stats,
+            NewOpinion {
+                staker: staker_key,
+                stake_amount,
+                text_hash,
+                ipfs_cid,
+                opinion_score,
+                market_prediction,
+                source_program,
+                confidential: false,
+            },
+            &clock,
+        )?;
+        emit_cpi!(event);
+        if let Some(mut event) = record_payment_reference(
+            ctx.accounts.reference.as_ref(),
+            ctx.accounts.payment_reference.as_mut(),
+            ctx.bumps.payment_reference,
+            staker_key,
+            market_key,
+            opinion_key,
+            stake_amount,
+        )? {
+            event.seq = ctx.accounts.market.next_event_seq();
+            emit_cpi!(event);
+        }
+
+        Ok(opinion_key)
+    }
+
+    /// Stake with any SPL token: the staker's input token is swapped into
+    /// USDC through the whitelisted swap program and the whole output is
+    /// staked, in one instruction. `swap_data` and the remaining accounts are
+    /// the aggregator route instruction, invoked with the staker's signature
+    /// and delivering into staker_usdc. Fails unless staker_usdc gains at
+    /// least `min_amount_out`; the output must also fall within the stake
+    /// limits, so routes should be quoted exact-out where possible.
+    pub fn stake_opinion_with_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, StakeOpinionWithSwap<'info>>,
+        swap_data: Vec<u8>,
+        min_amount_out: u64,
+        text_hash: [u8; 32],
+        ipfs_cid: String,
+        opinion_score: u8,
+        market_prediction: u8,
+    ) -> Result<Pubkey> {
+        let input_before = ctx.accounts.input_token_account.amount;
+        let usdc_before = ctx.accounts.staker_usdc.amount;
+        invoke_swap(&ctx.accounts.swap_program, ctx.remaining_accounts, swap_data)?;
+
+        ctx.accounts.input_token_account.reload()?;
+        ctx.accounts.staker_usdc.reload()?;
+        let amount_in = input_before.saturating_sub(ctx.accounts.input_token_account.amount);
+        let stake_amount = ctx.accounts.staker_usdc.amount.saturating_sub(usdc_before);
+        require!(stake_amount >= min_amount_out, OpinionError::SwapOutputTooLow);
+
+        let clock = Clock::get()?;
+        check_opinion_stake(
+            &ctx.accounts.market,
+            stake_amount,
+            &ipfs_cid,
+            opinion_score,
+            market_prediction,
+            clock.unix_timestamp,
+        )?;
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.staker_usdc.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.staker.to_account_info(),
+            },
+        );
+        transfer_with_memo(
+            cpi_ctx,
+            stake_amount,
+            &ctx.accounts.memo_program,
+            "stake",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        let market_key = ctx.accounts.market.key();
+        let staker_key = ctx.accounts.staker.key();
+        let opinion_key = ctx.accounts.opinion.key();
+
+        let event = init_opinion_stake(
+            OpinionStakeAccounts {
+                market: &mut ctx.accounts.market,
+                opinion: &ctx.accounts.opinion,
+                opinion_bump: ctx.bumps.opinion,
+                activity_log: &ctx.accounts.activity_log,
+                opinion_index: &mut ctx.accounts.opinion_index,
+                opinion_index_bump: ctx.bumps.opinion_index,
+                user_profile: &mut ctx.accounts.user_profile,
+                user_profile_bump: ctx.bumps.user_profile,
+            },
+            ctx.accounts.crowd_histogram.as_deref_mut().map(|h| &mut **h),
+            &mut ctx.accounts.global_stats,
+            NewOpinion {
+                staker: staker_key,
+                stake_amount,
+                text_hash,
+                ipfs_cid,
+                opinion_score,
+                market_prediction,
+                source_program: None,
+                confidential: false,
+            },
+            &clock,
+        )?;
+        emit_cpi!(event);
+        emit_cpi!(StakeSwappedEvent {
+            market: market_key,
+            opinion: opinion_key,
+            staker: staker_key,
+            input_mint: ctx.accounts.input_token_account.mint,
+            amount_in,
+            stake_amount,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(opinion_key)
+    }
+
+    /// Any relayer submits a stake the staker signed off-chain, so users
+    /// without SOL (or a connected wallet) can stake. The preceding
+    /// instruction must be an Ed25519 verification of the staker's signature
+    /// over `stake_intent_message(&intent)`. The stake and relay fee are
+    /// pulled from the staker's USDC account through a prior approval to the
+    /// ["intent_delegate"] PDA; the relayer pays the account rent. Each
+    /// intent can land at most once since the staker's Opinion PDA is
+    /// created here.
+    pub fn stake_with_intent(ctx: Context<StakeWithIntent>, intent: StakeIntent) -> Result<Pubkey> {
+        let clock = Clock::get()?;
+        require!(clock.unix_timestamp <= intent.expiry, OpinionError::IntentExpired);
+        require_keys_eq!(
+            intent.market,
+            ctx.accounts.market.key(),
+            OpinionError::IntentMarketMismatch
+        );
+        require!(intent.relay_fee <= MAX_RELAY_FEE, OpinionError::RelayFeeTooHigh);
+        check_opinion_stake(
+            &ctx.accounts.market,
+            intent.stake_amount,
+            &intent.ipfs_cid,
+            intent.opinion_score,
+            intent.market_prediction,
+            clock.unix_timestamp,
+        )?;
+        verify_ed25519_signature(
+            &ctx.accounts.instructions,
+            &intent.staker,
+            &stake_intent_message(&intent),
+        )?;
+
+        let seeds: &[&[u8]] = &[b"intent_delegate", &[ctx.bumps.intent_delegate]];
+        let signer_seeds = &[seeds];
+        let stake_cpi = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.staker_usdc.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.intent_delegate.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_with_memo(
+            stake_cpi,
+            intent.stake_amount,
+            &ctx.accounts.memo_program,
+            "stake",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+        if intent.relay_fee > 0 {
+            let fee_cpi = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.staker_usdc.to_account_info(),
+                    to: ctx.accounts.relayer_usdc.to_account_info(),
+                    authority: ctx.accounts.intent_delegate.to_account_info(),
+                },
+                signer_seeds,
+            );
+            transfer_with_memo(
+                fee_cpi,
+                intent.relay_fee,
+                &ctx.accounts.memo_program,
+                "relay_fee",
+                Some(&ctx.accounts.market.uuid),
+            )?;
+        }
+
+        let market_key = ctx.accounts.market.key();
+        let staker_key = intent.staker;
+        let opinion_key = ctx.accounts.opinion.key();
+
+        let event = init_opinion_stake(
+            OpinionStakeAccounts {
+                market: &mut ctx.accounts.market,
+                opinion: &ctx.accounts.opinion,
+                opinion_bump: ctx.bumps.opinion,
+                activity_log: &ctx.accounts.activity_log,
+                opinion_index: &mut ctx.accounts.opinion_index,
+                opinion_index_bump: ctx.bumps.opinion_index,
+                user_profile: &mut ctx.accounts.user_profile,
+                user_profile_bump: ctx.bumps.user_profile,
+            },
+            ctx.accounts.crowd_histogram.as_deref_mut().map(|h| &mut **h),
+            &mut ctx.accounts.global_stats,
+            NewOpinion {
+                staker: staker_key,
+                stake_amount: intent.stake_amount,
+                text_hash: intent.text_hash,
+                ipfs_cid: intent.ipfs_cid,
+                opinion_score: intent.opinion_score,
+                market_prediction: intent.market_prediction,
+                source_program: None,
+                confidential: false,
+            },
+            &clock,
+        )?;
+        emit_cpi!(event);
+        emit_cpi!(StakeIntentRelayedEvent {
+            market: market_key,
+            staker: staker_key,
+            relayer: ctx.accounts.relayer.key(),
+            relay_fee: intent.relay_fee,
+            nonce: intent.nonce,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(opinion_key)
+    }
+
+    /// Point-of-sale check that the payment carrying Solana Pay `reference`
+    /// funded `market` with at least `min_amount`. Fails (so a simulation
+    /// reports the mismatch) unless the recorded payment matches.
+    pub fn validate_payment_reference(
+        ctx: Context<ValidatePaymentReference>,
+        _reference: Pubkey,
+        market: Pubkey,
+        min_amount: u64,
+    ) -> Result<()> {
+        let record = &ctx.accounts.payment_reference;
+        require!(
+            record.market == market && record.amount >= min_amount,
+            OpinionError::PaymentReferenceMismatch
+        );
+        msg!(
+            "Payment reference valid: reference={} market={} amount={}",
+            record.reference,
+            record.market,
+            record.amount
+        );
+        Ok(())
+    }
+
+    /// Stake an opinion on a confidential market without publishing its size.
+    /// The stake moves as a Token-2022 confidential transfer of
+    /// config.confidential_mint into the market's confidential escrow, its
+    /// equality, ciphertext validity and range proofs passed as context state
+    /// accounts. The opinion counts toward staker_count now but carries no
+    /// stake until the staker reveals it with reveal_confidential_stake after
+    /// the market closes, so the client must keep the transfer's openings.
+    pub fn stake_opinion_confidential(
+        ctx: Context<StakeOpinionConfidential>,
+        text_hash: [u8; 32],
+        ipfs_cid: String,
+        opinion_score: u8,
+        market_prediction: u8,
+        transfer: ConfidentialStakeTransfer,
+    ) -> Result<Pubkey> {
+        let clock = Clock::get()?;
+        check_opinion_inputs(
+            &ctx.accounts.market,
+            &ipfs_cid,
+            opinion_score,
+            market_prediction,
+            clock.unix_timestamp,
+        )?;
+
+        let staker_key = ctx.accounts.staker.key();
+        let transfer_ix = confidential_transfer::inner_transfer(
+            &token_2022::ID,
+            &ctx.accounts.staker_confidential.key(),
+            &ctx.accounts.confidential_mint.key(),
+            &ctx.accounts.confidential_escrow.key(),
+            &bytemuck::pod_read_unaligned::<DecryptableBalance>(&transfer.new_source_decryptable_balance),
+            &bytemuck::pod_read_unaligned::<PodElGamalCiphertext>(&transfer.auditor_ciphertext_lo),
+            &bytemuck::pod_read_unaligned::<PodElGamalCiphertext>(&transfer.auditor_ciphertext_hi),
+            &staker_key,
+            &[],
+            ProofLocation::ContextStateAccount(&ctx.accounts.equality_proof.key()),
+            ProofLocation::ContextStateAccount(&ctx.accounts.ciphertext_validity_proof.key()),
+            ProofLocation::ContextStateAccount(&ctx.accounts.range_proof.key()),
+        )?;
+        invoke(
+            &transfer_ix,
+            &[
+                ctx.accounts.staker_confidential.to_account_info(),
+                ctx.accounts.confidential_mint.to_account_info(),
+                ctx.accounts.confidential_escrow.to_account_info(),
+                ctx.accounts.equality_proof.to_account_info(),
+                ctx.accounts.ciphertext_validity_proof.to_account_info(),
+                ctx.accounts.range_proof.to_account_info(),
+                ctx.accounts.staker.to_account_info(),
+            ],
+        )?;
+
+        let market_key = ctx.accounts.market.key();
+        let opinion_key = ctx.accounts.opinion.key();
+
+        let record = &mut ctx.accounts.confidential_stake;
+        record.opinion = opinion_key;
+        record.auditor_ciphertext_lo = transfer.auditor_ciphertext_lo;
+        record.auditor_ciphertext_hi = transfer.auditor_ciphertext_hi;
+        record.amount = 0;
+        record.bump = ctx.bumps.confidential_stake;
+        record.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        let market = &mut ctx.accounts.market;
+        market.staker_count = market.staker_count.saturating_add(1);
+        market.confidential_stakes = market.confidential_stakes.saturating_add(1);
+        let new = NewOpinion {
+            staker: staker_key,
+            stake_amount: 0,
+            text_hash,
+            ipfs_cid,
+            opinion_score,
+            market_prediction,
+            source_program: None,
+            confidential: true,
+        };
+        init_opinion(
+            &mut OpinionStakeAccounts {
+                market: &mut ctx.accounts.market,
+                opinion: &ctx.accounts.opinion,
+                opinion_bump: ctx.bumps.opinion,
+                activity_log: &ctx.accounts.activity_log,
+                opinion_index: &mut ctx.accounts.opinion_index,
+                opinion_index_bump: ctx.bumps.opinion_index,
+                user_profile: &mut ctx.accounts.user_profile,
+                user_profile_bump: ctx.bumps.user_profile,
+            },
+            &new,
+            &clock,
+        )?;
+
+        emit_cpi!(ConfidentialStakeCommittedEvent {
+            market: market_key,
+            opinion: opinion_key,
+            staker: staker_key,
+            opinion_score,
+            market_prediction,
+            ipfs_cid: new.ipfs_cid,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(opinion_key)
+    }
+
+    /// Reveal a confidential stake once the market has closed by opening the
+    /// Pedersen commitments of its recorded auditor ciphertexts (low 16 and
+    /// high 32 bits) to `stake_amount`. Only the staker knows the openings, so
+    /// the amount cannot be misstated; the stake is then weighed into the pool
+    /// and crowd score like a public stake. Reveals are accepted until
+    /// release_confidential_stakes, also while Refunding so the stake can be
+    /// recovered. Stakes outside MIN_STAKE..=MAX_STAKE cannot be revealed and
+    /// are forfeited on release with any other unrevealed stake.
+    pub fn reveal_confidential_stake(
+        ctx: Context<RevealConfidentialStake>,
+        stake_amount: u64,
+        opening_lo: [u8; 32],
+        opening_hi: [u8; 32],
+    ) -> Result<()> {
+        require!(stake_amount >= MIN_STAKE, OpinionError::StakeTooSmall);
+        require!(stake_amount <= MAX_STAKE, OpinionError::StakeTooLarge);
+        let now = Clock::get()?.unix_timestamp;
+        let refunding = {
+            let market = &ctx.accounts.market;
+            require!(market.is_confidential(), OpinionError::NotConfidentialMarket);
+            require!(!market.confidential_released, OpinionError::ConfidentialStakesAlreadyReleased);
+            require!(now >= market.closes_at, OpinionError::MarketNotExpired);
+            market.state == MarketState::Refunding
+        };
+        let record = &mut ctx.accounts.confidential_stake;
+        let amount_lo = stake_amount & ((1 << CONFIDENTIAL_AMOUNT_LO_BITS) - 1);
+        let amount_hi = stake_amount >> CONFIDENTIAL_AMOUNT_LO_BITS;
+        require!(
+            pedersen_opens(&record.auditor_ciphertext_lo, amount_lo, &opening_lo)
+                && pedersen_opens(&record.auditor_ciphertext_hi, amount_hi, &opening_hi),
+            OpinionError::InvalidConfidentialOpening
+        );
+        record.amount = stake_amount;
+
+        let mut opinion = ctx.accounts.opinion.load_mut()?;
+        require!(opinion.confidential_pending(), OpinionError::ConfidentialStakeAlreadyRevealed);
+        opinion.set_stake_amount(stake_amount);
+        opinion.backing_total = opinion.backing_total.saturating_add(stake_amount);
+        opinion.set_confidential_pending(false);
+        let staker_key = opinion.staker;
+        let opinion_score = opinion.opinion_score;
+        let market_prediction = opinion.market_prediction;
+
+        let market = &mut ctx.accounts.market;
+        market.record_stake_weight(
+            ctx.accounts.crowd_histogram.as_deref_mut().map(|h| &mut **h),
+            opinion_score,
+            market_prediction,
+            stake_amount,
+        )?;
+        market.confidential_revealed = market.confidential_revealed.saturating_add(1);
+        market.confidential_revealed_total =
+            market.confidential_revealed_total.saturating_add(stake_amount);
+        let total_stake_after = market.total_stake;
+        // A refunding market already left the active TVL
+        if !refunding {
+            ctx.accounts.global_stats.record_stake(stake_amount);
+        }
+        let profile = &mut ctx.accounts.user_profile;
+        profile.record_stake(stake_amount);
+        profile.last_active = now;
+
+        emit_cpi!(ConfidentialStakeRevealedEvent {
+            market: ctx.accounts.market.key(),
+            opinion: ctx.accounts.opinion.key(),
+            staker: staker_key,
+            stake_amount,
+            total_stake_after,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Oracle empties a closed market's confidential escrow into its USDC
+    /// escrow, which scoring waits for. Applies the pending stakes, withdraws
+    /// `amount` and proves (zero ciphertext proof) that nothing is left, so
+    /// `amount` is exactly what stakers sent. Then unwraps it into
+    /// escrow_token_account through the wrapper program: `unwrap_data` and
+    /// the remaining accounts are its unwrap instruction, signed by the escrow
+    /// authority. Runs once every stake is revealed, when `amount` must equal
+    /// the revealed total, or after CONFIDENTIAL_REVEAL_WINDOW, when the
+    /// unrevealed remainder is forfeited to the bonus pool. A Refunding market
+    /// is released too: the revealed stakes then join the refund base.
+    pub fn release_confidential_stakes<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReleaseConfidentialStakes<'info>>,
+        expected_pending_credits: u64,
+        amount: u64,
+        unwrap_data: Vec<u8>,
+    ) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.is_confidential(), OpinionError::NotConfidentialMarket);
+        require!(!market.confidential_released, OpinionError::ConfidentialStakesAlreadyReleased);
+        require!(
+            matches!(
+                market.state,
+                MarketState::Active | MarketState::Closed | MarketState::Refunding
+            ),
+            OpinionError::MarketNotClosed
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= market.closes_at, OpinionError::MarketNotExpired);
+        let all_revealed = market.confidential_revealed == market.confidential_stakes;
+        if all_revealed {
+            require!(
+                amount == market.confidential_revealed_total,
+                OpinionError::ConfidentialAggregateMismatch
+            );
+        } else {
+            require!(
+                now >= market.closes_at + CONFIDENTIAL_REVEAL_WINDOW,
+                OpinionError::ConfidentialRevealWindowOpen
+            );
+            require!(
+                amount >= market.confidential_revealed_total,
+                OpinionError::ConfidentialAggregateMismatch
+            );
+        }
+
+        let market_key = market.key();
+        let escrow_bump = market.escrow_authority_bump;
+        let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+        let signer_seeds = &[seeds];
+        let confidential_escrow = ctx.accounts.confidential_escrow.to_account_info();
+        let authority = ctx.accounts.escrow_authority.to_account_info();
+        // The escrow is emptied for good, so its decryptable balances (which
+        // Token-2022 only stores) are left zeroed
+        let zero_balance: DecryptableBalance = bytemuck::Zeroable::zeroed();
+
+        let apply_ix = confidential_transfer::inner_apply_pending_balance(
+            &token_2022::ID,
+            &confidential_escrow.key(),
+            expected_pending_credits,
+            &zero_balance,
+            &authority.key(),
+            &[],
+        )?;
+        invoke_signed(&apply_ix, &[confidential_escrow.clone(), authority.clone()], signer_seeds)?;
+
+        let withdraw_ix = confidential_transfer::inner_withdraw(
+            &token_2022::ID,
+            &confidential_escrow.key(),
+            &ctx.accounts.confidential_mint.key(),
+            amount,
+            ctx.accounts.confidential_mint.decimals,
+            &zero_balance,
+            &authority.key(),
+            &[],
+            ProofLocation::ContextStateAccount(&ctx.accounts.equality_proof.key()),
+            ProofLocation::ContextStateAccount(&ctx.accounts.range_proof.key()),
+        )?;
+        invoke_signed(
+            &withdraw_ix,
+            &[
+                confidential_escrow.clone(),
+                ctx.accounts.confidential_mint.to_account_info(),
+                ctx.accounts.equality_proof.to_account_info(),
+                ctx.accounts.range_proof.to_account_info(),
+                authority.clone(),
+            ],
+            signer_seeds,
+        )?;
+
+        let empty_ix = confidential_transfer::inner_empty_account(
+            &token_2022::ID,
+            &confidential_escrow.key(),
+            &authority.key(),
+            &[],
+            ProofLocation::ContextStateAccount(&ctx.accounts.zero_balance_proof.key()),
+        )?;
+        invoke_signed(
+            &empty_ix,
+            &[
+                confidential_escrow,
+                ctx.accounts.zero_balance_proof.to_account_info(),
+                authority.clone(),
+            ],
+            signer_seeds,
+        )?;
+
+        // Unwrap into USDC; the escrow authority signs for the wrapped tokens
+        let escrow_before = ctx.accounts.escrow_token_account.amount;
+        let unwrap_ix = Instruction {
+            program_id: ctx.accounts.wrap_program.key(),
+            accounts: ctx
+                .remaining_accounts
+                .iter()
+                .map(|acc| AccountMeta {
+                    pubkey: acc.key(),
+                    is_signer: acc.is_signer || acc.key() == authority.key(),
+                    is_writable: acc.is_writable,
+                })
+                .collect(),
+            data: unwrap_data,
+        };
+        invoke_signed(&unwrap_ix, ctx.remaining_accounts, signer_seeds)?;
+        ctx.accounts.escrow_token_account.reload()?;
+        require!(
+            ctx.accounts.escrow_token_account.amount.saturating_sub(escrow_before) == amount,
+            OpinionError::ConfidentialAggregateMismatch
+        );
+
+        let market = &mut ctx.accounts.market;
+        let revealed_total = market.confidential_revealed_total;
+        let forfeited = amount - revealed_total;
+        market.bonus_pool = market.bonus_pool.checked_add(forfeited).ok_or(OpinionError::Overflow)?;
+        market.confidential_released = true;
+        // Refunds so far split the escrow without the confidential stakes;
+        // the released ones (and any forfeit) go to the remaining refunds
+        if market.state == MarketState::Refunding {
+            market.refund_base = market.refund_base.saturating_add(revealed_total);
+            market.refund_outstanding = market.refund_outstanding.saturating_add(revealed_total);
+        }
+
+        emit_cpi!(ConfidentialStakesReleasedEvent {
+            market: market_key,
+            released_amount: amount,
+            revealed_total,
+            forfeited,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Back or Slash another user's opinion — Layer 1 of the Triple-Check.
+    /// Reactor's stake goes into the escrow and affects the opinion's weight score.
+    ///
+    /// CPI: partner programs call `opinion_market::cpi::react_to_opinion` (build
+    /// with the `cpi` feature). The reactor must still sign; the calling program
+    /// is recorded as `source_program`. Returns the created Reaction PDA.
+    pub fn react_to_opinion(
+        ctx: Context<ReactToOpinion>,
+        reaction_type: ReactionType,
+        stake_amount: u64,
+    ) -> Result<Pubkey> {
+        require!(stake_amount >= MIN_STAKE, OpinionError::StakeTooSmall);
+        require!(stake_amount <= MAX_STAKE, OpinionError::StakeTooLarge);
+
+        let clock = Clock::get()?;
+        {
+            let market = &ctx.accounts.market;
+            require!(market.state == MarketState::Active, OpinionError::MarketNotActive);
+            require!(clock.unix_timestamp < market.reactions_close_at, OpinionError::MarketExpired);
+        }
+
+        // Cannot react to your own opinion
+        require!(
+            ctx.accounts.reactor.key() != ctx.accounts.opinion.load()?.staker,
+            OpinionError::CannotReactToOwnOpinion
+        );
+
+        // Transfer reaction stake into market escrow
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.reactor_usdc.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.reactor.to_account_info(),
+            },
+        );
+        transfer_with_memo(
+            cpi_ctx,
+            stake_amount,
+            &ctx.accounts.memo_program,
+            "reaction_stake",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        let market_key = ctx.accounts.market.key();
+        let opinion_key = ctx.accounts.opinion.key();
+        let reactor_key = ctx.accounts.reactor.key();
+        let reaction_key = ctx.accounts.reaction.key();
+        let reaction_type_for_event = reaction_type.clone();
+        let source_program = cpi_source_program(&ctx.accounts.instructions)?;
+
+        // Update opinion's backing or slashing total
+        let mut opinion = ctx.accounts.opinion.load_mut()?;
+        match reaction_type {
+            ReactionType::Back => {
+                opinion.backing_total = opinion.backing_total
+                    .checked_add(stake_amount)
+                    .ok_or(OpinionError::Overflow)?;
+            }
+            ReactionType::Slash => {
+                opinion.slashing_total = opinion.slashing_total
+                    .checked_add(stake_amount)
+                    .ok_or(OpinionError::Overflow)?;
+            }
+        }
+        ctx.accounts.market.check_reaction_caps(&opinion)?;
+
+        // Store reaction record (one per reactor per opinion — enforced by PDA seeds)
+        let reaction = &mut ctx.accounts.reaction;
+        reaction.opinion = opinion_key;
+        reaction.reactor = reactor_key;
+        reaction.reaction_type = reaction_type.clone();
+        reaction.stake_amount = stake_amount;
+        reaction.scored = false;
+        reaction.source_program = source_program;
+        reaction.bump = ctx.bumps.reaction;
+        reaction.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        // Add to market total pool
+        let market = &mut ctx.accounts.market;
+        market.total_stake = market.total_stake
+            .checked_add(stake_amount)
+            .ok_or(OpinionError::Overflow)?;
+        market.add_reaction_stake(&reaction_type, stake_amount)?;
+        ctx.accounts.global_stats.record_stake(stake_amount);
+        ctx.accounts
+            .activity_log
+            .load_mut()?
+            .record(reactor_key, (&reaction_type).into(), stake_amount, clock.slot);
+
+        emit_cpi!(ReactionSubmittedEvent {
+            market: market_key,
+            opinion: opinion_key,
+            reactor: reactor_key,
+            reaction_type: reaction_type_for_event,
+            stake_amount,
+            source_program,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+        match reaction_type {
+            ReactionType::Back => emit_cpi!(YourOpinionBackedEvent {
+                staker: opinion.staker,
+                market: market_key,
+                opinion: opinion_key,
+                reactor: reactor_key,
+                stake_amount,
+                backing_total: opinion.backing_total,
+                seq: ctx.accounts.market.next_event_seq(),
+            }),
+            ReactionType::Slash => emit_cpi!(YourOpinionSlashedEvent {
+                staker: opinion.staker,
+                market: market_key,
+                opinion: opinion_key,
+                reactor: reactor_key,
+                stake_amount,
+                slashing_total: opinion.slashing_total,
+                seq: ctx.accounts.market.next_event_seq(),
+            }),
+        }
+
+        Ok(reaction_key)
+    }
+
+    /// Back or Slash several opinions in one transaction.
+    /// remaining_accounts: [opinion_0, reaction_0, opinion_1, reaction_1, ...]
+    /// where reaction_i is the uninitialized PDA ["reaction", opinion_i, reactor].
+    /// All stakes are moved into escrow with a single transfer.
+    pub fn react_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReactBatch<'info>>,
+        reactions: Vec<BatchReaction>,
+    ) -> Result<()> {
+        require!(!reactions.is_empty(), OpinionError::EmptyBatch);
+        require!(reactions.len() <= MAX_BATCH_REACTIONS, OpinionError::BatchTooLarge);
+        require!(
+            ctx.remaining_accounts.len() == reactions.len() * 2,
+            OpinionError::BatchAccountsMismatch
+        );
+
+        let clock = Clock::get()?;
+        {
+            let market = &ctx.accounts.market;
+            require!(market.state == MarketState::Active, OpinionError::MarketNotActive);
+            require!(clock.unix_timestamp < market.reactions_close_at, OpinionError::MarketExpired);
+        }
+
+        let market_key = ctx.accounts.market.key();
+        let reactor_key = ctx.accounts.reactor.key();
+        let reactor_info = ctx.accounts.reactor.to_account_info();
+        let system_program_info = ctx.accounts.system_program.to_account_info();
+        let source_program = cpi_source_program(&ctx.accounts.instructions)?;
+        let mut total_amount: u64 = 0;
+
+        for (i, entry) in reactions.iter().enumerate() {
+            require!(entry.stake_amount >= MIN_STAKE, OpinionError::StakeTooSmall);
+            require!(entry.stake_amount <= MAX_STAKE, OpinionError::StakeTooLarge);
+
+            let opinion_info = &ctx.remaining_accounts[i * 2];
+            let reaction_info = &ctx.remaining_accounts[i * 2 + 1];
+
+            let opinion_loader = AccountLoader::<Opinion>::try_from(opinion_info)?;
+            let mut opinion = opinion_loader.load_mut()?;
+            require!(opinion.market == market_key, OpinionError::OpinionMarketMismatch);
+            require!(reactor_key != opinion.staker, OpinionError::CannotReactToOwnOpinion);
+
+            let opinion_key = opinion_info.key();
+            let (expected_reaction, reaction_bump) = Pubkey::find_program_address(
+                &[b"reaction", opinion_key.as_ref(), reactor_key.as_ref()],
+                &crate::ID,
+            );
+            require_keys_eq!(reaction_info.key(), expected_reaction, OpinionError::InvalidReactionAccount);
+
+            // One reaction per (reactor, opinion) — creation fails if the PDA already exists
+            create_pda_account(
+                &reactor_info,
+                reaction_info,
+                &system_program_info,
+                Reaction::SPACE,
+                &[b"reaction", opinion_key.as_ref(), reactor_key.as_ref(), &[reaction_bump]],
+            )?;
+
+            let reaction = Reaction {
+                opinion: opinion_key,
+                reactor: reactor_key,
+                reaction_type: entry.reaction_type.clone(),
+                stake_amount: entry.stake_amount,
+                scored: false,
+                source_program,
+                bump: reaction_bump,
+                schema_version: ACCOUNT_SCHEMA_VERSION,
+            };
+            reaction.try_serialize(&mut &mut reaction_info.try_borrow_mut_data()?[..])?;
+
+            match entry.reaction_type {
+                ReactionType::Back => {
+                    opinion.backing_total = opinion.backing_total
+                        .checked_add(entry.stake_amount)
+                        .ok_or(OpinionError::Overflow)?;
+                }
+                ReactionType::Slash => {
+                    opinion.slashing_total = opinion.slashing_total
+                        .checked_add(entry.stake_amount)
+                        .ok_or(OpinionError::Overflow)?;
+                }
+            }
+            ctx.accounts.market.check_reaction_caps(&opinion)?;
+            ctx.accounts.market.add_reaction_stake(&entry.reaction_type, entry.stake_amount)?;
+            ctx.accounts.activity_log.load_mut()?.record(
+                reactor_key,
+                (&entry.reaction_type).into(),
+                entry.stake_amount,
+                clock.slot,
+            );
+
+            total_amount = total_amount
+                .checked_add(entry.stake_amount)
+                .ok_or(OpinionError::Overflow)?;
+
+            emit_cpi!(ReactionSubmittedEvent {
+                market: market_key,
+                opinion: opinion_key,
+                reactor: reactor_key,
+                reaction_type: entry.reaction_type.clone(),
+                stake_amount: entry.stake_amount,
+                source_program,
+                seq: ctx.accounts.market.next_event_seq(),
+            });
+            match entry.reaction_type {
+                ReactionType::Back => emit_cpi!(YourOpinionBackedEvent {
+                    staker: opinion.staker,
+                    market: market_key,
+                    opinion: opinion_key,
+                    reactor: reactor_key,
+                    stake_amount: entry.stake_amount,
+                    backing_total: opinion.backing_total,
+                    seq: ctx.accounts.market.next_event_seq(),
+                }),
+                ReactionType::Slash => emit_cpi!(YourOpinionSlashedEvent {
+                    staker: opinion.staker,
+                    market: market_key,
+                    opinion: opinion_key,
+                    reactor: reactor_key,
+                    stake_amount: entry.stake_amount,
+                    slashing_total: opinion.slashing_total,
+                    seq: ctx.accounts.market.next_event_seq(),
+                }),
+            }
+        }
+
+        // Single transfer of the summed stake into market escrow
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.reactor_usdc.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.reactor.to_account_info(),
+            },
+        );
+        transfer_with_memo(
+            cpi_ctx,
+            total_amount,
+            &ctx.accounts.memo_program,
+            "reaction_stake",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        let market = &mut ctx.accounts.market;
+        market.total_stake = market.total_stake
+            .checked_add(total_amount)
+            .ok_or(OpinionError::Overflow)?;
+        ctx.accounts.global_stats.record_stake(total_amount);
+
+        Ok(())
+    }
+
+    /// Free, non-staked emoji reaction. Does not affect scoring; counters are
+    /// for frontend ranking. Each wallet is limited to one per LITE_REACT_COOLDOWN.
+    pub fn lite_react(ctx: Context<LiteReact>, emoji_code: u8) -> Result<()> {
+        require!((emoji_code as usize) < LITE_REACTION_KINDS, OpinionError::InvalidEmojiCode);
+
+        let clock = Clock::get()?;
+        {
+            let market = &ctx.accounts.market;
+            require!(market.state == MarketState::Active, OpinionError::MarketNotActive);
+            require!(clock.unix_timestamp < market.reactions_close_at, OpinionError::MarketExpired);
+        }
+
+        let reactor_key = ctx.accounts.reactor.key();
+        let rate_limit = &mut ctx.accounts.lite_reactor;
+        // Freshly created accounts have last_reacted_at == 0 and pass the check
+        require!(
+            clock.unix_timestamp >= rate_limit.last_reacted_at + LITE_REACT_COOLDOWN,
+            OpinionError::LiteReactCooldown
+        );
+        rate_limit.reactor = reactor_key;
+        rate_limit.last_reacted_at = clock.unix_timestamp;
+        rate_limit.bump = ctx.bumps.lite_reactor;
+        rate_limit.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        let opinion_key = ctx.accounts.opinion.key();
+        let counts = &mut ctx.accounts.lite_reaction_counts;
+        counts.opinion = opinion_key;
+        counts.bump = ctx.bumps.lite_reaction_counts;
+        counts.schema_version = ACCOUNT_SCHEMA_VERSION;
+        let slot = &mut counts.counts[emoji_code as usize];
+        *slot = slot.saturating_add(1);
+        let count_after = *slot;
+
+        emit_cpi!(LiteReactionEvent {
+            market: ctx.accounts.market.key(),
+            opinion: opinion_key,
+            reactor: reactor_key,
+            emoji_code,
+            count_after,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Close a market after its duration expires. Permissionless.
+    /// A market with a single opinion goes straight to Refunding.
+    pub fn close_market(ctx: Context<CloseMarket>) -> Result<()> {
+        let clock = Clock::get()?;
+        let market_key = ctx.accounts.market.key();
+        let market = &mut ctx.accounts.market;
+        let single_staker = market.close_at(clock.unix_timestamp)?;
+        let staker_count = market.staker_count;
+        let total_stake = market.total_stake;
+        if single_staker {
+            ctx.accounts.global_stats.record_market_exit(market);
+        } else if let Some(queue) = ctx.accounts.scoring_queue.as_mut() {
+            queue.enqueue(market_key, &mut ctx.accounts.market)?;
+        }
+        sync_registry(market_key, &ctx.accounts.market, ctx.accounts.registry_page.as_mut())?;
+        ctx.accounts
+            .activity_log
+            .load_mut()?
+            .record(ctx.accounts.caller.key(), ActivityKind::Close, total_stake, clock.slot);
+
+        emit_cpi!(MarketClosedEvent {
+            market: market_key,
+            closed_at: clock.unix_timestamp,
+            total_stakers: staker_count,
+            total_stake,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+        if single_staker {
+            emit_cpi!(refunding_event(&mut ctx.accounts.market, market_key));
+        }
+
+        Ok(())
+    }
+
+    /// Creator registers a Clockwork thread that calls close_market once
+    /// reactions_close_at passes, enqueueing the market for scoring. The
+    /// thread is owned by the program's thread authority PDA and funded with
+    /// LIFECYCLE_THREAD_FUNDING from the creator.
+    pub fn register_lifecycle_thread(ctx: Context<RegisterLifecycleThread>) -> Result<()> {
+        let market_key = ctx.accounts.market.key();
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Active, OpinionError::MarketNotActive);
+        require!(
+            market.lifecycle_thread == Pubkey::default(),
+            OpinionError::LifecycleThreadExists
+        );
+
+        let thread_key = ctx.accounts.thread.key();
+        let scoring_queue = ctx.accounts.scoring_queue.as_ref().map(|queue| queue.key());
+        let close_ix = lifecycle_close_ix(market_key, market, thread_key, scoring_queue);
+        let data = anchor_ix_data(
+            "thread_create",
+            (
+                LIFECYCLE_THREAD_FUNDING,
+                market_key.to_bytes().to_vec(),
+                vec![ThreadInstruction::from(close_ix)],
+                ThreadTrigger::Timestamp { unix_ts: market.reactions_close_at },
+            ),
+        )?;
+        let create_ix = Instruction {
+            program_id: CLOCKWORK_THREAD_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(ctx.accounts.thread_authority.key(), true),
+                AccountMeta::new(ctx.accounts.creator.key(), true),
+                AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+                AccountMeta::new(thread_key, false),
+            ],
+            data,
+        };
+        let seeds: &[&[u8]] = &[b"thread_authority", &[ctx.bumps.thread_authority]];
+        invoke_signed(
+            &create_ix,
+            &[
+                ctx.accounts.thread_authority.to_account_info(),
+                ctx.accounts.creator.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.thread.to_account_info(),
+                ctx.accounts.clockwork_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        ctx.accounts.market.lifecycle_thread = thread_key;
+        msg!("Lifecycle thread registered: market={} thread={}", market_key, thread_key);
+        Ok(())
+    }
+
+    /// Anyone deletes a market's lifecycle thread once the market has left
+    /// Active, returning the thread's remaining lamports to the creator.
+    pub fn close_lifecycle_thread(ctx: Context<CloseLifecycleThread>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.state != MarketState::Active, OpinionError::MarketNotClosed);
+        require_keys_eq!(
+            ctx.accounts.thread.key(),
+            market.lifecycle_thread,
+            OpinionError::LifecycleThreadMismatch
+        );
+
+        let delete_ix = Instruction {
+            program_id: CLOCKWORK_THREAD_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(ctx.accounts.thread_authority.key(), true),
+                AccountMeta::new(ctx.accounts.creator.key(), false),
+                AccountMeta::new(ctx.accounts.thread.key(), false),
+            ],
+            data: anchor_ix_data("thread_delete", ())?,
+        };
+        let seeds: &[&[u8]] = &[b"thread_authority", &[ctx.bumps.thread_authority]];
+        invoke_signed(
+            &delete_ix,
+            &[
+                ctx.accounts.thread_authority.to_account_info(),
+                ctx.accounts.creator.to_account_info(),
+                ctx.accounts.thread.to_account_info(),
+                ctx.accounts.clockwork_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        let market_key = ctx.accounts.market.key();
+        ctx.accounts.market.lifecycle_thread = Pubkey::default();
+        msg!("Lifecycle thread closed: market={}", market_key);
+        Ok(())
+    }
+
+    /// Anyone releases a closed market's escrowed creation fee: back to the
+    /// creator if the market drew at least fee_refund_min_opinions opinions,
+    /// otherwise to the treasury less the creating partner's share, which is
+    /// paid to its payout_usdc (or to the treasury if the partner has since
+    /// been removed). The fee account is closed to the creator.
+    pub fn release_creation_fee(ctx: Context<ReleaseCreationFee>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.state != MarketState::Active, OpinionError::MarketNotClosed);
+        require!(market.creation_fee_escrowed > 0, OpinionError::CreationFeeReleased);
+
+        let amount = ctx.accounts.creation_fee_escrow.amount;
+        let refunded = market.staker_count >= market.fee_refund_min_opinions;
+        let destination = if refunded {
+            ctx.accounts.creator_usdc.to_account_info()
+        } else {
+            ctx.accounts.treasury_usdc.to_account_info()
+        };
+
+        let market_key = market.key();
+        let escrow_bump = market.escrow_authority_bump;
+        let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+        let signer_seeds = &[seeds];
+
+        let mut partner_fee = 0;
+        if !refunded && market.partner_fee > 0 {
+            let partner_info =
+                ctx.accounts.partner.as_ref().ok_or(OpinionError::PartnerAccountsRequired)?;
+            // A removed partner's entry is closed; its share stays with the treasury
+            if partner_info.owner == &crate::ID {
+                let mut partner =
+                    Partner::try_deserialize(&mut &partner_info.try_borrow_data()?[..])?;
+                let partner_usdc = ctx
+                    .accounts
+                    .partner_usdc
+                    .as_ref()
+                    .ok_or(OpinionError::PartnerAccountsRequired)?;
+                require_keys_eq!(partner_usdc.key(), partner.payout_usdc, OpinionError::Unauthorized);
+                partner_fee = market.partner_fee.min(amount);
+                let partner_cpi = CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.creation_fee_escrow.to_account_info(),
+                        to: partner_usdc.to_account_info(),
+                        authority: ctx.accounts.escrow_authority.to_account_info(),
+                    },
+                    signer_seeds,
+                );
+                transfer_with_memo(
+                    partner_cpi,
+                    partner_fee,
+                    &ctx.accounts.memo_program,
+                    "partner_fee",
+                    Some(&market.uuid),
+                )?;
+                partner.fees_earned = partner.fees_earned.saturating_add(partner_fee);
+                partner.try_serialize(&mut &mut partner_info.try_borrow_mut_data()?[..])?;
+            }
+        }
+        let amount = amount - partner_fee;
+
+        let fee_cpi = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.creation_fee_escrow.to_account_info(),
+                to: destination,
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_with_memo(
+            fee_cpi,
+            amount,
+            &ctx.accounts.memo_program,
+            "creation_fee_release",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        let close_cpi = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.creation_fee_escrow.to_account_info(),
+                destination: ctx.accounts.creator.to_account_info(),
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::close_account(close_cpi)?;
+
+        let market_key = ctx.accounts.market.key();
+        let market = &mut ctx.accounts.market;
+        market.creation_fee_escrowed = 0;
+
+        emit_cpi!(CreationFeeReleasedEvent {
+            market: market_key,
+            creator: market.creator,
+            amount,
+            refunded,
+            partner_fee,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+        Ok(())
+    }
+
+    /// Anyone credits a settled market's total_stake to its creator's profile,
+    /// counting toward creation-fee rebate tiers. Once per market.
+    pub fn record_creator_volume(ctx: Context<RecordCreatorVolume>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(market.state == MarketState::Settled, OpinionError::MarketNotAwaitingSettlement);
+        require!(!market.volume_recorded, OpinionError::VolumeAlreadyRecorded);
+        market.volume_recorded = true;
+        let volume = market.total_stake;
+
+        let profile = &mut ctx.accounts.creator_profile;
+        profile.settled_volume = profile.settled_volume.saturating_add(volume);
+        profile.markets_settled = profile.markets_settled.saturating_add(1);
+
+        msg!(
+            "Creator volume recorded: creator={} volume={} rebate_bps={}",
+            profile.creator,
+            profile.settled_volume,
+            profile.rebate_bps()
+        );
+        Ok(())
+    }
+
+    /// Anyone adds a closed market that missed the queue at close_market.
+    pub fn enqueue_market(ctx: Context<EnqueueMarket>) -> Result<()> {
+        let market_key = ctx.accounts.market.key();
+        require!(
+            matches!(ctx.accounts.market.state, MarketState::Closed | MarketState::Scored),
+            OpinionError::MarketNotClosed
+        );
+        ctx.accounts.scoring_queue.enqueue(market_key, &mut ctx.accounts.market)
+    }
+
+    /// Anyone refreshes queue entries from their markets' current progress and
+    /// drops finalized (Settled or Refunding) markets.
+    /// remaining_accounts: queued markets (read-only).
+    pub fn refresh_scoring_queue<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RefreshScoringQueue<'info>>,
+    ) -> Result<()> {
+        require!(!ctx.remaining_accounts.is_empty(), OpinionError::EmptyBatch);
+        require!(ctx.remaining_accounts.len() <= MAX_ORACLE_BATCH, OpinionError::BatchTooLarge);
+
+        let queue = &mut ctx.accounts.scoring_queue;
+        for market_info in ctx.remaining_accounts.iter() {
+            let market: Account<'info, Market> = Account::try_from(market_info)?;
+            let entry = QueueEntry::from_market(market_info.key(), &market);
+            if let Some(slot) = queue.entries.iter_mut().find(|e| e.market == entry.market) {
+                *slot = entry;
+            }
+        }
+        queue.entries.retain(|e| !e.finalized);
+
+        Ok(())
+    }
+
+    /// A paid staker opts in to a pro-rata share (by payout_amount) of payouts
+    /// left unclaimed when the claim window ends.
+    pub fn opt_in_redistribution(ctx: Context<OptInRedistribution>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Settled, OpinionError::MarketNotAwaitingSettlement);
+        require!(
+            Clock::get()?.unix_timestamp < market.claim_deadline(),
+            OpinionError::ClaimWindowClosed
+        );
+
+        let mut opinion = ctx.accounts.opinion.load_mut()?;
+        require!(opinion.paid(), OpinionError::NotPaid);
+        if opinion.redistribution_opt_in() {
+            return Ok(());
+        }
+        opinion.set_redistribution_opt_in(true);
+
+        let payout_amount = opinion.payout_amount;
+        let market = &mut ctx.accounts.market;
+        market.opt_in_payout_total = market
+            .opt_in_payout_total
+            .checked_add(payout_amount)
+            .ok_or(OpinionError::Overflow)?;
+        Ok(())
+    }
+
+    /// Anyone marks unpaid opinions as expired once the claim window ends.
+    /// remaining_accounts: writable opinions of this market.
+    pub fn expire_payouts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExpirePayouts<'info>>,
+    ) -> Result<()> {
+        require!(!ctx.remaining_accounts.is_empty(), OpinionError::EmptyBatch);
+        require!(ctx.remaining_accounts.len() <= MAX_ORACLE_BATCH, OpinionError::BatchTooLarge);
+
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Settled, OpinionError::MarketNotAwaitingSettlement);
+        require!(
+            Clock::get()?.unix_timestamp >= market.claim_deadline(),
+            OpinionError::ClaimWindowOpen
+        );
+        let market_key = market.key();
+
+        for opinion_info in ctx.remaining_accounts.iter() {
+            let opinion_loader = AccountLoader::<Opinion>::try_from(opinion_info)?;
+            let mut opinion = opinion_loader.load_mut()?;
+            require!(opinion.market == market_key, OpinionError::OpinionMarketMismatch);
+            if opinion.paid() || opinion.expired() {
+                continue;
+            }
+            opinion.set_expired(true);
+
+            emit_cpi!(PayoutExpiredEvent {
+                market: market_key,
+                opinion: opinion_info.key(),
+                staker: opinion.staker,
+                seq: ctx.accounts.market.next_event_seq(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Anyone marks unpaid opinions whose payout is below config.min_payout as
+    /// below_threshold. Their amounts stay in escrow for sweep_dust and can no
+    /// longer be claimed. Opinions at or above the floor are left untouched.
+    /// remaining_accounts: writable opinions of this market.
+    pub fn skip_dust_payouts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SkipDustPayouts<'info>>,
+    ) -> Result<()> {
+        require!(!ctx.remaining_accounts.is_empty(), OpinionError::EmptyBatch);
+        require!(ctx.remaining_accounts.len() <= MAX_ORACLE_BATCH, OpinionError::BatchTooLarge);
+
+        let min_payout = ctx.accounts.config.min_payout;
+        let market_key = ctx.accounts.market.key();
+        let mut skipped_total = 0u64;
+
+        for opinion_info in ctx.remaining_accounts.iter() {
+            let opinion_loader = AccountLoader::<Opinion>::try_from(opinion_info)?;
+            let mut opinion = opinion_loader.load_mut()?;
+            require!(opinion.market == market_key, OpinionError::OpinionMarketMismatch);
+            if opinion.paid() || opinion.expired() || opinion.below_threshold() {
+                continue;
+            }
+            let amount = ctx.accounts.market.claimable_payout(&opinion)?;
+            if amount >= min_payout {
+                continue;
+            }
+            opinion.set_below_threshold(true);
+            skipped_total = skipped_total.saturating_add(amount);
+
+            emit_cpi!(PayoutSkippedEvent {
+                market: market_key,
+                opinion: opinion_info.key(),
+                staker: opinion.staker,
+                amount,
+                seq: ctx.accounts.market.next_event_seq(),
+            });
+        }
+
+        let market = &mut ctx.accounts.market;
+        market.below_threshold_total = market.below_threshold_total.saturating_add(skipped_total);
+        Ok(())
+    }
+
+    /// Opted-in staker collects their share of the unclaimed escrow after the
+    /// claim window: redistribution_pool × payout_amount / opt_in_payout_total.
+    /// The pool is snapshotted from the escrow balance at the first call.
+    pub fn claim_redistribution(ctx: Context<ClaimPayout>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Settled, OpinionError::MarketNotAwaitingSettlement);
+        require!(now >= market.claim_deadline(), OpinionError::ClaimWindowOpen);
+        require!(now < market.sweep_after(), OpinionError::RedistributionClosed);
+
+        let mut opinion = ctx.accounts.opinion.load_mut()?;
+        require!(
+            opinion.redistribution_opt_in() && !opinion.redistribution_claimed(),
+            OpinionError::NotRedistributable
+        );
+
+        // Bounties keepers have yet to claim are not unclaimed payouts
+        let pool = if market.redistribution_started {
+            market.redistribution_pool
+        } else {
+            ctx.accounts
+                .escrow_token_account
+                .amount
+                .saturating_sub(market.settlement_bounty_outstanding())
+        };
+        let share = (pool as u128)
+            .checked_mul(opinion.payout_amount as u128)
+            .ok_or(OpinionError::Overflow)?
+            .checked_div(market.opt_in_payout_total as u128)
+            .ok_or(OpinionError::Overflow)? as u64;
+
+        let market_key = market.key();
+        let escrow_bump = market.escrow_authority_bump;
+        let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+        let signer_seeds = &[seeds];
+
+        let share_cpi = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: ctx.accounts.staker_usdc.to_account_info(),
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_with_memo(
+            share_cpi,
+            share,
+            &ctx.accounts.memo_program,
+            "redistribution",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        let market_key = ctx.accounts.market.key();
+        let market = &mut ctx.accounts.market;
+        market.redistribution_pool = pool;
+        market.redistribution_started = true;
+        opinion.set_redistribution_claimed(true);
+
+        let profile = &mut ctx.accounts.user_profile;
+        profile.touch(opinion.staker, ctx.bumps.user_profile, now);
+        profile.record_claim(share);
+        if let Some(leaderboard) = ctx.accounts.earner_leaderboard.as_mut() {
+            leaderboard.record(&ctx.accounts.user_profile);
+        }
+
+        emit_cpi!(RedistributionClaimedEvent {
+            market: market_key,
+            opinion: ctx.accounts.opinion.key(),
+            staker: opinion.staker,
+            amount: share,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Keeper collects the settlement bounties it accrued on a market once the
+    /// market settles, scaled down pro rata if the protocol fee could not
+    /// cover every keeper's. On a refunding market the bounties lapse: the
+    /// account closes and nothing leaves the escrow.
+    pub fn claim_settlement_bounty(ctx: Context<ClaimSettlementBounty>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let amount = match market.state {
+            MarketState::Settled => {
+                require!(
+                    Clock::get()?.unix_timestamp < market.sweep_after(),
+                    OpinionError::ClaimWindowClosed
+                );
+                let share = (ctx.accounts.settlement_bounty.accrued as u128
+                    * market.settlement_bounty_reserved as u128
+                    / market.settlement_bounty_accrued.max(1) as u128) as u64;
+                share.min(market.settlement_bounty_outstanding())
+            }
+            MarketState::Refunding => 0,
+            _ => return err!(OpinionError::MarketNotAwaitingSettlement),
+        };
+
+        if amount > 0 {
+            let market_key = market.key();
+            let escrow_bump = market.escrow_authority_bump;
+            let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+            let signer_seeds = &[seeds];
+            let bounty_cpi = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: ctx.accounts.keeper_usdc.to_account_info(),
+                    authority: ctx.accounts.escrow_authority.to_account_info(),
+                },
+                signer_seeds,
+            );
+            transfer_with_memo(
+                bounty_cpi,
+                amount,
+                &ctx.accounts.memo_program,
+                "settlement_bounty",
+                Some(&market.uuid),
+            )?;
+        }
+
+        let market = &mut ctx.accounts.market;
+        market.settlement_bounty_paid = market.settlement_bounty_paid.saturating_add(amount);
+        emit_cpi!(SettlementBountyPaidEvent {
+            market: market.key(),
+            keeper: ctx.accounts.keeper.key(),
+            amount,
+            total_paid: market.settlement_bounty_paid,
+            seq: market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Anyone sweeps whatever is left in a settled market's escrow (rounding
+    /// dust from claim_payout, plus anything unclaimed or not redistributed) to
+    /// the treasury once the claim (and any redistribution) window has ended,
+    /// then closes the escrow and returns its rent to
+    /// the market creator.
+    pub fn sweep_dust(ctx: Context<SweepDust>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Settled, OpinionError::MarketNotAwaitingSettlement);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= market.sweep_after(), OpinionError::ClaimWindowOpen);
+
+        let market_key = market.key();
+        let escrow_bump = market.escrow_authority_bump;
+        let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+        let signer_seeds = &[seeds];
+
+        let amount = ctx.accounts.escrow_token_account.amount;
+        if amount > 0 {
+            let sweep_cpi = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: ctx.accounts.treasury_usdc.to_account_info(),
+                    authority: ctx.accounts.escrow_authority.to_account_info(),
+                },
+                signer_seeds,
+            );
+            transfer_with_memo(
+                sweep_cpi,
+                amount,
+                &ctx.accounts.memo_program,
+                "dust_sweep",
+                Some(&ctx.accounts.market.uuid),
+            )?;
+        }
+
+        let close_cpi = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.escrow_token_account.to_account_info(),
+                destination: ctx.accounts.creator.to_account_info(),
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::close_account(close_cpi)?;
+
+        let ledger = &mut ctx.accounts.treasury_ledger;
+        ledger.dust_swept = ledger.dust_swept.saturating_add(amount);
+
+        emit_cpi!(DustSweptEvent {
+            market: market_key,
+            amount,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Anyone settles a closed Pyth-resolved market from `price_update`, the
+    /// feed's first price published in the PYTH_RESOLUTION_WINDOW after
+    /// closes_at. The protocol fee goes to the treasury and the rest is split
+    /// by stake among the winning side at claim_payout; no oracle is involved.
+    /// A market with no winning stake refunds everyone instead. Settle hooks
+    /// are notified; their programs follow in remaining_accounts.
+    pub fn resolve_pyth_market<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ResolvePythMarket<'info>>,
+    ) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.is_objective(), OpinionError::NotObjectiveMarket);
+        require!(market.state == MarketState::Closed, OpinionError::MarketNotClosed);
+        require!(
+            !market.is_confidential() || market.confidential_released,
+            OpinionError::ConfidentialStakesNotReleased
+        );
+
+        let update = PythPriceUpdate::load(&ctx.accounts.price_update)?;
+        require!(update.feed_id == market.pyth_feed_id, OpinionError::InvalidPriceUpdate);
+        require!(update.exponent == market.pyth_exponent, OpinionError::InvalidPriceUpdate);
+        require!(
+            update.publish_time >= market.closes_at
+                && update.publish_time <= market.closes_at + PYTH_RESOLUTION_WINDOW
+                && update.prev_publish_time < market.closes_at,
+            OpinionError::PriceOutsideResolutionWindow
+        );
+
+        require_reviewer(
+            &ctx.accounts.config,
+            market,
+            ctx.accounts.reviewer.as_ref().map(|r| r.key()),
+            false,
+        )?;
+
+        let market_key = market.key();
+        let outcome = update.price >= market.pyth_threshold;
+        let market = &mut ctx.accounts.market;
+        market.pyth_outcome = outcome;
+        let winning_stake = market.winning_stake();
+        if winning_stake == 0 {
+            market.start_refunding();
+            ctx.accounts.global_stats.record_market_exit(market);
+            sync_registry(market_key, market, ctx.accounts.registry_page.as_mut())?;
+            emit_cpi!(refunding_event(&mut ctx.accounts.market, market_key));
+            return Ok(());
+        }
+
+        let total_stake = market.settlement_stake()?;
+        let protocol_fee = ctx.accounts.config.protocol_fee(total_stake);
+        let distributable_pool = total_stake - protocol_fee;
+
+        let escrow_bump = market.escrow_authority_bump;
+        let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+        let signer_seeds = &[seeds];
+        let fee_cpi = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: ctx.accounts.treasury_usdc.to_account_info(),
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_with_memo(
+            fee_cpi,
+            protocol_fee,
+            &ctx.accounts.memo_program,
+            "protocol_fee",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        let ledger = &mut ctx.accounts.treasury_ledger;
+        ledger.protocol_fees = ledger.protocol_fees.saturating_add(protocol_fee);
+        ledger.markets_finalized = ledger.markets_finalized.saturating_add(1);
+
+        let market = &mut ctx.accounts.market;
+        market.distributable_pool = distributable_pool;
+        market.protocol_fee_generated = protocol_fee;
+        market.state = MarketState::Settled;
+        market.settled_at = Clock::get()?.unix_timestamp;
+        ctx.accounts.global_stats.record_settlement(market);
+        sync_registry(market_key, market, ctx.accounts.registry_page.as_mut())?;
+
+        emit_cpi!(PythMarketResolvedEvent {
+            market: market_key,
+            price: update.price,
+            publish_time: update.publish_time,
+            outcome,
+            winning_stake,
+            distributable_pool,
+            protocol_fee,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+        notify_settle_hooks(
+            &ctx.accounts.market,
+            ctx.accounts.settle_hooks.as_ref(),
+            ctx.accounts.hook_authority.as_ref(),
+            ctx.bumps.hook_authority,
+            ctx.remaining_accounts,
+        )?;
+        Ok(())
+    }
+
+    /// Oracle records the market-level AI sentiment score.
+    /// Also transitions the market to Scored (ready for per-opinion settlement).
+    pub fn record_sentiment(
+        ctx: Context<RecordSentiment>,
+        score: u8,
+        confidence: u8,
+        summary_hash: [u8; 32],
+        scoring_run: ScoringRun,
+    ) -> Result<()> {
+        require!(score <= 100, OpinionError::InvalidScore);
+        require!(confidence <= 2, OpinionError::InvalidConfidence);
+
+        let minimum = ctx.accounts.config.oracle_bond_minimum;
+        if minimum > 0 {
+            let bond = ctx.accounts.oracle_bond.as_ref().ok_or(OpinionError::OracleBondRequired)?;
+            require!(bond.amount >= minimum, OpinionError::OracleBondBelowMinimum);
+        }
+
+        let market = &mut ctx.accounts.market;
+        require!(market.state == MarketState::Closed, OpinionError::MarketNotClosed);
+        require!(!market.is_objective(), OpinionError::ObjectiveMarket);
+        require!(
+            !market.is_confidential() || market.confidential_released,
+            OpinionError::ConfidentialStakesNotReleased
+        );
+
+        market.sentiment_score = score;
+        market.confidence = confidence;
+        market.summary_hash = summary_hash;
+        market.scoring_run = scoring_run;
+        market.state = MarketState::Scored;
+
+        record_audit(
+            &ctx.accounts.market,
+            ctx.accounts.audit_log.as_mut(),
+            AuditAction::RecordSentiment,
+            Pubkey::default(),
+            [score, confidence, 0, 0],
+            summary_hash,
+        )?;
+        sync_registry(ctx.accounts.market.key(), &ctx.accounts.market, ctx.accounts.registry_page.as_mut())?;
+
+        emit_cpi!(SentimentRecordedEvent {
+            market: ctx.accounts.market.key(),
+            sentiment_score: score,
+            confidence,
+            summary_hash,
+            scoring_run,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Oracle corrects the market-level sentiment while the market is Scored
+    /// (before finalization). Emits both the previous and new values.
+    pub fn update_sentiment(
+        ctx: Context<RecordSentiment>,
+        score: u8,
+        confidence: u8,
+        summary_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(score <= 100, OpinionError::InvalidScore);
+        require!(confidence <= 2, OpinionError::InvalidConfidence);
+
+        let market_key = ctx.accounts.market.key();
+        let market = &mut ctx.accounts.market;
+        require!(market.state == MarketState::Scored, OpinionError::MarketNotScored);
+
+        let previous_sentiment_score = market.sentiment_score;
+        let previous_confidence = market.confidence;
+        let previous_summary_hash = market.summary_hash;
+
+        market.sentiment_score = score;
+        market.confidence = confidence;
+        market.summary_hash = summary_hash;
+
+        record_audit(
+            &ctx.accounts.market,
+            ctx.accounts.audit_log.as_mut(),
+            AuditAction::UpdateSentiment,
+            Pubkey::default(),
+            [score, confidence, 0, 0],
+            summary_hash,
+        )?;
+
+        emit_cpi!(SentimentCorrectedEvent {
+            market: market_key,
+            previous_sentiment_score,
+            previous_confidence,
+            previous_summary_hash,
+            sentiment_score: score,
+            confidence,
+            summary_hash,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Oracle posts the lowest and highest net backing across the market's
+    /// opinions — the Layer 1 normalization range. Every settled weight_score is
+    /// then recomputed from it on-chain, and finalize_settlement requires both
+    /// endpoints to be hit by some opinion. Only before the first settlement.
+    pub fn post_weight_range(ctx: Context<RecordSentiment>, min_net: i64, max_net: i64) -> Result<()> {
+        require!(min_net <= max_net, OpinionError::NetBackingOutOfRange);
+
+        let market = &mut ctx.accounts.market;
+        require!(market.state == MarketState::Scored, OpinionError::MarketNotScored);
+        require!(market.settled_count == 0, OpinionError::WeightRangeLocked);
+
+        market.weight_min_net = min_net;
+        market.weight_max_net = max_net;
+        market.weight_range_posted = true;
+        market.weight_min_attained = false;
+        market.weight_max_attained = false;
+
+        let mut digest = [0u8; 32];
+        digest[..8].copy_from_slice(&min_net.to_le_bytes());
+        digest[8..16].copy_from_slice(&max_net.to_le_bytes());
+        record_audit(
+            &ctx.accounts.market,
+            ctx.accounts.audit_log.as_mut(),
+            AuditAction::PostWeightRange,
+            Pubkey::default(),
+            [0; 4],
+            digest,
+        )?;
+
+        msg!("Weight range posted: min_net={} max_net={}", min_net, max_net);
+        Ok(())
+    }
+
+    /// Oracle records the AI quality score for a single opinion — Layer 3.
+    /// Called once per opinion before settle_opinion.
+    pub fn record_ai_score(
+        ctx: Context<RecordAiScore>,
+        ai_score: u8,
+    ) -> Result<()> {
+        require!(ai_score <= 100, OpinionError::InvalidScore);
+
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Scored, OpinionError::MarketNotScored);
+
+        let market_key = ctx.accounts.market.key();
+        let opinion_key = ctx.accounts.opinion.key();
+        let staker_key = ctx.accounts.opinion.load()?.staker;
+
+        let mut opinion = ctx.accounts.opinion.load_mut()?;
+        opinion.set_ai_score(ai_score);
+        if !opinion.ai_scored() {
+            opinion.set_ai_scored(true);
+            let market = &mut ctx.accounts.market;
+            market.ai_scored_count = market.ai_scored_count.saturating_add(1);
+        }
+
+        record_audit(
+            &ctx.accounts.market,
+            ctx.accounts.audit_log.as_mut(),
+            AuditAction::RecordAiScore,
+            opinion_key,
+            [ai_score, 0, 0, 0],
+            [0u8; 32],
+        )?;
+
+        emit_cpi!(AiScoreRecordedEvent {
+            market: market_key,
+            opinion: opinion_key,
+            staker: staker_key,
+            ai_score,
+            scoring_run: ctx.accounts.market.scoring_run,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Relayable record_ai_score: anyone may submit it, provided the preceding
+    /// instruction is an Ed25519 verification of the oracle's signature over
+    /// `ai_score_message(..)`. The first score recorded for an opinion earns
+    /// the relayer the settlement bounty when settlement_bounty is passed.
+    pub fn record_ai_score_signed(
+        ctx: Context<RecordAiScoreSigned>,
+        ai_score: u8,
+        nonce: u64,
+    ) -> Result<()> {
+        require!(ai_score <= 100, OpinionError::InvalidScore);
+
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Scored, OpinionError::MarketNotScored);
+
+        let market_key = ctx.accounts.market.key();
+        let opinion_key = ctx.accounts.opinion.key();
+        verify_ed25519_signature(
+            &ctx.accounts.instructions,
+            &ctx.accounts.market.oracle(&ctx.accounts.config),
+            &ai_score_message(&market_key, &opinion_key, ai_score, nonce),
+        )?;
+        ctx.accounts.market.consume_signed_nonce(nonce)?;
+
+        let staker_key = ctx.accounts.opinion.load()?.staker;
+        let mut opinion = ctx.accounts.opinion.load_mut()?;
+        opinion.set_ai_score(ai_score);
+        let first_score = !opinion.ai_scored();
+        if first_score {
+            opinion.set_ai_scored(true);
+            let market = &mut ctx.accounts.market;
+            market.ai_scored_count = market.ai_scored_count.saturating_add(1);
+        }
+
+        record_audit(
+            &ctx.accounts.market,
+            ctx.accounts.audit_log.as_mut(),
+            AuditAction::RecordAiScore,
+            opinion_key,
+            [ai_score, 0, 0, 0],
+            [0u8; 32],
+        )?;
+
+        emit_cpi!(AiScoreRecordedEvent {
+            market: market_key,
+            opinion: opinion_key,
+            staker: staker_key,
+            ai_score,
+            scoring_run: ctx.accounts.market.scoring_run,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+        // Re-relaying an existing score earns nothing
+        if first_score {
+            let bounty = accrue_settlement_bounty(
+                &mut ctx.accounts.market,
+                &ctx.accounts.config,
+                ctx.accounts.relayer.key(),
+                ctx.accounts.settlement_bounty.as_mut(),
+                ctx.bumps.settlement_bounty,
+            )?;
+            if let Some(event) = bounty {
+                emit_cpi!(event);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Oracle records AI quality scores for many opinions in one transaction.
+    /// remaining_accounts: writable opinions, matched positionally to `ai_scores`.
+    pub fn record_ai_scores<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RecordAiScores<'info>>,
+        ai_scores: Vec<u8>,
+    ) -> Result<()> {
+        require!(!ai_scores.is_empty(), OpinionError::EmptyBatch);
+        require!(ai_scores.len() <= MAX_ORACLE_BATCH, OpinionError::BatchTooLarge);
+        require!(
+            ctx.remaining_accounts.len() == ai_scores.len(),
+            OpinionError::BatchAccountsMismatch
+        );
+
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Scored, OpinionError::MarketNotScored);
+        require_reviewer(
+            &ctx.accounts.config,
+            market,
+            ctx.accounts.reviewer.as_ref().map(|r| r.key()),
+            true,
+        )?;
+        let market_key = market.key();
+
+        for (opinion_info, &ai_score) in ctx.remaining_accounts.iter().zip(ai_scores.iter()) {
+            require!(ai_score <= 100, OpinionError::InvalidScore);
+
+            let opinion_loader = AccountLoader::<Opinion>::try_from(opinion_info)?;
+            let mut opinion = opinion_loader.load_mut()?;
+            require!(opinion.market == market_key, OpinionError::OpinionMarketMismatch);
+
+            opinion.set_ai_score(ai_score);
+            if !opinion.ai_scored() {
+                opinion.set_ai_scored(true);
+                let market = &mut ctx.accounts.market;
+                market.ai_scored_count = market.ai_scored_count.saturating_add(1);
+            }
+
+            record_audit(
+                &ctx.accounts.market,
+                ctx.accounts.audit_log.as_mut(),
+                AuditAction::RecordAiScore,
+                opinion_info.key(),
+                [ai_score, 0, 0, 0],
+                [0u8; 32],
+            )?;
+
+            emit_cpi!(AiScoreRecordedEvent {
+                market: market_key,
+                opinion: opinion_info.key(),
+                staker: opinion.staker,
+                ai_score,
+                scoring_run: ctx.accounts.market.scoring_run,
+                seq: ctx.accounts.market.next_event_seq(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Opinion author disputes their ai_score by posting AI_CHALLENGE_BOND.
+    /// The market cannot be finalized until the oracle responds.
+    pub fn challenge_ai_score(ctx: Context<ChallengeAiScore>) -> Result<()> {
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.staker_usdc.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.staker.to_account_info(),
+            },
+        );
+        transfer_with_memo(
+            cpi_ctx,
+            AI_CHALLENGE_BOND,
+            &ctx.accounts.memo_program,
+            "ai_challenge_bond",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        let market_key = ctx.accounts.market.key();
+        let opinion_key = ctx.accounts.opinion.key();
+        let staker_key = ctx.accounts.staker.key();
+        let ai_score = ctx.accounts.opinion.load()?.ai_score();
+
+        let challenge = &mut ctx.accounts.ai_challenge;
+        challenge.market = market_key;
+        challenge.opinion = opinion_key;
+        challenge.staker = staker_key;
+        challenge.original_score = ai_score;
+        challenge.bond = AI_CHALLENGE_BOND;
+        challenge.resolved = false;
+        challenge.bump = ctx.bumps.ai_challenge;
+        challenge.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        let market = &mut ctx.accounts.market;
+        market.open_ai_challenges = market.open_ai_challenges.saturating_add(1);
+
+        emit_cpi!(AiScoreChallengedEvent {
+            market: market_key,
+            opinion: opinion_key,
+            staker: staker_key,
+            ai_score,
+            bond: AI_CHALLENGE_BOND,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Oracle confirms or corrects a challenged ai_score. A correction refunds
+    /// the author's bond and re-applies the Triple-Check formula if the opinion
+    /// was already settled; a confirmation sends the bond to the treasury.
+    pub fn respond_ai_challenge(ctx: Context<RespondAiChallenge>, final_score: u8) -> Result<()> {
+        require!(final_score <= 100, OpinionError::InvalidScore);
+        require!(!ctx.accounts.ai_challenge.resolved, OpinionError::ChallengeAlreadyResolved);
+
+        let market_key = ctx.accounts.market.key();
+        let opinion_key = ctx.accounts.opinion.key();
+        let original_score = ctx.accounts.ai_challenge.original_score;
+        let bond = ctx.accounts.ai_challenge.bond;
+        let corrected = final_score != original_score;
+
+        let escrow_bump = ctx.accounts.market.escrow_authority_bump;
+        let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+        let signer_seeds = &[seeds];
+
+        let bond_to = if corrected {
+            ctx.accounts.staker_usdc.to_account_info()
+        } else {
+            ctx.accounts.treasury_usdc.to_account_info()
+        };
+        let bond_cpi = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: bond_to,
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_with_memo(
+            bond_cpi,
+            bond,
+            &ctx.accounts.memo_program,
+            "ai_challenge_bond_release",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        let challenge = &mut ctx.accounts.ai_challenge;
+        challenge.resolved = true;
+
+        let market = &mut ctx.accounts.market;
+        market.open_ai_challenges = market.open_ai_challenges.saturating_sub(1);
+
+        if corrected {
+            let mut opinion = ctx.accounts.opinion.load_mut()?;
+            opinion.set_ai_score(final_score);
+            if opinion.settled() {
+                let (weight_score, consensus_score) = (opinion.weight_score(), opinion.consensus_score());
+                let vindication_share_bps = ctx.accounts.config.vindication_share_bps;
+                let settlement = apply_opinion_settlement(
+                    &mut ctx.accounts.market,
+                    market_key,
+                    &mut opinion,
+                    opinion_key,
+                    weight_score,
+                    consensus_score,
+                    vindication_share_bps,
+                )?;
+                if let Some(event) = settlement.vindication {
+                    emit_cpi!(event);
+                }
+                emit_cpi!(settlement.settled);
+            }
+        }
+
+        record_audit(
+            &ctx.accounts.market,
+            ctx.accounts.audit_log.as_mut(),
+            AuditAction::RespondAiChallenge,
+            opinion_key,
+            [original_score, final_score, 0, 0],
+            [0u8; 32],
+        )?;
+
+        emit_cpi!(AiChallengeResolvedEvent {
+            market: market_key,
+            opinion: opinion_key,
+            original_score,
+            final_score,
+            corrected,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Oracle settles a single opinion by applying the Triple-Check formula.
+    /// Called once per opinion after all AI scores are recorded; a second call
+    /// is rejected (use correct_settlement).
+    ///
+    /// Oracle computes off-chain:
+    ///   crowd_score = Σ(prediction_i × amount_i) / Σ(amount_i)  (verified on-chain)
+    ///   weight_score_i = round((netBacking_i - minNet) / range × 95) + 5  (verified on-chain)
+    ///   consensus_score_i = max(0, 100 - |prediction_i - crowd_score|)  (verified on-chain)
+    ///
+    /// On-chain we compute:
+    ///   combined_bps = weight*w + consensus*c + ai*a  (market scoring_weights, range 0–10000)
+    ///   combined_score = combined_bps / 100              (stored 0–100)
+    ///
+    /// A slashed opinion that still reaches VINDICATION_SCORE_THRESHOLD earns
+    /// vindication_share_bps of its slashing_total, reserved out of the pools.
+    pub fn settle_opinion(
+        ctx: Context<SettleOpinion>,
+        crowd_score: u8,
+        weight_score: u8,
+        consensus_score: u8,
+    ) -> Result<()> {
+        require!(crowd_score <= 100, OpinionError::InvalidScore);
+        require!(weight_score <= 100, OpinionError::InvalidScore);
+        require!(consensus_score <= 100, OpinionError::InvalidScore);
+
+        require!(!ctx.accounts.opinion.load()?.settled(), OpinionError::OpinionAlreadySettled);
+
+        let market = &mut ctx.accounts.market;
+        require!(market.state == MarketState::Scored, OpinionError::MarketNotScored);
+
+        market.verify_crowd_score(crowd_score)?;
+        // Store crowd_score on market — idempotent, same value every call
+        market.crowd_score = crowd_score;
+
+        let market_key = ctx.accounts.market.key();
+        let opinion_key = ctx.accounts.opinion.key();
+        let vindication_share_bps = ctx.accounts.config.vindication_share_bps;
+        let settlement = apply_opinion_settlement(
+            &mut ctx.accounts.market,
+            market_key,
+            &mut *ctx.accounts.opinion.load_mut()?,
+            opinion_key,
+            weight_score,
+            consensus_score,
+            vindication_share_bps,
+        )?;
+        if let Some(event) = settlement.vindication {
+            emit_cpi!(event);
+        }
+        emit_cpi!(settlement.settled);
+
+        record_audit(
+            &ctx.accounts.market,
+            ctx.accounts.audit_log.as_mut(),
+            AuditAction::SettleOpinion,
+            opinion_key,
+            [crowd_score, weight_score, consensus_score, ctx.accounts.opinion.load()?.combined_score()],
+            [0u8; 32],
+        )
+    }
+
+    /// Oracle overwrites the Layer 1/2 scores of an already-settled opinion
+    /// before finalization. The only way to change a settlement; emits
+    /// SettlementCorrectedEvent with the previous and new scores.
+    pub fn correct_settlement(
+        ctx: Context<SettleOpinion>,
+        weight_score: u8,
+        consensus_score: u8,
+    ) -> Result<()> {
+        require!(weight_score <= 100, OpinionError::InvalidScore);
+        require!(consensus_score <= 100, OpinionError::InvalidScore);
+        require!(
+            ctx.accounts.market.state == MarketState::Scored,
+            OpinionError::MarketNotScored
+        );
+
+        let mut opinion = ctx.accounts.opinion.load_mut()?;
+        require!(opinion.settled(), OpinionError::OpinionNotSettled);
+        let previous_weight_score = opinion.weight_score();
+        let previous_consensus_score = opinion.consensus_score();
+        let previous_combined_score = opinion.combined_score();
+
+        let market_key = ctx.accounts.market.key();
+        let opinion_key = ctx.accounts.opinion.key();
+        let vindication_share_bps = ctx.accounts.config.vindication_share_bps;
+        let settlement = apply_opinion_settlement(
+            &mut ctx.accounts.market,
+            market_key,
+            &mut opinion,
+            opinion_key,
+            weight_score,
+            consensus_score,
+            vindication_share_bps,
+        )?;
+        if let Some(event) = settlement.vindication {
+            emit_cpi!(event);
+        }
+        emit_cpi!(settlement.settled);
+
+        record_audit(
+            &ctx.accounts.market,
+            ctx.accounts.audit_log.as_mut(),
+            AuditAction::CorrectSettlement,
+            opinion_key,
+            [
+                ctx.accounts.market.crowd_score,
+                weight_score,
+                consensus_score,
+                opinion.combined_score(),
+            ],
+            [0u8; 32],
+        )?;
+
+        emit_cpi!(SettlementCorrectedEvent {
+            market: market_key,
+            opinion: opinion_key,
+            previous_weight_score,
+            previous_consensus_score,
+            previous_combined_score,
+            weight_score,
+            consensus_score,
+            combined_score: opinion.combined_score(),
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Oracle commits every opinion's scores as one Merkle root so keepers can
+    /// crank per-opinion settlement with execute_settlement. Re-posting
+    /// replaces the root for opinions not yet settled.
+    pub fn post_scores_root(
+        ctx: Context<SettleOpinionsBatch>,
+        scores_root: [u8; 32],
+        crowd_score: u8,
+    ) -> Result<()> {
+        require!(crowd_score <= 100, OpinionError::InvalidScore);
+
+        require_reviewer(
+            &ctx.accounts.config,
+            &ctx.accounts.market,
+            ctx.accounts.reviewer.as_ref().map(|r| r.key()),
+            true,
+        )?;
+        let market_key = ctx.accounts.market.key();
+        let market = &mut ctx.accounts.market;
+        require!(market.state == MarketState::Scored, OpinionError::MarketNotScored);
+        market.verify_crowd_score(crowd_score)?;
+        market.crowd_score = crowd_score;
+        market.scores_root = scores_root;
+
+        record_audit(
+            &ctx.accounts.market,
+            ctx.accounts.audit_log.as_mut(),
+            AuditAction::PostScoresRoot,
+            Pubkey::default(),
+            [crowd_score, 0, 0, 0],
+            scores_root,
+        )?;
+
+        emit_cpi!(ScoresRootPostedEvent {
+            market: market_key,
+            scores_root,
+            crowd_score,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Anyone settles one opinion against the oracle's posted scores root.
+    /// The proof fixes the AI score and Layer 1/2 scores; the Triple-Check
+    /// formula is applied exactly as in settle_opinion. Accrues the keeper the
+    /// settlement bounty when settlement_bounty is passed.
+    pub fn execute_settlement(
+        ctx: Context<ExecuteSettlement>,
+        weight_score: u8,
+        consensus_score: u8,
+        ai_score: u8,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require!(weight_score <= 100, OpinionError::InvalidScore);
+        require!(consensus_score <= 100, OpinionError::InvalidScore);
+        require!(ai_score <= 100, OpinionError::InvalidScore);
+        require!(proof.len() <= MAX_MERKLE_PROOF_LEN, OpinionError::MerkleProofTooLong);
+
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Scored, OpinionError::MarketNotScored);
+        require!(market.scores_root != [0u8; 32], OpinionError::ScoresRootNotPosted);
+        require!(!ctx.accounts.opinion.load()?.settled(), OpinionError::OpinionAlreadySettled);
+        require_reviewer(
+            &ctx.accounts.config,
+            market,
+            ctx.accounts.reviewer.as_ref().map(|r| r.key()),
+            false,
+        )?;
+
+        let market_key = market.key();
+        let opinion_key = ctx.accounts.opinion.key();
+        let leaf = score_leaf(&opinion_key, weight_score, consensus_score, ai_score);
+        require!(
+            verify_merkle_proof(&proof, market.scores_root, leaf),
+            OpinionError::InvalidMerkleProof
+        );
+
+        ctx.accounts.opinion.load_mut()?.set_ai_score(ai_score);
+        let vindication_share_bps = ctx.accounts.config.vindication_share_bps;
+        let settlement = apply_opinion_settlement(
+            &mut ctx.accounts.market,
+            market_key,
+            &mut *ctx.accounts.opinion.load_mut()?,
+            opinion_key,
+            weight_score,
+            consensus_score,
+            vindication_share_bps,
+        )?;
+        if let Some(event) = settlement.vindication {
+            emit_cpi!(event);
+        }
+        emit_cpi!(settlement.settled);
+        let bounty = accrue_settlement_bounty(
+            &mut ctx.accounts.market,
+            &ctx.accounts.config,
+            ctx.accounts.keeper.key(),
+            ctx.accounts.settlement_bounty.as_mut(),
+            ctx.bumps.settlement_bounty,
+        )?;
+        if let Some(event) = bounty {
+            emit_cpi!(event);
+        }
+        Ok(())
+    }
+
+    /// Anyone folds closed-market opinions into the market's inputs hash chain:
+    ///   inputs_hash = sha256(inputs_hash || opinion || backing || slashing || prediction)
+    /// The chain is the public commitment a scoring proof is checked against.
+    /// remaining_accounts: writable opinions.
+    pub fn snapshot_opinion_inputs<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SnapshotOpinionInputs<'info>>,
+    ) -> Result<()> {
+        require!(!ctx.remaining_accounts.is_empty(), OpinionError::EmptyBatch);
+        require!(ctx.remaining_accounts.len() <= MAX_ORACLE_BATCH, OpinionError::BatchTooLarge);
+
+        let market_key = ctx.accounts.market.key();
+        let market = &mut ctx.accounts.market;
+        require!(
+            matches!(market.state, MarketState::Closed | MarketState::Scored),
+            OpinionError::MarketNotClosed
+        );
+
+        for opinion_info in ctx.remaining_accounts.iter() {
+            let opinion_loader = AccountLoader::<Opinion>::try_from(opinion_info)?;
+            let mut opinion = opinion_loader.load_mut()?;
+            require!(opinion.market == market_key, OpinionError::OpinionMarketMismatch);
+            require!(!opinion.inputs_snapshotted(), OpinionError::InputsAlreadySnapshotted);
+
+            market.inputs_hash = hashv(&[
+                market.inputs_hash.as_ref(),
+                opinion_info.key.as_ref(),
+                &opinion.backing_total.to_le_bytes(),
+                &opinion.slashing_total.to_le_bytes(),
+                &[opinion.market_prediction],
+            ])
+            .to_bytes();
+            let index = market.inputs_snapshot_count;
+            market.inputs_snapshot_count = index.saturating_add(1);
+
+            opinion.set_inputs_snapshotted(true);
+
+            emit_cpi!(OpinionInputsSnapshotEvent {
+                market: market_key,
+                opinion: opinion_info.key(),
+                index,
+                inputs_hash: market.inputs_hash,
+                seq: market.next_event_seq(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Anyone posts a Layer 1/2 scores root with a Groth16 proof that it was
+    /// computed per the published formula from the snapshotted on-chain inputs.
+    /// Public inputs: [hash_to_field(inputs_hash), hash_to_field(root), crowd_score].
+    pub fn post_verified_scores(
+        ctx: Context<PostVerifiedScores>,
+        verified_scores_root: [u8; 32],
+        crowd_score: u8,
+        proof: Groth16Proof,
+    ) -> Result<()> {
+        require!(crowd_score <= 100, OpinionError::InvalidScore);
+
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Scored, OpinionError::MarketNotScored);
+        require!(
+            market.inputs_snapshot_count == market.staker_count,
+            OpinionError::InputsSnapshotIncomplete
+        );
+        market.verify_crowd_score(crowd_score)?;
+
+        let mut crowd_input = [0u8; 32];
+        crowd_input[31] = crowd_score;
+        verify_groth16(
+            &ctx.accounts.scoring_verifier.vk,
+            &proof,
+            &[
+                hash_to_field(&market.inputs_hash),
+                hash_to_field(&verified_scores_root),
+                crowd_input,
+            ],
+        )?;
+
+        let market_key = market.key();
+        let inputs_hash = market.inputs_hash;
+        let market = &mut ctx.accounts.market;
+        market.lock_settlement_mode(SettlementMode::TripleCheck)?;
+        market.crowd_score = crowd_score;
+        market.verified_scores_root = verified_scores_root;
+
+        emit_cpi!(VerifiedScoresPostedEvent {
+            market: market_key,
+            verified_scores_root,
+            inputs_hash,
+            crowd_score,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Anyone settles one opinion against the proof-verified scores root,
+    /// using the oracle-attested ai_score already recorded on the opinion.
+    /// Accrues the keeper the settlement bounty when settlement_bounty is passed.
+    pub fn execute_verified_settlement(
+        ctx: Context<ExecuteSettlement>,
+        weight_score: u8,
+        consensus_score: u8,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require!(weight_score <= 100, OpinionError::InvalidScore);
+        require!(consensus_score <= 100, OpinionError::InvalidScore);
+        require!(proof.len() <= MAX_MERKLE_PROOF_LEN, OpinionError::MerkleProofTooLong);
+
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Scored, OpinionError::MarketNotScored);
+        require!(market.verified_scores_root != [0u8; 32], OpinionError::VerifiedScoresNotPosted);
+        require!(!ctx.accounts.opinion.load()?.settled(), OpinionError::OpinionAlreadySettled);
+        require_reviewer(
+            &ctx.accounts.config,
+            market,
+            ctx.accounts.reviewer.as_ref().map(|r| r.key()),
+            false,
+        )?;
+
+        let market_key = market.key();
+        let opinion_key = ctx.accounts.opinion.key();
+        let leaf = verified_score_leaf(&opinion_key, weight_score, consensus_score);
+        require!(
+            verify_merkle_proof(&proof, market.verified_scores_root, leaf),
+            OpinionError::InvalidMerkleProof
+        );
+
+        let vindication_share_bps = ctx.accounts.config.vindication_share_bps;
+        let settlement = apply_opinion_settlement(
+            &mut ctx.accounts.market,
+            market_key,
+            &mut *ctx.accounts.opinion.load_mut()?,
+            opinion_key,
+            weight_score,
+            consensus_score,
+            vindication_share_bps,
+        )?;
+        if let Some(event) = settlement.vindication {
+            emit_cpi!(event);
+        }
+        emit_cpi!(settlement.settled);
+        let bounty = accrue_settlement_bounty(
+            &mut ctx.accounts.market,
+            &ctx.accounts.config,
+            ctx.accounts.keeper.key(),
+            ctx.accounts.settlement_bounty.as_mut(),
+            ctx.bumps.settlement_bounty,
+        )?;
+        if let Some(event) = bounty {
+            emit_cpi!(event);
+        }
+        Ok(())
+    }
+
+    /// Relayable settle_opinion: anyone may submit it, provided the preceding
+    /// instruction is an Ed25519 verification of the oracle's signature over
+    /// `settle_opinion_message(..)`. Accrues the relayer the settlement bounty
+    /// when settlement_bounty is passed.
+    pub fn settle_opinion_signed(
+        ctx: Context<SettleOpinionSigned>,
+        crowd_score: u8,
+        weight_score: u8,
+        consensus_score: u8,
+        nonce: u64,
+    ) -> Result<()> {
+        require!(crowd_score <= 100, OpinionError::InvalidScore);
+        require!(weight_score <= 100, OpinionError::InvalidScore);
+        require!(consensus_score <= 100, OpinionError::InvalidScore);
+
+        let market_key = ctx.accounts.market.key();
+        let opinion_key = ctx.accounts.opinion.key();
+        verify_ed25519_signature(
+            &ctx.accounts.instructions,
+            &ctx.accounts.market.oracle(&ctx.accounts.config),
+            &settle_opinion_message(
+                &market_key,
+                &opinion_key,
+                crowd_score,
+                weight_score,
+                consensus_score,
+                nonce,
+            ),
+        )?;
+        ctx.accounts.market.consume_signed_nonce(nonce)?;
+        require!(!ctx.accounts.opinion.load()?.settled(), OpinionError::OpinionAlreadySettled);
+
+        let market = &mut ctx.accounts.market;
+        require!(market.state == MarketState::Scored, OpinionError::MarketNotScored);
+        market.verify_crowd_score(crowd_score)?;
+        market.crowd_score = crowd_score;
+
+        let vindication_share_bps = ctx.accounts.config.vindication_share_bps;
+        let settlement = apply_opinion_settlement(
+            &mut ctx.accounts.market,
+            market_key,
+            &mut *ctx.accounts.opinion.load_mut()?,
+            opinion_key,
+            weight_score,
+            consensus_score,
+            vindication_share_bps,
+        )?;
+        if let Some(event) = settlement.vindication {
+            emit_cpi!(event);
+        }
+        emit_cpi!(settlement.settled);
+        let bounty = accrue_settlement_bounty(
+            &mut ctx.accounts.market,
+            &ctx.accounts.config,
+            ctx.accounts.relayer.key(),
+            ctx.accounts.settlement_bounty.as_mut(),
+            ctx.bumps.settlement_bounty,
+        )?;
+        if let Some(event) = bounty {
+            emit_cpi!(event);
+        }
+
+        record_audit(
+            &ctx.accounts.market,
+            ctx.accounts.audit_log.as_mut(),
+            AuditAction::SettleOpinion,
+            opinion_key,
+            [crowd_score, weight_score, consensus_score, ctx.accounts.opinion.load()?.combined_score()],
+            [0u8; 32],
+        )
+    }
+
+    /// Oracle settles many opinions in one transaction with a shared crowd_score.
+    /// remaining_accounts: writable opinions, matched positionally to `settlements`.
+    pub fn settle_opinions_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleOpinionsBatch<'info>>,
+        crowd_score: u8,
+        settlements: Vec<OpinionSettlement>,
+    ) -> Result<()> {
+        require!(crowd_score <= 100, OpinionError::InvalidScore);
+        require!(!settlements.is_empty(), OpinionError::EmptyBatch);
+        require!(settlements.len() <= MAX_ORACLE_BATCH, OpinionError::BatchTooLarge);
+        require!(
+            ctx.remaining_accounts.len() == settlements.len(),
+            OpinionError::BatchAccountsMismatch
+        );
+
+        require_reviewer(
+            &ctx.accounts.config,
+            &ctx.accounts.market,
+            ctx.accounts.reviewer.as_ref().map(|r| r.key()),
+            true,
+        )?;
+        let market = &mut ctx.accounts.market;
+        require!(market.state == MarketState::Scored, OpinionError::MarketNotScored);
+        market.verify_crowd_score(crowd_score)?;
+        market.crowd_score = crowd_score;
+
+        let market_key = ctx.accounts.market.key();
+        let vindication_share_bps = ctx.accounts.config.vindication_share_bps;
+
+        for (opinion_info, entry) in ctx.remaining_accounts.iter().zip(settlements.iter()) {
+            require!(entry.weight_score <= 100, OpinionError::InvalidScore);
+            require!(entry.consensus_score <= 100, OpinionError::InvalidScore);
+
+            let opinion_loader = AccountLoader::<Opinion>::try_from(opinion_info)?;
+            let mut opinion = opinion_loader.load_mut()?;
+            require!(opinion.market == market_key, OpinionError::OpinionMarketMismatch);
+            require!(!opinion.settled(), OpinionError::OpinionAlreadySettled);
+
+            let settlement = apply_opinion_settlement(
+                &mut ctx.accounts.market,
+                market_key,
+                &mut opinion,
+                opinion_info.key(),
+                entry.weight_score,
+                entry.consensus_score,
+                vindication_share_bps,
+            )?;
+            if let Some(event) = settlement.vindication {
+                emit_cpi!(event);
+            }
+            emit_cpi!(settlement.settled);
+
+            record_audit(
+                &ctx.accounts.market,
+                ctx.accounts.audit_log.as_mut(),
+                AuditAction::SettleOpinion,
+                opinion_info.key(),
+                [crowd_score, entry.weight_score, entry.consensus_score, opinion.combined_score()],
+                [0u8; 32],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Oracle calls this once after all opinions are settled.
+    /// Deducts protocol fee, stores distributable_pool, transitions to Settled.
+    /// Also sends protocol fee to treasury, less the settlement bounties it
+    /// holds back for keepers to claim. Vindication bonuses are held back from the pools and
+    /// paid to their authors in claim_payout.
+    ///
+    /// The oracle posts the payout divisors here so claim_payout never has to
+    /// trust totals supplied by the claimer. `total_net_backing` is the
+    /// Σ opinion_pool_weight for the market's payout_mode; `top_n_cutoff` is
+    /// the N-th highest combined_score (ignored outside TopN mode). Both
+    /// divisors must match the totals tallied as opinions settled; TopN
+    /// markets check theirs and the cutoff against the settled scores, so
+    /// exactly N slots are paid.
+    pub fn finalize_settlement<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FinalizeSettlement<'info>>,
+        total_net_backing: u64,
+        sum_prediction_weights: u64,
+        top_n_cutoff: u8,
+    ) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Scored, OpinionError::MarketNotScored);
+        require!(market.total_stake > 0, OpinionError::EmptyPrizePool);
+        require!(market.open_ai_challenges == 0, OpinionError::OpenAiChallenges);
+        require!(market.settled_count == market.staker_count, OpinionError::UnsettledOpinions);
+        require!(!market.optimistic_open, OpinionError::OptimisticSettlementOpen);
+        market.require_settlement_mode(SettlementMode::TripleCheck)?;
+        require!(
+            market.weight_min_attained && market.weight_max_attained,
+            OpinionError::WeightRangeNotAttained
+        );
+        require_reviewer(
+            &ctx.accounts.config,
+            market,
+            ctx.accounts.reviewer.as_ref().map(|r| r.key()),
+            false,
+        )?;
+        if market.settlement_tallied() {
+            require!(
+                sum_prediction_weights == market.settled_prediction_weight,
+                OpinionError::SettlementTotalsMismatch
+            );
+            match market.payout_mode {
+                PayoutMode::TopN(n) => {
+                    let split = market.top_n_split(n).ok_or(OpinionError::TopNMismatch)?;
+                    require!(
+                        top_n_cutoff == split.cutoff && total_net_backing == split.total_weight(),
+                        OpinionError::TopNMismatch
+                    );
+                }
+                _ => require!(
+                    total_net_backing == market.settled_pool_weight,
+                    OpinionError::SettlementTotalsMismatch
+                ),
+            }
+        }
+        let message = finalize_settlement_message(
+            &market.key(),
+            total_net_backing,
+            sum_prediction_weights,
+            top_n_cutoff,
+        );
+        require_committee_approval(
+            &ctx.accounts.config,
+            ctx.accounts.settlement_proposal.as_deref(),
+            &market.key(),
+            &message,
+        )?;
+        record_audit(
+            market,
+            ctx.accounts.audit_log.as_mut(),
+            AuditAction::FinalizeSettlement,
+            Pubkey::default(),
+            [market.crowd_score, top_n_cutoff, 0, 0],
+            hashv(&[&message]).to_bytes(),
+        )?;
+
+        let total_stake = market.settlement_stake()?;
+        // No-loss markets put only the sponsorship and yield at risk:
+        // opinion stakes come back as refund floors, reactions at par
+        let poolable_stake = if market.no_loss {
+            total_stake - market.total_stake
+        } else {
+            total_stake
+                .checked_sub(market.vindication_total)
+                .ok_or(OpinionError::Overflow)?
+                .checked_sub(market.refund_floor_total)
+                .ok_or(OpinionError::Overflow)?
+        };
+        let gross_protocol_fee = ctx.accounts.config.protocol_fee(poolable_stake);
+        // Fee-waived stakers' share of the fee stays in escrow for their claims
+        let fee_waiver_pool = (gross_protocol_fee as u128
+            * market.fee_waived_stake as u128
+            * ctx.accounts.config.fee_waiver_bps as u128
+            / (total_stake as u128 * 10_000)) as u64;
+        let protocol_fee = gross_protocol_fee - fee_waiver_pool;
+        // Charity markets donate the whole fee; nothing goes to insurance
+        let is_charity = market.charity_usdc != Pubkey::default();
+        let insurance_amount = if is_charity {
+            0
+        } else {
+            (protocol_fee as u128 * ctx.accounts.config.insurance_share_bps as u128 / 10_000) as u64
+        };
+        let after_protocol_fee = poolable_stake
+            .checked_sub(gross_protocol_fee)
+            .ok_or(OpinionError::Overflow)?;
+        let oracle_fee = ctx.accounts.config.oracle_fee(after_protocol_fee);
+        let charity_share = ((after_protocol_fee - oracle_fee) as u128
+            * market.charity_pool_bps as u128
+            / 10_000) as u64;
+        // Accrued settlement bounties stay in escrow for their keepers, up to
+        // the fee left after insurance; the pool never covers them
+        let bounty_reserved = market.settlement_bounty_accrued.min(protocol_fee - insurance_amount);
+        let distributable_pool = after_protocol_fee - oracle_fee - charity_share;
+
+        // Send protocol fee (less the insurance slice) to treasury, or the
+        // fee plus the pool slice to a charity market's charity
+        let market_key = market.key();
+        let escrow_bump = market.escrow_authority_bump;
+        let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+        let signer_seeds = &[seeds];
+
+        let fee_destination = if is_charity {
+            ctx.accounts
+                .charity_usdc
+                .as_ref()
+                .ok_or(OpinionError::CharityAccountRequired)?
+                .to_account_info()
+        } else {
+            ctx.accounts.treasury_usdc.to_account_info()
+        };
+        let fee_cpi = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: fee_destination,
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_with_memo(
+            fee_cpi,
+            protocol_fee - insurance_amount - bounty_reserved + charity_share,
+            &ctx.accounts.memo_program,
+            "protocol_fee",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+        if is_charity {
+            emit_cpi!(CharityDonationEvent {
+                market: ctx.accounts.market.key(),
+                charity_usdc: ctx.accounts.market.charity_usdc,
+                protocol_fee,
+                pool_share: charity_share,
+                seq: ctx.accounts.market.next_event_seq(),
+            });
+        }
+
+        // Route the insurance slice of the fee to the insurance vault
+        if insurance_amount > 0 {
+            let (Some(vault), Some(vault_usdc)) = (
+                ctx.accounts.insurance_vault.as_mut(),
+                ctx.accounts.insurance_vault_usdc.as_ref(),
+            ) else {
+                return err!(OpinionError::InsuranceVaultRequired);
+            };
+            let insurance_cpi = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: vault_usdc.to_account_info(),
+                    authority: ctx.accounts.escrow_authority.to_account_info(),
+                },
+                signer_seeds,
+            );
+            transfer_with_memo(
+                insurance_cpi,
+                insurance_amount,
+                &ctx.accounts.memo_program,
+                "insurance",
+                Some(&ctx.accounts.market.uuid),
+            )?;
+            vault.total_deposited = vault.total_deposited.saturating_add(insurance_amount);
+
+            emit_cpi!(InsuranceFundedEvent {
+                market: ctx.accounts.market.key(),
+                amount: insurance_amount,
+                vault_balance: vault_usdc.amount.saturating_add(insurance_amount),
+                seq: ctx.accounts.market.next_event_seq(),
+            });
+        }
+
+        // Compensate the oracle operator that scored this market
+        if oracle_fee > 0 {
+            let oracle_fee_cpi = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: ctx.accounts.oracle_usdc.to_account_info(),
+                    authority: ctx.accounts.escrow_authority.to_account_info(),
+                },
+                signer_seeds,
+            );
+            transfer_with_memo(
+                oracle_fee_cpi,
+                oracle_fee,
+                &ctx.accounts.memo_program,
+                "oracle_fee",
+                Some(&ctx.accounts.market.uuid),
+            )?;
+        }
+
+        let ledger = &mut ctx.accounts.treasury_ledger;
+        if !is_charity {
+            ledger.protocol_fees = ledger
+                .protocol_fees
+                .saturating_add(protocol_fee - insurance_amount - bounty_reserved);
+        }
+        ledger.oracle_fees = ledger.oracle_fees.saturating_add(oracle_fee);
+        ledger.markets_finalized = ledger.markets_finalized.saturating_add(1);
+
+        // Split distributable pool: 70% opinion, 30% prediction (of which 20% is jackpot)
+        let opinion_pool = distributable_pool * 70 / 100;
+        let full_prediction_pool = distributable_pool - opinion_pool; // 30%
+        let jackpot_amount = full_prediction_pool * 20 / 100;         // 6% of total
+        let prediction_pool = full_prediction_pool - jackpot_amount;  // 24% of total
+
+        let market_key = ctx.accounts.market.key();
+        let market = &mut ctx.accounts.market;
+        market.distributable_pool = distributable_pool;
+        market.fee_waiver_pool = fee_waiver_pool;
+        market.protocol_fee_generated = gross_protocol_fee;
+        market.settlement_bounty_reserved = bounty_reserved;
+        market.opinion_pool = opinion_pool;
+        market.prediction_pool = prediction_pool;
+        market.jackpot_amount = jackpot_amount;
+        market.jackpot_claimed = false;
+        market.total_net_backing = total_net_backing;
+        market.sum_prediction_weights = sum_prediction_weights;
+        market.top_n_cutoff = top_n_cutoff;
+        market.state = MarketState::Settled;
+        market.settled_at = Clock::get()?.unix_timestamp;
+        ctx.accounts.global_stats.record_settlement(market);
+        sync_registry(market_key, market, ctx.accounts.registry_page.as_mut())?;
+
+        emit_cpi!(MarketFinalizedEvent {
+            market: market_key,
+            total_pool: total_stake,
+            distributable_pool,
+            protocol_fee,
+            oracle_fee,
+            crowd_score: market.crowd_score,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        if ctx.accounts.wormhole_program.is_some() {
+            let (message, emitter, sequence) = post_settlement_message(&ctx)?;
+            emit_cpi!(SettlementMirroredEvent {
+                market: market_key,
+                message,
+                emitter,
+                sequence,
+                seq: ctx.accounts.market.next_event_seq(),
+            });
+        }
+
+        notify_settle_hooks(
+            &ctx.accounts.market,
+            ctx.accounts.settle_hooks.as_ref(),
+            ctx.accounts.hook_authority.as_ref(),
+            ctx.bumps.hook_authority,
+            ctx.remaining_accounts,
+        )?;
+
+        Ok(())
+    }
+
+    /// Oracle settles the whole market in one transaction by committing a Merkle
+    /// root over (opinion, weight, consensus, ai, payout) leaves. Replaces the
+    /// per-opinion settle_opinion + finalize_settlement flow; stakers then claim
+    /// with claim_payout_with_proof. Leaf payouts already include every pool
+    /// (opinion, prediction, jackpot, vindication) and must fit the
+    /// post-fee distributable pool.
+    pub fn post_settlement_root<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FinalizeSettlement<'info>>,
+        settlement_root: [u8; 32],
+        crowd_score: u8,
+        total_payout: u64,
+    ) -> Result<()> {
+        let market_key = ctx.accounts.market.key();
+        require_committee_approval(
+            &ctx.accounts.config,
+            ctx.accounts.settlement_proposal.as_deref(),
+            &market_key,
+            &settlement_root_message(
+                &market_key,
+                &settlement_root,
+                crowd_score,
+                total_payout,
+                ctx.accounts.market.signed_nonce,
+            ),
+        )?;
+        record_audit(
+            &ctx.accounts.market,
+            ctx.accounts.audit_log.as_mut(),
+            AuditAction::PostSettlementRoot,
+            Pubkey::default(),
+            [crowd_score, 0, 0, 0],
+            settlement_root,
+        )?;
+
+        let event = commit_settlement_root(
+            &mut ctx.accounts.market,
+            EscrowAccounts {
+                token_account: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+                memo_program: ctx.accounts.memo_program.to_account_info(),
+            },
+            ctx.accounts.treasury_usdc.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.config,
+            RootCommitment { settlement_root, crowd_score, total_payout },
+            ctx.accounts.reviewer.as_ref().map(|r| r.key()),
+        )?;
+        ctx.accounts.global_stats.record_settlement(&ctx.accounts.market);
+        sync_registry(ctx.accounts.market.key(), &ctx.accounts.market, ctx.accounts.registry_page.as_mut())?;
+        emit_cpi!(event);
+        notify_settle_hooks(
+            &ctx.accounts.market,
+            ctx.accounts.settle_hooks.as_ref(),
+            ctx.accounts.hook_authority.as_ref(),
+            ctx.bumps.hook_authority,
+            ctx.remaining_accounts,
+        )?;
+        Ok(())
+    }
+
+    /// Relayable post_settlement_root: anyone may submit it, provided the
+    /// preceding instruction is an Ed25519 verification of the oracle's
+    /// signature over `settlement_root_message(..)`. Accrues the relayer the
+    /// settlement bounty when settlement_bounty is passed.
+    pub fn post_settlement_root_signed<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PostSettlementRootSigned<'info>>,
+        settlement_root: [u8; 32],
+        crowd_score: u8,
+        total_payout: u64,
+        nonce: u64,
+    ) -> Result<()> {
+        let message = settlement_root_message(
+            &ctx.accounts.market.key(),
+            &settlement_root,
+            crowd_score,
+            total_payout,
+            nonce,
+        );
+        verify_ed25519_signature(
+            &ctx.accounts.instructions,
+            &ctx.accounts.market.oracle(&ctx.accounts.config),
+            &message,
+        )?;
+        ctx.accounts.market.consume_signed_nonce(nonce)?;
+        require_committee_approval(
+            &ctx.accounts.config,
+            ctx.accounts.settlement_proposal.as_deref(),
+            &ctx.accounts.market.key(),
+            &message,
+        )?;
+        record_audit(
+            &ctx.accounts.market,
+            ctx.accounts.audit_log.as_mut(),
+            AuditAction::PostSettlementRoot,
+            Pubkey::default(),
+            [crowd_score, 0, 0, 0],
+            settlement_root,
+        )?;
+
+        // Accrued before committing so the fee charged below reserves it
+        let bounty = accrue_settlement_bounty(
+            &mut ctx.accounts.market,
+            &ctx.accounts.config,
+            ctx.accounts.relayer.key(),
+            ctx.accounts.settlement_bounty.as_mut(),
+            ctx.bumps.settlement_bounty,
+        )?;
+        if let Some(event) = bounty {
+            emit_cpi!(event);
+        }
+
+        let event = commit_settlement_root(
+            &mut ctx.accounts.market,
+            EscrowAccounts {
+                token_account: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+                memo_program: ctx.accounts.memo_program.to_account_info(),
+            },
+            ctx.accounts.treasury_usdc.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.config,
+            RootCommitment { settlement_root, crowd_score, total_payout },
+            ctx.accounts.reviewer.as_ref().map(|r| r.key()),
+        )?;
+        ctx.accounts.global_stats.record_settlement(&ctx.accounts.market);
+        sync_registry(ctx.accounts.market.key(), &ctx.accounts.market, ctx.accounts.registry_page.as_mut())?;
+        emit_cpi!(event);
+        notify_settle_hooks(
+            &ctx.accounts.market,
+            ctx.accounts.settle_hooks.as_ref(),
+            ctx.accounts.hook_authority.as_ref(),
+            ctx.bumps.hook_authority,
+            ctx.remaining_accounts,
+        )?;
+        Ok(())
+    }
+
+    /// Oracle proposes a Merkle settlement root optimistically. It becomes final
+    /// via finalize_optimistic_settlement once CHALLENGE_WINDOW passes without
+    /// a dispute; a disputed proposal is settled by resolve_dispute instead.
+    pub fn propose_settlement_root(
+        ctx: Context<ProposeSettlementRoot>,
+        settlement_root: [u8; 32],
+        crowd_score: u8,
+        total_payout: u64,
+    ) -> Result<()> {
+        require!(crowd_score <= 100, OpinionError::InvalidScore);
+
+        let market = &mut ctx.accounts.market;
+        require!(market.state == MarketState::Scored, OpinionError::MarketNotScored);
+        require!(market.total_stake > 0, OpinionError::EmptyPrizePool);
+        market.verify_crowd_score(crowd_score)?;
+        market.lock_settlement_mode(SettlementMode::TripleCheck)?;
+        market.optimistic_open = true;
+        record_audit(
+            market,
+            ctx.accounts.audit_log.as_mut(),
+            AuditAction::ProposeSettlementRoot,
+            Pubkey::default(),
+            [crowd_score, 0, 0, 0],
+            settlement_root,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let market_key = market.key();
+        let pending = &mut ctx.accounts.optimistic_settlement;
+        pending.market = market_key;
+        pending.settlement_root = settlement_root;
+        pending.crowd_score = crowd_score;
+        pending.total_payout = total_payout;
+        pending.proposed_at = now;
+        pending.challenger = None;
+        pending.bond_amount = 0;
+        pending.bump = ctx.bumps.optimistic_settlement;
+        pending.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        emit_cpi!(SettlementProposedEvent {
+            market: market_key,
+            settlement_root,
+            crowd_score,
+            total_payout,
+            challenge_ends_at: now + CHALLENGE_WINDOW,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Anyone disputes a proposed settlement within the challenge window by
+    /// posting CHALLENGE_BOND into the market escrow.
+    pub fn dispute_settlement(ctx: Context<DisputeSettlement>) -> Result<()> {
+        let pending = &ctx.accounts.optimistic_settlement;
+        require!(pending.challenger.is_none(), OpinionError::SettlementDisputed);
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now < pending.proposed_at + CHALLENGE_WINDOW,
+            OpinionError::ChallengeWindowClosed
+        );
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.challenger_usdc.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.challenger.to_account_info(),
+            },
+        );
+        transfer_with_memo(
+            cpi_ctx,
+            CHALLENGE_BOND,
+            &ctx.accounts.memo_program,
+            "dispute_bond",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        let challenger_key = ctx.accounts.challenger.key();
+        let pending = &mut ctx.accounts.optimistic_settlement;
+        pending.challenger = Some(challenger_key);
+        pending.bond_amount = CHALLENGE_BOND;
+
+        emit_cpi!(SettlementDisputedEvent {
+            market: ctx.accounts.market.key(),
+            challenger: challenger_key,
+            bond: CHALLENGE_BOND,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Anyone commits an undisputed proposal once the challenge window has
+    /// passed, with the reviewer's co-signature when the config requires it.
+    /// Settle hooks are notified; their programs follow in remaining_accounts.
+    pub fn finalize_optimistic_settlement<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FinalizeOptimisticSettlement<'info>>,
+    ) -> Result<()> {
+        let pending = &ctx.accounts.optimistic_settlement;
+        require!(pending.challenger.is_none(), OpinionError::SettlementDisputed);
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= pending.proposed_at + CHALLENGE_WINDOW,
+            OpinionError::ChallengeWindowOpen
+        );
+
+        let (settlement_root, crowd_score, total_payout) =
+            (pending.settlement_root, pending.crowd_score, pending.total_payout);
+        ctx.accounts.market.optimistic_open = false;
+        let event = commit_settlement_root(
+            &mut ctx.accounts.market,
+            EscrowAccounts {
+                token_account: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+                memo_program: ctx.accounts.memo_program.to_account_info(),
+            },
+            ctx.accounts.treasury_usdc.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.config,
+            RootCommitment { settlement_root, crowd_score, total_payout },
+            ctx.accounts.reviewer.as_ref().map(|r| r.key()),
+        )?;
+        ctx.accounts.global_stats.record_settlement(&ctx.accounts.market);
+        sync_registry(ctx.accounts.market.key(), &ctx.accounts.market, ctx.accounts.registry_page.as_mut())?;
+        emit_cpi!(event);
+        notify_settle_hooks(
+            &ctx.accounts.market,
+            ctx.accounts.settle_hooks.as_ref(),
+            ctx.accounts.hook_authority.as_ref(),
+            ctx.bumps.hook_authority,
+            ctx.remaining_accounts,
+        )?;
+        Ok(())
+    }
+
+    /// Fallback authority (admin, plus committee approval when enabled) re-scores
+    /// a disputed market. If the result matches the oracle's proposal the
+    /// challenger's bond is slashed to the treasury; otherwise it is returned.
+    /// A market that left Scored before the dispute was resolved (it began
+    /// refunding) just returns the bond. Settle hooks are notified when the
+    /// market settles; their programs follow in remaining_accounts.
+    pub fn resolve_dispute<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ResolveDispute<'info>>,
+        settlement_root: [u8; 32],
+        crowd_score: u8,
+        total_payout: u64,
+    ) -> Result<()> {
+        let market_key = ctx.accounts.market.key();
+        let pending = &ctx.accounts.optimistic_settlement;
+        let challenger = pending.challenger.ok_or(OpinionError::SettlementNotDisputed)?;
+        let bond = pending.bond_amount;
+        let scored = ctx.accounts.market.state == MarketState::Scored;
+        let challenger_won = !scored
+            || settlement_root != pending.settlement_root
+            || crowd_score != pending.crowd_score
+            || total_payout != pending.total_payout;
+
+        if scored {
+            require_committee_approval(
+                &ctx.accounts.config,
+                ctx.accounts.settlement_proposal.as_deref(),
+                &market_key,
+                &settlement_root_message(
+                    &market_key,
+                    &settlement_root,
+                    crowd_score,
+                    total_payout,
+                    ctx.accounts.market.signed_nonce,
+                ),
+            )?;
+        }
+
+        let bond_to = if challenger_won {
+            ctx.accounts.challenger_usdc.to_account_info()
+        } else {
+            ctx.accounts.treasury_usdc.to_account_info()
+        };
+        release_dispute_bond(
+            &ctx.accounts.market,
+            EscrowAccounts {
+                token_account: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+                memo_program: ctx.accounts.memo_program.to_account_info(),
+            },
+            bond_to,
+            ctx.accounts.token_program.to_account_info(),
+            bond,
+        )?;
+        ctx.accounts.optimistic_settlement.bond_amount = 0;
+        ctx.accounts.market.optimistic_open = false;
+
+        emit_cpi!(DisputeResolvedEvent {
+            market: market_key,
+            challenger,
+            challenger_won,
+            bond,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+        if !scored {
+            return Ok(());
+        }
+
+        // A successful dispute slashes the oracle bond into this market's pool
+        if challenger_won {
+            if let Some(event) = slash_oracle_bond_into_pool(
+                &mut ctx.accounts.market,
+                &ctx.accounts.config,
+                ctx.accounts.oracle_bond.as_mut(),
+                ctx.accounts.oracle_bond_vault.as_ref().map(|v| v.to_account_info()),
+                ctx.accounts.escrow_token_account.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.memo_program.to_account_info(),
+            )? {
+                emit_cpi!(event);
+            }
+        }
+
+        let event = commit_settlement_root(
+            &mut ctx.accounts.market,
+            EscrowAccounts {
+                token_account: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+                memo_program: ctx.accounts.memo_program.to_account_info(),
+            },
+            ctx.accounts.treasury_usdc.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.config,
+            RootCommitment { settlement_root, crowd_score, total_payout },
+            ctx.accounts.reviewer.as_ref().map(|r| r.key()),
+        )?;
+        ctx.accounts.global_stats.record_settlement(&ctx.accounts.market);
+        sync_registry(ctx.accounts.market.key(), &ctx.accounts.market, ctx.accounts.registry_page.as_mut())?;
+        emit_cpi!(event);
+        notify_settle_hooks(
+            &ctx.accounts.market,
+            ctx.accounts.settle_hooks.as_ref(),
+            ctx.accounts.hook_authority.as_ref(),
+            ctx.bumps.hook_authority,
+            ctx.remaining_accounts,
+        )?;
+        Ok(())
+    }
+
+    /// Admin upholds a dispute without settling: the challenger's bond is
+    /// returned, the oracle bond is slashed into the pool, and the market
+    /// stays Scored so the oracle can propose again or settle directly.
+    pub fn reject_settlement_proposal(ctx: Context<RejectSettlementProposal>) -> Result<()> {
+        let market_key = ctx.accounts.market.key();
+        let pending = &ctx.accounts.optimistic_settlement;
+        let challenger = pending.challenger.ok_or(OpinionError::SettlementNotDisputed)?;
+        let bond = pending.bond_amount;
+
+        release_dispute_bond(
+            &ctx.accounts.market,
+            EscrowAccounts {
+                token_account: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+                memo_program: ctx.accounts.memo_program.to_account_info(),
+            },
+            ctx.accounts.challenger_usdc.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            bond,
+        )?;
+        ctx.accounts.optimistic_settlement.bond_amount = 0;
+        ctx.accounts.market.optimistic_open = false;
+
+        emit_cpi!(DisputeResolvedEvent {
+            market: market_key,
+            challenger,
+            challenger_won: true,
+            bond,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+        if let Some(event) = slash_oracle_bond_into_pool(
+            &mut ctx.accounts.market,
+            &ctx.accounts.config,
+            ctx.accounts.oracle_bond.as_mut(),
+            ctx.accounts.oracle_bond_vault.as_ref().map(|v| v.to_account_info()),
+            ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.memo_program.to_account_info(),
+        )? {
+            emit_cpi!(event);
+        }
+        Ok(())
+    }
+
+    /// Staker claims a Merkle-committed payout. The proof also writes the
+    /// opinion's final scores on-chain for display.
+    pub fn claim_payout_with_proof(
+        ctx: Context<ClaimPayout>,
+        weight_score: u8,
+        consensus_score: u8,
+        ai_score: u8,
+        payout: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require!(proof.len() <= MAX_MERKLE_PROOF_LEN, OpinionError::MerkleProofTooLong);
+
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Settled, OpinionError::MarketNotAwaitingSettlement);
+        require!(market.settlement_root != [0u8; 32], OpinionError::NotMerkleSettled);
+        require!(
+            Clock::get()?.unix_timestamp < market.claim_deadline(),
+            OpinionError::ClaimWindowClosed
+        );
+        require!(!ctx.accounts.opinion.load()?.paid(), OpinionError::AlreadyPaid);
+        require!(!ctx.accounts.opinion.load()?.expired(), OpinionError::ClaimWindowClosed);
+
+        let opinion_key = ctx.accounts.opinion.key();
+        require!(
+            consensus_score
+                == compute_consensus_score(ctx.accounts.opinion.load()?.market_prediction, market.crowd_score),
+            OpinionError::ConsensusScoreMismatch
+        );
+        let net = ctx.accounts.opinion.load()?.net_backing();
+        require!(
+            net >= market.weight_min_net && net <= market.weight_max_net,
+            OpinionError::NetBackingOutOfRange
+        );
+        require!(
+            weight_score == compute_weight_score(net, market.weight_min_net, market.weight_max_net),
+            OpinionError::WeightScoreMismatch
+        );
+        let leaf = settlement_leaf(&opinion_key, weight_score, consensus_score, ai_score, payout);
+        require!(
+            verify_merkle_proof(&proof, market.settlement_root, leaf),
+            OpinionError::InvalidMerkleProof
+        );
+
+        let claimed_after = market.merkle_claimed_total
+            .checked_add(payout)
+            .ok_or(OpinionError::Overflow)?;
+        require!(claimed_after <= market.merkle_payout_total, OpinionError::PayoutExceedsPool);
+
+        let market_key = market.key();
+        let escrow_bump = market.escrow_authority_bump;
+        let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+        let signer_seeds = &[seeds];
+
+        let payout_cpi = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: ctx.accounts.staker_usdc.to_account_info(),
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_with_memo(
+            payout_cpi,
+            payout,
+            &ctx.accounts.memo_program,
+            "payout",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        let market_key = ctx.accounts.market.key();
+        let staker_key = ctx.accounts.opinion.load()?.staker;
+        let combined_score = compute_combined_score(
+            &ctx.accounts.market.scoring_weights,
+            weight_score,
+            consensus_score,
+            ai_score,
+        )?;
+
+        let mut opinion = ctx.accounts.opinion.load_mut()?;
+        opinion.set_weight_score(weight_score);
+        opinion.set_consensus_score(consensus_score);
+        opinion.set_ai_score(ai_score);
+        opinion.set_combined_score(combined_score);
+        opinion.set_settled(true);
+        opinion.payout_amount = payout;
+        opinion.set_paid(true);
+
+        let market = &mut ctx.accounts.market;
+        market.merkle_claimed_total = claimed_after;
+        market.record_podium(staker_key, combined_score, payout);
+
+        let now = Clock::get()?.unix_timestamp;
+        let profile = &mut ctx.accounts.user_profile;
+        profile.touch(staker_key, ctx.bumps.user_profile, now);
+        profile.record_claim(payout);
+        profile.record_score(&mut opinion, now);
+        if let Some(leaderboard) = ctx.accounts.earner_leaderboard.as_mut() {
+            leaderboard.record(&ctx.accounts.user_profile);
+        }
+
+        emit_cpi!(PayoutClaimedEvent {
+            market: market_key,
+            opinion: opinion_key,
+            staker: staker_key,
+            payout_amount: payout,
+            combined_score,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Staker claims their proportional payout after settlement.
+    /// Dual pool payout:
+    ///   - Opinion pool: proportional to net backing received
+    ///   - Prediction pool: inverse distance from crowd score
+    ///
+    /// Divisors (total_net_backing, sum_prediction_weights) are read from the
+    /// Market, where the oracle stored them at finalize_settlement. Vesting
+    /// payouts release only what has vested since the last claim.
+    ///
+    /// `amount` withdraws only part of what is available (None = all of it);
+    /// the opinion stays claimable until claimed_so_far reaches the payout.
+    pub fn claim_payout(ctx: Context<ClaimPayout>, amount: Option<u64>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let (entitlement, available) =
+            market.payout_release(&*ctx.accounts.opinion.load()?, Clock::get()?.unix_timestamp)?;
+        let total_payout = match amount {
+            Some(amount) => {
+                require!(amount > 0 && amount <= available, OpinionError::ClaimExceedsAvailable);
+                amount
+            }
+            None => available,
+        };
+
+        let market_key = market.key();
+        let escrow_bump = market.escrow_authority_bump;
+        let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+        let signer_seeds = &[seeds];
+
+        let payout_cpi = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: ctx.accounts.staker_usdc.to_account_info(),
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_with_memo(
+            payout_cpi,
+            total_payout,
+            &ctx.accounts.memo_program,
+            "payout",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        let market_key = ctx.accounts.market.key();
+        let opinion_key = ctx.accounts.opinion.key();
+        let staker_key = ctx.accounts.opinion.load()?.staker;
+        let combined_score_val = ctx.accounts.opinion.load()?.combined_score();
+
+        let now = Clock::get()?.unix_timestamp;
+        let mut opinion = ctx.accounts.opinion.load_mut()?;
+        ctx.accounts.user_profile.record_score(&mut opinion, now);
+        opinion.record_claim(entitlement, total_payout);
+        let fully_paid = opinion.paid();
+
+        let market = &mut ctx.accounts.market;
+        if fully_paid {
+            market.record_podium(staker_key, combined_score_val, entitlement);
+        }
+
+        let profile = &mut ctx.accounts.user_profile;
+        profile.touch(staker_key, ctx.bumps.user_profile, now);
+        profile.record_claim(total_payout);
+        if let Some(leaderboard) = ctx.accounts.earner_leaderboard.as_mut() {
+            leaderboard.record(&ctx.accounts.user_profile);
+        }
+
+        emit_cpi!(PayoutClaimedEvent {
+            market: market_key,
+            opinion: opinion_key,
+            staker: staker_key,
+            payout_amount: total_payout,
+            combined_score: combined_score_val,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Staker claims several settled payouts in one transaction. Remaining
+    /// accounts are (market, opinion, escrow, escrow authority) groups;
+    /// markets may differ.
+    /// Each claim follows claim_payout and pays into staker_usdc.
+    pub fn claim_payouts_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimPayoutsBatch<'info>>,
+    ) -> Result<()> {
+        require!(!ctx.remaining_accounts.is_empty(), OpinionError::EmptyBatch);
+        require!(ctx.remaining_accounts.len().is_multiple_of(4), OpinionError::BatchAccountsMismatch);
+        require!(
+            ctx.remaining_accounts.len() / 4 <= MAX_BATCH_CLAIMS,
+            OpinionError::BatchTooLarge
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let staker_key = ctx.accounts.staker.key();
+        let usdc_mint = ctx.accounts.config.usdc_mint;
+        let mut claimed_total = 0u64;
+        ctx.accounts.user_profile.touch(staker_key, ctx.bumps.user_profile, now);
+
+        for group in ctx.remaining_accounts.chunks(4) {
+            let mut market: Account<'info, Market> = Account::try_from(&group[0])?;
+            let opinion_loader = AccountLoader::<Opinion>::try_from(&group[1])?;
+            let mut opinion = opinion_loader.load_mut()?;
+            let escrow: Account<'info, TokenAccount> = Account::try_from(&group[2])?;
+            let market_key = group[0].key();
+            require_keys_eq!(opinion.market, market_key, OpinionError::OpinionMarketMismatch);
+            require_keys_eq!(opinion.staker, staker_key, OpinionError::Unauthorized);
+            let (escrow_key, _) =
+                Pubkey::find_program_address(&[b"escrow", market_key.as_ref()], &crate::ID);
+            require_keys_eq!(escrow.key(), escrow_key, OpinionError::BatchAccountsMismatch);
+            require_keys_eq!(escrow.mint, usdc_mint, OpinionError::MintMismatch);
+            let escrow_authority = Pubkey::create_program_address(
+                &[b"escrow_authority", market_key.as_ref(), &[market.escrow_authority_bump]],
+                &crate::ID,
+            )
+            .map_err(|_| error!(OpinionError::BatchAccountsMismatch))?;
+            require_keys_eq!(group[3].key(), escrow_authority, OpinionError::BatchAccountsMismatch);
+
+            let (entitlement, total_payout) = market.payout_release(&opinion, now)?;
+
+            let seeds: &[&[u8]] =
+                &[b"escrow_authority", market_key.as_ref(), &[market.escrow_authority_bump]];
+            let signer_seeds = &[seeds];
+            let payout_cpi = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: escrow.to_account_info(),
+                    to: ctx.accounts.staker_usdc.to_account_info(),
+                    authority: group[3].clone(),
+                },
+                signer_seeds,
+            );
+            transfer_with_memo(
+                payout_cpi,
+                total_payout,
+                &ctx.accounts.memo_program,
+                "payout",
+                Some(&market.uuid),
+            )?;
+
+            let profile = &mut ctx.accounts.user_profile;
+            profile.record_score(&mut opinion, now);
+            profile.record_claim(total_payout);
+            opinion.record_claim(entitlement, total_payout);
+            if opinion.paid() {
+                market.record_podium(staker_key, opinion.combined_score(), entitlement);
+            }
+            let seq = market.next_event_seq();
+            market.exit(&crate::ID)?;
+            claimed_total = claimed_total.saturating_add(total_payout);
+
+            emit_cpi!(PayoutClaimedEvent {
+                market: market_key,
+                opinion: group[1].key(),
+                staker: staker_key,
+                payout_amount: total_payout,
+                combined_score: opinion.combined_score(),
+                seq,
+            });
+        }
+
+        if let Some(leaderboard) = ctx.accounts.earner_leaderboard.as_mut() {
+            leaderboard.record(&ctx.accounts.user_profile);
+        }
+
+        msg!(
+            "Batch claim: staker={} claims={} total={}",
+            staker_key,
+            ctx.remaining_accounts.len() / 4,
+            claimed_total
+        );
+        Ok(())
+    }
+
+    /// Anyone previews an opinion's payout on a settled (non-Merkle) market
+    /// with the same math claim_payout uses, returning a PayoutPreview as
+    /// return data. Transfers nothing; meant to be simulated by frontends.
+    pub fn preview_payout(ctx: Context<PreviewPayout>) -> Result<PayoutPreview> {
+        let market = &ctx.accounts.market;
+        let opinion = ctx.accounts.opinion.load()?;
+        require!(market.state == MarketState::Settled, OpinionError::MarketNotAwaitingSettlement);
+        require!(market.settlement_root == [0u8; 32], OpinionError::MerkleSettlementActive);
+
+        let entitlement = if opinion.paid() {
+            opinion.payout_amount
+        } else if opinion.expired() || opinion.below_threshold() {
+            0
+        } else {
+            let raw = market.raw_payout(&opinion)?;
+            market.capped_payout(&opinion, raw)?
+        };
+        let claimable_now = market
+            .payout_release(&opinion, Clock::get()?.unix_timestamp)
+            .map_or(0, |(_, release)| release);
+        Ok(PayoutPreview {
+            entitlement,
+            claimed_so_far: opinion.claimed_so_far,
+            claimable_now,
+            claim_deadline: market.claim_deadline(),
+        })
+    }
+
+    /// Anyone (typically a notification keeper, after MarketFinalizedEvent)
+    /// emits a PayoutReadyEvent for each opinion in the remaining accounts
+    /// that has an unclaimed payout on this settled (non-Merkle) market,
+    /// using claim_payout's math. Each opinion is announced once; others are
+    /// skipped. Fails until a capped market's cap tally completes, so no
+    /// uncapped amount is ever announced.
+    pub fn announce_payouts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AnnouncePayouts<'info>>,
+    ) -> Result<()> {
+        require!(!ctx.remaining_accounts.is_empty(), OpinionError::EmptyBatch);
+        require!(ctx.remaining_accounts.len() <= MAX_ORACLE_BATCH, OpinionError::BatchTooLarge);
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Settled, OpinionError::MarketNotAwaitingSettlement);
+        require!(market.settlement_root == [0u8; 32], OpinionError::MerkleSettlementActive);
+
+        let market_key = market.key();
+        let claim_deadline = market.claim_deadline();
+        for opinion_info in ctx.remaining_accounts.iter() {
+            let opinion_loader = AccountLoader::<Opinion>::try_from(opinion_info)?;
+            let mut opinion = opinion_loader.load_mut()?;
+            require!(opinion.market == market_key, OpinionError::OpinionMarketMismatch);
+            if opinion.payout_announced()
+                || opinion.paid()
+                || opinion.expired()
+                || opinion.below_threshold()
+                || opinion.confidential_pending()
+            {
+                continue;
+            }
+            let raw = ctx.accounts.market.raw_payout(&opinion)?;
+            let amount = ctx.accounts.market.capped_payout(&opinion, raw)?;
+            if amount == 0 {
+                continue;
+            }
+            opinion.set_payout_announced(true);
+
+            emit_cpi!(PayoutReadyEvent {
+                staker: opinion.staker,
+                market: market_key,
+                opinion: opinion_info.key(),
+                amount,
+                claim_deadline,
+                seq: ctx.accounts.market.next_event_seq(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Anyone reads a user's reputation decayed to the current time, returned
+    /// as return data. Meant to be simulated by frontends; programs gating on
+    /// reputation can deserialize the UserProfile and call reputation_at.
+    pub fn get_reputation(ctx: Context<GetReputation>) -> Result<u64> {
+        Ok(ctx.accounts.user_profile.reputation_at(Clock::get()?.unix_timestamp))
+    }
+
+    /// Staker claims their payout and, in the same instruction, swaps it into
+    /// another token through the whitelisted swap program. The payout lands in
+    /// staker_usdc first; `swap_data` and the remaining accounts are the
+    /// aggregator route instruction, invoked with the staker's signature. Fails
+    /// unless output_token_account gains at least `min_amount_out`, or if the
+    /// staker has registered a PayoutPreference (use claim_payout).
+    pub fn claim_payout_swapped<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimPayoutSwapped<'info>>,
+        swap_data: Vec<u8>,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let (entitlement, total_payout) =
+            market.payout_release(&*ctx.accounts.opinion.load()?, Clock::get()?.unix_timestamp)?;
+
+        let market_key = market.key();
+        let escrow_bump = market.escrow_authority_bump;
+        let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+        let signer_seeds = &[seeds];
+
+        let payout_cpi = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: ctx.accounts.staker_usdc.to_account_info(),
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_with_memo(
+            payout_cpi,
+            total_payout,
+            &ctx.accounts.memo_program,
+            "payout",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        // Route the payout through the aggregator; only the staker signs
+        let balance_before = ctx.accounts.output_token_account.amount;
+        invoke_swap(&ctx.accounts.swap_program, ctx.remaining_accounts, swap_data)?;
+
+        ctx.accounts.output_token_account.reload()?;
+        let amount_out = ctx
+            .accounts
+            .output_token_account
+            .amount
+            .saturating_sub(balance_before);
+        require!(amount_out >= min_amount_out, OpinionError::SwapOutputTooLow);
+
+        let market_key = ctx.accounts.market.key();
+        let opinion_key = ctx.accounts.opinion.key();
+        let staker_key = ctx.accounts.opinion.load()?.staker;
+        let combined_score_val = ctx.accounts.opinion.load()?.combined_score();
+
+        let now = Clock::get()?.unix_timestamp;
+        let mut opinion = ctx.accounts.opinion.load_mut()?;
+        ctx.accounts.user_profile.record_score(&mut opinion, now);
+        opinion.record_claim(entitlement, total_payout);
+        let fully_paid = opinion.paid();
+
+        let market = &mut ctx.accounts.market;
+        if fully_paid {
+            market.record_podium(staker_key, combined_score_val, entitlement);
+        }
+
+        let profile = &mut ctx.accounts.user_profile;
+        profile.touch(staker_key, ctx.bumps.user_profile, now);
+        profile.record_claim(total_payout);
+        if let Some(leaderboard) = ctx.accounts.earner_leaderboard.as_mut() {
+            leaderboard.record(&ctx.accounts.user_profile);
+        }
+
+        emit_cpi!(PayoutClaimedEvent {
+            market: market_key,
+            opinion: opinion_key,
+            staker: staker_key,
+            payout_amount: total_payout,
+            combined_score: combined_score_val,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+        emit_cpi!(PayoutSwappedEvent {
+            market: market_key,
+            opinion: opinion_key,
+            staker: staker_key,
+            payout_amount: total_payout,
+            output_mint: ctx.accounts.output_token_account.mint,
+            amount_out,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Staker mints a compressed NFT badge for a paid opinion into a badge
+    /// tree, encoding the market, podium rank and combined score. Once per
+    /// opinion; can be sent in the same transaction as claim_payout.
+    pub fn mint_winner_badge(ctx: Context<MintWinnerBadge>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Settled, OpinionError::MarketNotAwaitingSettlement);
+        let mut opinion = ctx.accounts.opinion.load_mut()?;
+        require!(opinion.paid(), OpinionError::NotPaid);
+        require!(!opinion.badge_minted(), OpinionError::BadgeAlreadyMinted);
+
+        let market_key = market.key();
+        let staker_key = ctx.accounts.staker.key();
+        let rank = market
+            .podium
+            .iter()
+            .position(|entry| entry.staker == staker_key)
+            .map_or(0, |index| index as u8 + 1);
+        let combined_score = opinion.combined_score();
+        let metadata = BadgeMetadata::winner(&market_key, rank, combined_score);
+
+        let mint_ix = Instruction {
+            program_id: BUBBLEGUM_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(ctx.accounts.tree_config.key(), false),
+                AccountMeta::new_readonly(staker_key, false),
+                AccountMeta::new_readonly(staker_key, false),
+                AccountMeta::new(ctx.accounts.merkle_tree.key(), false),
+                AccountMeta::new(staker_key, true),
+                AccountMeta::new_readonly(ctx.accounts.badge_authority.key(), true),
+                AccountMeta::new_readonly(NOOP_PROGRAM_ID, false),
+                AccountMeta::new_readonly(ACCOUNT_COMPRESSION_PROGRAM_ID, false),
+                AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+            ],
+            data: anchor_ix_data("mint_v1", metadata)?,
+        };
+        let seeds: &[&[u8]] = &[b"badge_authority", &[ctx.bumps.badge_authority]];
+        invoke_signed(
+            &mint_ix,
+            &[
+                ctx.accounts.tree_config.to_account_info(),
+                ctx.accounts.staker.to_account_info(),
+                ctx.accounts.merkle_tree.to_account_info(),
+                ctx.accounts.badge_authority.to_account_info(),
+                ctx.accounts.log_wrapper.to_account_info(),
+                ctx.accounts.compression_program.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.bubblegum_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+        opinion.set_badge_minted(true);
+        drop(opinion);
+
+        emit_cpi!(WinnerBadgeMintedEvent {
+            market: market_key,
+            opinion: ctx.accounts.opinion.key(),
+            staker: staker_key,
+            merkle_tree: ctx.accounts.merkle_tree.key(),
+            rank,
+            combined_score,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+        Ok(())
+    }
+
+    /// Anyone mints the market's Metaplex Core asset, owned by the creator
+    /// with the market PDA as update authority, so the market shows up in
+    /// wallets and marketplaces. Its attributes are refreshed by
+    /// sync_market_asset.
+    pub fn mint_market_asset(ctx: Context<MintMarketAsset>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.asset == Pubkey::default(), OpinionError::MarketAssetExists);
+
+        let market_key = market.key();
+        let uuid_hex: String =
+            market.uuid[..4].iter().map(|byte| format!("{:02x}", byte)).collect();
+        let args = CoreCreateArgs {
+            data_state: 0,
+            name: format!("Opinion Market {}", uuid_hex),
+            uri: format!("{}{}", MARKET_ASSET_URI_BASE, market_key),
+            plugins: Some(vec![(market.asset_attributes(&ctx.accounts.market_statement), Some(2))]),
+        };
+        let mut data = vec![MPL_CORE_CREATE_V1_IX];
+        args.serialize(&mut data)?;
+
+        let asset_key = ctx.accounts.market_asset.key();
+        let create_ix = Instruction {
+            program_id: MPL_CORE_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(asset_key, true),
+                AccountMeta::new_readonly(MPL_CORE_PROGRAM_ID, false),
+                AccountMeta::new_readonly(MPL_CORE_PROGRAM_ID, false),
+                AccountMeta::new(ctx.accounts.payer.key(), true),
+                AccountMeta::new_readonly(market.creator, false),
+                AccountMeta::new_readonly(market_key, false),
+                AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+                AccountMeta::new_readonly(MPL_CORE_PROGRAM_ID, false),
+            ],
+            data,
+        };
+        invoke_signed(
+            &create_ix,
+            &[
+                ctx.accounts.market_asset.to_account_info(),
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.creator.to_account_info(),
+                market.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.core_program.to_account_info(),
+            ],
+            &[&[b"market_asset", market_key.as_ref(), &[ctx.bumps.market_asset]]],
+        )?;
+
+        let market = &mut ctx.accounts.market;
+        market.asset = asset_key;
+        emit_cpi!(MarketAssetMintedEvent {
+            market: market_key,
+            asset: asset_key,
+            owner: market.creator,
+            seq: market.next_event_seq(),
+        });
+        Ok(())
+    }
+
+    /// Anyone refreshes the market asset's attributes from the market; the
+    /// market PDA signs as update authority and the payer covers any realloc.
+    pub fn sync_market_asset(ctx: Context<SyncMarketAsset>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let mut data = vec![MPL_CORE_UPDATE_PLUGIN_V1_IX];
+        market.asset_attributes(&ctx.accounts.market_statement).serialize(&mut data)?;
+
+        let update_ix = Instruction {
+            program_id: MPL_CORE_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(market.asset, false),
+                AccountMeta::new_readonly(MPL_CORE_PROGRAM_ID, false),
+                AccountMeta::new(ctx.accounts.payer.key(), true),
+                AccountMeta::new_readonly(market.key(), true),
+                AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+                AccountMeta::new_readonly(MPL_CORE_PROGRAM_ID, false),
+            ],
+            data,
+        };
+        invoke_signed(
+            &update_ix,
+            &[
+                ctx.accounts.market_asset.to_account_info(),
+                ctx.accounts.payer.to_account_info(),
+                market.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.core_program.to_account_info(),
+            ],
+            &[&[b"market", market.uuid.as_ref(), &[market.bump]]],
+        )?;
+
+        msg!("Market asset synced: market={} state={}", market.key(), market.state.label());
+        Ok(())
+    }
+
+    /// Staker mints points for the protocol fee their opinion generated:
+    /// protocol_fee_generated × stake / total_stake × points_rate_bps. Points
+    /// land in the staker's frozen points ATA. Once per opinion, after
+    /// settlement; can be sent in the same transaction as claim_payout.
+    pub fn claim_fee_points(ctx: Context<ClaimFeePoints>) -> Result<()> {
+        let rate = ctx.accounts.config.points_rate_bps;
+        require!(rate > 0, OpinionError::PointsSeasonInactive);
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Settled, OpinionError::MarketNotAwaitingSettlement);
+        let mut opinion = ctx.accounts.opinion.load_mut()?;
+        require!(!opinion.points_claimed(), OpinionError::PointsAlreadyClaimed);
+
+        let fee_share = if market.total_stake > 0 {
+            (market.protocol_fee_generated as u128 * opinion.stake_amount() as u128
+                / market.total_stake as u128) as u64
+        } else {
+            0
+        };
+        let points = (fee_share as u128 * rate as u128 / 10_000) as u64;
+
+        let mint_bump = ctx.bumps.points_mint;
+        let seeds: &[&[u8]] = &[b"points_mint", &[mint_bump]];
+        let signer_seeds = &[seeds];
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let points_mint = ctx.accounts.points_mint.to_account_info();
+        let staker_points = ctx.accounts.staker_points.to_account_info();
+
+        if ctx.accounts.staker_points.is_frozen() {
+            token::thaw_account(CpiContext::new_with_signer(
+                token_program.clone(),
+                ThawAccount {
+                    account: staker_points.clone(),
+                    mint: points_mint.clone(),
+                    authority: points_mint.clone(),
+                },
+                signer_seeds,
+            ))?;
+        }
+        token::mint_to(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                MintTo {
+                    mint: points_mint.clone(),
+                    to: staker_points.clone(),
+                    authority: points_mint.clone(),
+                },
+                signer_seeds,
+            ),
+            points,
+        )?;
+        token::freeze_account(CpiContext::new_with_signer(
+            token_program,
+            FreezeAccount {
+                account: staker_points,
+                mint: points_mint.clone(),
+                authority: points_mint,
+            },
+            signer_seeds,
+        ))?;
+        opinion.set_points_claimed(true);
+
+        emit_cpi!(FeePointsClaimedEvent {
+            market: ctx.accounts.market.key(),
+            opinion: ctx.accounts.opinion.key(),
+            staker: opinion.staker,
+            fee_share,
+            points,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+        Ok(())
+    }
+
+    /// Staker mints soulbound reputation for a paid opinion: payout ×
+    /// combined_score / 100, into their non-transferable reputation account,
+    /// so the balance tracks score-weighted winnings. Once per opinion; can be
+    /// sent in the same transaction as claim_payout.
+    pub fn claim_reputation(ctx: Context<ClaimReputation>) -> Result<()> {
+        let mut opinion = ctx.accounts.opinion.load_mut()?;
+        require!(opinion.paid(), OpinionError::NotPaid);
+        require!(!opinion.reputation_claimed(), OpinionError::ReputationAlreadyClaimed);
+
+        let payout = opinion.payout_amount;
+        let combined_score = opinion.combined_score();
+        let amount = (payout as u128 * combined_score as u128 / 100) as u64;
+        if amount > 0 {
+            let mint = ctx.accounts.reputation_mint.to_account_info();
+            let seeds: &[&[u8]] = &[b"reputation_mint", &[ctx.bumps.reputation_mint]];
+            token_2022::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_2022_program.to_account_info(),
+                    token_2022::MintTo {
+                        mint: mint.clone(),
+                        to: ctx.accounts.staker_reputation.to_account_info(),
+                        authority: mint,
+                    },
+                    &[seeds],
+                ),
+                amount,
+            )?;
+        }
+        opinion.set_reputation_claimed(true);
+
+        emit_cpi!(ReputationMintedEvent {
+            market: ctx.accounts.market.key(),
+            opinion: ctx.accounts.opinion.key(),
+            staker: opinion.staker,
+            payout,
+            combined_score,
+            amount,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+        Ok(())
+    }
+
+    /// Permissionless crank: counts a settled market's opinion in its
+    /// staker's UserProfile score average and reputation, so opinions that
+    /// paid nothing (and so are never claimed) count too. Claims record the
+    /// score themselves if this has not run; either way it counts once.
+    pub fn record_opinion_score(ctx: Context<RecordOpinionScore>) -> Result<()> {
+        require!(
+            ctx.accounts.market.state == MarketState::Settled,
+            OpinionError::MarketNotAwaitingSettlement
+        );
+        let mut opinion = ctx.accounts.opinion.load_mut()?;
+        require!(opinion.settled() && !opinion.refunded(), OpinionError::OpinionNotSettled);
+        require!(!opinion.score_recorded(), OpinionError::ScoreAlreadyRecorded);
+
+        let now = Clock::get()?.unix_timestamp;
+        let profile = &mut ctx.accounts.user_profile;
+        profile.touch(opinion.staker, ctx.bumps.user_profile, now);
+        profile.record_score(&mut opinion, now);
+
+        msg!(
+            "Opinion score recorded: opinion={} staker={} score={}",
+            ctx.accounts.opinion.key(),
+            opinion.staker,
+            opinion.combined_score()
+        );
+        Ok(())
+    }
+
+    /// Staker registers (or changes) the token account their payouts go to.
+    /// From then on claims pay there whichever wallet signs.
+    pub fn set_payout_preference(ctx: Context<SetPayoutPreference>) -> Result<()> {
+        let pref = &mut ctx.accounts.payout_preference;
+        pref.staker = ctx.accounts.staker.key();
+        pref.destination = ctx.accounts.destination.key();
+        pref.bump = ctx.bumps.payout_preference;
+        pref.schema_version = ACCOUNT_SCHEMA_VERSION;
+        msg!("Payout destination set: staker={} destination={}", pref.staker, pref.destination);
+        Ok(())
+    }
+
+    /// Staker removes their payout destination; payouts go back to accounts
+    /// owned by the staking key.
+    pub fn clear_payout_preference(ctx: Context<ClearPayoutPreference>) -> Result<()> {
+        msg!("Payout destination cleared: staker={}", ctx.accounts.staker.key());
+        Ok(())
+    }
+
+    /// Staker opens their compound vault (and its USDC account). No delegate is
+    /// set until set_compound_allowance.
+    pub fn open_compound_vault(ctx: Context<OpenCompoundVault>) -> Result<()> {
+        let vault = &mut ctx.accounts.compound_vault;
+        vault.owner = ctx.accounts.owner.key();
+        vault.delegate = Pubkey::default();
+        vault.delegate_allowance = 0;
+        vault.max_stake_per_market = 0;
+        vault.total_compounded = 0;
+        vault.total_restaked = 0;
+        vault.bump = ctx.bumps.compound_vault;
+        vault.schema_version = ACCOUNT_SCHEMA_VERSION;
+        msg!("Compound vault opened: owner={}", vault.owner);
+        Ok(())
+    }
+
+    /// Owner sets who may claim into and stake from the vault on their behalf,
+    /// how much that delegate may stake in total, and a per-stake ceiling
+    /// (0 = MAX_STAKE) that applies to the owner as well.
+    pub fn set_compound_allowance(
+        ctx: Context<SetCompoundAllowance>,
+        delegate: Pubkey,
+        delegate_allowance: u64,
+        max_stake_per_market: u64,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.compound_vault;
+        vault.delegate = delegate;
+        vault.delegate_allowance = delegate_allowance;
+        vault.max_stake_per_market = max_stake_per_market;
+        msg!(
+            "Compound allowance set: owner={} delegate={} allowance={} per_market={}",
+            vault.owner,
+            delegate,
+            delegate_allowance,
+            max_stake_per_market
+        );
+        Ok(())
+    }
+
+    /// Owner or delegate claims the owner's payout into their compound vault
+    /// instead of their wallet. Unavailable once the owner has registered a
+    /// PayoutPreference, whose destination receives every payout.
+    pub fn claim_payout_to_vault(ctx: Context<ClaimPayoutToVault>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let (entitlement, total_payout) =
+            market.payout_release(&*ctx.accounts.opinion.load()?, Clock::get()?.unix_timestamp)?;
+
+        let market_key = market.key();
+        let escrow_bump = market.escrow_authority_bump;
+        let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+        let signer_seeds = &[seeds];
+
+        let payout_cpi = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: ctx.accounts.vault_usdc.to_account_info(),
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_with_memo(
+            payout_cpi,
+            total_payout,
+            &ctx.accounts.memo_program,
+            "payout",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        let market_key = ctx.accounts.market.key();
+        let opinion_key = ctx.accounts.opinion.key();
+        let staker_key = ctx.accounts.opinion.load()?.staker;
+        let combined_score_val = ctx.accounts.opinion.load()?.combined_score();
+
+        let now = Clock::get()?.unix_timestamp;
+        let mut opinion = ctx.accounts.opinion.load_mut()?;
+        ctx.accounts.user_profile.record_score(&mut opinion, now);
+        opinion.record_claim(entitlement, total_payout);
+        let fully_paid = opinion.paid();
+
+        let market = &mut ctx.accounts.market;
+        if fully_paid {
+            market.record_podium(staker_key, combined_score_val, entitlement);
+        }
+
+        let profile = &mut ctx.accounts.user_profile;
+        profile.touch(staker_key, ctx.bumps.user_profile, now);
+        profile.record_claim(total_payout);
+        if let Some(leaderboard) = ctx.accounts.earner_leaderboard.as_mut() {
+            leaderboard.record(&ctx.accounts.user_profile);
+        }
+
+        let vault = &mut ctx.accounts.compound_vault;
+        vault.total_compounded = vault.total_compounded.saturating_add(total_payout);
+
+        emit_cpi!(PayoutClaimedEvent {
+            market: market_key,
+            opinion: opinion_key,
+            staker: staker_key,
+            payout_amount: total_payout,
+            combined_score: combined_score_val,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+        emit_cpi!(PayoutCompoundedEvent {
+            owner: staker_key,
+            market: market_key,
+            amount: total_payout,
+            vault_balance: ctx.accounts.vault_usdc.amount.saturating_add(total_payout),
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Owner or delegate stakes a new opinion for the vault owner, paid from
+    /// the compound vault. A delegate's stakes draw down delegate_allowance;
+    /// every stake respects max_stake_per_market. The signer pays the
+    /// Opinion account's rent.
+    pub fn stake_from_vault(
+        ctx: Context<StakeFromVault>,
+        stake_amount: u64,
+        text_hash: [u8; 32],
+        ipfs_cid: String,
+        opinion_score: u8,
+        market_prediction: u8,
+    ) -> Result<Pubkey> {
+        let clock = Clock::get()?;
+        check_opinion_stake(
+            &ctx.accounts.market,
+            stake_amount,
+            &ipfs_cid,
+            opinion_score,
+            market_prediction,
+            clock.unix_timestamp,
+        )?;
+
+        let authority_key = ctx.accounts.authority.key();
+        let vault = &mut ctx.accounts.compound_vault;
+        require!(
+            vault.max_stake_per_market == 0 || stake_amount <= vault.max_stake_per_market,
+            OpinionError::CompoundAllowanceExceeded
+        );
+        if authority_key != vault.owner {
+            vault.delegate_allowance = vault
+                .delegate_allowance
+                .checked_sub(stake_amount)
+                .ok_or(OpinionError::CompoundAllowanceExceeded)?;
+        }
+        vault.total_restaked = vault.total_restaked.saturating_add(stake_amount);
+
+        let owner_key = vault.owner;
+        let vault_seeds: &[&[u8]] = &[b"compound_vault", owner_key.as_ref(), &[vault.bump]];
+        let signer_seeds = &[vault_seeds];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_usdc.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.compound_vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_with_memo(
+            cpi_ctx,
+            stake_amount,
+            &ctx.accounts.memo_program,
+            "stake",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        let market_key = ctx.accounts.market.key();
+        let opinion_key = ctx.accounts.opinion.key();
+
+        let mut opinion = ctx.accounts.opinion.load_init()?;
+        opinion.market = market_key;
+        opinion.staker = owner_key;
+        opinion.set_stake_amount(stake_amount);
+        opinion.text_hash = text_hash;
+        opinion.set_ipfs_cid(&ipfs_cid);
+        opinion.created_at = clock.unix_timestamp;
+        opinion.opinion_score = opinion_score;
+        opinion.market_prediction = market_prediction;
+        opinion.source_program = Pubkey::default();
+        opinion.bump = ctx.bumps.opinion;
+        opinion.schema_version = ACCOUNT_SCHEMA_VERSION;
+        opinion.reset_for_stake(stake_amount);
+
+        let market = &mut ctx.accounts.market;
+        market.record_opinion_stake(
+            ctx.accounts.crowd_histogram.as_deref_mut().map(|h| &mut **h),
+            opinion_score,
+            market_prediction,
+            stake_amount,
+        )?;
+        let total_stake_after = market.total_stake;
+        ctx.accounts.global_stats.record_stake(stake_amount);
+        ctx.accounts
+            .activity_log
+            .load_mut()?
+            .record(owner_key, ActivityKind::Stake, stake_amount, clock.slot);
+        ctx.accounts.opinion_index.push(
+            &mut ctx.accounts.market,
+            market_key,
+            opinion_key,
+            ctx.bumps.opinion_index,
+        );
+        let profile = &mut ctx.accounts.user_profile;
+        profile.touch(owner_key, ctx.bumps.user_profile, clock.unix_timestamp);
+        profile.record_stake(stake_amount);
+
+        emit_cpi!(OpinionStakedEvent {
+            market: market_key,
+            staker: owner_key,
+            stake_amount,
+            opinion_score,
+            market_prediction,
+            ipfs_cid,
+            total_stake_after,
+            source_program: None,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(opinion_key)
+    }
+
+    /// Owner withdraws USDC from their compound vault to their wallet.
+    pub fn withdraw_compound_vault(ctx: Context<WithdrawCompoundVault>, amount: u64) -> Result<()> {
+        let owner_key = ctx.accounts.owner.key();
+        let vault_bump = ctx.accounts.compound_vault.bump;
+        let vault_seeds: &[&[u8]] = &[b"compound_vault", owner_key.as_ref(), &[vault_bump]];
+        let signer_seeds = &[vault_seeds];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_usdc.to_account_info(),
+                to: ctx.accounts.owner_usdc.to_account_info(),
+                authority: ctx.accounts.compound_vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_with_memo(cpi_ctx, amount, &ctx.accounts.memo_program, "vault_withdrawal", None)?;
+
+        msg!("Compound vault withdrawal: owner={} amount={}", owner_key, amount);
+        Ok(())
+    }
+
+    /// Anyone delivers a staker's unclaimed payout to the staker's USDC ATA
+    /// (created at the keeper's expense if missing), or to their registered
+    /// payout destination if they have one. The keeper keeps push_bounty_bps
+    /// of the payout as a bounty. Only after PUSH_GRACE_PERIOD, so stakers
+    /// first get to claim in full.
+    pub fn push_payout(ctx: Context<PushPayout>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= market.settled_at.saturating_add(PUSH_GRACE_PERIOD),
+            OpinionError::PushGracePeriodActive
+        );
+        let (entitlement, total_payout) =
+            market.payout_release(&*ctx.accounts.opinion.load()?, now)?;
+        let bounty = (total_payout as u128 * ctx.accounts.config.push_bounty_bps as u128 / 10_000) as u64;
+        let delivered = total_payout - bounty;
+        require!(
+            ctx.accounts.payout_preference.data_is_empty() || ctx.accounts.payout_destination.is_some(),
+            OpinionError::PayoutDestinationMismatch
+        );
+        let destination = match &ctx.accounts.payout_destination {
+            Some(dest) => dest.to_account_info(),
+            None => ctx.accounts.staker_usdc.to_account_info(),
+        };
+
+        let market_key = market.key();
+        let escrow_bump = market.escrow_authority_bump;
+        let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+        let signer_seeds = &[seeds];
+
+        let payout_cpi = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: destination,
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_with_memo(
+            payout_cpi,
+            delivered,
+            &ctx.accounts.memo_program,
+            "payout",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        if bounty > 0 {
+            let bounty_cpi = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: ctx.accounts.keeper_usdc.to_account_info(),
+                    authority: ctx.accounts.escrow_authority.to_account_info(),
+                },
+                signer_seeds,
+            );
+            transfer_with_memo(
+                bounty_cpi,
+                bounty,
+                &ctx.accounts.memo_program,
+                "keeper_bounty",
+                Some(&ctx.accounts.market.uuid),
+            )?;
+        }
+
+        let market_key = ctx.accounts.market.key();
+        let opinion_key = ctx.accounts.opinion.key();
+        let staker_key = ctx.accounts.opinion.load()?.staker;
+        let combined_score_val = ctx.accounts.opinion.load()?.combined_score();
+
+        let now = Clock::get()?.unix_timestamp;
+        let mut opinion = ctx.accounts.opinion.load_mut()?;
+        ctx.accounts.user_profile.record_score(&mut opinion, now);
+        opinion.record_claim(entitlement, total_payout);
+        let fully_paid = opinion.paid();
+
+        let market = &mut ctx.accounts.market;
+        if fully_paid {
+            market.record_podium(staker_key, combined_score_val, entitlement);
+        }
+
+        let profile = &mut ctx.accounts.user_profile;
+        profile.touch(staker_key, ctx.bumps.user_profile, now);
+        profile.record_claim(total_payout);
+        if let Some(leaderboard) = ctx.accounts.earner_leaderboard.as_mut() {
+            leaderboard.record(&ctx.accounts.user_profile);
+        }
+
+        emit_cpi!(PayoutClaimedEvent {
+            market: market_key,
+            opinion: opinion_key,
+            staker: staker_key,
+            payout_amount: total_payout,
+            combined_score: combined_score_val,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+        emit_cpi!(PayoutPushedEvent {
+            market: market_key,
+            opinion: opinion_key,
+            staker: staker_key,
+            keeper: ctx.accounts.keeper.key(),
+            delivered,
+            bounty,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Oracle claims the jackpot on behalf of the top predictor.
+    /// Can only be called once per market (guarded by jackpot_claimed).
+    pub fn claim_jackpot(ctx: Context<ClaimJackpot>, jackpot_winner: Pubkey) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Settled, OpinionError::MarketNotAwaitingSettlement);
+        require!(!market.jackpot_claimed, OpinionError::JackpotAlreadyClaimed);
+        require!(
+            Clock::get()?.unix_timestamp < market.claim_deadline(),
+            OpinionError::ClaimWindowClosed
+        );
+        require!(
+            ctx.accounts.winner_token_account.owner == jackpot_winner,
+            OpinionError::Unauthorized
+        );
+
+        let jackpot = market.jackpot_amount;
+        let market_key = market.key();
+        let escrow_bump = market.escrow_authority_bump;
+        let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+        let signer_seeds = &[seeds];
+
+        let jackpot_cpi = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: ctx.accounts.winner_token_account.to_account_info(),
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_with_memo(
+            jackpot_cpi,
+            jackpot,
+            &ctx.accounts.memo_program,
+            "jackpot",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        let market_key = ctx.accounts.market.key();
+        let market = &mut ctx.accounts.market;
+        market.jackpot_claimed = true;
+
+        emit_cpi!(JackpotClaimedEvent {
+            market: market_key,
+            winner: jackpot_winner,
+            amount: jackpot,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Anyone marks an opinion fee-waived when its staker's ReactorStats meet
+    /// the config reputation threshold. Must run before finalize_settlement,
+    /// which reserves the waived fee for these opinions' claims.
+    pub fn apply_fee_waiver(ctx: Context<ApplyFeeWaiver>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let market = &ctx.accounts.market;
+        require!(
+            matches!(market.state, MarketState::Active | MarketState::Closed | MarketState::Scored),
+            OpinionError::MarketNotActive
+        );
+        require!(!ctx.accounts.opinion.load()?.fee_waived(), OpinionError::FeeWaiverAlreadyApplied);
+
+        let stats = &ctx.accounts.reactor_stats;
+        require!(
+            config.fee_waiver_bps > 0
+                && stats.reactions_scored >= config.fee_waiver_min_scored
+                && stats.accuracy_bps() >= config.fee_waiver_min_accuracy_bps,
+            OpinionError::ReputationTooLow
+        );
+
+        let stake_amount = ctx.accounts.opinion.load()?.stake_amount();
+        ctx.accounts.opinion.load_mut()?.set_fee_waived(true);
+        let market = &mut ctx.accounts.market;
+        market.fee_waived_stake = market.fee_waived_stake.saturating_add(stake_amount);
+
+        msg!(
+            "Fee waiver applied: market={} opinion={}",
+            market.key(),
+            ctx.accounts.opinion.key()
+        );
+        Ok(())
+    }
+
+    /// Create the global top-earners leaderboard. Permissionless, one-time.
+    pub fn initialize_earner_leaderboard(ctx: Context<InitializeEarnerLeaderboard>) -> Result<()> {
+        let leaderboard = &mut ctx.accounts.leaderboard;
+        leaderboard.entries = Vec::new();
+        leaderboard.bump = ctx.bumps.leaderboard;
+        leaderboard.schema_version = ACCOUNT_SCHEMA_VERSION;
+        Ok(())
+    }
+
+    /// Create the global reactor leaderboard. Permissionless, one-time.
+    pub fn initialize_reactor_leaderboard(ctx: Context<InitializeReactorLeaderboard>) -> Result<()> {
+        let leaderboard = &mut ctx.accounts.leaderboard;
+        leaderboard.entries = Vec::new();
+        leaderboard.bump = ctx.bumps.leaderboard;
+        leaderboard.schema_version = ACCOUNT_SCHEMA_VERSION;
+        Ok(())
+    }
+
+    /// Count a reaction toward its reactor's accuracy once the market is settled.
+    /// Permissionless crank: a Back aligns when the opinion's combined_score is
+    /// at least REACTION_ALIGNMENT_THRESHOLD, a Slash when it is below.
+    pub fn score_reaction(ctx: Context<ScoreReaction>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Settled, OpinionError::MarketNotAwaitingSettlement);
+
+        let reaction = &ctx.accounts.reaction;
+        require!(!reaction.scored, OpinionError::ReactionAlreadyScored);
+
+        let combined_score = ctx.accounts.opinion.load()?.combined_score();
+        let aligned = match reaction.reaction_type {
+            ReactionType::Back => combined_score >= REACTION_ALIGNMENT_THRESHOLD,
+            ReactionType::Slash => combined_score < REACTION_ALIGNMENT_THRESHOLD,
+        };
+        let reactor_key = reaction.reactor;
+        let reaction_type = reaction.reaction_type.clone();
+        let stake_amount = reaction.stake_amount;
+
+        let stats = &mut ctx.accounts.reactor_stats;
+        stats.reactor = reactor_key;
+        stats.reactions_scored = stats.reactions_scored.saturating_add(1);
+        if aligned {
+            stats.reactions_aligned = stats.reactions_aligned.saturating_add(1);
+        }
+        stats.total_staked = stats.total_staked.saturating_add(stake_amount);
+        stats.bump = ctx.bumps.reactor_stats;
+        stats.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        ctx.accounts.leaderboard.record(stats);
+        ctx.accounts.reaction.scored = true;
+
+        emit_cpi!(ReactionScoredEvent {
+            market: ctx.accounts.market.key(),
+            opinion: ctx.accounts.opinion.key(),
+            reactor: reactor_key,
+            reaction_type,
+            combined_score,
+            aligned,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Oracle commits a Switchboard on-demand randomness account for the
+    /// market's lottery. The randomness must have been committed in the
+    /// previous slot (same transaction bundle), so its value is still unknown.
+    /// One request per market; it cannot be re-rolled.
+    pub fn request_lottery_randomness(ctx: Context<RequestLotteryRandomness>) -> Result<()> {
+        require!(
+            ctx.accounts.market.state == MarketState::Scored,
+            OpinionError::MarketNotScored
+        );
+        ctx.accounts.market.require_settlement_mode(SettlementMode::Lottery)?;
+
+        let clock = Clock::get()?;
+        let seed_slot = {
+            let data = ctx.accounts.randomness_account.try_borrow_data()?;
+            let randomness = RandomnessAccountData::parse(data)
+                .map_err(|_| error!(OpinionError::InvalidRandomnessAccount))?;
+            require!(
+                randomness.seed_slot == clock.slot.saturating_sub(1),
+                OpinionError::InvalidRandomnessAccount
+            );
+            randomness.seed_slot
+        };
+
+        let market_key = ctx.accounts.market.key();
+        let request = &mut ctx.accounts.randomness_request;
+        request.market = market_key;
+        request.randomness_account = ctx.accounts.randomness_account.key();
+        request.seed_slot = seed_slot;
+        request.randomness = None;
+        request.requested_at = clock.unix_timestamp;
+        request.fulfilled_at = None;
+        request.bump = ctx.bumps.randomness_request;
+        request.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        emit_cpi!(VrfRandomnessRequestedEvent {
+            market: market_key,
+            vrf_request_id: seed_slot,
+            request_timestamp: clock.unix_timestamp,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Anyone stores the revealed Switchboard randomness for a market's
+    /// lottery. Must run in the same slot as the Switchboard reveal.
+    pub fn consume_randomness(ctx: Context<ConsumeRandomness>) -> Result<()> {
+        let request = &ctx.accounts.randomness_request;
+        require!(request.randomness.is_none(), OpinionError::RandomnessAlreadyConsumed);
+
+        let clock = Clock::get()?;
+        let value = {
+            let data = ctx.accounts.randomness_account.try_borrow_data()?;
+            let randomness = RandomnessAccountData::parse(data)
+                .map_err(|_| error!(OpinionError::InvalidRandomnessAccount))?;
+            require!(
+                randomness.seed_slot == request.seed_slot,
+                OpinionError::InvalidRandomnessAccount
+            );
+            randomness
+                .get_value(&clock)
+                .map_err(|_| error!(OpinionError::RandomnessNotRevealed))?
+        };
+
+        let request = &mut ctx.accounts.randomness_request;
+        request.randomness = Some(value);
+        request.fulfilled_at = Some(clock.unix_timestamp);
+
+        emit_cpi!(VrfRandomnessFulfilledEvent {
+            market: request.market,
+            vrf_request_id: request.seed_slot,
+            randomness: value,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Anyone settles the lottery from consumed Switchboard randomness. The
+    /// winner is drawn stake-weighted over every opinion of the market.
+    /// remaining_accounts: all opinions, sorted ascending by address, then the
+    /// settle hook programs.
+    pub fn run_lottery_random<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RunLotteryRandom<'info>>,
+    ) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.state == MarketState::Scored, OpinionError::MarketNotScored);
+        require!(market.total_stake > 0, OpinionError::EmptyPrizePool);
+        let staker_count = market.staker_count as usize;
+        require!(
+            ctx.remaining_accounts.len() >= staker_count,
+            OpinionError::LotteryOpinionsMismatch
+        );
+        let (opinion_infos, hook_programs) = ctx.remaining_accounts.split_at(staker_count);
+        let randomness = ctx
+            .accounts
+            .randomness_request
+            .randomness
+            .ok_or(OpinionError::RandomnessNotConsumed)?;
+
+        let market_key = market.key();
+        let mut entries = Vec::with_capacity(staker_count);
+        let mut total_weight = 0u64;
+        let mut previous: Option<Pubkey> = None;
+        for opinion_info in opinion_infos.iter() {
+            require!(
+                previous.is_none_or(|key| key < *opinion_info.key),
+                OpinionError::LotteryOpinionsMismatch
+            );
+            previous = Some(*opinion_info.key);
+
+            let opinion_loader = AccountLoader::<Opinion>::try_from(opinion_info)?;
+            let opinion = opinion_loader.load()?;
+            require!(opinion.market == market_key, OpinionError::OpinionMarketMismatch);
+            total_weight = total_weight
+                .checked_add(opinion.stake_amount())
+                .ok_or(OpinionError::Overflow)?;
+            entries.push((opinion.staker, opinion.stake_amount()));
+        }
+        require!(total_weight > 0, OpinionError::EmptyPrizePool);
+
+        let mut draw_bytes = [0u8; 8];
+        draw_bytes.copy_from_slice(&randomness[..8]);
+        let draw = u64::from_le_bytes(draw_bytes) % total_weight;
+
+        let mut cumulative = 0u64;
+        let winner = entries
+            .iter()
+            .find(|(_, stake)| {
+                cumulative += stake;
+                draw < cumulative
+            })
+            .map(|(staker, _)| *staker)
+            .ok_or(OpinionError::LotteryOpinionsMismatch)?;
+        require!(
+            ctx.accounts.winner_token_account.owner == winner,
+            OpinionError::Unauthorized
+        );
+
+        let event = distribute_lottery_prize(
+            &mut ctx.accounts.market,
+            EscrowAccounts {
+                token_account: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+                memo_program: ctx.accounts.memo_program.to_account_info(),
+            },
+            ctx.accounts.winner_token_account.to_account_info(),
+            ctx.accounts.treasury_usdc.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.config,
+            winner,
+            ctx.accounts.reviewer.as_ref().map(|r| r.key()),
+        )?;
+        ctx.accounts.global_stats.record_settlement(&ctx.accounts.market);
+        sync_registry(ctx.accounts.market.key(), &ctx.accounts.market, ctx.accounts.registry_page.as_mut())?;
+        emit_cpi!(event);
+        notify_settle_hooks(
+            &ctx.accounts.market,
+            ctx.accounts.settle_hooks.as_ref(),
+            ctx.accounts.hook_authority.as_ref(),
+            ctx.bumps.hook_authority,
+            hook_programs,
+        )?;
+        Ok(())
+    }
+
+    /// Anyone moves a market the oracle failed to finalize by score_deadline
+    /// (or earlier, if OracleStatus shows the oracle silent past
+    /// ORACLE_LIVENESS_TIMEOUT) into Refunding, after which every stake is
+    /// returned exactly once.
+    /// The missed deadline slashes the oracle bond into the refunds, but only
+    /// for a market the oracle owed a score: one closed (or scored) with stake
+    /// in it. An empty market, or one nobody closed, costs the oracle nothing.
+    /// A challenger's bond still in escrow is returned first, so take_refund
+    /// never pays it out to stakers.
+    pub fn enter_refunding(ctx: Context<EnterRefunding>) -> Result<()> {
+        let clock = Clock::get()?;
+        let market_key = ctx.accounts.market.key();
+        let market = &ctx.accounts.market;
+        let oracle_at_fault = matches!(market.state, MarketState::Closed | MarketState::Scored)
+            && market.total_stake > 0;
+        let oracle_dead = ctx
+            .accounts
+            .oracle_status
+            .as_ref()
+            .is_some_and(|status| !status.is_alive(clock.unix_timestamp));
+        if !ctx.accounts.market.begin_refunding(clock.unix_timestamp, oracle_dead)? {
+            return Ok(());
+        }
+        if ctx.accounts.market.optimistic_open {
+            let pending = ctx
+                .accounts
+                .optimistic_settlement
+                .as_mut()
+                .ok_or(OpinionError::ChallengeBondAccountsRequired)?;
+            if let Some(challenger) = pending.challenger.filter(|_| pending.bond_amount > 0) {
+                let bond = pending.bond_amount;
+                let (Some(challenger_usdc), Some(escrow_authority)) =
+                    (ctx.accounts.challenger_usdc.as_ref(), ctx.accounts.escrow_authority.as_ref())
+                else {
+                    return err!(OpinionError::ChallengeBondAccountsRequired);
+                };
+                release_dispute_bond(
+                    &ctx.accounts.market,
+                    EscrowAccounts {
+                        token_account: ctx.accounts.escrow_token_account.to_account_info(),
+                        authority: escrow_authority.to_account_info(),
+                        memo_program: ctx.accounts.memo_program.to_account_info(),
+                    },
+                    challenger_usdc.to_account_info(),
+                    ctx.accounts.token_program.to_account_info(),
+                    bond,
+                )?;
+                pending.bond_amount = 0;
+                emit_cpi!(DisputeResolvedEvent {
+                    market: market_key,
+                    challenger,
+                    challenger_won: true,
+                    bond,
+                    seq: ctx.accounts.market.next_event_seq(),
+                });
+            }
+            ctx.accounts.market.optimistic_open = false;
+        }
+        ctx.accounts.global_stats.record_market_exit(&ctx.accounts.market);
+        sync_registry(ctx.accounts.market.key(), &ctx.accounts.market, ctx.accounts.registry_page.as_mut())?;
+
+        let slashed = if oracle_at_fault {
+            slash_oracle_bond(
+                &ctx.accounts.config,
+                ctx.accounts.oracle_bond.as_mut(),
+                ctx.accounts.oracle_bond_vault.as_ref().map(|v| v.to_account_info()),
+                ctx.accounts.escrow_token_account.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.memo_program.to_account_info(),
+                &ctx.accounts.market.uuid,
+            )?
+        } else {
+            0
+        };
+        if slashed > 0 {
+            ctx.accounts.market.oracle_slashed = slashed;
+            emit_cpi!(OracleBondSlashedEvent {
+                market: market_key,
+                amount: slashed,
+                remaining_bond: ctx.accounts.oracle_bond.as_ref().map_or(0, |b| b.amount),
+                seq: ctx.accounts.market.next_event_seq(),
+            });
+        }
+
+        emit_cpi!(refunding_event(&mut ctx.accounts.market, market_key));
+        Ok(())
+    }
+
+    /// Refund a staker's opinion stake once the market is Refunding, plus
+    /// any of their own reaction stakes on this market passed as
+    /// remaining_accounts (reaction, target opinion) pairs — those Reaction
+    /// accounts are closed. Every refund is the principal's pro-rata share of
+    /// the escrow balance (see Market::take_refund). Each opinion refunds once.
+    /// A confidential stake refunds after its staker revealed it and the
+    /// oracle released the confidential escrow.
+    pub fn recover_stake<'info>(ctx: Context<'_, '_, 'info, 'info, RecoverStake<'info>>) -> Result<()> {
+        let clock = Clock::get()?;
+        ctx.accounts.market.require_refunding(clock.unix_timestamp)?;
+        require!(ctx.remaining_accounts.len().is_multiple_of(2), OpinionError::BatchAccountsMismatch);
+        require!(
+            ctx.remaining_accounts.len() / 2 <= MAX_BATCH_REACTIONS,
+            OpinionError::BatchTooLarge
+        );
+
+        let mut opinion = ctx.accounts.opinion.load_mut()?;
+        require!(!opinion.paid() && !opinion.refunded(), OpinionError::AlreadyPaid);
+        if ctx.accounts.market.is_confidential() {
+            // Confidential stakes are refunded once revealed and released
+            require!(!opinion.confidential_pending(), OpinionError::ConfidentialStakeUnrevealed);
+            require!(
+                ctx.accounts.market.confidential_released,
+                OpinionError::ConfidentialStakesNotReleased
+            );
+        }
+        opinion.set_refunded(true);
+        let mut principal = opinion.stake_amount();
+        let mut escrow_balance = ctx.accounts.escrow_token_account.amount;
+        let mut refund = ctx.accounts.market.take_refund(principal, escrow_balance)?;
+        escrow_balance -= refund;
+
+        let market_key = ctx.accounts.market.key();
+        let staker_key = ctx.accounts.staker.key();
+        for pair in ctx.remaining_accounts.chunks(2) {
+            let reaction: Account<'info, Reaction> = Account::try_from(&pair[0])?;
+            let target_loader = AccountLoader::<Opinion>::try_from(&pair[1])?;
+            let mut target = target_loader.load_mut()?;
+            require_keys_eq!(reaction.reactor, staker_key, OpinionError::Unauthorized);
+            require_keys_eq!(reaction.opinion, pair[1].key(), OpinionError::OpinionMarketMismatch);
+            require_keys_eq!(target.market, market_key, OpinionError::OpinionMarketMismatch);
+
+            let stake_amount = reaction.stake_amount;
+            let reaction_refund = ctx.accounts.market.take_refund(stake_amount, escrow_balance)?;
+            escrow_balance -= reaction_refund;
+            refund = refund.checked_add(reaction_refund).ok_or(OpinionError::Overflow)?;
+            principal = principal.checked_add(stake_amount).ok_or(OpinionError::Overflow)?;
+
+            // Withdrawn stake no longer counts toward Layer 1 or the market pool
+            match reaction.reaction_type {
+                ReactionType::Back => {
+                    target.backing_total = target.backing_total.saturating_sub(stake_amount);
+                }
+                ReactionType::Slash => {
+                    target.slashing_total = target.slashing_total.saturating_sub(stake_amount);
+                }
+            }
+            reaction.close(ctx.accounts.staker.to_account_info())?;
+            let market = &mut ctx.accounts.market;
+            market.total_stake = market.total_stake.saturating_sub(stake_amount);
+            market.remove_reaction_stake(&reaction.reaction_type, stake_amount);
+        }
+
+        let market = &ctx.accounts.market;
+        let escrow_bump = market.escrow_authority_bump;
+        let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+        let signer_seeds = &[seeds];
+
+        let recovery_cpi = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: ctx.accounts.staker_usdc.to_account_info(),
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_with_memo(
+            recovery_cpi,
+            refund,
+            &ctx.accounts.memo_program,
+            "stake_refund",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        msg!(
+            "Stake recovered: staker={} principal={} amount={}",
+            staker_key,
+            principal,
+            refund
+        );
+
+        Ok(())
+    }
+
+    /// Refund a reactor's Back/Slash stake (its pro-rata share of the escrow,
+    /// see Market::take_refund) once the market is Refunding, or at par once
+    /// a no-loss market is Settled. The Reaction account is closed to prevent
+    /// replay.
+    pub fn recover_reaction_stake(ctx: Context<RecoverReactionStake>) -> Result<()> {
+        let clock = Clock::get()?;
+        let market = &ctx.accounts.market;
+        let reaction = &ctx.accounts.reaction;
+        let stake_amount = reaction.stake_amount;
+        let reaction_type = reaction.reaction_type.clone();
+
+        if market.no_loss && market.state == MarketState::Settled {
+            require!(clock.unix_timestamp < market.claim_deadline(), OpinionError::ClaimWindowClosed);
+            return return_no_loss_reaction(&ctx, stake_amount);
+        }
+        market.require_refunding(clock.unix_timestamp)?;
+
+        let escrow_balance = ctx.accounts.escrow_token_account.amount;
+        let refund = ctx.accounts.market.take_refund(stake_amount, escrow_balance)?;
+        let market = &ctx.accounts.market;
+
+        let market_key = market.key();
+        let escrow_bump = market.escrow_authority_bump;
+        let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+        let signer_seeds = &[seeds];
+
+        let recovery_cpi = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: ctx.accounts.reactor_usdc.to_account_info(),
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_with_memo(
+            recovery_cpi,
+            refund,
+            &ctx.accounts.memo_program,
+            "reaction_refund",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        // Withdrawn stake no longer counts toward Layer 1 or the market pool
+        let mut opinion = ctx.accounts.opinion.load_mut()?;
+        match reaction_type {
+            ReactionType::Back => {
+                opinion.backing_total = opinion.backing_total.saturating_sub(stake_amount);
+            }
+            ReactionType::Slash => {
+                opinion.slashing_total = opinion.slashing_total.saturating_sub(stake_amount);
+            }
+        }
+        let market = &mut ctx.accounts.market;
+        market.total_stake = market.total_stake.saturating_sub(stake_amount);
+        market.remove_reaction_stake(&reaction_type, stake_amount);
+
+        msg!("Reaction stake recovered: reactor={} amount={}", ctx.accounts.reactor.key(), refund);
+
+        Ok(())
+    }
+
+    /// Anyone opens a market's compressed reaction tree while it is Active.
+    /// Reactions can then be submitted with react_compressed, which costs no
+    /// per-reaction rent.
+    pub fn init_reaction_tree(ctx: Context<InitReactionTree>) -> Result<()> {
+        require!(ctx.accounts.market.state == MarketState::Active, OpinionError::MarketNotActive);
+
+        let mut tree = ctx.accounts.reaction_tree.load_init()?;
+        tree.market = ctx.accounts.market.key();
+        tree.bump = ctx.bumps.reaction_tree;
+        tree.schema_version = ACCOUNT_SCHEMA_VERSION;
+        tree.initialize();
+
+        msg!("Reaction tree opened: market={}", tree.market);
+        Ok(())
+    }
+
+    /// Anyone opens the ActivityLog of a market created before logs existed;
+    /// stakes, reactions and close_market require it.
+    pub fn init_activity_log(ctx: Context<InitActivityLog>) -> Result<()> {
+        let mut log = ctx.accounts.activity_log.load_init()?;
+        log.market = ctx.accounts.market.key();
+        log.bump = ctx.bumps.activity_log;
+        log.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        msg!("Activity log opened: market={}", log.market);
+        Ok(())
+    }
+
+    /// Back or Slash an opinion as a leaf in the market's ReactionTree
+    /// instead of a Reaction account. Stake, opinion totals and caps work as
+    /// in react_to_opinion; the leaf is logged in CompressedReactionEvent for
+    /// indexers. Unlike Reaction PDAs, a reactor may react more than once.
+    /// Returns the leaf index.
+    pub fn react_compressed(
+        ctx: Context<ReactCompressed>,
+        reaction_type: ReactionType,
+        stake_amount: u64,
+    ) -> Result<u32> {
+        require!(stake_amount >= MIN_STAKE, OpinionError::StakeTooSmall);
+        require!(stake_amount <= MAX_STAKE, OpinionError::StakeTooLarge);
+
+        let clock = Clock::get()?;
+        {
+            let market = &ctx.accounts.market;
+            require!(market.state == MarketState::Active, OpinionError::MarketNotActive);
+            require!(clock.unix_timestamp < market.reactions_close_at, OpinionError::MarketExpired);
+        }
+
+        let reactor_key = ctx.accounts.reactor.key();
+        let mut opinion = ctx.accounts.opinion.load_mut()?;
+        require!(reactor_key != opinion.staker, OpinionError::CannotReactToOwnOpinion);
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.reactor_usdc.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.reactor.to_account_info(),
+            },
+        );
+        transfer_with_memo(
+            cpi_ctx,
+            stake_amount,
+            &ctx.accounts.memo_program,
+            "reaction_stake",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        match reaction_type {
+            ReactionType::Back => {
+                opinion.backing_total = opinion.backing_total
+                    .checked_add(stake_amount)
+                    .ok_or(OpinionError::Overflow)?;
+            }
+            ReactionType::Slash => {
+                opinion.slashing_total = opinion.slashing_total
+                    .checked_add(stake_amount)
+                    .ok_or(OpinionError::Overflow)?;
+            }
+        }
+        ctx.accounts.market.check_reaction_caps(&opinion)?;
+
+        let market = &mut ctx.accounts.market;
+        market.total_stake = market.total_stake
+            .checked_add(stake_amount)
+            .ok_or(OpinionError::Overflow)?;
+        market.add_reaction_stake(&reaction_type, stake_amount)?;
+        ctx.accounts.global_stats.record_stake(stake_amount);
+        ctx.accounts
+            .activity_log
+            .load_mut()?
+            .record(reactor_key, (&reaction_type).into(), stake_amount, clock.slot);
+
+        let opinion_key = ctx.accounts.opinion.key();
+        let leaf = reaction_leaf(&opinion_key, &reactor_key, &reaction_type, stake_amount, false);
+        let mut tree = ctx.accounts.reaction_tree.load_mut()?;
+        let leaf_index = tree.append(leaf)?;
+
+        emit_cpi!(CompressedReactionEvent {
+            market: ctx.accounts.market.key(),
+            opinion: opinion_key,
+            reactor: reactor_key,
+            reaction_type: reaction_type.clone(),
+            stake_amount,
+            leaf_index,
+            leaf,
+            root: tree.root(),
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+        match reaction_type {
+            ReactionType::Back => emit_cpi!(YourOpinionBackedEvent {
+                staker: opinion.staker,
+                market: ctx.accounts.market.key(),
+                opinion: opinion_key,
+                reactor: reactor_key,
+                stake_amount,
+                backing_total: opinion.backing_total,
+                seq: ctx.accounts.market.next_event_seq(),
+            }),
+            ReactionType::Slash => emit_cpi!(YourOpinionSlashedEvent {
+                staker: opinion.staker,
+                market: ctx.accounts.market.key(),
+                opinion: opinion_key,
+                reactor: reactor_key,
+                stake_amount,
+                slashing_total: opinion.slashing_total,
+                seq: ctx.accounts.market.next_event_seq(),
+            }),
+        }
+
+        Ok(leaf_index)
+    }
+
+    /// Reactor recovers a compressed reaction's stake from a Refunding market
+    /// (or at par from a settled no-loss market), proving the leaf against
+    /// the tree. The leaf is zeroed so it can't be recovered twice.
+    pub fn recover_compressed_reaction(
+        ctx: Context<RecoverCompressedReaction>,
+        args: CompressedReactionProof,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let reactor_key = ctx.accounts.reactor.key();
+        let opinion_key = ctx.accounts.opinion.key();
+        let stake_amount = args.stake_amount;
+
+        let leaf = reaction_leaf(&opinion_key, &reactor_key, &args.reaction_type, stake_amount, args.scored);
+        let root = {
+            let mut tree = ctx.accounts.reaction_tree.load_mut()?;
+            tree.replace(args.root, args.leaf_index, &args.proof, leaf, [0u8; 32])?;
+            tree.root()
+        };
+
+        let market = &ctx.accounts.market;
+        let no_loss_return = market.no_loss && market.state == MarketState::Settled;
+        let amount = if no_loss_return {
+            require!(now < market.claim_deadline(), OpinionError::ClaimWindowClosed);
+            stake_amount
+        } else {
+            market.require_refunding(now)?;
+            let escrow_balance = ctx.accounts.escrow_token_account.amount;
+            ctx.accounts.market.take_refund(stake_amount, escrow_balance)?
+        };
+
+        let market_key = ctx.accounts.market.key();
+        let escrow_bump = ctx.accounts.market.escrow_authority_bump;
+        let seeds: &[&[u8]] = &[b"escrow_authority", market_key.as_ref(), &[escrow_bump]];
+        let signer_seeds = &[seeds];
+        let recovery_cpi = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: ctx.accounts.reactor_usdc.to_account_info(),
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_with_memo(
+            recovery_cpi,
+            amount,
+            &ctx.accounts.memo_program,
+            "reaction_refund",
+            Some(&ctx.accounts.market.uuid),
+        )?;
+
+        if !no_loss_return {
+            // Withdrawn stake no longer counts toward Layer 1 or the market pool
+            let mut opinion = ctx.accounts.opinion.load_mut()?;
+            match args.reaction_type {
+                ReactionType::Back => {
+                    opinion.backing_total = opinion.backing_total.saturating_sub(stake_amount);
+                }
+                ReactionType::Slash => {
+                    opinion.slashing_total = opinion.slashing_total.saturating_sub(stake_amount);
+                }
+            }
+            let market = &mut ctx.accounts.market;
+            market.total_stake = market.total_stake.saturating_sub(stake_amount);
+            market.remove_reaction_stake(&args.reaction_type, stake_amount);
+        }
+
+        emit_cpi!(ReactionLeafReplacedEvent {
+            market: ctx.accounts.market.key(),
+            leaf_index: args.leaf_index,
+            leaf: [0u8; 32],
+            root,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+        msg!("Compressed reaction stake recovered: reactor={} amount={}", reactor_key, amount);
+        Ok(())
+    }
+
+    /// Permissionless crank: counts a compressed reaction in its reactor's
+    /// accuracy stats once the market settles, like score_reaction. The leaf
+    /// is rewritten with `scored` set so it counts only once.
+    pub fn score_compressed_reaction(
+        ctx: Context<ScoreCompressedReaction>,
+        args: CompressedReactionProof,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.market.state == MarketState::Settled,
+            OpinionError::MarketNotAwaitingSettlement
+        );
+        require!(!args.scored, OpinionError::ReactionAlreadyScored);
+
+        let reactor_key = ctx.accounts.reactor.key();
+        let opinion_key = ctx.accounts.opinion.key();
+        let old_leaf = reaction_leaf(&opinion_key, &reactor_key, &args.reaction_type, args.stake_amount, false);
+        let new_leaf = reaction_leaf(&opinion_key, &reactor_key, &args.reaction_type, args.stake_amount, true);
+        let root = {
+            let mut tree = ctx.accounts.reaction_tree.load_mut()?;
+            tree.replace(args.root, args.leaf_index, &args.proof, old_leaf, new_leaf)?;
+            tree.root()
+        };
+
+        let combined_score = ctx.accounts.opinion.load()?.combined_score();
+        let aligned = match args.reaction_type {
+            ReactionType::Back => combined_score >= REACTION_ALIGNMENT_THRESHOLD,
+            ReactionType::Slash => combined_score < REACTION_ALIGNMENT_THRESHOLD,
+        };
+
+        let stats = &mut ctx.accounts.reactor_stats;
+        stats.reactor = reactor_key;
+        stats.reactions_scored = stats.reactions_scored.saturating_add(1);
+        if aligned {
+            stats.reactions_aligned = stats.reactions_aligned.saturating_add(1);
+        }
+        stats.total_staked = stats.total_staked.saturating_add(args.stake_amount);
+        stats.bump = ctx.bumps.reactor_stats;
+        stats.schema_version = ACCOUNT_SCHEMA_VERSION;
+
+        ctx.accounts.leaderboard.record(stats);
+
+        emit_cpi!(ReactionLeafReplacedEvent {
+            market: ctx.accounts.market.key(),
+            leaf_index: args.leaf_index,
+            leaf: new_leaf,
+            root,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+        emit_cpi!(ReactionScoredEvent {
+            market: ctx.accounts.market.key(),
+            opinion: opinion_key,
+            reactor: reactor_key,
+            reaction_type: args.reaction_type,
+            combined_score,
+            aligned,
+            seq: ctx.accounts.market.next_event_seq(),
+        });
+
+        Ok(())
+    }
+
+    /// Anyone creates the market's address lookup table (owned by the market
+    /// PDA, derived from `recent_slot`) and fills it with the accounts every
+    /// settlement and claim touches, so batch transactions fit the packet
+    /// limit. Once the table exists, later calls add any hot account it is
+    /// missing, e.g. after a treasury change. The payer covers the rent.
+    pub fn bootstrap_lookup_table(
+        ctx: Context<BootstrapLookupTable>,
+        recent_slot: u64,
+    ) -> Result<()> {
+        let market_key = ctx.accounts.market.key();
+        let market_uuid = ctx.accounts.market.uuid;
+        let market_bump = ctx.accounts.market.bump;
+        let seeds: &[&[u8]] = &[b"market", &market_uuid, &[market_bump]];
+        let table_key = ctx.accounts.lookup_table.key();
+
+        if ctx.accounts.market.lookup_table == Pubkey::default() {
+            let (create_ix, derived) =
+                create_lookup_table(market_key, ctx.accounts.payer.key(), recent_slot);
+            require_keys_eq!(table_key, derived, OpinionError::LookupTableMismatch);
+            invoke_signed(
+                &create_ix,
+                &[
+                    ctx.accounts.lookup_table.to_account_info(),
+                    ctx.accounts.market.to_account_info(),
+                    ctx.accounts.payer.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                    ctx.accounts.address_lookup_table_program.to_account_info(),
+                ],
+                &[seeds],
+            )?;
+            ctx.accounts.market.lookup_table = table_key;
+        } else {
+            require_keys_eq!(
+                table_key,
+                ctx.accounts.market.lookup_table,
+                OpinionError::LookupTableMismatch
+            );
+        }
+
+        let (event_authority, _) =
+            Pubkey::find_program_address(&[b"__event_authority"], &crate::ID);
+        let hot = [
+            market_key,
+            ctx.accounts.config.key(),
+            ctx.accounts.global_stats.key(),
+            ctx.accounts.escrow_token_account.key(),
+            ctx.accounts.escrow_authority.key(),
+            ctx.accounts.treasury_usdc.key(),
+            ctx.accounts.token_program.key(),
+            event_authority,
+            crate::ID,
+        ];
+        let missing: Vec<Pubkey> = {
+            let data = ctx.accounts.lookup_table.try_borrow_data()?;
+            let table = AddressLookupTable::deserialize(&data)
+                .map_err(|_| error!(OpinionError::LookupTableMismatch))?;
+            hot.into_iter().filter(|key| !table.addresses.contains(key)).collect()
+        };
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let added = missing.len();
+        let extend_ix =
+            extend_lookup_table(table_key, market_key, Some(ctx.accounts.payer.key()), missing);
+        invoke_signed(
+            &extend_ix,
+            &[
+                ctx.accounts.lookup_table.to_account_info(),
+                ctx.accounts.market.to_account_info(),
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.address_lookup_table_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+        msg!(
+            "Lookup table bootstrapped: market={} table={} added={}",
+            market_key,
+            table_key,
+            added
+        );
+        Ok(())
+    }
+
+    /// Anyone hands a baseline market's escrow token accounts from the market
+    /// PDA to its escrow authority PDA. Run after migrate_market; payouts and
+    /// refunds fail until it has. The creation fee escrow must be passed
+    /// while it still holds the fee.
+    pub fn migrate_escrow_authority(ctx: Context<MigrateEscrowAuthority>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.yield_deposited == 0, OpinionError::YieldStillDeposited);
+        require!(
+            market.creation_fee_escrowed == 0 || ctx.accounts.creation_fee_escrow.is_some(),
+            OpinionError::CreationFeeEscrowRequired
+        );
+
+        let market_uuid = market.uuid;
+        let market_bump = market.bump;
+        let seeds: &[&[u8]] = &[b"market", &market_uuid, &[market_bump]];
+        let signer_seeds = &[seeds];
+        let escrow_authority = ctx.accounts.escrow_authority.key();
+
+        let escrows = std::iter::once(ctx.accounts.escrow_token_account.to_account_info())
+            .chain(ctx.accounts.creation_fee_escrow.as_ref().map(|e| e.to_account_info()));
+        for escrow in escrows {
+            token::set_authority(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    SetAuthority {
+                        current_authority: ctx.accounts.market.to_account_info(),
+                        account_or_mint: escrow,
+                    },
+                    signer_seeds,
+                ),
+                AuthorityType::AccountOwner,
+                Some(escrow_authority),
+            )?;
+        }
+
+        ctx.accounts.market.escrow_authority_bump = ctx.bumps.escrow_authority;
+        msg!(
+            "Escrow authority migrated: market={} authority={}",
+            ctx.accounts.market.key(),
+            escrow_authority
+        );
+        Ok(())
+    }
+
+    /// Anyone upgrades a baseline market to the current layout. Its inline
+    /// statement moves to a new MarketStatement account and the market
+    /// account grows to Market::SPACE, the payer funding both. Until then
+    /// every other instruction rejects it.
+    pub fn migrate_market(ctx: Context<MigrateMarket>) -> Result<()> {
+        let info = ctx.accounts.market.to_account_info();
+        let payer = ctx.accounts.payer.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        require!(
+            !info.try_borrow_data()?.starts_with(MARKET_DISCRIMINATOR),
+            OpinionError::AlreadyMigrated
+        );
+        require!(
+            info.try_borrow_data()?.starts_with(&BASELINE_MARKET_DISCRIMINATOR),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        let (market, statement) =
+            BaselineMarket::deserialize(&mut &info.try_borrow_data()?[8..])?.split()?;
+
+        let market_key = info.key();
+        let bump = ctx.bumps.market_statement;
+        let statement_info = ctx.accounts.market_statement.to_account_info();
+        create_pda_account(
+            &payer,
+            &statement_info,
+            &system_program,
+            MarketStatement::space_for(statement.len()),
+            &[b"market_statement", market_key.as_ref(), &[bump]],
+        )?;
+        MarketStatement {
+            market: market_key,
+            statement,
+            bump,
+            schema_version: ACCOUNT_SCHEMA_VERSION,
+        }
+        .try_serialize(&mut &mut statement_info.try_borrow_mut_data()?[..])?;
+
+        grow_program_account(
+            &info,
+            &BASELINE_MARKET_DISCRIMINATOR,
+            Market::SPACE,
+            &payer,
+            &system_program,
+        )?;
+        market.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        emit_cpi!(AccountMigratedEvent {
+            account: market_key,
+            from_version: 0,
+            to_version: ACCOUNT_SCHEMA_VERSION,
+            seq: ctx.accounts.global_stats.next_event_seq(),
+        });
+        Ok(())
+    }
+
+    /// Anyone rewrites a baseline opinion in the packed layout, growing it to
+    /// Opinion::SPACE with the payer covering the extra rent. Until then
+    /// every other instruction rejects it.
+    pub fn migrate_opinion(ctx: Context<MigrateOpinion>) -> Result<()> {
+        let info = ctx.accounts.opinion.to_account_info();
+        require!(
+            !info.try_borrow_data()?.starts_with(OPINION_DISCRIMINATOR),
+            OpinionError::AlreadyMigrated
+        );
+        require!(
+            info.try_borrow_data()?.starts_with(&BASELINE_OPINION_DISCRIMINATOR),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        let opinion = BaselineOpinion::deserialize(&mut &info.try_borrow_data()?[8..])?.repack()?;
+
+        grow_program_account(
+            &info,
+            &BASELINE_OPINION_DISCRIMINATOR,
+            Opinion::SPACE,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        {
+            let mut data = info.try_borrow_mut_data()?;
+            data[..8].copy_from_slice(OPINION_DISCRIMINATOR);
+            data[8..Opinion::SPACE].copy_from_slice(bytemuck::bytes_of(&opinion));
+        }
+
+        emit_cpi!(AccountMigratedEvent {
+            account: info.key(),
+            from_version: 0,
+            to_version: ACCOUNT_SCHEMA_VERSION,
+            seq: ctx.accounts.global_stats.next_event_seq(),
+        });
+        Ok(())
+    }
+
+    /// The program's upgrade authority rewrites the baseline ProgramConfig in
+    /// the current layout, reallocating it to ProgramConfig::SPACE. Every
+    /// tunable the baseline lacked takes initialize's default, and the
+    /// upgrade authority becomes admin. Every instruction that takes the
+    /// config fails until this has run.
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        let info = ctx.accounts.config.to_account_info();
+        require!(info.data_len() < ProgramConfig::SPACE, OpinionError::AlreadyMigrated);
+        require!(
+            info.try_borrow_data()?.starts_with(ProgramConfig::DISCRIMINATOR),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        let old = BaselineConfig::deserialize(&mut &info.try_borrow_data()?[8..])?;
+
+        grow_program_account(
+            &info,
+            ProgramConfig::DISCRIMINATOR,
+            ProgramConfig::SPACE,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        ProgramConfig::with_defaults(
+            old.oracle_authority,
+            old.treasury,
+            old.usdc_mint,
+            ctx.accounts.authority.key(),
+            old.bump,
+        )
+        .try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        emit_cpi!(AccountMigratedEvent {
+            account: info.key(),
+            from_version: 0,
+            to_version: ACCOUNT_SCHEMA_VERSION,
+            seq: ctx.accounts.global_stats.next_event_seq(),
+        });
+        Ok(())
+    }
+}
+
+// ── Account Contexts ─────────────────────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub deployer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = deployer,
+        space = ProgramConfig::SPACE,
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub usdc_mint: Account<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(member: Pubkey)]
+pub struct AddOracleMember<'info> {
+    #[account(mut, constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = OracleMember::SPACE,
+        seeds = [b"oracle_member", member.as_ref()],
+        bump,
+    )]
+    pub oracle_member: Account<'info, OracleMember>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTreasuryLedger<'info> {
+    #[account(mut, constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = TreasuryLedger::SPACE,
+        seeds = [b"treasury_ledger"],
+        bump,
+    )]
+    pub treasury_ledger: Account<'info, TreasuryLedger>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeGlobalStats<'info> {
+    #[account(mut, constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = GlobalStats::SPACE,
+        seeds = [b"global_stats"],
+        bump,
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct EscrowYield<'info> {
+    #[account(constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    /// Lending receipt (e.g. cToken) account; must be owned by the escrow
+    /// authority
+    #[account(
+        mut,
+        constraint = receipt_token_account.owner == escrow_authority.key() @ OpinionError::Unauthorized,
+    )]
+    pub receipt_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: must be the lending program whitelisted in config
+    #[account(
+        executable,
+        constraint = lending_program.key() == config.lending_program
+            && config.lending_program != Pubkey::default() @ OpinionError::LendingProgramNotAllowed,
+    )]
+    pub lending_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeInsuranceVault<'info> {
+    #[account(mut, constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = InsuranceVault::SPACE,
+        seeds = [b"insurance_vault"],
+        bump,
+    )]
+    pub insurance_vault: Account<'info, InsuranceVault>,
+
+    #[account(
+        init,
+        payer = admin,
+        token::mint = usdc_mint,
+        token::authority = insurance_vault,
+        seeds = [b"insurance_vault_usdc"],
+        bump,
+    )]
+    pub insurance_vault_usdc: Account<'info, TokenAccount>,
+
+    #[account(constraint = usdc_mint.key() == config.usdc_mint @ OpinionError::MintMismatch)]
+    pub usdc_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePointsMint<'info> {
+    #[account(mut, constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        mint::decimals = 6,
+        mint::authority = points_mint,
+        mint::freeze_authority = points_mint,
+        seeds = [b"points_mint"],
+        bump,
+    )]
+    pub points_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptTreasury<'info> {
+    #[account(
+        constraint = new_treasury.key() == config.pending_treasury @ OpinionError::TreasuryMismatch,
+    )]
+    pub new_treasury: Signer<'info>,
+
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    /// Where protocol fees will be paid
+    #[account(
+        constraint = new_treasury_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = new_treasury_usdc.owner == new_treasury.key() @ OpinionError::TreasuryMismatch,
+        constraint = new_treasury_usdc.delegate.is_none()
+            && new_treasury_usdc.close_authority.is_none()
+            && !new_treasury_usdc.is_frozen() @ OpinionError::UnsafeTreasuryAccount,
+    )]
+    pub new_treasury_usdc: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeReputationMint<'info> {
+    #[account(mut, constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    /// CHECK: created here as a non-transferable Token-2022 mint
+    #[account(mut, seeds = [b"reputation_mint"], bump)]
+    pub reputation_mint: UncheckedAccount<'info>,
+
+    pub token_2022_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeBadgeTree<'info> {
+    #[account(mut, constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    /// CHECK: PDA that creates badge trees and signs every badge mint
+    #[account(seeds = [b"badge_authority"], bump)]
+    pub badge_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Bubblegum tree config, initialized by create_tree
+    #[account(
+        mut,
+        seeds = [merkle_tree.key().as_ref()],
+        seeds::program = BUBBLEGUM_PROGRAM_ID,
+        bump,
+    )]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: pre-allocated concurrent Merkle tree, initialized by create_tree
+    #[account(mut, owner = ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: the noop program
+    #[account(address = NOOP_PROGRAM_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: the account compression program
+    #[account(address = ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: the Bubblegum program
+    #[account(address = BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MintWinnerBadge<'info> {
+    /// Receives the badge and pays the mint
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        constraint = opinion.load()?.market == market.key(),
+        constraint = opinion.load()?.staker == staker.key() @ OpinionError::Unauthorized,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    /// CHECK: PDA that creates badge trees and signs every badge mint;
+    /// Bubblegum rejects trees it does not control
+    #[account(seeds = [b"badge_authority"], bump)]
+    pub badge_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Bubblegum tree config of `merkle_tree`
+    #[account(
+        mut,
+        seeds = [merkle_tree.key().as_ref()],
+        seeds::program = BUBBLEGUM_PROGRAM_ID,
+        bump,
+    )]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: a badge tree created by initialize_badge_tree
+    #[account(mut, owner = ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: the noop program
+    #[account(address = NOOP_PROGRAM_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: the account compression program
+    #[account(address = ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: the Bubblegum program
+    #[account(address = BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MintMarketAsset<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [b"market_statement", market.key().as_ref()],
+        bump = market_statement.bump,
+    )]
+    pub market_statement: Account<'info, MarketStatement>,
+
+    /// CHECK: the asset account, created by Core under this PDA's signature
+    #[account(mut, seeds = [b"market_asset", market.key().as_ref()], bump)]
+    pub market_asset: UncheckedAccount<'info>,
+
+    /// CHECK: the market creator, who owns the asset
+    #[account(address = market.creator)]
+    pub creator: UncheckedAccount<'info>,
+
+    /// CHECK: the Metaplex Core program
+    #[account(address = MPL_CORE_PROGRAM_ID)]
+    pub core_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SyncMarketAsset<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"market", market.uuid.as_ref()], bump = market.bump)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [b"market_statement", market.key().as_ref()],
+        bump = market_statement.bump,
+    )]
+    pub market_statement: Account<'info, MarketStatement>,
+
+    /// CHECK: the market's Core asset
+    #[account(mut, address = market.asset @ OpinionError::Unauthorized)]
+    pub market_asset: UncheckedAccount<'info>,
+
+    /// CHECK: the Metaplex Core program
+    #[account(address = MPL_CORE_PROGRAM_ID)]
+    pub core_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimFeePoints<'info> {
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        constraint = opinion.load()?.market == market.key(),
+        constraint = opinion.load()?.staker == staker.key() @ OpinionError::Unauthorized,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    #[account(mut, seeds = [b"points_mint"], bump)]
+    pub points_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = staker,
+        associated_token::mint = points_mint,
+        associated_token::authority = staker,
+    )]
+    pub staker_points: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimReputation<'info> {
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        constraint = opinion.load()?.market == market.key(),
+        constraint = opinion.load()?.staker == staker.key() @ OpinionError::Unauthorized,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation_mint"],
+        bump,
+        mint::token_program = token_2022_program,
+    )]
+    pub reputation_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = staker,
+        associated_token::mint = reputation_mint,
+        associated_token::authority = staker,
+        associated_token::token_program = token_2022_program,
+    )]
+    pub staker_reputation: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    pub token_2022_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordOpinionScore<'info> {
+    /// Permissionless crank — pays for the staker's profile on first use
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"market", market.uuid.as_ref()], bump = market.bump)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        constraint = opinion.load()?.market == market.key() @ OpinionError::OpinionMarketMismatch,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = UserProfile::SPACE,
+        seeds = [b"user_profile", opinion.load()?.staker.as_ref()],
+        bump,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CompensateFromInsurance<'info> {
+    #[account(constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    /// Market the compensation is for
+    #[account(mut, seeds = [b"market", market.uuid.as_ref()], bump = market.bump)]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, seeds = [b"insurance_vault"], bump = insurance_vault.bump)]
+    pub insurance_vault: Account<'info, InsuranceVault>,
+
+    #[account(mut, seeds = [b"insurance_vault_usdc"], bump)]
+    pub insurance_vault_usdc: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = recipient_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+    )]
+    pub recipient_usdc: Account<'info, TokenAccount>,
+
+    /// Required when the oracle committee is enabled
+    #[account(
+        seeds = [b"settlement_proposal", market.key().as_ref()],
+        bump = settlement_proposal.bump,
+    )]
+    pub settlement_proposal: Option<Account<'info, SettlementProposal>>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeScoringQueue<'info> {
+    #[account(mut, constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = ScoringQueue::SPACE,
+        seeds = [b"scoring_queue"],
+        bump,
+    )]
+    pub scoring_queue: Box<Account<'info, ScoringQueue>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EnqueueMarket<'info> {
+    /// Any keeper
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, seeds = [b"scoring_queue"], bump = scoring_queue.bump)]
+    pub scoring_queue: Box<Account<'info, ScoringQueue>>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshScoringQueue<'info> {
+    /// Any keeper
+    pub caller: Signer<'info>,
+
+    #[account(mut, seeds = [b"scoring_queue"], bump = scoring_queue.bump)]
+    pub scoring_queue: Box<Account<'info, ScoringQueue>>,
+}
+
+#[derive(Accounts)]
+pub struct SetScoringVerifier<'info> {
+    #[account(mut, constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = ScoringVerifier::SPACE,
+        seeds = [b"scoring_verifier"],
+        bump,
+    )]
+    pub scoring_verifier: Account<'info, ScoringVerifier>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetMarketOracle<'info> {
+    #[account(constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveOracleMember<'info> {
+    #[account(mut, constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"oracle_member", oracle_member.member.as_ref()],
+        bump = oracle_member.bump,
+    )]
+    pub oracle_member: Account<'info, OracleMember>,
+}
+
+#[derive(Accounts)]
+#[instruction(program: Pubkey)]
+pub struct RegisterPartner<'info> {
+    #[account(mut, constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = Partner::SPACE,
+        seeds = [b"partner", program.as_ref()],
+        bump,
+    )]
+    pub partner: Account<'info, Partner>,
+
+    #[account(constraint = payout_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch)]
+    pub payout_usdc: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePartner<'info> {
+    #[account(constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"partner", partner.program.as_ref()],
+        bump = partner.bump,
+    )]
+    pub partner: Account<'info, Partner>,
+
+    #[account(constraint = payout_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch)]
+    pub payout_usdc: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct RemovePartner<'info> {
+    #[account(mut, constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"partner", partner.program.as_ref()],
+        bump = partner.bump,
+    )]
+    pub partner: Account<'info, Partner>,
+}
+
+#[derive(Accounts)]
+pub struct AddCharity<'info> {
+    #[account(mut, constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = Charity::SPACE,
+        seeds = [b"charity", charity_usdc.key().as_ref()],
+        bump,
+    )]
+    pub charity: Account<'info, Charity>,
+
+    #[account(constraint = charity_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch)]
+    pub charity_usdc: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveCharity<'info> {
+    #[account(mut, constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"charity", charity.usdc_account.as_ref()],
+        bump = charity.bump,
+    )]
+    pub charity: Account<'info, Charity>,
+}
+
+#[derive(Accounts)]
+#[instruction(program: Pubkey)]
+pub struct AddHookProgram<'info> {
+    #[account(mut, constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = HookProgram::SPACE,
+        seeds = [b"hook_program", program.as_ref()],
+        bump,
+    )]
+    pub hook_program: Account<'info, HookProgram>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveHookProgram<'info> {
+    #[account(mut, constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"hook_program", hook_program.program.as_ref()],
+        bump = hook_program.bump,
+    )]
+    pub hook_program: Account<'info, HookProgram>,
+}
+
+#[derive(Accounts)]
+pub struct SubscribeSettleHook<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The subscribing program's ["settle_hook"] PDA, signing via CPI
+    pub hook_signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"hook_program", hook_program.program.as_ref()],
+        bump = hook_program.bump,
+    )]
+    pub hook_program: Account<'info, HookProgram>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+        constraint = !matches!(market.state, MarketState::Settled | MarketState::Refunding)
+            @ OpinionError::MarketAlreadySettled,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = SettleHooks::SPACE,
+        seeds = [b"settle_hooks", market.key().as_ref()],
+        bump,
+    )]
+    pub settle_hooks: Account<'info, SettleHooks>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnsubscribeSettleHook<'info> {
+    /// The hook program's ["settle_hook"] PDA or the admin
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"settle_hooks", market.key().as_ref()],
+        bump = settle_hooks.bump,
+    )]
+    pub settle_hooks: Account<'info, SettleHooks>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ApproveSettlement<'info> {
+    #[account(mut)]
+    pub member: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    /// Existence of this PDA proves committee membership
+    #[account(
+        seeds = [b"oracle_member", member.key().as_ref()],
+        bump = oracle_member.bump,
+    )]
+    pub oracle_member: Account<'info, OracleMember>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Scored @ OpinionError::MarketNotScored,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init_if_needed,
+        payer = member,
+        space = SettlementProposal::SPACE,
+        seeds = [b"settlement_proposal", market.key().as_ref()],
+        bump,
+    )]
+    pub settlement_proposal: Account<'info, SettlementProposal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct OracleHeartbeat<'info> {
+    /// The config's oracle or a market's oracle_override; pings its own status
+    #[account(mut)]
+    pub oracle_authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = oracle_authority,
+        space = OracleStatus::SPACE,
+        seeds = [b"oracle_status", oracle_authority.key().as_ref()],
+        bump,
+    )]
+    pub oracle_status: Account<'info, OracleStatus>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositOracleBond<'info> {
+    /// The config's oracle or a market's oracle_override; bonds under its own key
+    #[account(mut)]
+    pub oracle_authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = oracle_authority,
+        space = OracleBond::SPACE,
+        seeds = [b"oracle_bond", oracle_authority.key().as_ref()],
+        bump,
+    )]
+    pub oracle_bond: Account<'info, OracleBond>,
+
+    #[account(
+        init_if_needed,
+        payer = oracle_authority,
+        token::mint = usdc_mint,
+        token::authority = oracle_bond,
+        seeds = [b"oracle_bond_vault", oracle_authority.key().as_ref()],
+        bump,
+    )]
+    pub oracle_bond_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = oracle_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = oracle_usdc.owner == oracle_authority.key(),
+    )]
+    pub oracle_usdc: Account<'info, TokenAccount>,
+
+    #[account(constraint = usdc_mint.key() == config.usdc_mint @ OpinionError::MintMismatch)]
+    pub usdc_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawOracleBond<'info> {
+    pub oracle_authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"oracle_bond", oracle_authority.key().as_ref()],
+        bump = oracle_bond.bump,
+    )]
+    pub oracle_bond: Account<'info, OracleBond>,
+
+    #[account(mut, seeds = [b"oracle_bond_vault", oracle_authority.key().as_ref()], bump)]
+    pub oracle_bond_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = oracle_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = oracle_usdc.owner == oracle_authority.key(),
+    )]
+    pub oracle_usdc: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateOracleBond<'info> {
+    #[account(
+        mut,
+        constraint = oracle_authority.key() == legacy_bond.oracle @ OpinionError::Unauthorized,
+    )]
+    pub oracle_authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(mut, close = oracle_authority, seeds = [b"oracle_bond"], bump = legacy_bond.bump)]
+    pub legacy_bond: Account<'info, OracleBond>,
+
+    #[account(mut, seeds = [b"oracle_bond_vault"], bump)]
+    pub legacy_bond_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = oracle_authority,
+        space = OracleBond::SPACE,
+        seeds = [b"oracle_bond", oracle_authority.key().as_ref()],
+        bump,
+    )]
+    pub oracle_bond: Account<'info, OracleBond>,
+
+    #[account(
+        init_if_needed,
+        payer = oracle_authority,
+        token::mint = usdc_mint,
+        token::authority = oracle_bond,
+        seeds = [b"oracle_bond_vault", oracle_authority.key().as_ref()],
+        bump,
+    )]
+    pub oracle_bond_vault: Account<'info, TokenAccount>,
+
+    #[account(constraint = usdc_mint.key() == config.usdc_mint @ OpinionError::MintMismatch)]
+    pub usdc_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct SetOracleBondParams<'info> {
+    #[account(constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        seeds = [b"oracle_bond", config.oracle_authority.as_ref()],
+        bump = oracle_bond.bump,
+    )]
+    pub oracle_bond: Account<'info, OracleBond>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(statement: String, duration_secs: u64, uuid: [u8; 16])]
+pub struct CreateMarket<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = Market::SPACE,
+        seeds = [b"market", uuid.as_ref()],
+        bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    /// Sized to the statement; for create_market_hashed `statement` is the CID
+    #[account(
+        init,
+        payer = creator,
+        space = MarketStatement::space_for(statement.len()),
+        seeds = [b"market_statement", market.key().as_ref()],
+        bump,
+    )]
+    pub market_statement: Box<Account<'info, MarketStatement>>,
+
+    /// First page of the market's audit trail
+    #[account(
+        init,
+        payer = creator,
+        space = AuditLog::SPACE,
+        seeds = [b"audit_log", market.key().as_ref(), &0u32.to_le_bytes()],
+        bump,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Box<Account<'info, GlobalStats>>,
+
+    /// Page the market is listed on; the first market on a page pays its rent
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = MarketRegistryPage::SPACE,
+        seeds = [b"market_registry".as_ref(), &global_stats.next_registry_page().to_le_bytes()],
+        bump,
+    )]
+    pub market_registry: Box<Account<'info, MarketRegistryPage>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = ActivityLog::SPACE,
+        seeds = [b"activity_log", market.key().as_ref()],
+        bump,
+    )]
+    pub activity_log: AccountLoader<'info, ActivityLog>,
+
+    #[account(
+        init,
+        payer = creator,
+        token::mint = usdc_mint,
+        token::authority = escrow_authority,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(seeds = [b"escrow_authority", market.key().as_ref()], bump)]
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = creator_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = creator_usdc.owner == creator.key(),
+    )]
+    pub creator_usdc: Account<'info, TokenAccount>,
+
+    /// Holds the creation fee until release_creation_fee
+    #[account(
+        init,
+        payer = creator,
+        token::mint = usdc_mint,
+        token::authority = escrow_authority,
+        seeds = [b"creation_fee", market.key().as_ref()],
+        bump,
+    )]
+    pub creation_fee_escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(constraint = usdc_mint.key() == config.usdc_mint @ OpinionError::MintMismatch)]
+    pub usdc_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = CreatorProfile::SPACE,
+        seeds = [b"creator_profile", creator.key().as_ref()],
+        bump,
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    /// CHECK: Solana Pay reference named by the payment request; only its key
+    /// is used
+    pub reference: Option<UncheckedAccount<'info>>,
+
+    /// Record of `reference`; pass with it to make the payment matchable
+    #[account(
+        init,
+        payer = creator,
+        space = PaymentReference::SPACE,
+        seeds = [
+            b"payment_reference".as_ref(),
+            reference.as_ref().map_or(Pubkey::default(), |r| r.key()).as_ref(),
+        ],
+        bump,
+    )]
+    pub payment_reference: Option<Box<Account<'info, PaymentReference>>>,
+
+    /// CHECK: instructions sysvar, read to record a CPI caller as created_via
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    /// Registry entry of the CPI caller, to owe it its revenue share
+    #[account(
+        mut,
+        seeds = [b"partner", partner.program.as_ref()],
+        bump = partner.bump,
+    )]
+    pub partner: Option<Box<Account<'info, Partner>>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SponsorMarket<'info> {
+    pub sponsor: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = sponsor_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = sponsor_usdc.owner == sponsor.key(),
+    )]
+    pub sponsor_usdc: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReleaseCreationFee<'info> {
+    /// Any keeper (or the creator)
+    pub caller: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"creation_fee", market.key().as_ref()],
+        bump,
+    )]
+    pub creation_fee_escrow: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    /// CHECK: the market creator; receives the fee account's rent
+    #[account(mut, address = market.creator @ OpinionError::Unauthorized)]
+    pub creator: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = creator_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = creator_usdc.owner == market.creator @ OpinionError::Unauthorized,
+    )]
+    pub creator_usdc: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = treasury_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = treasury_usdc.owner == config.treasury @ OpinionError::TreasuryMismatch,
+    )]
+    pub treasury_usdc: Account<'info, TokenAccount>,
+
+    /// CHECK: Partner entry of market.created_via, required when the partner
+    /// is owed a share; deserialized in the handler, since a removed
+    /// partner's share stays with the treasury
+    #[account(mut, seeds = [b"partner", market.created_via.as_ref()], bump)]
+    pub partner: Option<UncheckedAccount<'info>>,
+
+    /// The partner's payout_usdc; required while its entry exists
+    #[account(mut)]
+    pub partner_usdc: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+}
+
+#[derive(Accounts)]
+pub struct RecordCreatorVolume<'info> {
+    /// Any keeper (or the creator)
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"creator_profile", market.creator.as_ref()],
+        bump = creator_profile.bump,
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+}
+
+/// Creator-only market options, locked once the first opinion is staked
+#[derive(Accounts)]
+pub struct ConfigureMarket<'info> {
+    #[account(constraint = creator.key() == market.creator @ OpinionError::Unauthorized)]
+    pub creator: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Active @ OpinionError::MarketNotActive,
+        constraint = market.staker_count == 0 @ OpinionError::MarketAlreadyHasStakes,
+    )]
+    pub market: Account<'info, Market>,
+}
+
+/// ConfigureMarket's checks, with the statement account grown by `chunk`
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(chunk: String)]
+pub struct AppendStatement<'info> {
+    #[account(mut, constraint = creator.key() == market.creator @ OpinionError::Unauthorized)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Active @ OpinionError::MarketNotActive,
+        constraint = market.staker_count == 0 @ OpinionError::MarketAlreadyHasStakes,
+        constraint = market.statement_hash == [0u8; 32] @ OpinionError::StatementHashed,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"market_statement", market.key().as_ref()],
+        bump = market_statement.bump,
+        realloc = MarketStatement::space_for(market_statement.statement.len() + chunk.len()),
+        realloc::payer = creator,
+        realloc::zero = false,
+    )]
+    pub market_statement: Account<'info, MarketStatement>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetCharity<'info> {
+    #[account(constraint = creator.key() == market.creator @ OpinionError::Unauthorized)]
+    pub creator: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Active @ OpinionError::MarketNotActive,
+        constraint = market.staker_count == 0 @ OpinionError::MarketAlreadyHasStakes,
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Registry entry for charity_usdc; pass both to set a charity
+    #[account(
+        seeds = [b"charity", charity.usdc_account.as_ref()],
+        bump = charity.bump,
+    )]
+    pub charity: Option<Account<'info, Charity>>,
+
+    #[account(
+        constraint = charity.as_ref().is_some_and(|c| c.usdc_account == charity_usdc.key())
+            @ OpinionError::CharityNotRegistered,
+        constraint = charity_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+    )]
+    pub charity_usdc: Option<Account<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyStatement<'info> {
+    #[account(
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [b"market_statement", market.key().as_ref()],
+        bump = market_statement.bump,
+    )]
+    pub market_statement: Account<'info, MarketStatement>,
+}
+
+#[derive(Accounts)]
+pub struct GetPool<'info> {
+    #[account(
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct SyncRegistryEntry<'info> {
+    #[account(
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"market_registry".as_ref(), &market.registry_page().to_le_bytes()],
+        bump = registry_page.bump,
+    )]
+    pub registry_page: Account<'info, MarketRegistryPage>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecordCheckpoint<'info> {
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = keeper,
+        space = MarketCheckpoint::SPACE,
+        seeds = [b"checkpoint", market.key().as_ref(), &market.checkpoint_count.to_le_bytes()],
+        bump,
+    )]
+    pub checkpoint: Account<'info, MarketCheckpoint>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenAuditPage<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Current (full) page; required unless this opens page 0
+    #[account(
+        seeds = [b"audit_log", market.key().as_ref(), &market.audit_pages.saturating_sub(1).to_le_bytes()],
+        bump = previous_log.bump,
+    )]
+    pub previous_log: Option<Account<'info, AuditLog>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = AuditLog::SPACE,
+        seeds = [b"audit_log", market.key().as_ref(), &market.audit_pages.to_le_bytes()],
+        bump,
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenResultsPage<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Current (full) page; required unless this opens page 0
+    #[account(
+        seeds = [b"results_archive", market.key().as_ref(), &market.results_pages.saturating_sub(1).to_le_bytes()],
+        bump = previous_archive.bump,
+    )]
+    pub previous_archive: Option<Account<'info, ResultsArchive>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = ResultsArchive::SPACE,
+        seeds = [b"results_archive", market.key().as_ref(), &market.results_pages.to_le_bytes()],
+        bump,
+    )]
+    pub results_archive: Account<'info, ResultsArchive>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ArchiveOpinions<'info> {
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    /// The market's current results page
+    #[account(
+        mut,
+        seeds = [b"results_archive", market.key().as_ref(), &market.results_pages.saturating_sub(1).to_le_bytes()],
+        bump = results_archive.bump,
+    )]
+    pub results_archive: Account<'info, ResultsArchive>,
+}
+
+#[derive(Accounts)]
+pub struct CloseReactions<'info> {
+    #[account(
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct EnableConfidentialStakes<'info> {
+    #[account(mut, constraint = creator.key() == market.creator @ OpinionError::Unauthorized)]
+    pub creator: Signer<'info>,
+
+    /// Owns the escrow's ElGamal key; vouches for it by co-signing
+    #[account(constraint = oracle_authority.key() == market.oracle(&config) @ OpinionError::Unauthorized)]
+    pub oracle_authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Active @ OpinionError::MarketNotActive,
+        constraint = market.staker_count == 0 @ OpinionError::MarketAlreadyHasStakes,
+        constraint = !market.is_confidential() @ OpinionError::ConfidentialStakesDisabled,
+    )]
+    pub market: Account<'info, Market>,
+
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    #[account(
+        address = config.confidential_mint @ OpinionError::MintMismatch,
+        constraint = config.confidential_mint != Pubkey::default() @ OpinionError::ConfidentialStakesDisabled,
+        mint::token_program = token_2022_program,
+    )]
+    pub confidential_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    /// CHECK: created here as a Token-2022 account at this PDA
+    #[account(
+        mut,
+        seeds = [b"confidential_escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub confidential_escrow: UncheckedAccount<'info>,
+
+    /// CHECK: pubkey validity proof context state; verified by Token-2022
+    pub pubkey_validity_proof: UncheckedAccount<'info>,
+
+    pub token_2022_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetCrowdScoreMode<'info> {
+    #[account(mut, constraint = creator.key() == market.creator @ OpinionError::Unauthorized)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Active @ OpinionError::MarketNotActive,
+        constraint = market.staker_count == 0 @ OpinionError::MarketAlreadyHasStakes,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = CrowdHistogram::SPACE,
+        seeds = [b"crowd_histogram", market.key().as_ref()],
+        bump,
+    )]
+    pub crowd_histogram: Box<Account<'info, CrowdHistogram>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct StakeOpinion<'info> {
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// Recent-activity ring buffer (init_activity_log for older markets)
+    #[account(
+        mut,
+        seeds = [b"activity_log", market.key().as_ref()],
+        bump = activity_log.load()?.bump,
+    )]
+    pub activity_log: AccountLoader<'info, ActivityLog>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = staker,
+        space = Opinion::SPACE,
+        seeds = [b"opinion", market.key().as_ref(), staker.key().as_ref()],
+        bump,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    /// Page the opinion is listed on; the first opinion on a page pays its rent
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = OpinionIndexPage::SPACE,
+        seeds = [
+            b"opinion_index".as_ref(),
+            market.key().as_ref(),
+            &market.opinion_index_page().to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub opinion_index: Box<Account<'info, OpinionIndexPage>>,
+
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = UserProfile::SPACE,
+        seeds = [b"user_profile", staker.key().as_ref()],
+        bump,
+    )]
+    pub user_profile: Box<Account<'info, UserProfile>>,
+
+    #[account(
+        mut,
+        constraint = staker_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = staker_usdc.owner == staker.key(),
+    )]
+    pub staker_usdc: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: instructions sysvar, read to attribute CPI callers
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    /// Required when the market uses CrowdScoreMode::Median
+    #[account(
+        mut,
+        seeds = [b"crowd_histogram", market.key().as_ref()],
+        bump = crowd_histogram.bump,
+    )]
+    pub crowd_histogram: Option<Box<Account<'info, CrowdHistogram>>>,
+
+    /// CHECK: Solana Pay reference named by the payment request; only its key
+    /// is used
+    pub reference: Option<UncheckedAccount<'info>>,
+
+    /// Record of `reference`; pass with it to make the payment matchable
+    #[account(
+        init,
+        payer = staker,
+        space = PaymentReference::SPACE,
+        seeds = [
+            b"payment_reference".as_ref(),
+            reference.as_ref().map_or(Pubkey::default(), |r| r.key()).as_ref(),
+        ],
+        bump,
+    )]
+    pub payment_reference: Option<Box<Account<'info, PaymentReference>>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct StakeOpinionWithSwap<'info> {
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// Recent-activity ring buffer (init_activity_log for older markets)
+    #[account(
+        mut,
+        seeds = [b"activity_log", market.key().as_ref()],
+        bump = activity_log.load()?.bump,
+    )]
+    pub activity_log: AccountLoader<'info, ActivityLog>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = staker,
+        space = Opinion::SPACE,
+        seeds = [b"opinion", market.key().as_ref(), staker.key().as_ref()],
+        bump,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    /// Page the opinion is listed on; the first opinion on a page pays its rent
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = OpinionIndexPage::SPACE,
+        seeds = [
+            b"opinion_index".as_ref(),
+            market.key().as_ref(),
+            &market.opinion_index_page().to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub opinion_index: Box<Account<'info, OpinionIndexPage>>,
+
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = UserProfile::SPACE,
+        seeds = [b"user_profile", staker.key().as_ref()],
+        bump,
+    )]
+    pub user_profile: Box<Account<'info, UserProfile>>,
+
+    /// Token the route spends
+    #[account(
+        mut,
+        constraint = input_token_account.owner == staker.key() @ OpinionError::Unauthorized,
+        constraint = input_token_account.mint != config.usdc_mint @ OpinionError::SwapInputIsUsdc,
+    )]
+    pub input_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Receives the swap output, which is then staked from here
+    #[account(
+        mut,
+        constraint = staker_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = staker_usdc.owner == staker.key(),
+    )]
+    pub staker_usdc: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: must be the swap program whitelisted in config
+    #[account(
+        executable,
+        constraint = swap_program.key() == config.swap_program
+            && config.swap_program != Pubkey::default() @ OpinionError::SwapProgramNotAllowed,
+    )]
+    pub swap_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+    pub system_program: Program<'info, System>,
+
+    /// Required when the market uses CrowdScoreMode::Median
+    #[account(
+        mut,
+        seeds = [b"crowd_histogram", market.key().as_ref()],
+        bump = crowd_histogram.bump,
+    )]
+    pub crowd_histogram: Option<Box<Account<'info, CrowdHistogram>>>,
+}
+
+#[derive(Accounts)]
+#[instruction(reference: Pubkey)]
+pub struct ValidatePaymentReference<'info> {
+    #[account(
+        seeds = [b"payment_reference", reference.as_ref()],
+        bump = payment_reference.bump,
+    )]
+    pub payment_reference: Account<'info, PaymentReference>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct StakeOpinionConfidential<'info> {
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+        constraint = market.is_confidential() @ OpinionError::NotConfidentialMarket,
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Recent-activity ring buffer (init_activity_log for older markets)
+    #[account(
+        mut,
+        seeds = [b"activity_log", market.key().as_ref()],
+        bump = activity_log.load()?.bump,
+    )]
+    pub activity_log: AccountLoader<'info, ActivityLog>,
+
+    #[account(
+        init,
+        payer = staker,
+        space = Opinion::SPACE,
+        seeds = [b"opinion", market.key().as_ref(), staker.key().as_ref()],
+        bump,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    #[account(
+        init,
+        payer = staker,
+        space = ConfidentialStake::SPACE,
+        seeds = [b"confidential_stake", opinion.key().as_ref()],
+        bump,
+    )]
+    pub confidential_stake: Box<Account<'info, ConfidentialStake>>,
+
+    /// Page the opinion is listed on; the first opinion on a page pays its rent
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = OpinionIndexPage::SPACE,
+        seeds = [
+            b"opinion_index".as_ref(),
+            market.key().as_ref(),
+            &market.opinion_index_page().to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub opinion_index: Box<Account<'info, OpinionIndexPage>>,
+
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = UserProfile::SPACE,
+        seeds = [b"user_profile", staker.key().as_ref()],
+        bump,
+    )]
+    pub user_profile: Box<Account<'info, UserProfile>>,
+
+    #[account(address = config.confidential_mint @ OpinionError::MintMismatch)]
+    pub confidential_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    /// Staker's Token-2022 account of the confidential mint
+    #[account(
+        mut,
+        token::mint = confidential_mint,
+        token::authority = staker,
+        token::token_program = token_2022_program,
+    )]
+    pub staker_confidential: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// CHECK: the market's confidential escrow, created by enable_confidential_stakes
+    #[account(mut, address = market.confidential_escrow)]
+    pub confidential_escrow: UncheckedAccount<'info>,
+
+    /// CHECK: ciphertext-commitment equality proof context state; verified by Token-2022
+    pub equality_proof: UncheckedAccount<'info>,
+    /// CHECK: ciphertext validity proof context state; verified by Token-2022
+    pub ciphertext_validity_proof: UncheckedAccount<'info>,
+    /// CHECK: range proof context state; verified by Token-2022
+    pub range_proof: UncheckedAccount<'info>,
+
+    pub token_2022_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RevealConfidentialStake<'info> {
+    /// The staker, or anyone they gave the openings to
+    pub revealer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        mut,
+        constraint = opinion.load()?.market == market.key() @ OpinionError::OpinionMarketMismatch,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    #[account(
+        mut,
+        seeds = [b"confidential_stake", opinion.key().as_ref()],
+        bump = confidential_stake.bump,
+    )]
+    pub confidential_stake: Box<Account<'info, ConfidentialStake>>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", opinion.load()?.staker.as_ref()],
+        bump = user_profile.bump,
+    )]
+    pub user_profile: Box<Account<'info, UserProfile>>,
+
+    /// Required when the market uses CrowdScoreMode::Median
+    #[account(
+        mut,
+        seeds = [b"crowd_histogram", market.key().as_ref()],
+        bump = crowd_histogram.bump,
+    )]
+    pub crowd_histogram: Option<Box<Account<'info, CrowdHistogram>>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReleaseConfidentialStakes<'info> {
+    #[account(constraint = oracle_authority.key() == market.oracle(&config) @ OpinionError::Unauthorized)]
+    pub oracle_authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(address = config.confidential_mint @ OpinionError::MintMismatch)]
+    pub confidential_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    /// CHECK: the market's confidential escrow, created by enable_confidential_stakes
+    #[account(mut, address = market.confidential_escrow)]
+    pub confidential_escrow: UncheckedAccount<'info>,
+
+    /// CHECK: ciphertext-commitment equality proof context state; verified by Token-2022
+    pub equality_proof: UncheckedAccount<'info>,
+    /// CHECK: range proof context state; verified by Token-2022
+    pub range_proof: UncheckedAccount<'info>,
+    /// CHECK: zero ciphertext proof context state; verified by Token-2022
+    pub zero_balance_proof: UncheckedAccount<'info>,
+
+    /// CHECK: the whitelisted wrapper program; address checked against config
+    #[account(
+        address = config.confidential_wrap_program,
+        constraint = config.confidential_wrap_program != Pubkey::default() @ OpinionError::ConfidentialStakesDisabled,
+        executable,
+    )]
+    pub wrap_program: UncheckedAccount<'info>,
+
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(intent: StakeIntent)]
+pub struct StakeWithIntent<'info> {
+    /// Submits the intent and pays the account rent
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// Recent-activity ring buffer (init_activity_log for older markets)
+    #[account(
+        mut,
+        seeds = [b"activity_log", market.key().as_ref()],
+        bump = activity_log.load()?.bump,
+    )]
+    pub activity_log: AccountLoader<'info, ActivityLog>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = relayer,
+        space = Opinion::SPACE,
+        seeds = [b"opinion", market.key().as_ref(), intent.staker.as_ref()],
+        bump,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    /// Page the opinion is listed on; the first opinion on a page pays its rent
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = OpinionIndexPage::SPACE,
+        seeds = [
+            b"opinion_index".as_ref(),
+            market.key().as_ref(),
+            &market.opinion_index_page().to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub opinion_index: Box<Account<'info, OpinionIndexPage>>,
+
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = UserProfile::SPACE,
+        seeds = [b"user_profile", intent.staker.as_ref()],
+        bump,
+    )]
+    pub user_profile: Box<Account<'info, UserProfile>>,
+
+    /// CHECK: PDA the staker approved as delegate on staker_usdc; holds no data
+    #[account(seeds = [b"intent_delegate"], bump)]
+    pub intent_delegate: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = staker_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = staker_usdc.owner == intent.staker @ OpinionError::Unauthorized,
+    )]
+    pub staker_usdc: Account<'info, TokenAccount>,
+
+    /// Receives the relay fee
+    #[account(
+        mut,
+        constraint = relayer_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+    )]
+    pub relayer_usdc: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: instructions sysvar, read to find the Ed25519 verification
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    /// Required when the market uses CrowdScoreMode::Median
+    #[account(
+        mut,
+        seeds = [b"crowd_histogram", market.key().as_ref()],
+        bump = crowd_histogram.bump,
+    )]
+    pub crowd_histogram: Option<Box<Account<'info, CrowdHistogram>>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReactToOpinion<'info> {
+    #[account(mut)]
+    pub reactor: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// Recent-activity ring buffer (init_activity_log for older markets)
+    #[account(
+        mut,
+        seeds = [b"activity_log", market.key().as_ref()],
+        bump = activity_log.load()?.bump,
+    )]
+    pub activity_log: AccountLoader<'info, ActivityLog>,
+
+    #[account(
+        mut,
+        constraint = opinion.load()?.market == market.key(),
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    /// One reaction per (reactor, opinion) — enforced by PDA seeds
+    #[account(
+        init,
+        payer = reactor,
+        space = Reaction::SPACE,
+        seeds = [b"reaction", opinion.key().as_ref(), reactor.key().as_ref()],
+        bump,
+    )]
+    pub reaction: Account<'info, Reaction>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = reactor_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = reactor_usdc.owner == reactor.key(),
+    )]
+    pub reactor_usdc: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: instructions sysvar, read to attribute CPI callers
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReactBatch<'info> {
+    #[account(mut)]
+    pub reactor: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// Recent-activity ring buffer (init_activity_log for older markets)
+    #[account(
+        mut,
+        seeds = [b"activity_log", market.key().as_ref()],
+        bump = activity_log.load()?.bump,
+    )]
+    pub activity_log: AccountLoader<'info, ActivityLog>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = reactor_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = reactor_usdc.owner == reactor.key(),
+    )]
+    pub reactor_usdc: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: instructions sysvar, read to attribute CPI callers
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct LiteReact<'info> {
+    #[account(mut)]
+    pub reactor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(constraint = opinion.load()?.market == market.key() @ OpinionError::OpinionMarketMismatch)]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    #[account(
+        init_if_needed,
+        payer = reactor,
+        space = LiteReactionCounts::SPACE,
+        seeds = [b"lite_reactions", opinion.key().as_ref()],
+        bump,
+    )]
+    pub lite_reaction_counts: Account<'info, LiteReactionCounts>,
+
+    #[account(
+        init_if_needed,
+        payer = reactor,
+        space = LiteReactor::SPACE,
+        seeds = [b"lite_reactor", reactor.key().as_ref()],
+        bump,
+    )]
+    pub lite_reactor: Account<'info, LiteReactor>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseMarket<'info> {
+    /// CHECK: permissionless — anyone can call after expiry
+    pub caller: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// Recent-activity ring buffer (init_activity_log for older markets)
+    #[account(
+        mut,
+        seeds = [b"activity_log", market.key().as_ref()],
+        bump = activity_log.load()?.bump,
+    )]
+    pub activity_log: AccountLoader<'info, ActivityLog>,
+
+    /// The market's registry page; its entry is refreshed when passed
+    #[account(
+        mut,
+        seeds = [b"market_registry".as_ref(), &market.registry_page().to_le_bytes()],
+        bump = registry_page.bump,
+    )]
+    pub registry_page: Option<Account<'info, MarketRegistryPage>>,
+
+    /// Enqueues the market for crankers when passed
+    #[account(mut, seeds = [b"scoring_queue"], bump = scoring_queue.bump)]
+    pub scoring_queue: Option<Box<Account<'info, ScoringQueue>>>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterLifecycleThread<'info> {
+    /// Funds the thread
+    #[account(mut, constraint = creator.key() == market.creator @ OpinionError::Unauthorized)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Passed so the thread's close_market enqueues the market for scoring
+    #[account(seeds = [b"scoring_queue"], bump = scoring_queue.bump)]
+    pub scoring_queue: Option<Box<Account<'info, ScoringQueue>>>,
+
+    /// CHECK: PDA that owns every lifecycle thread; holds no data
+    #[account(seeds = [b"thread_authority"], bump)]
+    pub thread_authority: UncheckedAccount<'info>,
+
+    /// CHECK: created by the thread program at this address
+    #[account(
+        mut,
+        seeds = [b"thread", thread_authority.key().as_ref(), market.key().as_ref()],
+        seeds::program = CLOCKWORK_THREAD_PROGRAM_ID,
+        bump,
+    )]
+    pub thread: UncheckedAccount<'info>,
+
+    /// CHECK: the Clockwork thread program
+    #[account(address = CLOCKWORK_THREAD_PROGRAM_ID)]
+    pub clockwork_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseLifecycleThread<'info> {
+    /// Any keeper
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    /// CHECK: receives the thread's remaining lamports
+    #[account(mut, address = market.creator @ OpinionError::Unauthorized)]
+    pub creator: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that owns every lifecycle thread; holds no data
+    #[account(seeds = [b"thread_authority"], bump)]
+    pub thread_authority: UncheckedAccount<'info>,
+
+    /// CHECK: checked against market.lifecycle_thread
+    #[account(mut)]
+    pub thread: UncheckedAccount<'info>,
+
+    /// CHECK: the Clockwork thread program
+    #[account(address = CLOCKWORK_THREAD_PROGRAM_ID)]
+    pub clockwork_program: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecordSentiment<'info> {
+    #[account(constraint = oracle_authority.key() == market.oracle(&config) @ OpinionError::Unauthorized)]
+    pub oracle_authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    /// The market oracle's bond; required when config.oracle_bond_minimum > 0
+    #[account(
+        seeds = [b"oracle_bond", market.oracle(&config).as_ref()],
+        bump = oracle_bond.bump,
+    )]
+    pub oracle_bond: Option<Account<'info, OracleBond>>,
+
+    /// Current audit page
+    #[account(
+        mut,
+        seeds = [b"audit_log", market.key().as_ref(), &market.audit_pages.saturating_sub(1).to_le_bytes()],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Option<Account<'info, AuditLog>>,
+
+    /// The market's registry page; its entry is refreshed when passed
+    #[account(
+        mut,
+        seeds = [b"market_registry".as_ref(), &market.registry_page().to_le_bytes()],
+        bump = registry_page.bump,
+    )]
+    pub registry_page: Option<Account<'info, MarketRegistryPage>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecordAiScore<'info> {
+    #[account(constraint = oracle_authority.key() == market.oracle(&config) @ OpinionError::Unauthorized)]
+    pub oracle_authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        constraint = opinion.load()?.market == market.key(),
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    /// Current audit page
+    #[account(
+        mut,
+        seeds = [b"audit_log", market.key().as_ref(), &market.audit_pages.saturating_sub(1).to_le_bytes()],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Option<Account<'info, AuditLog>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecordAiScoreSigned<'info> {
+    /// Any fee payer relaying the oracle-signed payload
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        constraint = opinion.load()?.market == market.key() @ OpinionError::OpinionMarketMismatch,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    /// Records the settlement bounty owed to the relayer (see
+    /// ProgramConfig::settlement_bounty); nothing accrues when omitted
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = SettlementBounty::SPACE,
+        seeds = [b"settlement_bounty", market.key().as_ref(), relayer.key().as_ref()],
+        bump,
+    )]
+    pub settlement_bounty: Option<Account<'info, SettlementBounty>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: instructions sysvar, read to find the Ed25519 verification
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    /// Current audit page
+    #[account(
+        mut,
+        seeds = [b"audit_log", market.key().as_ref(), &market.audit_pages.saturating_sub(1).to_le_bytes()],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Option<Account<'info, AuditLog>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecordAiScores<'info> {
+    #[account(constraint = oracle_authority.key() == market.oracle(&config) @ OpinionError::Unauthorized)]
+    pub oracle_authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Current audit page
+    #[account(
+        mut,
+        seeds = [b"audit_log", market.key().as_ref(), &market.audit_pages.saturating_sub(1).to_le_bytes()],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Option<Account<'info, AuditLog>>,
+
+    /// Human reviewer co-signature (see ProgramConfig::reviewer_required)
+    pub reviewer: Option<Signer<'info>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ChallengeAiScore<'info> {
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Scored @ OpinionError::MarketNotScored,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        constraint = opinion.load()?.market == market.key() @ OpinionError::OpinionMarketMismatch,
+        constraint = opinion.load()?.staker == staker.key() @ OpinionError::Unauthorized,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    #[account(
+        init,
+        payer = staker,
+        space = AiScoreChallenge::SPACE,
+        seeds = [b"ai_challenge", opinion.key().as_ref()],
+        bump,
+    )]
+    pub ai_challenge: Account<'info, AiScoreChallenge>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = staker_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = staker_usdc.owner == staker.key(),
+    )]
+    pub staker_usdc: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RespondAiChallenge<'info> {
+    #[account(constraint = oracle_authority.key() == market.oracle(&config) @ OpinionError::Unauthorized)]
+    pub oracle_authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Scored @ OpinionError::MarketNotScored,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        constraint = opinion.load()?.market == market.key() @ OpinionError::OpinionMarketMismatch,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    #[account(
+        mut,
+        seeds = [b"ai_challenge", opinion.key().as_ref()],
+        bump = ai_challenge.bump,
+    )]
+    pub ai_challenge: Account<'info, AiScoreChallenge>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = staker_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = staker_usdc.owner == ai_challenge.staker @ OpinionError::Unauthorized,
+    )]
+    pub staker_usdc: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = treasury_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = treasury_usdc.owner == config.treasury @ OpinionError::TreasuryMismatch,
+    )]
+    pub treasury_usdc: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+
+    /// Current audit page
+    #[account(
+        mut,
+        seeds = [b"audit_log", market.key().as_ref(), &market.audit_pages.saturating_sub(1).to_le_bytes()],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Option<Account<'info, AuditLog>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleOpinion<'info> {
+    #[account(constraint = oracle_authority.key() == market.oracle(&config) @ OpinionError::Unauthorized)]
+    pub oracle_authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        constraint = opinion.load()?.market == market.key(),
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    /// Current audit page
+    #[account(
+        mut,
+        seeds = [b"audit_log", market.key().as_ref(), &market.audit_pages.saturating_sub(1).to_le_bytes()],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Option<Account<'info, AuditLog>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteSettlement<'info> {
+    /// Any keeper cranking settlement against the posted scores root
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        constraint = opinion.load()?.market == market.key() @ OpinionError::OpinionMarketMismatch,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    /// Records the settlement bounty owed to the keeper (see
+    /// ProgramConfig::settlement_bounty); nothing accrues when omitted
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        space = SettlementBounty::SPACE,
+        seeds = [b"settlement_bounty", market.key().as_ref(), keeper.key().as_ref()],
+        bump,
+    )]
+    pub settlement_bounty: Option<Account<'info, SettlementBounty>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Human reviewer co-signature (see ProgramConfig::reviewer_required)
+    pub reviewer: Option<Signer<'info>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SnapshotOpinionInputs<'info> {
+    /// Any keeper cranking the inputs snapshot
+    pub keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PostVerifiedScores<'info> {
+    /// Any prover; the Groth16 proof is the authorization
+    pub submitter: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(seeds = [b"scoring_verifier"], bump = scoring_verifier.bump)]
+    pub scoring_verifier: Account<'info, ScoringVerifier>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleOpinionSigned<'info> {
+    /// Any fee payer relaying the oracle-signed payload
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        constraint = opinion.load()?.market == market.key() @ OpinionError::OpinionMarketMismatch,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    /// Records the settlement bounty owed to the relayer (see
+    /// ProgramConfig::settlement_bounty); nothing accrues when omitted
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = SettlementBounty::SPACE,
+        seeds = [b"settlement_bounty", market.key().as_ref(), relayer.key().as_ref()],
+        bump,
+    )]
+    pub settlement_bounty: Option<Account<'info, SettlementBounty>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: instructions sysvar, read to find the Ed25519 verification
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    /// Current audit page
+    #[account(
+        mut,
+        seeds = [b"audit_log", market.key().as_ref(), &market.audit_pages.saturating_sub(1).to_le_bytes()],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Option<Account<'info, AuditLog>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleOpinionsBatch<'info> {
+    #[account(constraint = oracle_authority.key() == market.oracle(&config) @ OpinionError::Unauthorized)]
+    pub oracle_authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Current audit page
+    #[account(
+        mut,
+        seeds = [b"audit_log", market.key().as_ref(), &market.audit_pages.saturating_sub(1).to_le_bytes()],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Option<Account<'info, AuditLog>>,
+
+    /// Human reviewer co-signature (see ProgramConfig::reviewer_required)
+    pub reviewer: Option<Signer<'info>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ResolvePythMarket<'info> {
+    /// Any keeper
+    pub caller: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// The market's registry page; its entry is refreshed when passed
+    #[account(
+        mut,
+        seeds = [b"market_registry".as_ref(), &market.registry_page().to_le_bytes()],
+        bump = registry_page.bump,
+    )]
+    pub registry_page: Option<Account<'info, MarketRegistryPage>>,
+
+    /// CHECK: Pyth PriceUpdateV2; owner, feed and verification are checked
+    /// by the handler
+    pub price_update: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = treasury_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = treasury_usdc.owner == config.treasury @ OpinionError::TreasuryMismatch,
+    )]
+    pub treasury_usdc: Account<'info, TokenAccount>,
+
+    #[account(mut, seeds = [b"treasury_ledger"], bump = treasury_ledger.bump)]
+    pub treasury_ledger: Account<'info, TreasuryLedger>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+
+    /// Human reviewer co-signature (see ProgramConfig::reviewer_required)
+    pub reviewer: Option<Signer<'info>>,
+
+    /// Required once the market has settle hooks; the hook programs follow
+    /// in remaining_accounts
+    #[account(seeds = [b"settle_hooks", market.key().as_ref()], bump = settle_hooks.bump)]
+    pub settle_hooks: Option<Account<'info, SettleHooks>>,
+
+    /// CHECK: PDA signing settle hook CPIs; holds no data
+    #[account(seeds = [b"settle_hook_authority"], bump)]
+    pub hook_authority: Option<UncheckedAccount<'info>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FinalizeSettlement<'info> {
+    /// Pays the Wormhole fee and message rent when publishing
+    #[account(
+        mut,
+        constraint = oracle_authority.key() == market.oracle(&config) @ OpinionError::Unauthorized,
+    )]
+    pub oracle_authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// The market's registry page; its entry is refreshed when passed
+    #[account(
+        mut,
+        seeds = [b"market_registry".as_ref(), &market.registry_page().to_le_bytes()],
+        bump = registry_page.bump,
+    )]
+    pub registry_page: Option<Account<'info, MarketRegistryPage>>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = treasury_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = treasury_usdc.owner == config.treasury @ OpinionError::TreasuryMismatch,
+    )]
+    pub treasury_usdc: Account<'info, TokenAccount>,
+
+    /// Receives the oracle compensation (config.oracle_fee_*)
+    #[account(
+        mut,
+        constraint = oracle_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = oracle_usdc.owner == oracle_authority.key() @ OpinionError::Unauthorized,
+    )]
+    pub oracle_usdc: Account<'info, TokenAccount>,
+
+    #[account(mut, seeds = [b"treasury_ledger"], bump = treasury_ledger.bump)]
+    pub treasury_ledger: Account<'info, TreasuryLedger>,
+
+    /// Required when the oracle committee is enabled
+    #[account(
+        seeds = [b"settlement_proposal", market.key().as_ref()],
+        bump = settlement_proposal.bump,
+    )]
+    pub settlement_proposal: Option<Account<'info, SettlementProposal>>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+
+    /// Current audit page
+    #[account(
+        mut,
+        seeds = [b"audit_log", market.key().as_ref(), &market.audit_pages.saturating_sub(1).to_le_bytes()],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Option<Account<'info, AuditLog>>,
+
+    /// Human reviewer co-signature (see ProgramConfig::reviewer_required)
+    pub reviewer: Option<Signer<'info>>,
+
+    /// Required while config.insurance_share_bps is non-zero
+    #[account(mut, seeds = [b"insurance_vault"], bump = insurance_vault.bump)]
+    pub insurance_vault: Option<Account<'info, InsuranceVault>>,
+
+    #[account(mut, seeds = [b"insurance_vault_usdc"], bump)]
+    pub insurance_vault_usdc: Option<Account<'info, TokenAccount>>,
+
+    /// Required for charity markets; receives the protocol fee and pool slice
+    #[account(
+        mut,
+        address = market.charity_usdc @ OpinionError::CharityAccountRequired,
+        constraint = charity_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+    )]
+    pub charity_usdc: Option<Account<'info, TokenAccount>>,
+
+    /// Required once the market has settle hooks; the hook programs follow
+    /// in remaining_accounts
+    #[account(seeds = [b"settle_hooks", market.key().as_ref()], bump = settle_hooks.bump)]
+    pub settle_hooks: Option<Account<'info, SettleHooks>>,
+
+    /// CHECK: PDA signing settle hook CPIs; holds no data
+    #[account(seeds = [b"settle_hook_authority"], bump)]
+    pub hook_authority: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Wormhole core bridge. finalize_settlement posts the settlement
+    /// payload when it and the accounts below are passed.
+    #[account(address = WORMHOLE_CORE_BRIDGE_ID @ OpinionError::WormholeAccountsRequired)]
+    pub wormhole_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: core bridge config; verified by the bridge
+    #[account(mut)]
+    pub wormhole_bridge: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: the market's settlement message, created by the bridge
+    #[account(mut, seeds = [b"wormhole_message", market.key().as_ref()], bump)]
+    pub wormhole_message: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: PDA that emits every settlement message; holds no data
+    #[account(seeds = [b"emitter"], bump)]
+    pub wormhole_emitter: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: the emitter's sequence account; verified by the bridge
+    #[account(mut)]
+    pub wormhole_sequence: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: core bridge fee collector; verified by the bridge
+    #[account(mut)]
+    pub wormhole_fee_collector: Option<UncheckedAccount<'info>>,
+
+    pub clock: Option<Sysvar<'info, Clock>>,
+    pub rent: Option<Sysvar<'info, Rent>>,
+    pub system_program: Option<Program<'info, System>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PostSettlementRootSigned<'info> {
+    /// Any fee payer relaying the oracle-signed payload
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// The market's registry page; its entry is refreshed when passed
+    #[account(
+        mut,
+        seeds = [b"market_registry".as_ref(), &market.registry_page().to_le_bytes()],
+        bump = registry_page.bump,
+    )]
+    pub registry_page: Option<Account<'info, MarketRegistryPage>>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = treasury_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = treasury_usdc.owner == config.treasury @ OpinionError::TreasuryMismatch,
+    )]
+    pub treasury_usdc: Account<'info, TokenAccount>,
+
+    /// Required when the oracle committee is enabled
+    #[account(
+        seeds = [b"settlement_proposal", market.key().as_ref()],
+        bump = settlement_proposal.bump,
+    )]
+    pub settlement_proposal: Option<Account<'info, SettlementProposal>>,
+
+    /// CHECK: instructions sysvar, read to find the Ed25519 verification
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+
+    /// Records the settlement bounty owed to the relayer (see
+    /// ProgramConfig::settlement_bounty); nothing accrues when omitted
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = SettlementBounty::SPACE,
+        seeds = [b"settlement_bounty", market.key().as_ref(), relayer.key().as_ref()],
+        bump,
+    )]
+    pub settlement_bounty: Option<Account<'info, SettlementBounty>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Current audit page
+    #[account(
+        mut,
+        seeds = [b"audit_log", market.key().as_ref(), &market.audit_pages.saturating_sub(1).to_le_bytes()],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Option<Account<'info, AuditLog>>,
+
+    /// Human reviewer co-signature (see ProgramConfig::reviewer_required)
+    pub reviewer: Option<Signer<'info>>,
+
+    /// Required once the market has settle hooks; the hook programs follow
+    /// in remaining_accounts
+    #[account(seeds = [b"settle_hooks", market.key().as_ref()], bump = settle_hooks.bump)]
+    pub settle_hooks: Option<Account<'info, SettleHooks>>,
+
+    /// CHECK: PDA signing settle hook CPIs; holds no data
+    #[account(seeds = [b"settle_hook_authority"], bump)]
+    pub hook_authority: Option<UncheckedAccount<'info>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeSettlementRoot<'info> {
+    #[account(
+        mut,
+        constraint = oracle_authority.key() == market.oracle(&config) @ OpinionError::Unauthorized,
+    )]
+    pub oracle_authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Reused by a new proposal once the previous one was rejected
+    #[account(
+        init_if_needed,
+        payer = oracle_authority,
+        space = OptimisticSettlement::SPACE,
+        seeds = [b"optimistic_settlement", market.key().as_ref()],
+        bump,
+        constraint = !market.optimistic_open @ OpinionError::OptimisticSettlementOpen,
+    )]
+    pub optimistic_settlement: Account<'info, OptimisticSettlement>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Current audit page
+    #[account(
+        mut,
+        seeds = [b"audit_log", market.key().as_ref(), &market.audit_pages.saturating_sub(1).to_le_bytes()],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Option<Account<'info, AuditLog>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DisputeSettlement<'info> {
+    pub challenger: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Scored @ OpinionError::MarketNotScored,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"optimistic_settlement", market.key().as_ref()],
+        bump = optimistic_settlement.bump,
+    )]
+    pub optimistic_settlement: Account<'info, OptimisticSettlement>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = challenger_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = challenger_usdc.owner == challenger.key(),
+    )]
+    pub challenger_usdc: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FinalizeOptimisticSettlement<'info> {
+    /// Any fee payer cranking an undisputed proposal
+    pub cranker: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// The market's registry page; its entry is refreshed when passed
+    #[account(
+        mut,
+        seeds = [b"market_registry".as_ref(), &market.registry_page().to_le_bytes()],
+        bump = registry_page.bump,
+    )]
+    pub registry_page: Option<Account<'info, MarketRegistryPage>>,
+
+    #[account(
+        seeds = [b"optimistic_settlement", market.key().as_ref()],
+        bump = optimistic_settlement.bump,
+    )]
+    pub optimistic_settlement: Account<'info, OptimisticSettlement>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = treasury_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = treasury_usdc.owner == config.treasury @ OpinionError::TreasuryMismatch,
+    )]
+    pub treasury_usdc: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+
+    /// Human reviewer co-signature (see ProgramConfig::reviewer_required)
+    pub reviewer: Option<Signer<'info>>,
+
+    /// Required once the market has settle hooks; the hook programs follow
+    /// in remaining_accounts
+    #[account(seeds = [b"settle_hooks", market.key().as_ref()], bump = settle_hooks.bump)]
+    pub settle_hooks: Option<Account<'info, SettleHooks>>,
+
+    /// CHECK: PDA signing settle hook CPIs; holds no data
+    #[account(seeds = [b"settle_hook_authority"], bump)]
+    pub hook_authority: Option<UncheckedAccount<'info>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// The market's registry page; its entry is refreshed when passed
+    #[account(
+        mut,
+        seeds = [b"market_registry".as_ref(), &market.registry_page().to_le_bytes()],
+        bump = registry_page.bump,
+    )]
+    pub registry_page: Option<Account<'info, MarketRegistryPage>>,
+
+    #[account(
+        mut,
+        seeds = [b"optimistic_settlement", market.key().as_ref()],
+        bump = optimistic_settlement.bump,
+    )]
+    pub optimistic_settlement: Account<'info, OptimisticSettlement>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = treasury_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = treasury_usdc.owner == config.treasury @ OpinionError::TreasuryMismatch,
+    )]
+    pub treasury_usdc: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = challenger_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = Some(challenger_usdc.owner) == optimistic_settlement.challenger @ OpinionError::Unauthorized,
+    )]
+    pub challenger_usdc: Account<'info, TokenAccount>,
+
+    /// Required when the oracle committee is enabled
+    #[account(
+        seeds = [b"settlement_proposal", market.key().as_ref()],
+        bump = settlement_proposal.bump,
+    )]
+    pub settlement_proposal: Option<Account<'info, SettlementProposal>>,
+
+    /// Required when oracle slashing is enabled
+    #[account(
+        mut,
+        seeds = [b"oracle_bond", market.oracle(&config).as_ref()],
+        bump = oracle_bond.bump,
+    )]
+    pub oracle_bond: Option<Account<'info, OracleBond>>,
+
+    #[account(mut, seeds = [b"oracle_bond_vault", market.oracle(&config).as_ref()], bump)]
+    pub oracle_bond_vault: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+
+    /// Human reviewer co-signature (see ProgramConfig::reviewer_required)
+    pub reviewer: Option<Signer<'info>>,
+
+    /// Required once the market has settle hooks; the hook programs follow
+    /// in remaining_accounts
+    #[account(seeds = [b"settle_hooks", market.key().as_ref()], bump = settle_hooks.bump)]
+    pub settle_hooks: Option<Account<'info, SettleHooks>>,
+
+    /// CHECK: PDA signing settle hook CPIs; holds no data
+    #[account(seeds = [b"settle_hook_authority"], bump)]
+    pub hook_authority: Option<UncheckedAccount<'info>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RejectSettlementProposal<'info> {
+    #[account(constraint = admin.key() == config.admin @ OpinionError::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Scored @ OpinionError::MarketNotScored,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"optimistic_settlement", market.key().as_ref()],
+        bump = optimistic_settlement.bump,
+    )]
+    pub optimistic_settlement: Account<'info, OptimisticSettlement>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = challenger_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = Some(challenger_usdc.owner) == optimistic_settlement.challenger @ OpinionError::Unauthorized,
+    )]
+    pub challenger_usdc: Account<'info, TokenAccount>,
+
+    /// Required when oracle slashing is enabled
+    #[account(
+        mut,
+        seeds = [b"oracle_bond", market.oracle(&config).as_ref()],
+        bump = oracle_bond.bump,
+    )]
+    pub oracle_bond: Option<Account<'info, OracleBond>>,
+
+    #[account(mut, seeds = [b"oracle_bond_vault", market.oracle(&config).as_ref()], bump)]
+    pub oracle_bond_vault: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimPayout<'info> {
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = opinion.load()?.market == market.key(),
+        constraint = opinion.load()?.staker == staker.key() @ OpinionError::Unauthorized,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    /// CHECK: staker's PayoutPreference PDA; may be uninitialized
+    #[account(seeds = [b"payout_preference", staker.key().as_ref()], bump)]
+    pub payout_preference: UncheckedAccount<'info>,
+
+    /// Staker's registered payout destination, else a USDC account they own
+    #[account(
+        mut,
+        constraint = staker_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = is_payout_destination(&payout_preference, &staker.key(), &staker_usdc)
+            @ OpinionError::PayoutDestinationMismatch,
+    )]
+    pub staker_usdc: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = UserProfile::SPACE,
+        seeds = [b"user_profile", staker.key().as_ref()],
+        bump,
+    )]
+    pub user_profile: Box<Account<'info, UserProfile>>,
+
+    /// Refreshed with the user's new total_won when passed
+    #[account(
+        mut,
+        seeds = [b"earner_leaderboard"],
+        bump = earner_leaderboard.bump,
+    )]
+    pub earner_leaderboard: Option<Box<Account<'info, EarnerLeaderboard>>>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PreviewPayout<'info> {
+    #[account(
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(constraint = opinion.load()?.market == market.key() @ OpinionError::OpinionMarketMismatch)]
+    pub opinion: AccountLoader<'info, Opinion>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AnnouncePayouts<'info> {
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct GetReputation<'info> {
+    #[account(
+        seeds = [b"user_profile", user_profile.user.as_ref()],
+        bump = user_profile.bump,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimPayoutsBatch<'info> {
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    /// CHECK: staker's PayoutPreference PDA; may be uninitialized
+    #[account(seeds = [b"payout_preference", staker.key().as_ref()], bump)]
+    pub payout_preference: UncheckedAccount<'info>,
+
+    /// Staker's registered payout destination, else a USDC account they own
+    #[account(
+        mut,
+        constraint = staker_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = is_payout_destination(&payout_preference, &staker.key(), &staker_usdc)
+            @ OpinionError::PayoutDestinationMismatch,
+    )]
+    pub staker_usdc: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = UserProfile::SPACE,
+        seeds = [b"user_profile", staker.key().as_ref()],
+        bump,
+    )]
+    pub user_profile: Box<Account<'info, UserProfile>>,
+
+    /// Refreshed with the user's new total_won when passed
+    #[account(
+        mut,
+        seeds = [b"earner_leaderboard"],
+        bump = earner_leaderboard.bump,
+    )]
+    pub earner_leaderboard: Option<Box<Account<'info, EarnerLeaderboard>>>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimPayoutSwapped<'info> {
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = opinion.load()?.market == market.key(),
+        constraint = opinion.load()?.staker == staker.key() @ OpinionError::Unauthorized,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    /// CHECK: staker's PayoutPreference PDA; must be uninitialized, since the
+    /// swap spends from an account the staker owns
+    #[account(
+        seeds = [b"payout_preference", staker.key().as_ref()],
+        bump,
+        constraint = payout_preference.data_is_empty() @ OpinionError::PayoutDestinationMismatch,
+    )]
+    pub payout_preference: UncheckedAccount<'info>,
+
+    /// Receives the USDC payout; the swap route spends from here
+    #[account(
+        mut,
+        constraint = staker_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = staker_usdc.owner == staker.key(),
+    )]
+    pub staker_usdc: Account<'info, TokenAccount>,
+
+    /// Receives the swapped tokens
+    #[account(mut, constraint = output_token_account.owner == staker.key() @ OpinionError::Unauthorized)]
+    pub output_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: must be the swap program whitelisted in config
+    #[account(
+        executable,
+        constraint = swap_program.key() == config.swap_program
+            && config.swap_program != Pubkey::default() @ OpinionError::SwapProgramNotAllowed,
+    )]
+    pub swap_program: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = UserProfile::SPACE,
+        seeds = [b"user_profile", staker.key().as_ref()],
+        bump,
+    )]
+    pub user_profile: Box<Account<'info, UserProfile>>,
+
+    /// Refreshed with the user's new total_won when passed
+    #[account(
+        mut,
+        seeds = [b"earner_leaderboard"],
+        bump = earner_leaderboard.bump,
+    )]
+    pub earner_leaderboard: Option<Box<Account<'info, EarnerLeaderboard>>>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPayoutPreference<'info> {
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = PayoutPreference::SPACE,
+        seeds = [b"payout_preference", staker.key().as_ref()],
+        bump,
+    )]
+    pub payout_preference: Account<'info, PayoutPreference>,
+
+    #[account(constraint = destination.mint == config.usdc_mint @ OpinionError::MintMismatch)]
+    pub destination: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClearPayoutPreference<'info> {
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    #[account(
+        mut,
+        close = staker,
+        seeds = [b"payout_preference", staker.key().as_ref()],
+        bump = payout_preference.bump,
+    )]
+    pub payout_preference: Account<'info, PayoutPreference>,
+}
+
+#[derive(Accounts)]
+pub struct OpenCompoundVault<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = CompoundVault::SPACE,
+        seeds = [b"compound_vault", owner.key().as_ref()],
+        bump,
+    )]
+    pub compound_vault: Account<'info, CompoundVault>,
+
+    #[account(
+        init,
+        payer = owner,
+        token::mint = usdc_mint,
+        token::authority = compound_vault,
+        seeds = [b"compound_vault_usdc", owner.key().as_ref()],
+        bump,
+    )]
+    pub vault_usdc: Account<'info, TokenAccount>,
+
+    #[account(constraint = usdc_mint.key() == config.usdc_mint @ OpinionError::MintMismatch)]
+    pub usdc_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct SetCompoundAllowance<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"compound_vault", owner.key().as_ref()],
+        bump = compound_vault.bump,
+    )]
+    pub compound_vault: Account<'info, CompoundVault>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimPayoutToVault<'info> {
+    /// Vault owner or delegate; pays the owner's UserProfile rent if needed
+    #[account(
+        mut,
+        constraint = compound_vault.is_authorized(&authority.key()) @ OpinionError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = opinion.load()?.market == market.key(),
+        constraint = opinion.load()?.staker == compound_vault.owner @ OpinionError::Unauthorized,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    #[account(
+        mut,
+        seeds = [b"compound_vault", compound_vault.owner.as_ref()],
+        bump = compound_vault.bump,
+    )]
+    pub compound_vault: Account<'info, CompoundVault>,
+
+    #[account(
+        mut,
+        seeds = [b"compound_vault_usdc", compound_vault.owner.as_ref()],
+        bump,
+    )]
+    pub vault_usdc: Account<'info, TokenAccount>,
+
+    /// CHECK: owner's PayoutPreference PDA; must be uninitialized
+    #[account(
+        seeds = [b"payout_preference", compound_vault.owner.as_ref()],
+        bump,
+        constraint = payout_preference.data_is_empty() @ OpinionError::PayoutDestinationMismatch,
+    )]
+    pub payout_preference: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = UserProfile::SPACE,
+        seeds = [b"user_profile", compound_vault.owner.as_ref()],
+        bump,
+    )]
+    pub user_profile: Box<Account<'info, UserProfile>>,
+
+    /// Refreshed with the user's new total_won when passed
+    #[account(
+        mut,
+        seeds = [b"earner_leaderboard"],
+        bump = earner_leaderboard.bump,
+    )]
+    pub earner_leaderboard: Option<Box<Account<'info, EarnerLeaderboard>>>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct StakeFromVault<'info> {
+    /// Vault owner or delegate; pays the Opinion rent
+    #[account(
+        mut,
+        constraint = compound_vault.is_authorized(&authority.key()) @ OpinionError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
 
-        let clock = Clock::get()?;
-        {
-            let market = &ctx.accounts.market;
-            require!(market.state == MarketState::Active, OpinionError::MarketNotActive);
-            require!(clock.unix_timestamp < market.closes_at, OpinionError::MarketExpired);
-        }
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
 
-        // Cannot react to your own opinion
-        require!(
-            ctx.accounts.reactor.key() != ctx.accounts.opinion.staker,
-            OpinionError::CannotReactToOwnOpinion
-        );
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
 
-        // Transfer reaction stake into market escrow
-        let cpi_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.reactor_usdc.to_account_info(),
-                to: ctx.accounts.escrow_token_account.to_account_info(),
-                authority: ctx.accounts.reactor.to_account_info(),
-            },
-        );
-        token::transfer(cpi_ctx, stake_amount)?;
+    /// Recent-activity ring buffer (init_activity_log for older markets)
+    #[account(
+        mut,
+        seeds = [b"activity_log", market.key().as_ref()],
+        bump = activity_log.load()?.bump,
+    )]
+    pub activity_log: AccountLoader<'info, ActivityLog>,
 
-        let market_key = ctx.accounts.market.key();
-        let opinion_key = ctx.accounts.opinion.key();
-        let reactor_key = ctx.accounts.reactor.key();
-        let reaction_type_for_event = reaction_type.clone();
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
 
-        // Update opinion's backing or slashing total
-        let opinion = &mut ctx.accounts.opinion;
-        match reaction_type {
-            ReactionType::Back => {
-                opinion.backing_total = opinion.backing_total
-                    .checked_add(stake_amount)
-                    .ok_or(OpinionError::Overflow)?;
-            }
-            ReactionType::Slash => {
-                opinion.slashing_total = opinion.slashing_total
-                    .checked_add(stake_amount)
-                    .ok_or(OpinionError::Overflow)?;
-            }
-        }
+    #[account(
+        init,
+        payer = authority,
+        space = Opinion::SPACE,
+        seeds = [b"opinion", market.key().as_ref(), compound_vault.owner.as_ref()],
+        bump,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
 
-        // Store reaction record (one per reactor per opinion — enforced by PDA seeds)
-        let reaction = &mut ctx.accounts.reaction;
-        reaction.opinion = opinion_key;
-        reaction.reactor = reactor_key;
-        reaction.reaction_type = reaction_type.clone();
-        reaction.stake_amount = stake_amount;
-        reaction.bump = ctx.bumps.reaction;
+    /// Page the opinion is listed on; the first opinion on a page pays its rent
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = OpinionIndexPage::SPACE,
+        seeds = [
+            b"opinion_index".as_ref(),
+            market.key().as_ref(),
+            &market.opinion_index_page().to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub opinion_index: Box<Account<'info, OpinionIndexPage>>,
 
-        // Add to market total pool
-        let market = &mut ctx.accounts.market;
-        market.total_stake = market.total_stake
-            .checked_add(stake_amount)
-            .ok_or(OpinionError::Overflow)?;
+    #[account(
+        mut,
+        seeds = [b"compound_vault", compound_vault.owner.as_ref()],
+        bump = compound_vault.bump,
+    )]
+    pub compound_vault: Account<'info, CompoundVault>,
 
-        emit!(ReactionSubmittedEvent {
-            market: market_key,
-            opinion: opinion_key,
-            reactor: reactor_key,
-            reaction_type: reaction_type_for_event,
-            stake_amount,
-        });
+    #[account(
+        mut,
+        seeds = [b"compound_vault_usdc", compound_vault.owner.as_ref()],
+        bump,
+    )]
+    pub vault_usdc: Account<'info, TokenAccount>,
 
-        Ok(())
-    }
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = UserProfile::SPACE,
+        seeds = [b"user_profile", compound_vault.owner.as_ref()],
+        bump,
+    )]
+    pub user_profile: Box<Account<'info, UserProfile>>,
 
-    /// Close a market after its duration expires. Permissionless.
-    pub fn close_market(ctx: Context<CloseMarket>) -> Result<()> {
-        let clock = Clock::get()?;
-        let market_key = ctx.accounts.market.key();
-        let market = &mut ctx.accounts.market;
-        require!(market.state == MarketState::Active, OpinionError::MarketNotActive);
-        require!(clock.unix_timestamp >= market.closes_at, OpinionError::MarketNotExpired);
-        market.state = MarketState::Closed;
-        let staker_count = market.staker_count;
-        let total_stake = market.total_stake;
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+    pub system_program: Program<'info, System>,
 
-        emit!(MarketClosedEvent {
-            market: market_key,
-            closed_at: clock.unix_timestamp,
-            total_stakers: staker_count,
-            total_stake,
-        });
+    /// Required when the market uses CrowdScoreMode::Median
+    #[account(
+        mut,
+        seeds = [b"crowd_histogram", market.key().as_ref()],
+        bump = crowd_histogram.bump,
+    )]
+    pub crowd_histogram: Option<Box<Account<'info, CrowdHistogram>>>,
+}
 
-        Ok(())
-    }
+#[derive(Accounts)]
+pub struct WithdrawCompoundVault<'info> {
+    pub owner: Signer<'info>,
 
-    /// Oracle records the market-level AI sentiment score.
-    /// Also transitions the market to Scored (ready for per-opinion settlement).
-    pub fn record_sentiment(
-        ctx: Context<RecordSentiment>,
-        score: u8,
-        confidence: u8,
-        summary_hash: [u8; 32],
-    ) -> Result<()> {
-        require!(score <= 100, OpinionError::InvalidScore);
-        require!(confidence <= 2, OpinionError::InvalidConfidence);
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
 
-        let market = &mut ctx.accounts.market;
-        require!(market.state == MarketState::Closed, OpinionError::MarketNotClosed);
+    #[account(
+        seeds = [b"compound_vault", owner.key().as_ref()],
+        bump = compound_vault.bump,
+    )]
+    pub compound_vault: Account<'info, CompoundVault>,
 
-        market.sentiment_score = score;
-        market.confidence = confidence;
-        market.summary_hash = summary_hash;
-        market.state = MarketState::Scored;
+    #[account(
+        mut,
+        seeds = [b"compound_vault_usdc", owner.key().as_ref()],
+        bump,
+    )]
+    pub vault_usdc: Account<'info, TokenAccount>,
 
-        emit!(SentimentRecordedEvent {
-            market: ctx.accounts.market.key(),
-            sentiment_score: score,
-            confidence,
-            summary_hash,
-        });
+    #[account(
+        mut,
+        constraint = owner_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = owner_usdc.owner == owner.key(),
+    )]
+    pub owner_usdc: Account<'info, TokenAccount>,
 
-        Ok(())
-    }
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+}
 
-    /// Oracle records the AI quality score for a single opinion — Layer 3.
-    /// Called once per opinion before settle_opinion.
-    pub fn record_ai_score(
-        ctx: Context<RecordAiScore>,
-        ai_score: u8,
-    ) -> Result<()> {
-        require!(ai_score <= 100, OpinionError::InvalidScore);
+#[derive(Accounts)]
+pub struct ApplyFeeWaiver<'info> {
+    /// Anyone may apply a waiver the staker qualifies for
+    pub caller: Signer<'info>,
 
-        let market = &ctx.accounts.market;
-        require!(market.state == MarketState::Scored, OpinionError::MarketNotScored);
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
 
-        let market_key = ctx.accounts.market.key();
-        let opinion_key = ctx.accounts.opinion.key();
-        let staker_key = ctx.accounts.opinion.staker;
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
 
-        let opinion = &mut ctx.accounts.opinion;
-        opinion.ai_score = ai_score;
+    #[account(mut, constraint = opinion.load()?.market == market.key())]
+    pub opinion: AccountLoader<'info, Opinion>,
 
-        emit!(AiScoreRecordedEvent {
-            market: market_key,
-            opinion: opinion_key,
-            staker: staker_key,
-            ai_score,
-        });
+    #[account(
+        seeds = [b"reactor_stats", reactor_stats.reactor.as_ref()],
+        bump = reactor_stats.bump,
+        constraint = reactor_stats.reactor == opinion.load()?.staker @ OpinionError::Unauthorized,
+    )]
+    pub reactor_stats: Account<'info, ReactorStats>,
+}
 
-        Ok(())
-    }
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PushPayout<'info> {
+    /// Any keeper; pays for the staker's ATA if it has to be created
+    #[account(mut)]
+    pub keeper: Signer<'info>,
 
-    /// Oracle settles a single opinion by applying the Triple-Check formula.
-    /// Called once per opinion after all AI scores are recorded.
-    ///
-    /// Oracle computes off-chain:
-    ///   crowd_score = Σ(prediction_i × amount_i) / Σ(amount_i)
-    ///   weight_score_i = max(5, (netBacking_i - minNet) / range × 95 + 5)
-    ///   consensus_score_i = max(0, 100 - |prediction_i - crowd_score|)
-    ///
-    /// On-chain we compute:
-    ///   combined_bps = weight*50 + consensus*30 + ai*20  (range 0–10000)
-    ///   combined_score = combined_bps / 100              (stored 0–100)
-    pub fn settle_opinion(
-        ctx: Context<SettleOpinion>,
-        crowd_score: u8,
-        weight_score: u8,
-        consensus_score: u8,
-    ) -> Result<()> {
-        require!(crowd_score <= 100, OpinionError::InvalidScore);
-        require!(weight_score <= 100, OpinionError::InvalidScore);
-        require!(consensus_score <= 100, OpinionError::InvalidScore);
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
 
-        let market = &mut ctx.accounts.market;
-        require!(market.state == MarketState::Scored, OpinionError::MarketNotScored);
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
 
-        // Store crowd_score on market — idempotent, same value every call
-        market.crowd_score = crowd_score;
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
 
-        let market_key = ctx.accounts.market.key();
-        let opinion_key = ctx.accounts.opinion.key();
-        let ai_score_val = ctx.accounts.opinion.ai_score;
-        let staker_key = ctx.accounts.opinion.staker;
-
-        let opinion = &mut ctx.accounts.opinion;
-        opinion.weight_score = weight_score;
-        opinion.consensus_score = consensus_score;
-
-        // S = (W × 0.5) + (C × 0.3) + (A × 0.2)
-        // Computed as integer basis points (0–10000), then divided by 100
-        let combined_bps: u64 =
-            (weight_score as u64)
-                .checked_mul(WEIGHT_MULTIPLIER)
-                .ok_or(OpinionError::Overflow)?
-            .checked_add(
-                (consensus_score as u64)
-                    .checked_mul(CONSENSUS_MULTIPLIER)
-                    .ok_or(OpinionError::Overflow)?
-            )
-            .ok_or(OpinionError::Overflow)?
-            .checked_add(
-                (ai_score_val as u64)
-                    .checked_mul(AI_MULTIPLIER)
-                    .ok_or(OpinionError::Overflow)?
-            )
-            .ok_or(OpinionError::Overflow)?;
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
 
-        opinion.combined_score = (combined_bps / 100) as u8;
-        let combined_score_val = opinion.combined_score;
+    #[account(
+        mut,
+        constraint = opinion.load()?.market == market.key(),
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
 
-        emit!(OpinionSettledEvent {
-            market: market_key,
-            opinion: opinion_key,
-            staker: staker_key,
-            weight_score,
-            consensus_score,
-            ai_score: ai_score_val,
-            combined_score: combined_score_val,
-        });
+    /// CHECK: the opinion's staker; only used as the ATA owner
+    #[account(address = opinion.load()?.staker @ OpinionError::Unauthorized)]
+    pub staker: UncheckedAccount<'info>,
 
-        Ok(())
-    }
+    #[account(address = config.usdc_mint @ OpinionError::MintMismatch)]
+    pub usdc_mint: Account<'info, Mint>,
 
-    /// Oracle calls this once after all opinions are settled.
-    /// Deducts protocol fee, stores distributable_pool, transitions to Settled.
-    /// Also sends protocol fee to treasury.
-    pub fn finalize_settlement(ctx: Context<FinalizeSettlement>) -> Result<()> {
-        let market = &ctx.accounts.market;
-        require!(market.state == MarketState::Scored, OpinionError::MarketNotScored);
-        require!(market.total_stake > 0, OpinionError::EmptyPrizePool);
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = staker,
+    )]
+    pub staker_usdc: Account<'info, TokenAccount>,
 
-        let total_stake = market.total_stake;
-        let protocol_fee = total_stake
-            .checked_mul(PROTOCOL_FEE_BPS)
-            .ok_or(OpinionError::Overflow)?
-            .checked_div(10_000)
-            .ok_or(OpinionError::Overflow)?;
-        let distributable_pool = total_stake
-            .checked_sub(protocol_fee)
-            .ok_or(OpinionError::Overflow)?;
+    /// CHECK: staker's PayoutPreference PDA; may be uninitialized
+    #[account(seeds = [b"payout_preference", staker.key().as_ref()], bump)]
+    pub payout_preference: UncheckedAccount<'info>,
 
-        // Send protocol fee to treasury
-        let market_uuid = market.uuid;
-        let market_bump = market.bump;
-        let seeds: &[&[u8]] = &[b"market", &market_uuid, &[market_bump]];
-        let signer_seeds = &[seeds];
+    /// Required if the staker registered a payout destination
+    #[account(
+        mut,
+        constraint = payout_destination.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = is_payout_destination(&payout_preference, &staker.key(), payout_destination)
+            @ OpinionError::PayoutDestinationMismatch,
+    )]
+    pub payout_destination: Option<Account<'info, TokenAccount>>,
 
-        let fee_cpi = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.escrow_token_account.to_account_info(),
-                to: ctx.accounts.treasury_usdc.to_account_info(),
-                authority: ctx.accounts.market.to_account_info(),
-            },
-            signer_seeds,
-        );
-        token::transfer(fee_cpi, protocol_fee)?;
+    #[account(
+        mut,
+        constraint = keeper_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = keeper_usdc.owner == keeper.key(),
+    )]
+    pub keeper_usdc: Account<'info, TokenAccount>,
 
-        // Split distributable pool: 70% opinion, 30% prediction (of which 20% is jackpot)
-        let opinion_pool = distributable_pool * 70 / 100;
-        let full_prediction_pool = distributable_pool - opinion_pool; // 30%
-        let jackpot_amount = full_prediction_pool * 20 / 100;         // 6% of total
-        let prediction_pool = full_prediction_pool - jackpot_amount;  // 24% of total
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        space = UserProfile::SPACE,
+        seeds = [b"user_profile", staker.key().as_ref()],
+        bump,
+    )]
+    pub user_profile: Box<Account<'info, UserProfile>>,
 
-        let market_key = ctx.accounts.market.key();
-        let market = &mut ctx.accounts.market;
-        market.distributable_pool = distributable_pool;
-        market.opinion_pool = opinion_pool;
-        market.prediction_pool = prediction_pool;
-        market.jackpot_amount = jackpot_amount;
-        market.jackpot_claimed = false;
-        market.state = MarketState::Settled;
+    /// Refreshed with the user's new total_won when passed
+    #[account(
+        mut,
+        seeds = [b"earner_leaderboard"],
+        bump = earner_leaderboard.bump,
+    )]
+    pub earner_leaderboard: Option<Box<Account<'info, EarnerLeaderboard>>>,
 
-        emit!(MarketFinalizedEvent {
-            market: market_key,
-            total_pool: total_stake,
-            distributable_pool,
-            protocol_fee,
-            crowd_score: market.crowd_score,
-        });
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
 
-        Ok(())
-    }
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimJackpot<'info> {
+    #[account(constraint = oracle_authority.key() == market.oracle(&config) @ OpinionError::Unauthorized)]
+    pub oracle_authority: Signer<'info>,
 
-    /// Staker claims their proportional payout after settlement.
-    /// Dual pool payout:
-    ///   - Opinion pool: proportional to net backing received
-    ///   - Prediction pool: inverse distance from crowd score
-    ///
-    /// Oracle passes total_net_backing and sum_prediction_weights (computed off-chain).
-    pub fn claim_payout(
-        ctx: Context<ClaimPayout>,
-        _total_combined_score: u64,   // kept for backward compat, set to 1 if unused
-        total_net_backing: u64,
-        sum_prediction_weights: u64,
-    ) -> Result<()> {
-        let market = &ctx.accounts.market;
-        require!(market.state == MarketState::Settled, OpinionError::MarketNotAwaitingSettlement);
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
 
-        let opinion = &ctx.accounts.opinion;
-        require!(!opinion.paid, OpinionError::AlreadyPaid);
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
 
-        // Opinion pool payout — proportional to net backing received
-        let net_backing = {
-            let b = opinion.backing_total as i64;
-            let s = opinion.slashing_total as i64;
-            (b - s).max(0) as u64
-        };
-        let opinion_payout = if total_net_backing > 0 {
-            net_backing
-                .checked_mul(market.opinion_pool).ok_or(OpinionError::Overflow)?
-                .checked_div(total_net_backing).ok_or(OpinionError::Overflow)?
-        } else {
-            market.opinion_pool / market.staker_count as u64 // equal split fallback
-        };
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
 
-        // Prediction pool payout — inverse distance from crowd score
-        let diff = (opinion.market_prediction as i64 - market.crowd_score as i64).unsigned_abs();
-        let prediction_weight = 1_000_000u64 / (diff + 1);
-        let prediction_payout = if sum_prediction_weights > 0 {
-            prediction_weight
-                .checked_mul(market.prediction_pool).ok_or(OpinionError::Overflow)?
-                .checked_div(sum_prediction_weights).ok_or(OpinionError::Overflow)?
-        } else {
-            0
-        };
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
 
-        let total_payout = opinion_payout.checked_add(prediction_payout).ok_or(OpinionError::Overflow)?;
+    #[account(
+        mut,
+        constraint = winner_token_account.mint == config.usdc_mint @ OpinionError::MintMismatch,
+    )]
+    pub winner_token_account: Account<'info, TokenAccount>,
 
-        let market_uuid = market.uuid;
-        let market_bump = market.bump;
-        let seeds: &[&[u8]] = &[b"market", &market_uuid, &[market_bump]];
-        let signer_seeds = &[seeds];
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+}
 
-        let payout_cpi = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.escrow_token_account.to_account_info(),
-                to: ctx.accounts.staker_usdc.to_account_info(),
-                authority: ctx.accounts.market.to_account_info(),
-            },
-            signer_seeds,
-        );
-        token::transfer(payout_cpi, total_payout)?;
+#[derive(Accounts)]
+pub struct OptInRedistribution<'info> {
+    pub staker: Signer<'info>,
 
-        let market_key = ctx.accounts.market.key();
-        let opinion_key = ctx.accounts.opinion.key();
-        let staker_key = ctx.accounts.opinion.staker;
-        let combined_score_val = ctx.accounts.opinion.combined_score;
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
 
-        let opinion = &mut ctx.accounts.opinion;
-        opinion.payout_amount = total_payout;
-        opinion.paid = true;
+    #[account(
+        mut,
+        constraint = opinion.load()?.market == market.key(),
+        constraint = opinion.load()?.staker == staker.key() @ OpinionError::Unauthorized,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+}
 
-        // If this is the highest-earning staker, record as market winner for display
-        let market = &mut ctx.accounts.market;
-        if market.winner.is_none() {
-            market.winner = Some(staker_key);
-        }
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExpirePayouts<'info> {
+    /// Any keeper once the claim window ends
+    pub caller: Signer<'info>,
 
-        emit!(PayoutClaimedEvent {
-            market: market_key,
-            opinion: opinion_key,
-            staker: staker_key,
-            payout_amount: total_payout,
-            combined_score: combined_score_val,
-        });
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+}
 
-        Ok(())
-    }
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SkipDustPayouts<'info> {
+    /// Any keeper
+    pub caller: Signer<'info>,
 
-    /// Oracle claims the jackpot on behalf of the top predictor.
-    /// Can only be called once per market (guarded by jackpot_claimed).
-    pub fn claim_jackpot(ctx: Context<ClaimJackpot>, jackpot_winner: Pubkey) -> Result<()> {
-        let market = &ctx.accounts.market;
-        require!(market.state == MarketState::Settled, OpinionError::MarketNotAwaitingSettlement);
-        require!(!market.jackpot_claimed, OpinionError::JackpotAlreadyClaimed);
-        require!(
-            ctx.accounts.winner_token_account.owner == jackpot_winner,
-            OpinionError::Unauthorized
-        );
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
 
-        let jackpot = market.jackpot_amount;
-        let market_uuid = market.uuid;
-        let market_bump = market.bump;
-        let seeds: &[&[u8]] = &[b"market", &market_uuid, &[market_bump]];
-        let signer_seeds = &[seeds];
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+}
 
-        let jackpot_cpi = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.escrow_token_account.to_account_info(),
-                to: ctx.accounts.winner_token_account.to_account_info(),
-                authority: ctx.accounts.market.to_account_info(),
-            },
-            signer_seeds,
-        );
-        token::transfer(jackpot_cpi, jackpot)?;
+#[derive(Accounts)]
+pub struct TallyPayoutCap<'info> {
+    /// Any keeper
+    pub caller: Signer<'info>,
 
-        let market_key = ctx.accounts.market.key();
-        let market = &mut ctx.accounts.market;
-        market.jackpot_claimed = true;
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+}
 
-        emit!(JackpotClaimedEvent {
-            market: market_key,
-            winner: jackpot_winner,
-            amount: jackpot,
-        });
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimSettlementBounty<'info> {
+    #[account(mut)]
+    pub keeper: Signer<'info>,
 
-        Ok(())
-    }
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
 
-    /// Distribute prize pool (legacy single-winner path).
-    /// Kept for backward compatibility. New markets should use settle_opinion + claim_payout.
-    pub fn run_lottery(ctx: Context<RunLottery>, winner_pubkey: Pubkey) -> Result<()> {
-        require!(
-            ctx.accounts.winner_token_account.owner == winner_pubkey,
-            OpinionError::Unauthorized
-        );
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
 
-        let market = &ctx.accounts.market;
-        require!(market.state == MarketState::Scored, OpinionError::MarketNotScored);
-        require!(market.total_stake > 0, OpinionError::EmptyPrizePool);
+    #[account(
+        mut,
+        close = keeper,
+        seeds = [b"settlement_bounty", market.key().as_ref(), keeper.key().as_ref()],
+        bump = settlement_bounty.bump,
+    )]
+    pub settlement_bounty: Account<'info, SettlementBounty>,
 
-        let total_stake = market.total_stake;
-        let protocol_fee = total_stake
-            .checked_mul(PROTOCOL_FEE_BPS)
-            .unwrap()
-            .checked_div(10_000)
-            .unwrap();
-        let prize_pool = total_stake.checked_sub(protocol_fee).unwrap();
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
 
-        let market_uuid = market.uuid;
-        let market_bump = market.bump;
-        let seeds: &[&[u8]] = &[b"market", &market_uuid, &[market_bump]];
-        let signer_seeds = &[seeds];
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
 
-        let fee_cpi = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.escrow_token_account.to_account_info(),
-                to: ctx.accounts.treasury_usdc.to_account_info(),
-                authority: ctx.accounts.market.to_account_info(),
-            },
-            signer_seeds,
-        );
-        token::transfer(fee_cpi, protocol_fee)?;
+    #[account(
+        mut,
+        constraint = keeper_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = keeper_usdc.owner == keeper.key() @ OpinionError::Unauthorized,
+    )]
+    pub keeper_usdc: Account<'info, TokenAccount>,
 
-        let prize_cpi = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.escrow_token_account.to_account_info(),
-                to: ctx.accounts.winner_token_account.to_account_info(),
-                authority: ctx.accounts.market.to_account_info(),
-            },
-            signer_seeds,
-        );
-        token::transfer(prize_cpi, prize_pool)?;
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+}
 
-        let market = &mut ctx.accounts.market;
-        market.winner = Some(winner_pubkey);
-        market.state = MarketState::Settled;
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SweepDust<'info> {
+    /// Anyone may sweep once the claim window ends
+    pub caller: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
 
-        emit!(LotterySettledEvent {
-            market: ctx.accounts.market.key(),
-            winner: winner_pubkey,
-            prize_amount: prize_pool,
-            protocol_fee,
-        });
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
 
-        Ok(())
-    }
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
 
-    /// Allow stakers to recover their stake if market is abandoned (14+ days after close).
-    pub fn recover_stake(ctx: Context<RecoverStake>) -> Result<()> {
-        let clock = Clock::get()?;
-        let market = &ctx.accounts.market;
+    #[account(
+        mut,
+        constraint = treasury_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = treasury_usdc.owner == config.treasury @ OpinionError::TreasuryMismatch,
+    )]
+    pub treasury_usdc: Account<'info, TokenAccount>,
 
-        require!(
-            clock.unix_timestamp >= market.closes_at + RECOVERY_PERIOD,
-            OpinionError::MarketNotExpired
-        );
-        require!(
-            market.state != MarketState::Settled,
-            OpinionError::MarketNotActive
-        );
+    #[account(mut, seeds = [b"treasury_ledger"], bump = treasury_ledger.bump)]
+    pub treasury_ledger: Account<'info, TreasuryLedger>,
 
-        let opinion = &ctx.accounts.opinion;
-        let stake_amount = opinion.stake_amount;
+    /// CHECK: receives the escrow's rent; must be the market creator who paid it
+    #[account(mut, address = market.creator @ OpinionError::Unauthorized)]
+    pub creator: UncheckedAccount<'info>,
 
-        let market_uuid = market.uuid;
-        let market_bump = market.bump;
-        let seeds: &[&[u8]] = &[b"market", &market_uuid, &[market_bump]];
-        let signer_seeds = &[seeds];
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
+}
 
-        let recovery_cpi = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.escrow_token_account.to_account_info(),
-                to: ctx.accounts.staker_usdc.to_account_info(),
-                authority: ctx.accounts.market.to_account_info(),
-            },
-            signer_seeds,
-        );
-        token::transfer(recovery_cpi, stake_amount)?;
+#[derive(Accounts)]
+pub struct InitializeEarnerLeaderboard<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
 
-        msg!("Stake recovered: staker={} amount={}", ctx.accounts.staker.key(), stake_amount);
+    #[account(
+        init,
+        payer = payer,
+        space = EarnerLeaderboard::SPACE,
+        seeds = [b"earner_leaderboard"],
+        bump,
+    )]
+    pub leaderboard: Account<'info, EarnerLeaderboard>,
 
-        Ok(())
-    }
+    pub system_program: Program<'info, System>,
 }
 
-// ── Account Contexts ─────────────────────────────────────────────────────────
-
 #[derive(Accounts)]
-pub struct InitializeConfig<'info> {
+pub struct InitializeReactorLeaderboard<'info> {
     #[account(mut)]
-    pub deployer: Signer<'info>,
+    pub payer: Signer<'info>,
 
     #[account(
         init,
-        payer = deployer,
-        space = ProgramConfig::SPACE,
-        seeds = [b"config"],
+        payer = payer,
+        space = ReactorLeaderboard::SPACE,
+        seeds = [b"reactor_leaderboard"],
         bump,
     )]
-    pub config: Account<'info, ProgramConfig>,
+    pub leaderboard: Account<'info, ReactorLeaderboard>,
 
-    pub usdc_mint: Account<'info, Mint>,
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-#[instruction(statement: String, duration_secs: u64, uuid: [u8; 16])]
-pub struct CreateMarket<'info> {
+pub struct ScoreReaction<'info> {
+    /// Permissionless crank — pays for the reactor's stats account on first use
     #[account(mut)]
-    pub creator: Signer<'info>,
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(constraint = opinion.load()?.market == market.key() @ OpinionError::OpinionMarketMismatch)]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    #[account(
+        mut,
+        seeds = [b"reaction", opinion.key().as_ref(), reaction.reactor.as_ref()],
+        bump = reaction.bump,
+    )]
+    pub reaction: Account<'info, Reaction>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = ReactorStats::SPACE,
+        seeds = [b"reactor_stats", reaction.reactor.as_ref()],
+        bump,
+    )]
+    pub reactor_stats: Account<'info, ReactorStats>,
+
+    #[account(
+        mut,
+        seeds = [b"reactor_leaderboard"],
+        bump = leaderboard.bump,
+    )]
+    pub leaderboard: Account<'info, ReactorLeaderboard>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RequestLotteryRandomness<'info> {
+    #[account(mut, constraint = oracle_authority.key() == market.oracle(&config) @ OpinionError::Unauthorized)]
+    pub oracle_authority: Signer<'info>,
 
     #[account(seeds = [b"config"], bump = config.bump)]
     pub config: Account<'info, ProgramConfig>,
 
     #[account(
-        init,
-        payer = creator,
-        space = Market::SPACE,
-        seeds = [b"market", uuid.as_ref()],
-        bump,
+        mut,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
     )]
     pub market: Account<'info, Market>,
 
+    /// CHECK: Switchboard RandomnessAccountData, parsed in the handler
+    #[account(owner = SWITCHBOARD_ON_DEMAND_PROGRAM_ID @ OpinionError::InvalidRandomnessAccount)]
+    pub randomness_account: UncheckedAccount<'info>,
+
     #[account(
         init,
-        payer = creator,
-        token::mint = usdc_mint,
-        token::authority = market,
-        seeds = [b"escrow", market.key().as_ref()],
+        payer = oracle_authority,
+        space = RandomnessRequest::SPACE,
+        seeds = [b"randomness", market.key().as_ref()],
         bump,
     )]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    pub randomness_request: Account<'info, RandomnessRequest>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConsumeRandomness<'info> {
+    /// Any keeper bundling this with the Switchboard reveal
+    pub keeper: Signer<'info>,
 
     #[account(
         mut,
-        constraint = creator_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
-        constraint = creator_usdc.owner == creator.key(),
+        seeds = [b"randomness", randomness_request.market.as_ref()],
+        bump = randomness_request.bump,
     )]
-    pub creator_usdc: Account<'info, TokenAccount>,
+    pub randomness_request: Account<'info, RandomnessRequest>,
 
     #[account(
         mut,
-        constraint = treasury_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
-        constraint = treasury_usdc.owner == config.treasury @ OpinionError::TreasuryMismatch,
+        address = randomness_request.market,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
     )]
-    pub treasury_usdc: Account<'info, TokenAccount>,
-
-    #[account(constraint = usdc_mint.key() == config.usdc_mint @ OpinionError::MintMismatch)]
-    pub usdc_mint: Account<'info, Mint>,
+    pub market: Account<'info, Market>,
 
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+    /// CHECK: must be the committed Switchboard randomness account
+    #[account(
+        address = randomness_request.randomness_account @ OpinionError::InvalidRandomnessAccount,
+        owner = SWITCHBOARD_ON_DEMAND_PROGRAM_ID @ OpinionError::InvalidRandomnessAccount,
+    )]
+    pub randomness_account: UncheckedAccount<'info>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct StakeOpinion<'info> {
-    #[account(mut)]
-    pub staker: Signer<'info>,
+pub struct RunLotteryRandom<'info> {
+    /// Anyone may settle once randomness is consumed
+    pub caller: Signer<'info>,
 
     #[account(seeds = [b"config"], bump = config.bump)]
     pub config: Account<'info, ProgramConfig>,
@@ -1154,6 +17858,23 @@ pub struct StakeOpinion<'info> {
     )]
     pub market: Account<'info, Market>,
 
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// The market's registry page; its entry is refreshed when passed
+    #[account(
+        mut,
+        seeds = [b"market_registry".as_ref(), &market.registry_page().to_le_bytes()],
+        bump = registry_page.bump,
+    )]
+    pub registry_page: Option<Account<'info, MarketRegistryPage>>,
+
+    #[account(
+        seeds = [b"randomness", market.key().as_ref()],
+        bump = randomness_request.bump,
+    )]
+    pub randomness_request: Account<'info, RandomnessRequest>,
+
     #[account(
         mut,
         seeds = [b"escrow", market.key().as_ref()],
@@ -1161,30 +17882,47 @@ pub struct StakeOpinion<'info> {
     )]
     pub escrow_token_account: Account<'info, TokenAccount>,
 
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
     #[account(
-        init,
-        payer = staker,
-        space = Opinion::SPACE,
-        seeds = [b"opinion", market.key().as_ref(), staker.key().as_ref()],
-        bump,
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
     )]
-    pub opinion: Account<'info, Opinion>,
+    pub escrow_authority: UncheckedAccount<'info>,
 
     #[account(
         mut,
-        constraint = staker_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
-        constraint = staker_usdc.owner == staker.key(),
+        constraint = winner_token_account.mint == config.usdc_mint @ OpinionError::MintMismatch,
     )]
-    pub staker_usdc: Account<'info, TokenAccount>,
+    pub winner_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = treasury_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = treasury_usdc.owner == config.treasury @ OpinionError::TreasuryMismatch,
+    )]
+    pub treasury_usdc: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+    pub memo_program: Program<'info, Memo>,
+
+    /// Human reviewer co-signature (see ProgramConfig::reviewer_required)
+    pub reviewer: Option<Signer<'info>>,
+
+    /// Required once the market has settle hooks; the hook programs follow
+    /// in remaining_accounts
+    #[account(seeds = [b"settle_hooks", market.key().as_ref()], bump = settle_hooks.bump)]
+    pub settle_hooks: Option<Account<'info, SettleHooks>>,
+
+    /// CHECK: PDA signing settle hook CPIs; holds no data
+    #[account(seeds = [b"settle_hook_authority"], bump)]
+    pub hook_authority: Option<UncheckedAccount<'info>>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct ReactToOpinion<'info> {
-    #[account(mut)]
-    pub reactor: Signer<'info>,
+pub struct EnterRefunding<'info> {
+    /// CHECK: permissionless — anyone can call after score_deadline
+    pub caller: UncheckedAccount<'info>,
 
     #[account(seeds = [b"config"], bump = config.bump)]
     pub config: Account<'info, ProgramConfig>,
@@ -1196,44 +17934,77 @@ pub struct ReactToOpinion<'info> {
     )]
     pub market: Account<'info, Market>,
 
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// The market's registry page; its entry is refreshed when passed
     #[account(
         mut,
-        constraint = opinion.market == market.key(),
+        seeds = [b"market_registry".as_ref(), &market.registry_page().to_le_bytes()],
+        bump = registry_page.bump,
     )]
-    pub opinion: Account<'info, Opinion>,
+    pub registry_page: Option<Account<'info, MarketRegistryPage>>,
 
-    /// One reaction per (reactor, opinion) — enforced by PDA seeds
     #[account(
-        init,
-        payer = reactor,
-        space = Reaction::SPACE,
-        seeds = [b"reaction", opinion.key().as_ref(), reactor.key().as_ref()],
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
         bump,
     )]
-    pub reaction: Account<'info, Reaction>,
+    pub escrow_token_account: Account<'info, TokenAccount>,
 
+    /// Required when oracle slashing is enabled
     #[account(
         mut,
-        seeds = [b"escrow", market.key().as_ref()],
-        bump,
+        seeds = [b"oracle_bond", market.oracle(&config).as_ref()],
+        bump = oracle_bond.bump,
     )]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    pub oracle_bond: Option<Account<'info, OracleBond>>,
+
+    #[account(mut, seeds = [b"oracle_bond_vault", market.oracle(&config).as_ref()], bump)]
+    pub oracle_bond_vault: Option<Account<'info, TokenAccount>>,
 
+    /// Lets the market refund before score_deadline if the oracle is dead
+    #[account(
+        seeds = [b"oracle_status", market.oracle(&config).as_ref()],
+        bump = oracle_status.bump,
+    )]
+    pub oracle_status: Option<Account<'info, OracleStatus>>,
+
+    /// Required while a settlement proposal is open; a disputed one's bond
+    /// is returned to challenger_usdc through escrow_authority
     #[account(
         mut,
-        constraint = reactor_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
-        constraint = reactor_usdc.owner == reactor.key(),
+        seeds = [b"optimistic_settlement", market.key().as_ref()],
+        bump = optimistic_settlement.bump,
     )]
-    pub reactor_usdc: Account<'info, TokenAccount>,
+    pub optimistic_settlement: Option<Account<'info, OptimisticSettlement>>,
+
+    #[account(
+        mut,
+        constraint = challenger_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = optimistic_settlement.as_ref().and_then(|p| p.challenger) == Some(challenger_usdc.owner)
+            @ OpinionError::Unauthorized,
+    )]
+    pub challenger_usdc: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: Option<UncheckedAccount<'info>>,
 
     pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+    pub memo_program: Program<'info, Memo>,
 }
 
 #[derive(Accounts)]
-pub struct CloseMarket<'info> {
-    /// CHECK: permissionless — anyone can call after expiry
-    pub caller: UncheckedAccount<'info>,
+pub struct RecoverStake<'info> {
+    #[account(mut)]
+    pub staker: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
 
     #[account(
         mut,
@@ -1241,12 +18012,43 @@ pub struct CloseMarket<'info> {
         bump = market.bump,
     )]
     pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"opinion", market.key().as_ref(), staker.key().as_ref()],
+        bump = opinion.load()?.bump,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    #[account(
+        mut,
+        constraint = staker_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = staker_usdc.owner == staker.key(),
+    )]
+    pub staker_usdc: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
 }
 
 #[derive(Accounts)]
-pub struct RecordSentiment<'info> {
-    #[account(constraint = oracle_authority.key() == config.oracle_authority @ OpinionError::Unauthorized)]
-    pub oracle_authority: Signer<'info>,
+pub struct RecoverReactionStake<'info> {
+    #[account(mut)]
+    pub reactor: Signer<'info>,
 
     #[account(seeds = [b"config"], bump = config.bump)]
     pub config: Account<'info, ProgramConfig>,
@@ -1257,29 +18059,50 @@ pub struct RecordSentiment<'info> {
         bump = market.bump,
     )]
     pub market: Account<'info, Market>,
-}
 
-#[derive(Accounts)]
-pub struct RecordAiScore<'info> {
-    #[account(constraint = oracle_authority.key() == config.oracle_authority @ OpinionError::Unauthorized)]
-    pub oracle_authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"escrow", market.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
 
-    #[account(seeds = [b"config"], bump = config.bump)]
-    pub config: Account<'info, ProgramConfig>,
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
 
-    pub market: Account<'info, Market>,
+    #[account(
+        mut,
+        constraint = opinion.load()?.market == market.key() @ OpinionError::OpinionMarketMismatch,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    #[account(
+        mut,
+        close = reactor,
+        seeds = [b"reaction", opinion.key().as_ref(), reactor.key().as_ref()],
+        bump = reaction.bump,
+    )]
+    pub reaction: Account<'info, Reaction>,
 
     #[account(
         mut,
-        constraint = opinion.market == market.key(),
+        constraint = reactor_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = reactor_usdc.owner == reactor.key(),
     )]
-    pub opinion: Account<'info, Opinion>,
+    pub reactor_usdc: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
 }
 
 #[derive(Accounts)]
-pub struct SettleOpinion<'info> {
-    #[account(constraint = oracle_authority.key() == config.oracle_authority @ OpinionError::Unauthorized)]
-    pub oracle_authority: Signer<'info>,
+pub struct BootstrapLookupTable<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
 
     #[account(seeds = [b"config"], bump = config.bump)]
     pub config: Account<'info, ProgramConfig>,
@@ -1291,21 +18114,58 @@ pub struct SettleOpinion<'info> {
     )]
     pub market: Account<'info, Market>,
 
+    #[account(seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(seeds = [b"escrow", market.key().as_ref()], bump)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(seeds = [b"escrow_authority", market.key().as_ref()], bump)]
+    pub escrow_authority: UncheckedAccount<'info>,
+
     #[account(
-        mut,
-        constraint = opinion.market == market.key(),
+        constraint = treasury_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = treasury_usdc.owner == config.treasury @ OpinionError::TreasuryMismatch,
     )]
-    pub opinion: Account<'info, Opinion>,
+    pub treasury_usdc: Account<'info, TokenAccount>,
+
+    /// CHECK: the market's lookup table; checked against the address derived
+    /// from `recent_slot` on creation, else against market.lookup_table
+    #[account(mut)]
+    pub lookup_table: UncheckedAccount<'info>,
+
+    /// CHECK: the address lookup table program
+    #[account(address = solana_address_lookup_table_interface::program::ID)]
+    pub address_lookup_table_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct FinalizeSettlement<'info> {
-    #[account(constraint = oracle_authority.key() == config.oracle_authority @ OpinionError::Unauthorized)]
-    pub oracle_authority: Signer<'info>,
+pub struct MigrateMarket<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
 
-    #[account(seeds = [b"config"], bump = config.bump)]
-    pub config: Account<'info, ProgramConfig>,
+    /// CHECK: a baseline Market, which does not deserialize as the current
+    /// struct; the handler checks the discriminator before reading it
+    #[account(mut, owner = crate::ID)]
+    pub market: UncheckedAccount<'info>,
+
+    /// CHECK: the market's statement account, created by the handler
+    #[account(mut, seeds = [b"market_statement", market.key().as_ref()], bump)]
+    pub market_statement: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
 
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateEscrowAuthority<'info> {
     #[account(
         mut,
         seeds = [b"market", market.uuid.as_ref()],
@@ -1313,66 +18173,126 @@ pub struct FinalizeSettlement<'info> {
     )]
     pub market: Account<'info, Market>,
 
+    /// Still owned by the market PDA until this runs
     #[account(
         mut,
         seeds = [b"escrow", market.key().as_ref()],
         bump,
+        constraint = escrow_token_account.owner == market.key() @ OpinionError::AlreadyMigrated,
     )]
     pub escrow_token_account: Account<'info, TokenAccount>,
 
+    /// Required while market.creation_fee_escrowed is non-zero
     #[account(
         mut,
-        constraint = treasury_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
-        constraint = treasury_usdc.owner == config.treasury @ OpinionError::TreasuryMismatch,
+        seeds = [b"creation_fee", market.key().as_ref()],
+        bump,
+        constraint = creation_fee_escrow.owner == market.key() @ OpinionError::AlreadyMigrated,
     )]
-    pub treasury_usdc: Account<'info, TokenAccount>,
+    pub creation_fee_escrow: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: PDA that takes over the escrow token accounts; holds no data
+    #[account(seeds = [b"escrow_authority", market.key().as_ref()], bump)]
+    pub escrow_authority: UncheckedAccount<'info>,
 
     pub token_program: Program<'info, Token>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct ClaimPayout<'info> {
+pub struct MigrateConfig<'info> {
+    /// The program's upgrade authority
     #[account(mut)]
-    pub staker: Signer<'info>,
+    pub authority: Signer<'info>,
 
-    #[account(seeds = [b"config"], bump = config.bump)]
-    pub config: Account<'info, ProgramConfig>,
+    /// CHECK: the baseline ProgramConfig; checked and rewritten by the
+    /// handler
+    #[account(mut, owner = crate::ID, seeds = [b"config"], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    /// This program's ProgramData, naming its upgrade authority
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(authority.key()) @ OpinionError::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateOpinion<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: a baseline Opinion; checked by the handler before it is
+    /// rewritten
+    #[account(mut, owner = crate::ID)]
+    pub opinion: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitReactionTree<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
 
     #[account(
-        mut,
         seeds = [b"market", market.uuid.as_ref()],
         bump = market.bump,
     )]
     pub market: Account<'info, Market>,
 
     #[account(
-        mut,
-        seeds = [b"escrow", market.key().as_ref()],
+        init,
+        payer = payer,
+        space = ReactionTree::SPACE,
+        seeds = [b"reaction_tree", market.key().as_ref()],
         bump,
     )]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    pub reaction_tree: AccountLoader<'info, ReactionTree>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitActivityLog<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
 
     #[account(
-        mut,
-        constraint = opinion.market == market.key(),
-        constraint = opinion.staker == staker.key() @ OpinionError::Unauthorized,
+        seeds = [b"market", market.uuid.as_ref()],
+        bump = market.bump,
     )]
-    pub opinion: Account<'info, Opinion>,
+    pub market: Account<'info, Market>,
 
     #[account(
-        mut,
-        constraint = staker_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
-        constraint = staker_usdc.owner == staker.key(),
+        init,
+        payer = payer,
+        space = ActivityLog::SPACE,
+        seeds = [b"activity_log", market.key().as_ref()],
+        bump,
     )]
-    pub staker_usdc: Account<'info, TokenAccount>,
+    pub activity_log: AccountLoader<'info, ActivityLog>,
 
-    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct ClaimJackpot<'info> {
-    #[account(constraint = oracle_authority.key() == config.oracle_authority @ OpinionError::Unauthorized)]
-    pub oracle_authority: Signer<'info>,
+pub struct ReactCompressed<'info> {
+    #[account(mut)]
+    pub reactor: Signer<'info>,
 
     #[account(seeds = [b"config"], bump = config.bump)]
     pub config: Account<'info, ProgramConfig>,
@@ -1384,6 +18304,30 @@ pub struct ClaimJackpot<'info> {
     )]
     pub market: Account<'info, Market>,
 
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    /// Recent-activity ring buffer (init_activity_log for older markets)
+    #[account(
+        mut,
+        seeds = [b"activity_log", market.key().as_ref()],
+        bump = activity_log.load()?.bump,
+    )]
+    pub activity_log: AccountLoader<'info, ActivityLog>,
+
+    #[account(
+        mut,
+        constraint = opinion.load()?.market == market.key() @ OpinionError::OpinionMarketMismatch,
+    )]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    #[account(
+        mut,
+        seeds = [b"reaction_tree", market.key().as_ref()],
+        bump = reaction_tree.load()?.bump,
+    )]
+    pub reaction_tree: AccountLoader<'info, ReactionTree>,
+
     #[account(
         mut,
         seeds = [b"escrow", market.key().as_ref()],
@@ -1393,17 +18337,20 @@ pub struct ClaimJackpot<'info> {
 
     #[account(
         mut,
-        constraint = winner_token_account.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = reactor_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = reactor_usdc.owner == reactor.key(),
     )]
-    pub winner_token_account: Account<'info, TokenAccount>,
+    pub reactor_usdc: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct RunLottery<'info> {
-    #[account(constraint = oracle_authority.key() == config.oracle_authority @ OpinionError::Unauthorized)]
-    pub oracle_authority: Signer<'info>,
+pub struct RecoverCompressedReaction<'info> {
+    #[account(mut)]
+    pub reactor: Signer<'info>,
 
     #[account(seeds = [b"config"], bump = config.bump)]
     pub config: Account<'info, ProgramConfig>,
@@ -1422,55 +18369,600 @@ pub struct RunLottery<'info> {
     )]
     pub escrow_token_account: Account<'info, TokenAccount>,
 
+    /// CHECK: PDA that owns the market's escrow token accounts; holds no data
+    #[account(
+        seeds = [b"escrow_authority", market.key().as_ref()],
+        bump = market.escrow_authority_bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+
     #[account(
         mut,
-        constraint = winner_token_account.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = opinion.load()?.market == market.key() @ OpinionError::OpinionMarketMismatch,
     )]
-    pub winner_token_account: Account<'info, TokenAccount>,
+    pub opinion: AccountLoader<'info, Opinion>,
 
     #[account(
         mut,
-        constraint = treasury_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
-        constraint = treasury_usdc.owner == config.treasury @ OpinionError::TreasuryMismatch,
+        seeds = [b"reaction_tree", market.key().as_ref()],
+        bump = reaction_tree.load()?.bump,
     )]
-    pub treasury_usdc: Account<'info, TokenAccount>,
+    pub reaction_tree: AccountLoader<'info, ReactionTree>,
+
+    #[account(
+        mut,
+        constraint = reactor_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
+        constraint = reactor_usdc.owner == reactor.key(),
+    )]
+    pub reactor_usdc: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, Memo>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct RecoverStake<'info> {
+pub struct ScoreCompressedReaction<'info> {
+    /// Permissionless crank — pays for the reactor's stats account on first use
     #[account(mut)]
-    pub staker: Signer<'info>,
-
-    #[account(seeds = [b"config"], bump = config.bump)]
-    pub config: Account<'info, ProgramConfig>,
+    pub payer: Signer<'info>,
 
     #[account(
+        mut,
         seeds = [b"market", market.uuid.as_ref()],
         bump = market.bump,
     )]
     pub market: Account<'info, Market>,
 
+    #[account(constraint = opinion.load()?.market == market.key() @ OpinionError::OpinionMarketMismatch)]
+    pub opinion: AccountLoader<'info, Opinion>,
+
+    /// CHECK: the reactor committed in the leaf; only its key is used
+    pub reactor: UncheckedAccount<'info>,
+
     #[account(
         mut,
-        seeds = [b"escrow", market.key().as_ref()],
-        bump,
+        seeds = [b"reaction_tree", market.key().as_ref()],
+        bump = reaction_tree.load()?.bump,
     )]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    pub reaction_tree: AccountLoader<'info, ReactionTree>,
 
     #[account(
-        seeds = [b"opinion", market.key().as_ref(), staker.key().as_ref()],
-        bump = opinion.bump,
+        init_if_needed,
+        payer = payer,
+        space = ReactorStats::SPACE,
+        seeds = [b"reactor_stats", reactor.key().as_ref()],
+        bump,
     )]
-    pub opinion: Account<'info, Opinion>,
+    pub reactor_stats: Account<'info, ReactorStats>,
 
     #[account(
         mut,
-        constraint = staker_usdc.mint == config.usdc_mint @ OpinionError::MintMismatch,
-        constraint = staker_usdc.owner == staker.key(),
+        seeds = [b"reactor_leaderboard"],
+        bump = leaderboard.bump,
     )]
-    pub staker_usdc: Account<'info, TokenAccount>,
+    pub leaderboard: Account<'info, ReactorLeaderboard>,
 
-    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spl_token_2022::solana_zk_sdk::encryption::pedersen::{Pedersen, PedersenOpening};
+
+    fn ciphertext(amount: u64, opening: &PedersenOpening) -> [u8; 64] {
+        let mut ciphertext = [0u8; 64];
+        ciphertext[..32].copy_from_slice(&Pedersen::with(amount, opening).to_bytes());
+        ciphertext
+    }
+
+    #[test]
+    fn pedersen_opening_matches_committed_amount() {
+        let opening = PedersenOpening::new_rand();
+        let committed = ciphertext(12_345, &opening);
+        assert!(pedersen_opens(&committed, 12_345, opening.as_bytes()));
+        assert!(!pedersen_opens(&committed, 12_346, opening.as_bytes()));
+        assert!(!pedersen_opens(&committed, 12_345, PedersenOpening::new_rand().as_bytes()));
+    }
+
+    #[test]
+    fn pedersen_opening_splits_amount_into_lo_and_hi() {
+        let amount = 7_654_321u64;
+        let (opening_lo, opening_hi) = (PedersenOpening::new_rand(), PedersenOpening::new_rand());
+        let lo = amount & ((1 << CONFIDENTIAL_AMOUNT_LO_BITS) - 1);
+        let hi = amount >> CONFIDENTIAL_AMOUNT_LO_BITS;
+        assert!(pedersen_opens(&ciphertext(lo, &opening_lo), lo, opening_lo.as_bytes()));
+        assert!(pedersen_opens(&ciphertext(hi, &opening_hi), hi, opening_hi.as_bytes()));
+    }
+
+    #[test]
+    fn config_defaults_fill_the_current_layout() {
+        let mut template = Vec::new();
+        ProgramConfig::with_defaults(Pubkey::default(), Pubkey::default(), Pubkey::default(), Pubkey::default(), 0)
+            .try_serialize(&mut template)
+            .unwrap();
+        assert_eq!(template.len(), ProgramConfig::SPACE);
+    }
+
+    fn config_with_tiers(fee_tiers: [FeeTier; MAX_FEE_TIERS]) -> ProgramConfig {
+        let mut config =
+            ProgramConfig::with_defaults(Pubkey::default(), Pubkey::default(), Pubkey::default(), Pubkey::default(), 0);
+        config.fee_tiers = fee_tiers;
+        config
+    }
+
+    #[test]
+    fn protocol_fee_charges_each_tier_on_its_slice() {
+        let config = config_with_tiers(DEFAULT_FEE_TIERS);
+        assert_eq!(config.protocol_fee(0), 0);
+        assert_eq!(config.protocol_fee(999), 99);
+        // Up to and including the $1k threshold, everything is at 10%
+        assert_eq!(config.protocol_fee(1_000_000_000), 100_000_000);
+        assert_eq!(config.protocol_fee(1_000_000_010), 100_000_000);
+        assert_eq!(config.protocol_fee(1_000_000_020), 100_000_001);
+        assert_eq!(config.protocol_fee(3_000_000_000), 200_000_000);
+        // Unused u64::MAX tiers never apply, and the sum cannot overflow
+        let above = (u64::MAX - 1_000_000_000) as u128 * 500 / 10_000;
+        assert_eq!(config.protocol_fee(u64::MAX), 100_000_000 + above as u64);
+
+        // Rounding is applied once to the summed tiers, not per tier
+        let config = config_with_tiers([
+            FeeTier { threshold: 0, bps: 1_000 },
+            FeeTier { threshold: 100, bps: 500 },
+            FeeTier { threshold: 200, bps: 0 },
+            FeeTier { threshold: u64::MAX, bps: 0 },
+        ]);
+        assert_eq!(config.protocol_fee(150), 12);
+        assert_eq!(config.protocol_fee(200), 15);
+        assert_eq!(config.protocol_fee(10_000), 15);
+    }
+
+    fn zeroed_market() -> Market {
+        Market::try_deserialize_unchecked(&mut &vec![0u8; Market::SPACE][..]).unwrap()
+    }
+
+    /// Refund each stake in turn from a shared escrow, as recover_stake does
+    fn refund_all(escrow: u64, stakes: &[u64]) -> Vec<u64> {
+        let mut market = zeroed_market();
+        market.total_stake = stakes.iter().sum();
+        market.start_refunding();
+        let mut escrow = escrow;
+        stakes
+            .iter()
+            .map(|&stake| {
+                let refund = market.take_refund(stake, escrow).unwrap();
+                escrow -= refund;
+                refund
+            })
+            .collect()
+    }
+
+    /// An Active market with one opinion per stake, reactions closing at 1_000
+    fn active_market(stakes: &[u64]) -> Market {
+        let mut market = zeroed_market();
+        market.state = MarketState::Active;
+        market.reactions_close_at = 1_000;
+        market.staker_count = stakes.len() as u32;
+        market.total_stake = stakes.iter().sum();
+        market
+    }
+
+    #[test]
+    fn close_refunds_a_single_staker_market_at_par() {
+        let mut market = active_market(&[1_000_000]);
+        assert_eq!(market.close_at(999).unwrap_err(), OpinionError::MarketNotExpired.into());
+
+        assert!(market.close_at(1_000).unwrap());
+        assert!(market.state == MarketState::Refunding);
+        market.require_refunding(1_000).unwrap();
+        assert_eq!(market.take_refund(1_000_000, 1_000_000).unwrap(), 1_000_000);
+        assert_eq!(market.refund_outstanding, 0);
+    }
+
+    #[test]
+    fn close_holds_a_multi_staker_market_for_the_recovery_period() {
+        let mut market = active_market(&[1_000_000, 1_000_000]);
+        assert!(!market.close_at(1_000).unwrap());
+        assert!(market.state == MarketState::Closed);
+        assert_eq!(market.close_at(1_000).unwrap_err(), OpinionError::MarketNotActive.into());
+
+        // recover_stake is refused until the oracle misses its deadline
+        let deadline = 1_000 + RECOVERY_PERIOD;
+        assert_eq!(market.require_refunding(deadline - 1).unwrap_err(), OpinionError::MarketNotExpired.into());
+        assert_eq!(
+            market.begin_refunding(deadline - 1, false).unwrap_err(),
+            OpinionError::MarketNotExpired.into()
+        );
+        assert!(market.begin_refunding(deadline, false).unwrap());
+        market.require_refunding(deadline).unwrap();
+        assert_eq!(market.take_refund(1_000_000, 2_000_000).unwrap(), 1_000_000);
+    }
+
+    #[test]
+    fn refunds_split_an_escrow_shortfall_pro_rata() {
+        // 10% of the escrow already paid out (e.g. a settlement bounty)
+        assert_eq!(
+            refund_all(5_400_000, &[1_000_000, 2_000_000, 3_000_000]),
+            [900_000, 1_800_000, 2_700_000]
+        );
+        // Rounding dust goes to the last staker; the escrow is emptied exactly
+        let refunds = refund_all(2_000_000, &[1_000_000, 1_000_000, 1_000_000]);
+        assert_eq!(refunds, [666_666, 666_667, 666_667]);
+        assert_eq!(refunds.iter().sum::<u64>(), 2_000_000);
+        // A fully funded escrow refunds every stake at par
+        assert_eq!(
+            refund_all(3_500_000, &[500_000, 1_000_000, 2_000_000]),
+            [500_000, 1_000_000, 2_000_000]
+        );
+    }
+
+    #[test]
+    fn refunds_reject_principal_beyond_outstanding() {
+        let mut market = zeroed_market();
+        market.total_stake = 2_000_000;
+        market.start_refunding();
+        let rejected = OpinionError::RefundExceedsOutstanding.into();
+        assert_eq!(market.take_refund(0, 2_000_000).unwrap_err(), rejected);
+        assert_eq!(market.take_refund(2_000_001, 2_000_000).unwrap_err(), rejected);
+
+        // A refund retires its principal; only the rest can still be refunded
+        assert_eq!(market.take_refund(1_500_000, 2_000_000).unwrap(), 1_500_000);
+        assert_eq!(market.take_refund(1_000_000, 500_000).unwrap_err(), rejected);
+        assert_eq!(market.refund_outstanding, 500_000);
+    }
+
+    /// A Scored market at `crowd_score` whose weight range spans every
+    /// opinion below
+    fn scored_market(payout_mode: PayoutMode, crowd_score: u8) -> Market {
+        let mut market = zeroed_market();
+        market.state = MarketState::Scored;
+        market.payout_mode = payout_mode;
+        market.scoring_weights = ScoringWeights::DEFAULT;
+        market.crowd_score = crowd_score;
+        market.weight_range_posted = true;
+        market.weight_min_net = -1_000_000;
+        market.weight_max_net = 3_000_000;
+        market
+    }
+
+    fn opinion(backing_total: u64, slashing_total: u64, market_prediction: u8) -> Opinion {
+        let mut opinion: Opinion = bytemuck::Zeroable::zeroed();
+        opinion.backing_total = backing_total;
+        opinion.slashing_total = slashing_total;
+        opinion.market_prediction = market_prediction;
+        opinion
+    }
+
+    /// Settle with the scores settle_opinion would accept
+    fn settle(market: &mut Market, opinion: &mut Opinion) {
+        let weight = compute_weight_score(opinion.net_backing(), market.weight_min_net, market.weight_max_net);
+        let consensus = compute_consensus_score(opinion.market_prediction, market.crowd_score);
+        apply_opinion_settlement(market, Pubkey::default(), opinion, Pubkey::default(), weight, consensus, 0)
+            .unwrap();
+    }
+
+    #[test]
+    fn settlement_tallies_the_payout_divisors() {
+        let mut market = scored_market(PayoutMode::Proportional, 60);
+        let mut top = opinion(3_000_000, 0, 60);
+        let mut slashed = opinion(1_000_000, 2_000_000, 50);
+        settle(&mut market, &mut top);
+        settle(&mut market, &mut slashed);
+        assert!(market.settlement_tallied());
+        // A net-negative opinion adds no pool weight
+        assert_eq!(market.settled_pool_weight, 3_000_000);
+        assert_eq!(market.settled_prediction_weight, 1_000_000 + 1_000_000 / 11);
+
+        // Re-settlement replaces an opinion's weights rather than adding them
+        settle(&mut market, &mut top);
+        assert_eq!(market.settled_count, 2);
+        assert_eq!(market.settled_pool_weight, 3_000_000);
+        assert_eq!(market.settled_prediction_weight, 1_000_000 + 1_000_000 / 11);
+
+        let mut market = scored_market(PayoutMode::Exponential, 60);
+        let (mut top, mut slashed) = (opinion(3_000_000, 0, 60), opinion(1_000_000, 2_000_000, 50));
+        settle(&mut market, &mut top);
+        settle(&mut market, &mut slashed);
+        assert_eq!(
+            market.settled_pool_weight,
+            market.opinion_pool_weight(&top) + market.opinion_pool_weight(&slashed)
+        );
+
+        // TopN weights depend on every score; top_n_split checks them instead
+        let mut market = scored_market(PayoutMode::TopN(1), 60);
+        let mut top = opinion(3_000_000, 0, 60);
+        settle(&mut market, &mut top);
+        assert_eq!(market.settled_pool_weight, 0);
+        assert_eq!(market.settled_prediction_weight, 1_000_000);
+    }
+
+    /// A TopN(n) market with one settled opinion per score
+    fn top_n_market(n: u8, scores: &[u8]) -> (Market, Vec<Opinion>) {
+        let mut market = zeroed_market();
+        market.payout_mode = PayoutMode::TopN(n);
+        market.staker_count = scores.len() as u32;
+        market.settled_count = scores.len() as u32;
+        let opinions = scores
+            .iter()
+            .map(|&score| {
+                let mut opinion: Opinion = bytemuck::Zeroable::zeroed();
+                opinion.set_combined_score(score);
+                opinion.set_settled(true);
+                market.tally_combined_score(None, score);
+                opinion
+            })
+            .collect();
+        (market, opinions)
+    }
+
+    #[test]
+    fn top_n_split_shares_the_slots_left_at_the_cutoff() {
+        // Two tied at the cutoff share the one slot left below the 90
+        let (market, _) = top_n_market(2, &[90, 80, 80, 70]);
+        let split = market.top_n_split(2).unwrap();
+        assert_eq!(split, TopNSplit { cutoff: 80, above: 1, tied: 2, slots: 2 });
+        assert_eq!([90, 80, 70].map(|score| split.weight(score)), [2, 1, 0]);
+        assert_eq!(split.total_weight(), 4);
+
+        // N ending exactly on the last tied opinion gives each a whole slot
+        let split = market.top_n_split(3).unwrap();
+        assert_eq!(split, TopNSplit { cutoff: 80, above: 1, tied: 2, slots: 3 });
+        assert_eq!([90, 80, 70].map(|score| split.weight(score)), [2, 2, 0]);
+
+        // Fewer than N opinions: every one is inside, a slot each
+        let split = market.top_n_split(10).unwrap();
+        assert_eq!(split, TopNSplit { cutoff: 70, above: 3, tied: 1, slots: 4 });
+        assert_eq!(split.total_weight(), 4);
+
+        // Everyone tied at the cutoff splits the N slots evenly
+        let (tied, _) = top_n_market(2, &[50, 50, 50]);
+        let split = tied.top_n_split(2).unwrap();
+        assert_eq!(split, TopNSplit { cutoff: 50, above: 0, tied: 3, slots: 2 });
+        assert_eq!((split.weight(50), split.total_weight()), (2, 6));
+
+        assert_eq!(zeroed_market().top_n_split(2), Some(TopNSplit::default()));
+        // A settled score missing from the histogram leaves the posted cutoff
+        let mut untallied = market.clone();
+        untallied.settled_count += 1;
+        assert_eq!(untallied.top_n_split(2), None);
+    }
+
+    #[test]
+    fn top_n_payouts_round_down_within_the_pool() {
+        let (mut market, opinions) = top_n_market(2, &[90, 80, 80, 70]);
+        market.total_net_backing = market.top_n_split(2).unwrap().total_weight();
+        market.opinion_pool = 1_000_003;
+        let payouts: Vec<u64> = opinions.iter().map(|opinion| market.raw_payout(opinion).unwrap()).collect();
+        assert_eq!(payouts, [500_001, 250_000, 250_000, 0]);
+        assert!(payouts.iter().sum::<u64>() <= market.opinion_pool);
+    }
+
+    /// Count raw payouts into a capped market as tally_payout_cap does
+    fn tally_cap(market: &mut Market, raws: &[u64]) {
+        for &raw in raws {
+            let limit = market.payout_cap.limit(0).unwrap();
+            if raw > limit {
+                market.cap_excess_total += raw - limit;
+            } else {
+                market.uncapped_payout_total += raw;
+            }
+            market.cap_tally_count += 1;
+        }
+    }
+
+    #[test]
+    fn capped_payout_redistributes_excess_to_uncapped_opinions() {
+        let opinion: Opinion = bytemuck::Zeroable::zeroed();
+        let mut market = zeroed_market();
+        assert_eq!(market.capped_payout(&opinion, 1_000).unwrap(), 1_000);
+
+        market.payout_cap = ReactionCap::Absolute(400);
+        market.staker_count = 3;
+        tally_cap(&mut market, &[1_000, 300]);
+        assert_eq!(
+            market.capped_payout(&opinion, 300).unwrap_err(),
+            OpinionError::PayoutCapTallyIncomplete.into()
+        );
+        tally_cap(&mut market, &[100]);
+        assert_eq!((market.cap_excess_total, market.uncapped_payout_total), (600, 400));
+
+        // The 600 excess splits 3:1 over the uncapped payouts, bounded by the
+        // cap; what the cap turns away stays in escrow for sweep_dust
+        let payouts = [1_000, 300, 100].map(|raw| market.capped_payout(&opinion, raw).unwrap());
+        assert_eq!(payouts, [400, 400, 250]);
+
+        // With every opinion capped there is nobody to share the excess with
+        let mut market = zeroed_market();
+        market.payout_cap = ReactionCap::Absolute(400);
+        market.staker_count = 2;
+        tally_cap(&mut market, &[1_000, 500]);
+        assert_eq!(market.capped_payout(&opinion, 1_000).unwrap(), 400);
+        assert_eq!(market.capped_payout(&opinion, 399).unwrap(), 399);
+    }
+
+    /// Parent node as verify_merkle_proof hashes it
+    fn settlement_node(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        let (first, second) = if a <= b { (a, b) } else { (b, a) };
+        hashv(&[&[1u8][..], &first, &second]).to_bytes()
+    }
+
+    #[test]
+    fn merkle_proofs_verify_only_the_committed_leaves() {
+        let opinions: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let leaves: Vec<[u8; 32]> =
+            opinions.iter().map(|opinion| settlement_leaf(opinion, 80, 70, 60, 1_000_000)).collect();
+        let (left, right) = (settlement_node(leaves[0], leaves[1]), settlement_node(leaves[2], leaves[3]));
+        let root = settlement_node(left, right);
+
+        assert!(verify_merkle_proof(&[leaves[1], right], root, leaves[0]));
+        assert!(verify_merkle_proof(&[leaves[2], left], root, leaves[3]));
+        // Any payout other than the committed one is rejected
+        let inflated = settlement_leaf(&opinions[0], 80, 70, 60, 1_000_001);
+        assert!(!verify_merkle_proof(&[leaves[1], right], root, inflated));
+        // Short, empty, over-long or misordered proofs do not reach the root
+        assert!(!verify_merkle_proof(&[leaves[1]], root, leaves[0]));
+        assert!(!verify_merkle_proof(&[], root, leaves[0]));
+        assert!(!verify_merkle_proof(&[leaves[1], right, left], root, leaves[0]));
+        assert!(!verify_merkle_proof(&[right, leaves[1]], root, leaves[0]));
+        assert!(!verify_merkle_proof(&[leaves[1], right], [0u8; 32], leaves[0]));
+        // A one-leaf tree's root is the leaf, proven by an empty proof
+        assert!(verify_merkle_proof(&[], leaves[0], leaves[0]));
+    }
+
+    fn empty_tree() -> Box<ReactionTree> {
+        let mut tree: Box<ReactionTree> = Box::new(bytemuck::Zeroable::zeroed());
+        tree.initialize();
+        tree
+    }
+
+    fn leaf(n: u8) -> [u8; 32] {
+        [n; 32]
+    }
+
+    /// Root of a depth-REACTION_TREE_DEPTH tree holding `leaves`, and the
+    /// sibling path of the leaf at `index`, computed from scratch
+    fn reference(leaves: &[[u8; 32]], index: usize) -> ([u8; 32], Vec<[u8; 32]>) {
+        let mut level = leaves.to_vec();
+        let mut empty = [0u8; 32];
+        let mut proof = Vec::new();
+        for depth in 0..REACTION_TREE_DEPTH {
+            proof.push(level.get((index >> depth) ^ 1).copied().unwrap_or(empty));
+            level = level
+                .chunks(2)
+                .map(|pair| merkle_node(&pair[0], pair.get(1).unwrap_or(&empty)))
+                .collect();
+            empty = merkle_node(&empty, &empty);
+        }
+        (level.first().copied().unwrap_or(empty), proof)
+    }
+
+    #[test]
+    fn reaction_tree_appends_match_reference_root() {
+        let mut tree = empty_tree();
+        assert_eq!(tree.root(), reference(&[], 0).0);
+        let leaves: Vec<_> = (1..=5).map(leaf).collect();
+        for (i, l) in leaves.iter().enumerate() {
+            assert_eq!(tree.append(*l).unwrap(), i as u32);
+            assert_eq!(tree.root(), reference(&leaves[..=i], 0).0);
+        }
+    }
+
+    #[test]
+    fn reaction_tree_replace_fast_forwards_stale_proof() {
+        let mut tree = empty_tree();
+        let mut leaves: Vec<_> = (1..=2).map(leaf).collect();
+        for l in &leaves {
+            tree.append(*l).unwrap();
+        }
+        let (stale_root, stale_proof) = reference(&leaves, 0);
+
+        // Appends and a replace of another leaf since the proof was built
+        for n in 3..=5 {
+            tree.append(leaf(n)).unwrap();
+            leaves.push(leaf(n));
+        }
+        let (root, proof) = reference(&leaves, 3);
+        tree.replace(root, 3, &proof, leaves[3], leaf(40)).unwrap();
+        leaves[3] = leaf(40);
+
+        tree.replace(stale_root, 0, &stale_proof, leaves[0], leaf(10)).unwrap();
+        leaves[0] = leaf(10);
+        assert_eq!(tree.root(), reference(&leaves, 0).0);
+    }
+
+    #[test]
+    fn reaction_tree_rejects_proof_after_its_leaf_was_updated() {
+        let mut tree = empty_tree();
+        let leaves: Vec<_> = (1..=4).map(leaf).collect();
+        for l in &leaves {
+            tree.append(*l).unwrap();
+        }
+        let (root, proof) = reference(&leaves, 1);
+        tree.replace(root, 1, &proof, leaves[1], [0u8; 32]).unwrap();
+
+        // Replaying the same proof (e.g. recovering twice) must fail, whether
+        // against the old root or the current one
+        assert_eq!(
+            tree.replace(root, 1, &proof, leaves[1], [0u8; 32]).unwrap_err(),
+            OpinionError::StaleReactionProof.into()
+        );
+        assert_eq!(
+            tree.replace(tree.root(), 1, &proof, leaves[1], [0u8; 32]).unwrap_err(),
+            OpinionError::InvalidReactionProof.into()
+        );
+    }
+
+    #[test]
+    fn reaction_tree_rejects_root_evicted_from_changelog() {
+        let mut tree = empty_tree();
+        tree.append(leaf(1)).unwrap();
+        let (root, proof) = reference(&[leaf(1)], 0);
+        for n in 0..REACTION_TREE_BUFFER as u8 {
+            tree.append(leaf(n + 2)).unwrap();
+        }
+        assert_eq!(
+            tree.replace(root, 0, &proof, leaf(1), leaf(9)).unwrap_err(),
+            OpinionError::StaleReactionProof.into()
+        );
+    }
+
+    #[test]
+    fn reaction_tree_rejects_append_when_full() {
+        let mut tree = empty_tree();
+        tree.next_index = 1 << REACTION_TREE_DEPTH;
+        let root = tree.root();
+        assert_eq!(tree.append(leaf(1)).unwrap_err(), OpinionError::ReactionTreeFull.into());
+        assert_eq!(tree.root(), root);
+    }
+
+    #[test]
+    fn compressed_reaction_recovers_its_stake_once() {
+        let (opinion, reactor) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let stake = 1_000_000;
+        let reaction = reaction_leaf(&opinion, &reactor, &ReactionType::Back, stake, false);
+        let mut tree = empty_tree();
+        tree.append(reaction).unwrap();
+        let (root, proof) = reference(&[reaction], 0);
+
+        let mut market = zeroed_market();
+        market.total_stake = 2 * stake;
+        market.start_refunding();
+
+        // As recover_compressed_reaction: zero the leaf, then refund the stake
+        tree.replace(root, 0, &proof, reaction, [0u8; 32]).unwrap();
+        assert_eq!(tree.root(), reference(&[[0u8; 32]], 0).0);
+        assert_eq!(market.take_refund(stake, 2 * stake).unwrap(), stake);
+
+        assert_eq!(
+            tree.replace(root, 0, &proof, reaction, [0u8; 32]).unwrap_err(),
+            OpinionError::StaleReactionProof.into()
+        );
+    }
+
+    #[test]
+    fn compressed_reaction_scores_once() {
+        let (opinion, reactor) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let stake = 1_000_000;
+        let unscored = reaction_leaf(&opinion, &reactor, &ReactionType::Back, stake, false);
+        let scored = reaction_leaf(&opinion, &reactor, &ReactionType::Back, stake, true);
+        assert_ne!(unscored, scored);
+        let mut tree = empty_tree();
+        tree.append(unscored).unwrap();
+        let (root, proof) = reference(&[unscored], 0);
+
+        // As score_compressed_reaction: rewrite the leaf with scored set
+        tree.replace(root, 0, &proof, unscored, scored).unwrap();
+        assert_eq!(tree.root(), reference(&[scored], 0).0);
+
+        // The unscored leaf is gone, whichever root the proof is against
+        assert_eq!(
+            tree.replace(root, 0, &proof, unscored, scored).unwrap_err(),
+            OpinionError::StaleReactionProof.into()
+        );
+        assert_eq!(
+            tree.replace(tree.root(), 0, &proof, unscored, scored).unwrap_err(),
+            OpinionError::InvalidReactionProof.into()
+        );
+    }
 }